@@ -13,6 +13,6 @@ const TEST_PORT: u16 = 27616;
 const WORKER_COUNT: usize = 8;
 
 full_bench_impl!({
-    spawn(move || server::start(&format!("127.0.0.1:{}", TEST_PORT), "memory://", WORKER_COUNT));
+    spawn(move || server::start(&format!("127.0.0.1:{}", TEST_PORT), "memory://", WORKER_COUNT, None));
     ClientDatastore::new(TEST_PORT)
 });
@@ -7,7 +7,7 @@ use futures::Future;
 use indradb;
 use serde_json::value::Value as JsonValue;
 use std::cell::RefCell;
-use std::fmt::Debug;
+use std::collections::BTreeMap;
 use std::net::ToSocketAddrs;
 use std::rc::Rc;
 use std::thread::sleep;
@@ -17,8 +17,8 @@ use tokio_core::reactor::Core;
 use tokio_io::AsyncRead;
 use uuid::Uuid;
 
-fn map_indradb_error<T, E: Debug>(result: Result<T, E>) -> Result<T, indradb::Error> {
-    result.map_err(|err| format!("{:?}", err).into())
+fn map_indradb_error<T>(result: Result<T, CapnpError>) -> Result<T, indradb::Error> {
+    result.map_err(converters::to_indradb_error)
 }
 
 pub struct ClientDatastore {
@@ -86,10 +86,59 @@ impl indradb::Datastore for ClientDatastore {
         map_indradb_error(self.core.borrow_mut().run(f))
     }
 
+    fn bulk_insert_partial<I>(&self, items: I) -> Result<Vec<Result<(), indradb::BulkInsertItemError>>, indradb::Error>
+    where
+        I: Iterator<Item = indradb::BulkInsertItem>,
+    {
+        let items: Vec<indradb::BulkInsertItem> = items.collect();
+        let mut req = self.client.bulk_insert_partial_request();
+        map_indradb_error(converters::from_bulk_insert_items(
+            &items,
+            req.get().init_items(items.len() as u32),
+        ))?;
+
+        let f = req.send().promise.and_then(move |res| {
+            let list = res.get()?.get_result()?;
+            converters::to_bulk_insert_results(&list)
+        });
+
+        map_indradb_error(self.core.borrow_mut().run(f))
+    }
+
+    fn execute_batch<I>(&self, operations: I) -> Result<(), indradb::Error>
+    where
+        I: Iterator<Item = indradb::BatchOperation>,
+    {
+        let operations: Vec<indradb::BatchOperation> = operations.collect();
+        let mut req = self.client.execute_batch_request();
+        map_indradb_error(converters::from_batch_operations(
+            &operations,
+            req.get().init_operations(operations.len() as u32),
+        ))?;
+
+        let f = req.send().promise.and_then(move |res| {
+            res.get()?;
+            Ok(())
+        });
+
+        map_indradb_error(self.core.borrow_mut().run(f))
+    }
+
     fn transaction(&self) -> Result<ClientTransaction, indradb::Error> {
         let trans = self.client.transaction_request().send().pipeline.get_transaction();
         Ok(ClientTransaction::new(self.core.clone(), trans))
     }
+
+    fn health_check(&self) -> Result<(), indradb::Error> {
+        let req = self.client.health_check_request();
+
+        let f = req.send().promise.and_then(move |res| {
+            res.get()?;
+            Ok(())
+        });
+
+        map_indradb_error(self.core.borrow_mut().run(f))
+    }
 }
 
 pub struct ClientTransaction {
@@ -116,32 +165,7 @@ impl ClientTransaction {
     }
 }
 
-impl indradb::Transaction for ClientTransaction {
-    fn create_vertex(&self, v: &indradb::Vertex) -> Result<bool, indradb::Error> {
-        self.execute(move |trans| {
-            let mut req = trans.create_vertex_request();
-            converters::from_vertex(v, req.get().init_vertex());
-
-            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
-
-            Box::new(f)
-        })
-    }
-
-    fn create_vertex_from_type(&self, t: indradb::Type) -> Result<Uuid, indradb::Error> {
-        self.execute(move |trans| {
-            let mut req = trans.create_vertex_from_type_request();
-            req.get().set_t(&t.0);
-
-            let f = req.send().promise.and_then(move |res| {
-                let bytes = res.get()?.get_result()?;
-                Ok(Uuid::from_slice(bytes).unwrap())
-            });
-
-            Box::new(f)
-        })
-    }
-
+impl indradb::ReadTransaction for ClientTransaction {
     fn get_vertices(&self, q: &indradb::VertexQuery) -> Result<Vec<indradb::Vertex>, indradb::Error> {
         self.execute(move |trans| {
             let mut req = trans.get_vertices_request();
@@ -158,14 +182,27 @@ impl indradb::Transaction for ClientTransaction {
         })
     }
 
-    fn delete_vertices(&self, q: &indradb::VertexQuery) -> Result<(), indradb::Error> {
+    fn get_vertices_lenient(
+        &self,
+        q: &indradb::VertexQuery,
+    ) -> Result<(Vec<indradb::Vertex>, Vec<indradb::QueryError>), indradb::Error> {
         self.execute(move |trans| {
-            let mut req = trans.delete_vertices_request();
+            let mut req = trans.get_vertices_lenient_request();
             converters::from_vertex_query(&q, req.get().init_q());
 
             let f = req.send().promise.and_then(move |res| {
-                res.get()?;
-                Ok(())
+                let res = res.get()?;
+                let vertices: Result<Vec<indradb::Vertex>, CapnpError> = res
+                    .get_result()?
+                    .into_iter()
+                    .map(|reader| converters::to_vertex(&reader))
+                    .collect();
+                let errors: Result<Vec<indradb::QueryError>, CapnpError> = res
+                    .get_errors()?
+                    .into_iter()
+                    .map(|message| Ok(indradb::QueryError::new(message?.to_string())))
+                    .collect();
+                Ok((vertices?, errors?))
             });
 
             Box::new(f)
@@ -182,10 +219,10 @@ impl indradb::Transaction for ClientTransaction {
         })
     }
 
-    fn create_edge(&self, e: &indradb::EdgeKey) -> Result<bool, indradb::Error> {
+    fn estimate_vertex_count(&self, q: &indradb::VertexQuery) -> Result<u64, indradb::Error> {
         self.execute(move |trans| {
-            let mut req = trans.create_edge_request();
-            converters::from_edge_key(e, req.get().init_key());
+            let mut req = trans.estimate_vertex_count_request();
+            converters::from_vertex_query(&q, req.get().init_q());
 
             let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
 
@@ -209,37 +246,56 @@ impl indradb::Transaction for ClientTransaction {
         })
     }
 
-    fn delete_edges(&self, q: &indradb::EdgeQuery) -> Result<(), indradb::Error> {
+    fn get_edge_count(
+        &self,
+        id: Uuid,
+        type_filter: Option<&indradb::Type>,
+        direction: indradb::EdgeDirection,
+    ) -> Result<u64, indradb::Error> {
         self.execute(move |trans| {
-            let mut req = trans.delete_edges_request();
-            converters::from_edge_query(&q, req.get().init_q());
+            let mut req = trans.get_edge_count_request();
+            req.get().set_id(id.as_bytes());
 
-            let f = req.send().promise.and_then(move |res| {
-                res.get()?;
-                Ok(())
-            });
+            if let Some(type_filter) = type_filter {
+                req.get().set_type_filter(&type_filter.0);
+            }
+
+            req.get().set_direction(converters::from_edge_direction(direction));
+
+            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
 
             Box::new(f)
         })
     }
 
-    fn get_edge_count(
+    fn get_edge_counts(
         &self,
-        id: Uuid,
+        ids: &[Uuid],
         type_filter: Option<&indradb::Type>,
         direction: indradb::EdgeDirection,
-    ) -> Result<u64, indradb::Error> {
+    ) -> Result<Vec<(Uuid, u64)>, indradb::Error> {
         self.execute(move |trans| {
-            let mut req = trans.get_edge_count_request();
-            req.get().set_id(id.as_bytes());
+            let mut req = trans.get_edge_counts_request();
+
+            {
+                let mut builder = req.get().init_ids(ids.len() as u32);
+
+                for (i, id) in ids.iter().enumerate() {
+                    builder.set(i as u32, id.as_bytes());
+                }
+            }
 
             if let Some(type_filter) = type_filter {
                 req.get().set_type_filter(&type_filter.0);
             }
 
             req.get().set_direction(converters::from_edge_direction(direction));
+            let ids = ids.to_vec();
 
-            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
+            let f = req.send().promise.and_then(move |res| {
+                let counts = res.get()?.get_result()?;
+                Ok(ids.into_iter().zip(counts.iter()).collect())
+            });
 
             Box::new(f)
         })
@@ -268,17 +324,404 @@ impl indradb::Transaction for ClientTransaction {
         })
     }
 
+    fn get_edge_properties(
+        &self,
+        q: &indradb::EdgeQuery,
+        name: &str,
+    ) -> Result<Vec<indradb::EdgeProperty>, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_edge_properties_request();
+            converters::from_edge_query(&q, req.get().init_q());
+            req.get().set_name(name);
+
+            let f = req.send().promise.and_then(move |res| {
+                let list = res.get()?.get_result()?;
+                let list: Result<Vec<indradb::EdgeProperty>, CapnpError> = list
+                    .into_iter()
+                    .map(|reader| converters::to_edge_property(&reader))
+                    .collect();
+                list
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn get_edges_sorted_by_property(
+        &self,
+        q: &indradb::EdgeQuery,
+        name: &str,
+        direction: indradb::SortDirection,
+        limit: u32,
+    ) -> Result<Vec<indradb::Edge>, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_edges_sorted_by_property_request();
+            converters::from_edge_query(&q, req.get().init_q());
+            req.get().set_name(name);
+            req.get().set_direction(converters::from_sort_direction(direction));
+            req.get().set_limit(limit);
+
+            let f = req.send().promise.and_then(move |res| {
+                let list = res.get()?.get_result()?;
+                let list: Result<Vec<indradb::Edge>, CapnpError> =
+                    list.into_iter().map(|reader| converters::to_edge(&reader)).collect();
+                list
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn get_induced_edges(&self, ids: &[Uuid]) -> Result<Vec<indradb::Edge>, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_induced_edges_request();
+
+            {
+                let mut builder = req.get().init_ids(ids.len() as u32);
+
+                for (i, id) in ids.iter().enumerate() {
+                    builder.set(i as u32, id.as_bytes());
+                }
+            }
+
+            let f = req.send().promise.and_then(move |res| {
+                let list = res.get()?.get_result()?;
+                let list: Result<Vec<indradb::Edge>, CapnpError> =
+                    list.into_iter().map(|reader| converters::to_edge(&reader)).collect();
+                list
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn get_vertex_types(&self) -> Result<Vec<indradb::Type>, indradb::Error> {
+        self.execute(move |trans| {
+            let req = trans.get_vertex_types_request();
+
+            let f = req.send().promise.and_then(move |res| {
+                let list = res.get()?.get_result()?;
+                let list: Result<Vec<indradb::Type>, CapnpError> = list
+                    .into_iter()
+                    .map(|t| converters::map_capnp_err(indradb::Type::new(t?.to_string())))
+                    .collect();
+                list
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn get_full_record(&self, id: Uuid, edge_limit: u32) -> Result<Option<indradb::FullRecord>, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_full_record_request();
+            req.get().set_id(id.as_bytes());
+            req.get().set_edge_limit(edge_limit);
+
+            let f = req.send().promise.and_then(move |res| {
+                let res = res.get()?;
+
+                if res.has_result() {
+                    Ok(Some(converters::to_full_record(&res.get_result()?)?))
+                } else {
+                    Ok(None)
+                }
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn get_vertex_properties_multi(
+        &self,
+        q: &indradb::VertexQuery,
+        names: &[String],
+    ) -> Result<Vec<indradb::VertexProperty>, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_vertex_properties_multi_request();
+            converters::from_vertex_query(&q, req.get().init_q());
+
+            {
+                let mut builder = req.get().init_names(names.len() as u32);
+
+                for (i, name) in names.iter().enumerate() {
+                    builder.set(i as u32, name);
+                }
+            }
+
+            let f = req.send().promise.and_then(move |res| {
+                let list = res.get()?.get_result()?;
+                let list: Result<Vec<indradb::VertexProperty>, CapnpError> = list
+                    .into_iter()
+                    .map(|reader| converters::to_vertex_property(&reader))
+                    .collect();
+                list
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn get_vertex_properties_bounded(
+        &self,
+        q: &indradb::VertexQuery,
+        names: &[String],
+        max_bytes: u64,
+    ) -> Result<(Vec<indradb::VertexProperty>, bool), indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_vertex_properties_bounded_request();
+            converters::from_vertex_query(&q, req.get().init_q());
+            req.get().set_max_bytes(max_bytes);
+
+            {
+                let mut builder = req.get().init_names(names.len() as u32);
+
+                for (i, name) in names.iter().enumerate() {
+                    builder.set(i as u32, name);
+                }
+            }
+
+            let f = req.send().promise.and_then(move |res| {
+                let res = res.get()?;
+                let list: Result<Vec<indradb::VertexProperty>, CapnpError> = res
+                    .get_result()?
+                    .into_iter()
+                    .map(|reader| converters::to_vertex_property(&reader))
+                    .collect();
+                Ok((list?, res.get_truncated()))
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn get_all_vertex_properties_bounded(
+        &self,
+        q: &indradb::VertexQuery,
+        max_bytes: u64,
+    ) -> Result<(Vec<indradb::VertexProperty>, bool), indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_all_vertex_properties_bounded_request();
+            converters::from_vertex_query(&q, req.get().init_q());
+            req.get().set_max_bytes(max_bytes);
+
+            let f = req.send().promise.and_then(move |res| {
+                let res = res.get()?;
+                let list: Result<Vec<indradb::VertexProperty>, CapnpError> = res
+                    .get_result()?
+                    .into_iter()
+                    .map(|reader| converters::to_vertex_property(&reader))
+                    .collect();
+                Ok((list?, res.get_truncated()))
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn aggregate_edge_property(
+        &self,
+        q: &indradb::EdgeQuery,
+        name: &str,
+        op: indradb::AggregateOp,
+    ) -> Result<Option<f64>, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.aggregate_edge_property_request();
+            converters::from_edge_query(&q, req.get().init_q());
+            req.get().set_name(name);
+            req.get().set_op(converters::from_aggregate_op(op));
+
+            let f = req
+                .send()
+                .promise
+                .and_then(move |res| converters::to_aggregate_result(&res.get()?.get_result()?));
+
+            Box::new(f)
+        })
+    }
+
+    fn get_graph_stats(&self) -> Result<indradb::GraphStats, indradb::Error> {
+        self.execute(move |trans| {
+            let req = trans.get_graph_stats_request();
+            let f = req
+                .send()
+                .promise
+                .and_then(move |res| converters::to_graph_stats(&res.get()?.get_result()?));
+            Box::new(f)
+        })
+    }
+
+    fn count_vertices_by_type(&self) -> Result<BTreeMap<indradb::Type, u64>, indradb::Error> {
+        self.execute(move |trans| {
+            let req = trans.count_vertices_by_type_request();
+            let f = req
+                .send()
+                .promise
+                .and_then(move |res| converters::to_type_counts(&res.get()?.get_result()?));
+            Box::new(f)
+        })
+    }
+
+    fn get_vertex_property_names(&self, id: Uuid) -> Result<Vec<String>, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_vertex_property_names_request();
+            req.get().set_id(id.as_bytes());
+
+            let f = req.send().promise.and_then(move |res| {
+                let list = res.get()?.get_result()?;
+                let list: Result<Vec<String>, CapnpError> =
+                    list.into_iter().map(|name| Ok(name?.to_string())).collect();
+                list
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn get_edge_property_names(&self, key: &indradb::EdgeKey) -> Result<Vec<String>, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_edge_property_names_request();
+            converters::from_edge_key(key, req.get().init_key());
+
+            let f = req.send().promise.and_then(move |res| {
+                let list = res.get()?.get_result()?;
+                let list: Result<Vec<String>, CapnpError> =
+                    list.into_iter().map(|name| Ok(name?.to_string())).collect();
+                list
+            });
+
+            Box::new(f)
+        })
+    }
+}
+
+impl indradb::WriteTransaction for ClientTransaction {
+    fn create_vertex(&self, v: &indradb::Vertex) -> Result<bool, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.create_vertex_request();
+            converters::from_vertex(v, req.get().init_vertex());
+
+            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
+
+            Box::new(f)
+        })
+    }
+
+    fn create_vertex_from_type(&self, t: indradb::Type) -> Result<Uuid, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.create_vertex_from_type_request();
+            req.get().set_t(&t.0);
+
+            let f = req.send().promise.and_then(move |res| {
+                let bytes = res.get()?.get_result()?;
+                converters::to_uuid(bytes)
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn get_or_create_vertex(&self, id: Uuid, t: &indradb::Type) -> Result<(indradb::Vertex, bool), indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.get_or_create_vertex_request();
+            req.get().set_id(id.as_bytes());
+            req.get().set_t(&t.0);
+
+            let f = req.send().promise.and_then(move |res| {
+                let res = res.get()?;
+                let vertex = converters::to_vertex(&res.get_result()?)?;
+                Ok((vertex, res.get_created()))
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn delete_vertices(&self, q: &indradb::VertexQuery) -> Result<u64, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.delete_vertices_request();
+            converters::from_vertex_query(&q, req.get().init_q());
+
+            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
+
+            Box::new(f)
+        })
+    }
+
+    fn create_edge(&self, e: &indradb::EdgeKey) -> Result<bool, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.create_edge_request();
+            converters::from_edge_key(e, req.get().init_key());
+
+            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
+
+            Box::new(f)
+        })
+    }
+
+    fn create_edge_auto(&self, e: &indradb::EdgeKey, default_type: &indradb::Type) -> Result<bool, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.create_edge_auto_request();
+            converters::from_edge_key(e, req.get().init_key());
+            req.get().set_default_type(&default_type.0);
+
+            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
+
+            Box::new(f)
+        })
+    }
+
+    fn update_edge(&self, e: &indradb::EdgeKey) -> Result<bool, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.update_edge_request();
+            converters::from_edge_key(e, req.get().init_key());
+
+            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
+
+            Box::new(f)
+        })
+    }
+
+    fn delete_edges(&self, q: &indradb::EdgeQuery) -> Result<u64, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.delete_edges_request();
+            converters::from_edge_query(&q, req.get().init_q());
+
+            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
+
+            Box::new(f)
+        })
+    }
+
     fn set_vertex_properties(
         &self,
         q: &indradb::VertexQuery,
         name: &str,
         value: &JsonValue,
-    ) -> Result<(), indradb::Error> {
+    ) -> Result<u64, indradb::Error> {
         self.execute(move |trans| {
             let mut req = trans.set_vertex_properties_request();
             converters::from_vertex_query(&q, req.get().init_q());
             req.get().set_name(name);
-            req.get().set_value(&value.to_string());
+            set_property_value!(req.get(), value);
+
+            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
+
+            Box::new(f)
+        })
+    }
+
+    fn patch_vertex_property(
+        &self,
+        q: &indradb::VertexQuery,
+        name: &str,
+        patch: &JsonValue,
+    ) -> Result<(), indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.patch_vertex_property_request();
+            converters::from_vertex_query(&q, req.get().init_q());
+            req.get().set_name(name);
+            set_property_value!(req.get(), patch);
 
             let f = req.send().promise.and_then(move |res| {
                 res.get()?;
@@ -304,23 +747,14 @@ impl indradb::Transaction for ClientTransaction {
         })
     }
 
-    fn get_edge_properties(
-        &self,
-        q: &indradb::EdgeQuery,
-        name: &str,
-    ) -> Result<Vec<indradb::EdgeProperty>, indradb::Error> {
+    fn delete_all_vertex_properties(&self, q: &indradb::VertexQuery) -> Result<(), indradb::Error> {
         self.execute(move |trans| {
-            let mut req = trans.get_edge_properties_request();
-            converters::from_edge_query(&q, req.get().init_q());
-            req.get().set_name(name);
+            let mut req = trans.delete_all_vertex_properties_request();
+            converters::from_vertex_query(&q, req.get().init_q());
 
             let f = req.send().promise.and_then(move |res| {
-                let list = res.get()?.get_result()?;
-                let list: Result<Vec<indradb::EdgeProperty>, CapnpError> = list
-                    .into_iter()
-                    .map(|reader| converters::to_edge_property(&reader))
-                    .collect();
-                list
+                res.get()?;
+                Ok(())
             });
 
             Box::new(f)
@@ -332,7 +766,23 @@ impl indradb::Transaction for ClientTransaction {
             let mut req = trans.set_edge_properties_request();
             converters::from_edge_query(&q, req.get().init_q());
             req.get().set_name(name);
-            req.get().set_value(&value.to_string());
+            set_property_value!(req.get(), value);
+
+            let f = req.send().promise.and_then(move |res| {
+                res.get()?;
+                Ok(())
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn patch_edge_property(&self, q: &indradb::EdgeQuery, name: &str, patch: &JsonValue) -> Result<(), indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.patch_edge_property_request();
+            converters::from_edge_query(&q, req.get().init_q());
+            req.get().set_name(name);
+            set_property_value!(req.get(), patch);
 
             let f = req.send().promise.and_then(move |res| {
                 res.get()?;
@@ -357,4 +807,36 @@ impl indradb::Transaction for ClientTransaction {
             Box::new(f)
         })
     }
+
+    fn delete_all_edge_properties(&self, q: &indradb::EdgeQuery) -> Result<(), indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.delete_all_edge_properties_request();
+            converters::from_edge_query(&q, req.get().init_q());
+
+            let f = req.send().promise.and_then(move |res| {
+                res.get()?;
+                Ok(())
+            });
+
+            Box::new(f)
+        })
+    }
+
+    fn set_vertex_property_where(
+        &self,
+        filter: &indradb::PropertyCondition,
+        name: &str,
+        value: &JsonValue,
+    ) -> Result<u64, indradb::Error> {
+        self.execute(move |trans| {
+            let mut req = trans.set_vertex_property_where_request();
+            converters::from_property_condition(filter, req.get().init_filter());
+            req.get().set_name(name);
+            req.get().set_value(&value.to_string());
+
+            let f = req.send().promise.and_then(move |res| Ok(res.get()?.get_result()));
+
+            Box::new(f)
+        })
+    }
 }
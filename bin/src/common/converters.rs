@@ -5,15 +5,159 @@ use capnp;
 use capnp::Error as CapnpError;
 use chrono::{DateTime, TimeZone, Utc};
 use indradb;
+use rmp_serde;
 use serde_json;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::vec::IntoIter;
 use uuid::Uuid;
 
 const NANOS_PER_SEC: u64 = 1_000_000_000;
 
-pub fn map_capnp_err<T, E: Display>(result: Result<T, E>) -> Result<T, capnp::Error> {
-    result.map_err(|err| capnp::Error::failed(format!("{}", err)))
+// The capnp RPC system prefixes the descriptions of errors that came from
+// the other side of a connection with this.
+const REMOTE_EXCEPTION_PREFIX: &str = "remote exception: ";
+
+/// Sets a property value on a builder that has both a `value` and a
+/// `packed_value` field. Values are packed as MessagePack, so that large
+/// values aren't sent as JSON strings, unless the `json-property-values`
+/// feature is enabled, which is handy for inspecting messages.
+macro_rules! set_property_value {
+    ($builder:expr, $value:expr) => {
+        if cfg!(feature = "json-property-values") {
+            $builder.set_value(&$value.to_string());
+        } else {
+            $builder.set_packed_value(&$crate::converters::pack_property_value($value));
+        }
+    };
+}
+
+/// An error that can be sent to clients with a stable error code. See
+/// `coded_error`.
+pub trait ErrorCode: Display {
+    /// Gets the error's code.
+    fn error_code(&self) -> &'static str;
+}
+
+impl ErrorCode for indradb::Error {
+    fn error_code(&self) -> &'static str {
+        self.kind().code()
+    }
+}
+
+impl ErrorCode for indradb::ValidationError {
+    fn error_code(&self) -> &'static str {
+        "validation"
+    }
+}
+
+/// Creates an error to send to clients. Capnp errors only carry a
+/// description, so the code goes at the start of it in square brackets,
+/// e.g. `[validation] invalid type`, where clients can read it back with
+/// `error_code`. Codes are stable and made of lowercase letters and
+/// underscores; see `indradb::ErrorKind::code` for the codes of datastore
+/// errors.
+///
+/// # Arguments
+/// * `code` - The error code.
+/// * `message` - The error message.
+pub fn coded_error<M: Display>(code: &str, message: M) -> CapnpError {
+    CapnpError::failed(format!("[{}] {}", code, message))
+}
+
+pub fn map_capnp_err<T, E: ErrorCode>(result: Result<T, E>) -> Result<T, capnp::Error> {
+    result.map_err(|err| coded_error(err.error_code(), err))
+}
+
+// Gets the part of an error's description that's the code and message,
+// without the prefix added to errors from the other side of a connection.
+fn coded_description(err: &CapnpError) -> &str {
+    if err.description.starts_with(REMOTE_EXCEPTION_PREFIX) {
+        &err.description[REMOTE_EXCEPTION_PREFIX.len()..]
+    } else {
+        &err.description
+    }
+}
+
+/// Reads the code of an error made by `coded_error`, if it has one.
+///
+/// # Arguments
+/// * `err` - The error.
+pub fn error_code(err: &CapnpError) -> Option<&str> {
+    let description = coded_description(err);
+
+    if description.starts_with('[') {
+        description.find(']').map(|end| &description[1..end])
+    } else {
+        None
+    }
+}
+
+/// Converts an error from the server back into a datastore error, keeping
+/// its kind when the code identifies one. Errors without a code, or whose
+/// code doesn't map to a kind, become plain message errors with the full
+/// description, so the code can still be read from them.
+///
+/// # Arguments
+/// * `err` - The error.
+pub fn to_indradb_error(err: CapnpError) -> indradb::Error {
+    let code = match error_code(&err) {
+        Some(code) => code.to_string(),
+        None => return err.description.into(),
+    };
+
+    // Skip past the bracketed code and the space after it
+    let message = coded_description(&err).get(code.len() + 3..).unwrap_or("").to_string();
+
+    // Kinds that carry details were displayed as their description
+    // followed by the details
+    let details = || match message.find(": ") {
+        Some(index) => message[index + 2..].to_string(),
+        None => message.clone(),
+    };
+
+    match &code[..] {
+        "validation" => indradb::ValidationError::from(message.clone()).into(),
+        "traversal_limit_exceeded" => indradb::ErrorKind::TraversalLimitExceeded(details()).into(),
+        "bulk_insert_conflict" => indradb::ErrorKind::BulkInsertConflict(details()).into(),
+        "duplicate_bulk_insert_edges" => indradb::ErrorKind::DuplicateBulkInsertEdges(details()).into(),
+        "dangling_bulk_insert_edges" => indradb::ErrorKind::DanglingBulkInsertEdges(details()).into(),
+        "lock_poisoned" => indradb::ErrorKind::LockPoisoned.into(),
+        _ => err.description.into(),
+    }
+}
+
+/// Reads a UUID from the bytes a client sent, reporting how many bytes
+/// there were if it's not exactly 16.
+pub fn to_uuid(bytes: &[u8]) -> Result<Uuid, CapnpError> {
+    if bytes.len() != 16 {
+        return Err(coded_error(
+            "validation",
+            format!("expected 16-byte UUID, got {} bytes", bytes.len()),
+        ));
+    }
+
+    Uuid::from_slice(bytes).map_err(|err| coded_error("validation", err))
+}
+
+pub fn pack_property_value(value: &JsonValue) -> Vec<u8> {
+    rmp_serde::to_vec(value).expect("Expected JSON values to always be encodable as MessagePack")
+}
+
+/// Reads a property value, whichever way it was encoded. An empty JSON
+/// value isn't valid JSON, so rather than failing with a parse error, it's
+/// reported as a missing value.
+pub fn to_property_value(value: &str, packed_value: &[u8]) -> Result<JsonValue, CapnpError> {
+    if packed_value.is_empty() {
+        if value.is_empty() {
+            return Err(coded_error("validation", "property value missing"));
+        }
+
+        serde_json::from_str(value).map_err(|err| coded_error("validation", err))
+    } else {
+        rmp_serde::from_slice(packed_value).map_err(|err| coded_error("validation", err))
+    }
 }
 
 pub fn from_vertex<'a>(vertex: &indradb::Vertex, mut builder: autogen::vertex::Builder<'a>) {
@@ -22,13 +166,19 @@ pub fn from_vertex<'a>(vertex: &indradb::Vertex, mut builder: autogen::vertex::B
 }
 
 pub fn to_vertex<'a>(reader: &autogen::vertex::Reader<'a>) -> Result<indradb::Vertex, CapnpError> {
-    let id = map_capnp_err(Uuid::from_slice(reader.get_id()?))?;
+    let id = to_uuid(reader.get_id()?)?;
     let t = map_capnp_err(indradb::Type::new(reader.get_type()?.to_string()))?;
     Ok(indradb::Vertex::with_id(id, t))
 }
 
 pub fn from_edge<'a>(edge: &indradb::Edge, mut builder: autogen::edge::Builder<'a>) -> Result<(), CapnpError> {
     builder.set_created_datetime(edge.created_datetime.timestamp() as u64);
+
+    if let Some(weight) = edge.weight {
+        builder.set_weight(weight);
+        builder.set_has_weight(true);
+    }
+
     from_edge_key(&edge.key, builder.init_key());
     Ok(())
 }
@@ -36,7 +186,12 @@ pub fn from_edge<'a>(edge: &indradb::Edge, mut builder: autogen::edge::Builder<'
 pub fn to_edge<'a>(reader: &autogen::edge::Reader<'a>) -> Result<indradb::Edge, CapnpError> {
     let key = to_edge_key(&reader.get_key()?)?;
     let created_datetime = Utc.timestamp(reader.get_created_datetime() as i64, 0);
-    Ok(indradb::Edge::new(key, created_datetime))
+    let weight = if reader.get_has_weight() {
+        Some(reader.get_weight())
+    } else {
+        None
+    };
+    Ok(indradb::Edge::with_weight(key, created_datetime, weight))
 }
 
 pub fn from_edge_key<'a>(key: &indradb::EdgeKey, mut builder: autogen::edge_key::Builder<'a>) {
@@ -46,9 +201,9 @@ pub fn from_edge_key<'a>(key: &indradb::EdgeKey, mut builder: autogen::edge_key:
 }
 
 pub fn to_edge_key<'a>(reader: &autogen::edge_key::Reader<'a>) -> Result<indradb::EdgeKey, CapnpError> {
-    let outbound_id = map_capnp_err(Uuid::from_slice(reader.get_outbound_id()?))?;
+    let outbound_id = to_uuid(reader.get_outbound_id()?)?;
     let t = map_capnp_err(indradb::Type::new(reader.get_type()?.to_string()))?;
-    let inbound_id = map_capnp_err(Uuid::from_slice(reader.get_inbound_id()?))?;
+    let inbound_id = to_uuid(reader.get_inbound_id()?)?;
     Ok(indradb::EdgeKey::new(outbound_id, t, inbound_id))
 }
 
@@ -57,37 +212,203 @@ pub fn from_vertex_property<'a>(
     mut builder: autogen::vertex_property::Builder<'a>,
 ) {
     builder.set_id(property.id.as_bytes());
-    builder.set_value(&property.value.to_string());
+    builder.set_name(&property.name);
+    set_property_value!(builder, &property.value);
 }
 
 pub fn to_vertex_property<'a>(
     reader: &autogen::vertex_property::Reader<'a>,
 ) -> Result<indradb::VertexProperty, CapnpError> {
-    let id = map_capnp_err(Uuid::from_slice(reader.get_id()?))?;
-    let value = map_capnp_err(serde_json::from_str(reader.get_value()?))?;
-    Ok(indradb::VertexProperty::new(id, value))
+    let id = to_uuid(reader.get_id()?)?;
+    let name = reader.get_name()?.to_string();
+    let value = to_property_value(reader.get_value()?, reader.get_packed_value()?)?;
+    Ok(indradb::VertexProperty::new(id, name, value))
 }
 
 pub fn from_edge_property<'a>(property: &indradb::EdgeProperty, mut builder: autogen::edge_property::Builder<'a>) {
-    builder.set_value(&property.value.to_string());
+    builder.set_name(&property.name);
+    set_property_value!(builder, &property.value);
     from_edge_key(&property.key, builder.init_key());
 }
 
 pub fn to_edge_property<'a>(reader: &autogen::edge_property::Reader<'a>) -> Result<indradb::EdgeProperty, CapnpError> {
     let key = to_edge_key(&reader.get_key()?)?;
-    let value = map_capnp_err(serde_json::from_str(reader.get_value()?))?;
-    Ok(indradb::EdgeProperty::new(key, value))
+    let name = reader.get_name()?.to_string();
+    let value = to_property_value(reader.get_value()?, reader.get_packed_value()?)?;
+    Ok(indradb::EdgeProperty::new(key, name, value))
+}
+
+pub fn from_named_property<'a>(property: &indradb::NamedProperty, mut builder: autogen::property::Builder<'a>) {
+    builder.set_name(&property.name);
+    set_property_value!(builder, &property.value);
+}
+
+pub fn to_named_property<'a>(reader: &autogen::property::Reader<'a>) -> Result<indradb::NamedProperty, CapnpError> {
+    let name = reader.get_name()?.to_string();
+    let value = to_property_value(reader.get_value()?, reader.get_packed_value()?)?;
+    Ok(indradb::NamedProperty::new(name, value))
+}
+
+pub fn from_edge_record<'a>(
+    record: &indradb::EdgeRecord,
+    mut builder: autogen::edge_record::Builder<'a>,
+) -> Result<(), CapnpError> {
+    from_edge(&record.edge, builder.reborrow().init_edge())?;
+    let mut properties = builder.init_properties(record.properties.len() as u32);
+
+    for (i, property) in record.properties.iter().enumerate() {
+        from_named_property(property, properties.reborrow().get(i as u32));
+    }
+
+    Ok(())
+}
+
+pub fn to_edge_record<'a>(reader: &autogen::edge_record::Reader<'a>) -> Result<indradb::EdgeRecord, CapnpError> {
+    let edge = to_edge(&reader.get_edge()?)?;
+    let properties: Result<Vec<indradb::NamedProperty>, CapnpError> = reader
+        .get_properties()?
+        .into_iter()
+        .map(|reader| to_named_property(&reader))
+        .collect();
+    Ok(indradb::EdgeRecord::new(edge, properties?))
+}
+
+pub fn from_full_record<'a>(
+    record: &indradb::FullRecord,
+    mut builder: autogen::full_record::Builder<'a>,
+) -> Result<(), CapnpError> {
+    from_vertex(&record.vertex, builder.reborrow().init_vertex());
+
+    {
+        let mut properties = builder.reborrow().init_properties(record.properties.len() as u32);
+
+        for (i, property) in record.properties.iter().enumerate() {
+            from_named_property(property, properties.reborrow().get(i as u32));
+        }
+    }
+
+    {
+        let mut outbound_edges = builder
+            .reborrow()
+            .init_outbound_edges(record.outbound_edges.len() as u32);
+
+        for (i, edge_record) in record.outbound_edges.iter().enumerate() {
+            from_edge_record(edge_record, outbound_edges.reborrow().get(i as u32))?;
+        }
+    }
+
+    let mut inbound_edges = builder.init_inbound_edges(record.inbound_edges.len() as u32);
+
+    for (i, edge_record) in record.inbound_edges.iter().enumerate() {
+        from_edge_record(edge_record, inbound_edges.reborrow().get(i as u32))?;
+    }
+
+    Ok(())
+}
+
+pub fn to_full_record<'a>(reader: &autogen::full_record::Reader<'a>) -> Result<indradb::FullRecord, CapnpError> {
+    let vertex = to_vertex(&reader.get_vertex()?)?;
+    let properties: Result<Vec<indradb::NamedProperty>, CapnpError> = reader
+        .get_properties()?
+        .into_iter()
+        .map(|reader| to_named_property(&reader))
+        .collect();
+    let outbound_edges: Result<Vec<indradb::EdgeRecord>, CapnpError> = reader
+        .get_outbound_edges()?
+        .into_iter()
+        .map(|reader| to_edge_record(&reader))
+        .collect();
+    let inbound_edges: Result<Vec<indradb::EdgeRecord>, CapnpError> = reader
+        .get_inbound_edges()?
+        .into_iter()
+        .map(|reader| to_edge_record(&reader))
+        .collect();
+    Ok(indradb::FullRecord::new(
+        vertex,
+        properties?,
+        outbound_edges?,
+        inbound_edges?,
+    ))
+}
+
+pub fn from_type_counts<'a>(
+    counts: &BTreeMap<indradb::Type, u64>,
+    mut builder: capnp::struct_list::Builder<'a, autogen::type_count::Owned>,
+) {
+    for (i, (t, count)) in counts.iter().enumerate() {
+        let mut type_count = builder.reborrow().get(i as u32);
+        type_count.set_type(&t.0);
+        type_count.set_count(*count);
+    }
+}
+
+pub fn to_type_counts<'a>(
+    reader: &capnp::struct_list::Reader<'a, autogen::type_count::Owned>,
+) -> Result<BTreeMap<indradb::Type, u64>, CapnpError> {
+    let mut counts = BTreeMap::new();
+
+    for type_count in reader.into_iter() {
+        let t = map_capnp_err(indradb::Type::new(type_count.get_type()?.to_string()))?;
+        counts.insert(t, type_count.get_count());
+    }
+
+    Ok(counts)
+}
+
+pub fn from_graph_stats<'a>(stats: &indradb::GraphStats, mut builder: autogen::graph_stats::Builder<'a>) {
+    builder.set_vertices(stats.vertices);
+    builder.set_edges(stats.edges);
+    builder.set_vertex_properties(stats.vertex_properties);
+    builder.set_edge_properties(stats.edge_properties);
+    builder.set_average_degree(stats.average_degree);
+    from_type_counts(
+        &stats.vertex_types,
+        builder.init_vertex_types(stats.vertex_types.len() as u32),
+    );
+}
+
+pub fn to_graph_stats<'a>(reader: &autogen::graph_stats::Reader<'a>) -> Result<indradb::GraphStats, CapnpError> {
+    let vertex_types = to_type_counts(&reader.get_vertex_types()?)?;
+
+    Ok(indradb::GraphStats {
+        vertices: reader.get_vertices(),
+        edges: reader.get_edges(),
+        vertex_properties: reader.get_vertex_properties(),
+        edge_properties: reader.get_edge_properties(),
+        vertex_types,
+        average_degree: reader.get_average_degree(),
+    })
 }
 
 pub fn from_vertex_query<'a>(q: &indradb::VertexQuery, builder: autogen::vertex_query::Builder<'a>) {
     match q {
-        indradb::VertexQuery::All { start_id, limit } => {
+        indradb::VertexQuery::All {
+            start_id,
+            end_id,
+            high_filter,
+            low_filter,
+            limit,
+        } => {
             let mut builder = builder.init_all();
 
             if let Some(start_id) = start_id {
                 builder.set_start_id(start_id.as_bytes());
             }
 
+            if let Some(end_id) = end_id {
+                builder.set_end_id(end_id.as_bytes());
+            }
+
+            if let Some(high_filter) = high_filter {
+                builder.set_high_filter(high_filter.timestamp_nanos() as u64);
+                builder.set_has_high_filter(true);
+            }
+
+            if let Some(low_filter) = low_filter {
+                builder.set_low_filter(low_filter.timestamp_nanos() as u64);
+                builder.set_has_low_filter(true);
+            }
+
             builder.set_limit(*limit);
         }
         indradb::VertexQuery::Vertices { ids } => {
@@ -97,16 +418,72 @@ pub fn from_vertex_query<'a>(q: &indradb::VertexQuery, builder: autogen::vertex_
                 builder.set(i as u32, id.as_bytes());
             }
         }
+        indradb::VertexQuery::Types { types, limit } => {
+            let mut builder = builder.init_types();
+            builder.set_limit(*limit);
+            let mut types_builder = builder.init_types(types.len() as u32);
+
+            for (i, t) in types.iter().enumerate() {
+                types_builder.set(i as u32, &t.0);
+            }
+        }
+        indradb::VertexQuery::PropertyContains { name, substring, limit } => {
+            let mut builder = builder.init_property_contains();
+            builder.set_name(name);
+            builder.set_substring(substring);
+            builder.set_limit(*limit);
+        }
         indradb::VertexQuery::Pipe {
             edge_query,
             converter,
+            offset,
             limit,
         } => {
             let mut builder = builder.init_pipe();
             builder.set_converter(from_edge_direction(*converter));
+            builder.set_offset(*offset);
             builder.set_limit(*limit);
             from_edge_query(&edge_query, builder.init_edge_query());
         }
+        indradb::VertexQuery::Traverse {
+            start,
+            direction,
+            type_filter,
+            depth,
+            limit,
+        } => {
+            let mut builder = builder.init_traverse();
+            builder.set_direction(from_edge_direction(*direction));
+
+            if let Some(type_filter) = type_filter {
+                builder.set_type_filter(&type_filter.0);
+            }
+
+            builder.set_depth(*depth);
+            builder.set_limit(*limit);
+            from_vertex_query(&start, builder.init_start());
+        }
+        indradb::VertexQuery::DegreeAtLeast {
+            start,
+            direction,
+            type_filter,
+            min,
+            limit,
+        } => {
+            let mut builder = builder.init_degree_at_least();
+            builder.set_direction(from_edge_direction(*direction));
+
+            if let Some(type_filter) = type_filter {
+                builder.set_type_filter(&type_filter.0);
+            }
+
+            builder.set_min(*min);
+            builder.set_limit(*limit);
+
+            if let Some(start) = start {
+                from_vertex_query(&start, builder.init_start());
+            }
+        }
     }
 }
 
@@ -114,34 +491,95 @@ pub fn to_vertex_query<'a>(reader: &autogen::vertex_query::Reader<'a>) -> Result
     match reader.which()? {
         autogen::vertex_query::All(params) => {
             let start_id_bytes = params.get_start_id()?;
+            let end_id_bytes = params.get_end_id()?;
 
             Ok(indradb::VertexQuery::All {
                 start_id: if start_id_bytes.is_empty() {
                     None
                 } else {
-                    Some(map_capnp_err(Uuid::from_slice(start_id_bytes))?)
+                    Some(to_uuid(start_id_bytes)?)
+                },
+                end_id: if end_id_bytes.is_empty() {
+                    None
+                } else {
+                    Some(to_uuid(end_id_bytes)?)
                 },
+                high_filter: to_optional_datetime(params.get_high_filter(), params.get_has_high_filter()),
+                low_filter: to_optional_datetime(params.get_low_filter(), params.get_has_low_filter()),
                 limit: params.get_limit(),
             })
         }
         autogen::vertex_query::Vertices(params) => {
-            let ids: Result<Vec<Uuid>, CapnpError> = params
-                .get_ids()?
+            let ids: Result<Vec<Uuid>, CapnpError> =
+                params.get_ids()?.into_iter().map(|bytes| to_uuid(bytes?)).collect();
+            Ok(indradb::VertexQuery::Vertices { ids: ids? })
+        }
+        autogen::vertex_query::Types(params) => {
+            let types: Result<Vec<indradb::Type>, CapnpError> = params
+                .get_types()?
                 .into_iter()
-                .map(|bytes| map_capnp_err(Uuid::from_slice(bytes?)))
+                .map(|t| map_capnp_err(indradb::Type::new(t?.to_string())))
                 .collect();
-            Ok(indradb::VertexQuery::Vertices { ids: ids? })
+            Ok(indradb::VertexQuery::Types {
+                types: types?,
+                limit: params.get_limit(),
+            })
         }
+        autogen::vertex_query::PropertyContains(params) => Ok(indradb::VertexQuery::PropertyContains {
+            name: params.get_name()?.to_string(),
+            substring: params.get_substring()?.to_string(),
+            limit: params.get_limit(),
+        }),
         autogen::vertex_query::Pipe(params) => {
             let edge_query = Box::new(to_edge_query(&params.get_edge_query()?)?);
             let converter = to_edge_direction(params.get_converter()?);
+            let offset = params.get_offset();
             let limit = params.get_limit();
             Ok(indradb::VertexQuery::Pipe {
                 edge_query,
                 converter,
+                offset,
+                limit,
+            })
+        }
+        autogen::vertex_query::Traverse(params) => {
+            let start = Box::new(to_vertex_query(&params.get_start()?)?);
+            let direction = to_edge_direction(params.get_direction()?);
+            let type_filter = match params.get_type_filter()? {
+                "" => None,
+                value => Some(map_capnp_err(indradb::Type::new(value.to_string()))?),
+            };
+            let depth = params.get_depth();
+            let limit = params.get_limit();
+
+            Ok(indradb::VertexQuery::Traverse {
+                start,
+                direction,
+                type_filter,
+                depth,
                 limit,
             })
         }
+        autogen::vertex_query::DegreeAtLeast(params) => {
+            let start = if params.has_start() {
+                Some(Box::new(to_vertex_query(&params.get_start()?)?))
+            } else {
+                None
+            };
+            let direction = to_edge_direction(params.get_direction()?);
+            let type_filter = match params.get_type_filter()? {
+                "" => None,
+                value => Some(map_capnp_err(indradb::Type::new(value.to_string()))?),
+            };
+
+            Ok(indradb::VertexQuery::DegreeAtLeast {
+                start,
+                direction,
+                type_filter,
+                min: params.get_min(),
+                limit: params.get_limit(),
+            })
+        }
     }
 }
 
@@ -154,12 +592,39 @@ pub fn from_edge_query<'a>(q: &indradb::EdgeQuery, builder: autogen::edge_query:
                 from_edge_key(key, builder.reborrow().get(i as u32));
             }
         }
+        indradb::EdgeQuery::All {
+            type_filter,
+            high_filter,
+            low_filter,
+            limit,
+        } => {
+            let mut builder = builder.init_all();
+
+            if let Some(type_filter) = type_filter {
+                builder.set_type_filter(&type_filter.0);
+            }
+
+            if let Some(high_filter) = high_filter {
+                builder.set_high_filter(high_filter.timestamp_nanos() as u64);
+                builder.set_has_high_filter(true);
+            }
+
+            if let Some(low_filter) = low_filter {
+                builder.set_low_filter(low_filter.timestamp_nanos() as u64);
+                builder.set_has_low_filter(true);
+            }
+
+            builder.set_limit(*limit);
+        }
         indradb::EdgeQuery::Pipe {
             vertex_query,
             converter,
             type_filter,
+            type_filters,
             high_filter,
             low_filter,
+            after_inbound_id,
+            offset,
             limit,
         } => {
             let mut builder = builder.init_pipe();
@@ -171,12 +636,27 @@ pub fn from_edge_query<'a>(q: &indradb::EdgeQuery, builder: autogen::edge_query:
 
             if let Some(high_filter) = high_filter {
                 builder.set_high_filter(high_filter.timestamp_nanos() as u64);
+                builder.set_has_high_filter(true);
             }
 
             if let Some(low_filter) = low_filter {
                 builder.set_low_filter(low_filter.timestamp_nanos() as u64);
+                builder.set_has_low_filter(true);
             }
 
+            {
+                let mut type_filters_builder = builder.reborrow().init_type_filters(type_filters.len() as u32);
+
+                for (i, t) in type_filters.iter().enumerate() {
+                    type_filters_builder.set(i as u32, &t.0);
+                }
+            }
+
+            if let Some(after_inbound_id) = after_inbound_id {
+                builder.set_after_inbound_id(after_inbound_id.as_bytes());
+            }
+
+            builder.set_offset(*offset);
             builder.set_limit(*limit);
             from_vertex_query(&vertex_query, builder.init_vertex_query());
         }
@@ -193,6 +673,21 @@ pub fn to_edge_query<'a>(reader: &autogen::edge_query::Reader<'a>) -> Result<ind
                 .collect();
             Ok(indradb::EdgeQuery::Edges { keys: keys? })
         }
+        autogen::edge_query::All(params) => {
+            let type_filter = match params.get_type_filter()? {
+                "" => None,
+                value => Some(map_capnp_err(indradb::Type::new(value.to_string()))?),
+            };
+            let high_filter = to_optional_datetime(params.get_high_filter(), params.get_has_high_filter());
+            let low_filter = to_optional_datetime(params.get_low_filter(), params.get_has_low_filter());
+
+            Ok(indradb::EdgeQuery::All {
+                type_filter,
+                high_filter,
+                low_filter,
+                limit: params.get_limit(),
+            })
+        }
         autogen::edge_query::Pipe(params) => {
             let vertex_query = Box::new(to_vertex_query(&params.get_vertex_query()?)?);
             let converter = to_edge_direction(params.get_converter()?);
@@ -200,16 +695,31 @@ pub fn to_edge_query<'a>(reader: &autogen::edge_query::Reader<'a>) -> Result<ind
                 "" => None,
                 value => Some(map_capnp_err(indradb::Type::new(value.to_string()))?),
             };
-            let high_filter = to_optional_datetime(params.get_high_filter());
-            let low_filter = to_optional_datetime(params.get_low_filter());
+            let type_filters: Result<Vec<indradb::Type>, CapnpError> = params
+                .get_type_filters()?
+                .into_iter()
+                .map(|t| map_capnp_err(indradb::Type::new(t?.to_string())))
+                .collect();
+            let high_filter = to_optional_datetime(params.get_high_filter(), params.get_has_high_filter());
+            let low_filter = to_optional_datetime(params.get_low_filter(), params.get_has_low_filter());
+            let after_inbound_id_bytes = params.get_after_inbound_id()?;
+            let after_inbound_id = if after_inbound_id_bytes.is_empty() {
+                None
+            } else {
+                Some(to_uuid(after_inbound_id_bytes)?)
+            };
+            let offset = params.get_offset();
             let limit = params.get_limit();
 
             Ok(indradb::EdgeQuery::Pipe {
                 vertex_query,
                 converter,
                 type_filter,
+                type_filters: type_filters?,
                 high_filter,
                 low_filter,
+                after_inbound_id,
+                offset,
                 limit,
             })
         }
@@ -236,12 +746,12 @@ pub fn from_bulk_insert_items<'a>(
                 let mut builder = builder.init_vertex_property();
                 builder.set_id(id.as_bytes());
                 builder.set_name(name);
-                builder.set_value(&value.to_string());
+                set_property_value!(builder, value);
             }
             indradb::BulkInsertItem::EdgeProperty(key, name, value) => {
                 let mut builder = builder.init_edge_property();
                 builder.set_name(name);
-                builder.set_value(&value.to_string());
+                set_property_value!(builder, value);
                 from_edge_key(key, builder.get_key()?);
             }
         }
@@ -265,15 +775,15 @@ pub fn to_bulk_insert_items<'a>(
                 Ok(indradb::BulkInsertItem::Edge(edge_key))
             }
             autogen::bulk_insert_item::VertexProperty(params) => {
-                let id = map_capnp_err(Uuid::from_slice(params.get_id()?))?;
+                let id = to_uuid(params.get_id()?)?;
                 let name = params.get_name()?.to_string();
-                let value = map_capnp_err(serde_json::from_str(params.get_value()?))?;
+                let value = to_property_value(params.get_value()?, params.get_packed_value()?)?;
                 Ok(indradb::BulkInsertItem::VertexProperty(id, name, value))
             }
             autogen::bulk_insert_item::EdgeProperty(params) => {
                 let key = to_edge_key(&params.get_key()?)?;
                 let name = params.get_name()?.to_string();
-                let value = map_capnp_err(serde_json::from_str(params.get_value()?))?;
+                let value = to_property_value(params.get_value()?, params.get_packed_value()?)?;
                 Ok(indradb::BulkInsertItem::EdgeProperty(key, name, value))
             }
         })
@@ -281,10 +791,142 @@ pub fn to_bulk_insert_items<'a>(
     Ok(items?.into_iter())
 }
 
+pub fn from_batch_operations<'a>(
+    operations: &[indradb::BatchOperation],
+    mut builder: capnp::struct_list::Builder<'a, autogen::batch_operation::Owned>,
+) -> Result<(), CapnpError> {
+    for (i, operation) in operations.iter().enumerate() {
+        let builder = builder.reborrow().get(i as u32);
+
+        match operation {
+            indradb::BatchOperation::CreateVertex(vertex) => {
+                let mut builder = builder.init_create_vertex();
+                from_vertex(vertex, builder.get_vertex()?);
+            }
+            indradb::BatchOperation::CreateEdge(key) => {
+                let mut builder = builder.init_create_edge();
+                from_edge_key(key, builder.get_key()?);
+            }
+            indradb::BatchOperation::DeleteVertices(q) => {
+                from_vertex_query(q, builder.init_delete_vertices().init_q());
+            }
+            indradb::BatchOperation::DeleteEdges(q) => {
+                from_edge_query(q, builder.init_delete_edges().init_q());
+            }
+            indradb::BatchOperation::SetVertexProperties(q, name, value) => {
+                let mut builder = builder.init_set_vertex_properties();
+                builder.set_name(name);
+                set_property_value!(builder, value);
+                from_vertex_query(q, builder.init_q());
+            }
+            indradb::BatchOperation::SetEdgeProperties(q, name, value) => {
+                let mut builder = builder.init_set_edge_properties();
+                builder.set_name(name);
+                set_property_value!(builder, value);
+                from_edge_query(q, builder.init_q());
+            }
+            indradb::BatchOperation::DeleteVertexProperties(q, name) => {
+                let mut builder = builder.init_delete_vertex_properties();
+                builder.set_name(name);
+                from_vertex_query(q, builder.init_q());
+            }
+            indradb::BatchOperation::DeleteEdgeProperties(q, name) => {
+                let mut builder = builder.init_delete_edge_properties();
+                builder.set_name(name);
+                from_edge_query(q, builder.init_q());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn to_batch_operations<'a>(
+    reader: &capnp::struct_list::Reader<'a, autogen::batch_operation::Owned>,
+) -> Result<IntoIter<indradb::BatchOperation>, CapnpError> {
+    let operations: Result<Vec<indradb::BatchOperation>, CapnpError> = reader
+        .into_iter()
+        .map(|operation| match operation.which()? {
+            autogen::batch_operation::CreateVertex(params) => {
+                let vertex = to_vertex(&params.get_vertex()?)?;
+                Ok(indradb::BatchOperation::CreateVertex(vertex))
+            }
+            autogen::batch_operation::CreateEdge(params) => {
+                let key = to_edge_key(&params.get_key()?)?;
+                Ok(indradb::BatchOperation::CreateEdge(key))
+            }
+            autogen::batch_operation::DeleteVertices(params) => {
+                let q = to_vertex_query(&params.get_q()?)?;
+                Ok(indradb::BatchOperation::DeleteVertices(q))
+            }
+            autogen::batch_operation::DeleteEdges(params) => {
+                let q = to_edge_query(&params.get_q()?)?;
+                Ok(indradb::BatchOperation::DeleteEdges(q))
+            }
+            autogen::batch_operation::SetVertexProperties(params) => {
+                let q = to_vertex_query(&params.get_q()?)?;
+                let name = params.get_name()?.to_string();
+                let value = to_property_value(params.get_value()?, params.get_packed_value()?)?;
+                Ok(indradb::BatchOperation::SetVertexProperties(q, name, value))
+            }
+            autogen::batch_operation::SetEdgeProperties(params) => {
+                let q = to_edge_query(&params.get_q()?)?;
+                let name = params.get_name()?.to_string();
+                let value = to_property_value(params.get_value()?, params.get_packed_value()?)?;
+                Ok(indradb::BatchOperation::SetEdgeProperties(q, name, value))
+            }
+            autogen::batch_operation::DeleteVertexProperties(params) => {
+                let q = to_vertex_query(&params.get_q()?)?;
+                let name = params.get_name()?.to_string();
+                Ok(indradb::BatchOperation::DeleteVertexProperties(q, name))
+            }
+            autogen::batch_operation::DeleteEdgeProperties(params) => {
+                let q = to_edge_query(&params.get_q()?)?;
+                let name = params.get_name()?.to_string();
+                Ok(indradb::BatchOperation::DeleteEdgeProperties(q, name))
+            }
+        })
+        .collect();
+    Ok(operations?.into_iter())
+}
+
+pub fn from_bulk_insert_results<'a>(
+    results: &[Result<(), indradb::BulkInsertItemError>],
+    mut builder: capnp::struct_list::Builder<'a, autogen::bulk_insert_result::Owned>,
+) {
+    for (i, result) in results.iter().enumerate() {
+        let mut builder = builder.reborrow().get(i as u32);
+
+        match result {
+            Ok(()) => builder.set_success(()),
+            Err(indradb::BulkInsertItemError::MissingVertex) => builder.set_missing_vertex(()),
+            Err(indradb::BulkInsertItemError::MissingEdge) => builder.set_missing_edge(()),
+            Err(indradb::BulkInsertItemError::Internal(message)) => builder.set_internal(message),
+        }
+    }
+}
+
+pub fn to_bulk_insert_results<'a>(
+    reader: &capnp::struct_list::Reader<'a, autogen::bulk_insert_result::Owned>,
+) -> Result<Vec<Result<(), indradb::BulkInsertItemError>>, CapnpError> {
+    reader
+        .into_iter()
+        .map(|result| match result.which()? {
+            autogen::bulk_insert_result::Success(()) => Ok(Ok(())),
+            autogen::bulk_insert_result::MissingVertex(()) => Ok(Err(indradb::BulkInsertItemError::MissingVertex)),
+            autogen::bulk_insert_result::MissingEdge(()) => Ok(Err(indradb::BulkInsertItemError::MissingEdge)),
+            autogen::bulk_insert_result::Internal(message) => {
+                Ok(Err(indradb::BulkInsertItemError::Internal(message?.to_string())))
+            }
+        })
+        .collect()
+}
+
 pub fn from_edge_direction(direction: indradb::EdgeDirection) -> autogen::EdgeDirection {
     match direction {
         indradb::EdgeDirection::Outbound => autogen::EdgeDirection::Outbound,
         indradb::EdgeDirection::Inbound => autogen::EdgeDirection::Inbound,
+        indradb::EdgeDirection::Both => autogen::EdgeDirection::Both,
     }
 }
 
@@ -292,11 +934,96 @@ pub fn to_edge_direction(direction: autogen::EdgeDirection) -> indradb::EdgeDire
     match direction {
         autogen::EdgeDirection::Outbound => indradb::EdgeDirection::Outbound,
         autogen::EdgeDirection::Inbound => indradb::EdgeDirection::Inbound,
+        autogen::EdgeDirection::Both => indradb::EdgeDirection::Both,
+    }
+}
+
+pub fn from_sort_direction(direction: indradb::SortDirection) -> autogen::SortDirection {
+    match direction {
+        indradb::SortDirection::Ascending => autogen::SortDirection::Ascending,
+        indradb::SortDirection::Descending => autogen::SortDirection::Descending,
+    }
+}
+
+pub fn to_sort_direction(direction: autogen::SortDirection) -> indradb::SortDirection {
+    match direction {
+        autogen::SortDirection::Ascending => indradb::SortDirection::Ascending,
+        autogen::SortDirection::Descending => indradb::SortDirection::Descending,
     }
 }
 
-pub fn to_optional_datetime(timestamp: u64) -> Option<DateTime<Utc>> {
-    if timestamp == 0 {
+pub fn from_aggregate_op(op: indradb::AggregateOp) -> autogen::AggregateOp {
+    match op {
+        indradb::AggregateOp::Sum => autogen::AggregateOp::Sum,
+        indradb::AggregateOp::Avg => autogen::AggregateOp::Avg,
+        indradb::AggregateOp::Min => autogen::AggregateOp::Min,
+        indradb::AggregateOp::Max => autogen::AggregateOp::Max,
+        indradb::AggregateOp::Count => autogen::AggregateOp::Count,
+    }
+}
+
+pub fn to_aggregate_op(op: autogen::AggregateOp) -> indradb::AggregateOp {
+    match op {
+        autogen::AggregateOp::Sum => indradb::AggregateOp::Sum,
+        autogen::AggregateOp::Avg => indradb::AggregateOp::Avg,
+        autogen::AggregateOp::Min => indradb::AggregateOp::Min,
+        autogen::AggregateOp::Max => indradb::AggregateOp::Max,
+        autogen::AggregateOp::Count => indradb::AggregateOp::Count,
+    }
+}
+
+pub fn from_aggregate_result<'a>(result: Option<f64>, mut builder: autogen::aggregate_result::Builder<'a>) {
+    match result {
+        Some(value) => builder.set_value(value),
+        None => builder.set_empty(()),
+    }
+}
+
+pub fn to_aggregate_result<'a>(reader: &autogen::aggregate_result::Reader<'a>) -> Result<Option<f64>, CapnpError> {
+    match reader.which()? {
+        autogen::aggregate_result::Empty(()) => Ok(None),
+        autogen::aggregate_result::Value(value) => Ok(Some(value)),
+    }
+}
+
+pub fn from_property_condition<'a>(
+    condition: &indradb::PropertyCondition,
+    mut builder: autogen::property_condition::Builder<'a>,
+) {
+    builder.set_name(&condition.name);
+    builder.set_operator(match condition.operator {
+        indradb::ComparisonOperator::Equal => autogen::ComparisonOperator::Equal,
+        indradb::ComparisonOperator::NotEqual => autogen::ComparisonOperator::NotEqual,
+        indradb::ComparisonOperator::GreaterThan => autogen::ComparisonOperator::GreaterThan,
+        indradb::ComparisonOperator::GreaterThanOrEqual => autogen::ComparisonOperator::GreaterThanOrEqual,
+        indradb::ComparisonOperator::LessThan => autogen::ComparisonOperator::LessThan,
+        indradb::ComparisonOperator::LessThanOrEqual => autogen::ComparisonOperator::LessThanOrEqual,
+    });
+    builder.set_value(&condition.value.to_string());
+}
+
+pub fn to_property_condition<'a>(
+    reader: &autogen::property_condition::Reader<'a>,
+) -> Result<indradb::PropertyCondition, CapnpError> {
+    let name = reader.get_name()?.to_string();
+    let operator = match reader.get_operator()? {
+        autogen::ComparisonOperator::Equal => indradb::ComparisonOperator::Equal,
+        autogen::ComparisonOperator::NotEqual => indradb::ComparisonOperator::NotEqual,
+        autogen::ComparisonOperator::GreaterThan => indradb::ComparisonOperator::GreaterThan,
+        autogen::ComparisonOperator::GreaterThanOrEqual => indradb::ComparisonOperator::GreaterThanOrEqual,
+        autogen::ComparisonOperator::LessThan => indradb::ComparisonOperator::LessThan,
+        autogen::ComparisonOperator::LessThanOrEqual => indradb::ComparisonOperator::LessThanOrEqual,
+    };
+    let value = to_property_value(reader.get_value()?, &[])?;
+    Ok(indradb::PropertyCondition::new(name, operator, value))
+}
+
+/// Converts a nanosecond timestamp to a datetime, if it's set. The epoch is
+/// a valid timestamp, so whether it's set is sent separately; but older
+/// clients only mark unset timestamps by leaving them as zero, so non-zero
+/// timestamps are always considered set.
+pub fn to_optional_datetime(timestamp: u64, is_set: bool) -> Option<DateTime<Utc>> {
+    if !is_set && timestamp == 0 {
         None
     } else {
         let secs = timestamp / NANOS_PER_SEC;
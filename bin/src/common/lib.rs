@@ -12,6 +12,7 @@ extern crate futures_cpupool;
 extern crate lazy_static;
 extern crate libc;
 extern crate regex;
+extern crate rmp_serde;
 extern crate serde;
 extern crate serde_json;
 extern crate tokio_core;
@@ -1,40 +1,101 @@
 use autogen;
 use capnp::capability::Promise;
 use capnp::Error as CapnpError;
+use capnp::ErrorKind as CapnpErrorKind;
 use capnp_rpc::rpc_twoparty_capnp::Side;
 use capnp_rpc::twoparty::VatNetwork;
 use capnp_rpc::{RpcSystem, Server};
 use converters;
 use errors;
 use futures::{Future, Stream};
-use futures_cpupool::CpuPool;
+use futures_cpupool::{CpuFuture, CpuPool};
 use indradb;
 use indradb::{
-    Datastore as IndraDbDatastore, Edge, EdgeProperty, MemoryDatastore, RocksdbDatastore,
-    Transaction as IndraDbTransaction, Type, Vertex, VertexProperty,
+    BulkInsertItemError, Datastore as IndraDbDatastore, Edge, EdgeProperty, FullRecord, GraphStats, MemoryDatastore,
+    QueryError, RocksdbDatastore, Transaction as IndraDbTransaction, Type, Vertex, VertexProperty,
 };
-use serde_json;
+use std::collections::BTreeMap;
 use std::env;
 use std::net::SocketAddr;
 use std::net::ToSocketAddrs;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio_core::net::TcpListener;
-use tokio_core::reactor::Core;
+use tokio_core::reactor::{Core, Handle, Timeout};
 use tokio_io::AsyncRead;
 use uuid::Uuid;
 
+// Timeouts are reported as overloaded, rather than failed, since retrying
+// them later may work.
+fn timed_out() -> CapnpError {
+    let mut err = converters::coded_error("timed_out", "Operation timed out");
+    err.kind = CapnpErrorKind::Overloaded;
+    err
+}
+
+// Races an operation against the request timeout, if one is configured. Note
+// that when the timeout wins, the operation is not cancelled; it will still
+// run to completion on the worker pool, but its result is discarded.
+fn with_timeout<F>(handle: &Handle, timeout: Option<Duration>, f: F) -> Promise<(), CapnpError>
+where
+    F: Future<Item = (), Error = CapnpError> + 'static,
+{
+    match timeout {
+        Some(timeout) => {
+            let timer = pry!(Timeout::new(timeout, handle).map_err(|err| converters::coded_error("internal", err)));
+            let timer = timer.then(|_| -> Result<(), CapnpError> { Err(timed_out()) });
+            Promise::from_future(timer.select(f).map(|(value, _)| value).map_err(|(err, _)| err))
+        }
+        None => Promise::from_future(f),
+    }
+}
+
+// Runs an operation on the worker pool. If a request timeout is configured
+// and it has already elapsed by the time a worker picks up the operation,
+// e.g. because the pool was busy with slow operations, the operation is
+// skipped rather than run on behalf of a client that stopped waiting.
+fn spawn_with_deadline<F, R>(pool: &CpuPool, timeout: Option<Duration>, f: F) -> CpuFuture<R, CapnpError>
+where
+    F: FnOnce() -> Result<R, CapnpError> + Send + 'static,
+    R: Send + 'static,
+{
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    pool.spawn_fn(move || -> Result<R, CapnpError> {
+        if let Some(deadline) = deadline {
+            if Instant::now() >= deadline {
+                return Err(timed_out());
+            }
+        }
+
+        f()
+    })
+}
+
 struct Service<D: IndraDbDatastore<Trans = T> + Send + Sync + 'static, T: IndraDbTransaction + Send + Sync + 'static> {
     datastore: Arc<D>,
     pool: CpuPool,
+    handle: Handle,
+    timeout: Option<Duration>,
+    max_query_limit: Option<u32>,
 }
 
 impl<D: IndraDbDatastore<Trans = T> + Send + Sync + 'static, T: IndraDbTransaction + Send + Sync + 'static>
     Service<D, T>
 {
-    fn new(datastore: D, worker_count: usize) -> Self {
+    fn new(
+        datastore: D,
+        worker_count: usize,
+        handle: Handle,
+        timeout: Option<Duration>,
+        max_query_limit: Option<u32>,
+    ) -> Self {
         Self {
             datastore: Arc::new(datastore),
             pool: CpuPool::new(worker_count),
+            handle,
+            timeout,
+            max_query_limit,
         }
     }
 }
@@ -51,6 +112,24 @@ impl<D: IndraDbDatastore<Trans = T> + Send + Sync + 'static, T: IndraDbTransacti
         Promise::ok(())
     }
 
+    fn health_check(
+        &mut self,
+        _: autogen::service::HealthCheckParams,
+        mut res: autogen::service::HealthCheckResults,
+    ) -> Promise<(), CapnpError> {
+        let datastore = self.datastore.clone();
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(datastore.health_check())
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
     fn bulk_insert(
         &mut self,
         req: autogen::service::BulkInsertParams,
@@ -60,18 +139,16 @@ impl<D: IndraDbDatastore<Trans = T> + Send + Sync + 'static, T: IndraDbTransacti
         let cnp_items = pry!(pry!(req.get()).get_items());
         let items = pry!(converters::to_bulk_insert_items(&cnp_items));
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<(), CapnpError> {
-                converters::map_capnp_err(datastore.bulk_insert(items))?;
-                Ok(())
-            })
-            .and_then(move |_| -> Result<(), CapnpError> {
-                res.get().set_result(());
-                Ok(())
-            });
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(datastore.bulk_insert(items))?;
+            Ok(())
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
 
-        Promise::from_future(f)
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn transaction(
@@ -80,25 +157,120 @@ impl<D: IndraDbDatastore<Trans = T> + Send + Sync + 'static, T: IndraDbTransacti
         mut res: autogen::service::TransactionResults,
     ) -> Promise<(), CapnpError> {
         let trans = pry!(converters::map_capnp_err(self.datastore.transaction()));
-        let trans_server = Transaction::new(self.pool.clone(), trans);
+        let trans_server = Transaction::new(
+            self.pool.clone(),
+            self.handle.clone(),
+            self.timeout,
+            self.max_query_limit,
+            trans,
+        );
         let trans_client = autogen::transaction::ToClient::new(trans_server).from_server::<Server>();
         res.get().set_transaction(trans_client);
         Promise::ok(())
     }
+
+    fn execute_batch(
+        &mut self,
+        req: autogen::service::ExecuteBatchParams,
+        mut res: autogen::service::ExecuteBatchResults,
+    ) -> Promise<(), CapnpError> {
+        let datastore = self.datastore.clone();
+        let cnp_operations = pry!(pry!(req.get()).get_operations());
+        let mut operations: Vec<indradb::BatchOperation> =
+            pry!(converters::to_batch_operations(&cnp_operations)).collect();
+
+        // Queries in a batch are held to the same maximum limit as queries
+        // sent through a transaction.
+        if let Some(max) = self.max_query_limit {
+            for operation in &mut operations {
+                match operation {
+                    indradb::BatchOperation::DeleteVertices(q)
+                    | indradb::BatchOperation::SetVertexProperties(q, _, _)
+                    | indradb::BatchOperation::DeleteVertexProperties(q, _) => {
+                        q.clamp_limits(max);
+                    }
+                    indradb::BatchOperation::DeleteEdges(q)
+                    | indradb::BatchOperation::SetEdgeProperties(q, _, _)
+                    | indradb::BatchOperation::DeleteEdgeProperties(q, _) => {
+                        q.clamp_limits(max);
+                    }
+                    indradb::BatchOperation::CreateVertex(_) | indradb::BatchOperation::CreateEdge(_) => {}
+                }
+            }
+        }
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(datastore.execute_batch(operations.into_iter()))?;
+            Ok(())
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn bulk_insert_partial(
+        &mut self,
+        req: autogen::service::BulkInsertPartialParams,
+        mut res: autogen::service::BulkInsertPartialResults,
+    ) -> Promise<(), CapnpError> {
+        let datastore = self.datastore.clone();
+        let cnp_items = pry!(pry!(req.get()).get_items());
+        let items = pry!(converters::to_bulk_insert_items(&cnp_items));
+
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<Vec<Result<(), BulkInsertItemError>>, CapnpError> {
+                converters::map_capnp_err(datastore.bulk_insert_partial(items))
+            },
+        )
+        .and_then(move |results| -> Result<(), CapnpError> {
+            converters::from_bulk_insert_results(&results, res.get().init_result(results.len() as u32));
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
 }
 
 struct Transaction<T: IndraDbTransaction + Send + Sync + 'static> {
     pool: CpuPool,
+    handle: Handle,
+    timeout: Option<Duration>,
+    max_query_limit: Option<u32>,
     trans: Arc<T>,
 }
 
 impl<T: IndraDbTransaction + Send + Sync + 'static> Transaction<T> {
-    fn new(pool: CpuPool, trans: T) -> Self {
+    fn new(pool: CpuPool, handle: Handle, timeout: Option<Duration>, max_query_limit: Option<u32>, trans: T) -> Self {
         Self {
             pool,
+            handle,
+            timeout,
+            max_query_limit,
             trans: Arc::new(trans),
         }
     }
+
+    // Lowers a limit to the configured maximum, if there is one.
+    fn clamp_limit(&self, limit: u32) -> u32 {
+        self.max_query_limit.map_or(limit, |max| limit.min(max))
+    }
+
+    // Lowers the limits of a vertex query to the configured maximum, if
+    // there is one. Returns whether any limit was lowered.
+    fn clamp_vertex_query(&self, q: &mut indradb::VertexQuery) -> bool {
+        self.max_query_limit.map_or(false, |max| q.clamp_limits(max))
+    }
+
+    // Lowers the limits of an edge query to the configured maximum, if there
+    // is one. Returns whether any limit was lowered.
+    fn clamp_edge_query(&self, q: &mut indradb::EdgeQuery) -> bool {
+        self.max_query_limit.map_or(false, |max| q.clamp_limits(max))
+    }
 }
 
 impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server for Transaction<T> {
@@ -111,15 +283,15 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
         let cnp_vertex = pry!(pry!(req.get()).get_vertex());
         let vertex = pry!(converters::to_vertex(&cnp_vertex));
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<bool, CapnpError> { converters::map_capnp_err(trans.create_vertex(&vertex)) })
-            .and_then(move |created| -> Result<(), CapnpError> {
-                res.get().set_result(created);
-                Ok(())
-            });
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<bool, CapnpError> {
+            converters::map_capnp_err(trans.create_vertex(&vertex))
+        })
+        .and_then(move |created| -> Result<(), CapnpError> {
+            res.get().set_result(created);
+            Ok(())
+        });
 
-        Promise::from_future(f)
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn create_vertex_from_type(
@@ -131,17 +303,42 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
         let cnp_t = pry!(pry!(req.get()).get_t());
         let t = pry!(converters::map_capnp_err(indradb::Type::new(cnp_t.to_string())));
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<Uuid, CapnpError> {
-                converters::map_capnp_err(trans.create_vertex_from_type(t))
-            })
-            .and_then(move |id| -> Result<(), CapnpError> {
-                res.get().set_result(id.as_bytes());
-                Ok(())
-            });
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<Uuid, CapnpError> {
+            converters::map_capnp_err(trans.create_vertex_from_type(t))
+        })
+        .and_then(move |id| -> Result<(), CapnpError> {
+            res.get().set_result(id.as_bytes());
+            Ok(())
+        });
 
-        Promise::from_future(f)
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_or_create_vertex(
+        &mut self,
+        req: autogen::transaction::GetOrCreateVertexParams,
+        mut res: autogen::transaction::GetOrCreateVertexResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let id = pry!(converters::to_uuid(pry!(params.get_id())));
+        let cnp_t = pry!(params.get_t());
+        let t = pry!(converters::map_capnp_err(indradb::Type::new(cnp_t.to_string())));
+
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<(Vertex, bool), CapnpError> {
+                converters::map_capnp_err(trans.get_or_create_vertex(id, &t))
+            },
+        )
+        .and_then(move |(vertex, created)| -> Result<(), CapnpError> {
+            res.get().set_created(created);
+            converters::from_vertex(&vertex, res.get().init_result());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn get_vertices(
@@ -151,22 +348,62 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
     ) -> Promise<(), CapnpError> {
         let trans = self.trans.clone();
         let cnp_q = pry!(pry!(req.get()).get_q());
-        let q = pry!(converters::to_vertex_query(&cnp_q));
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        let clamped = self.clamp_vertex_query(&mut q);
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<Vec<Vertex>, CapnpError> {
+            converters::map_capnp_err(trans.get_vertices(&q))
+        })
+        .and_then(move |vertices| -> Result<(), CapnpError> {
+            res.get().set_clamped(clamped);
+            let mut res = res.get().init_result(vertices.len() as u32);
+
+            for (i, vertex) in vertices.into_iter().enumerate() {
+                converters::from_vertex(&vertex, res.reborrow().get(i as u32));
+            }
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<Vec<Vertex>, CapnpError> { converters::map_capnp_err(trans.get_vertices(&q)) })
-            .and_then(move |vertices| -> Result<(), CapnpError> {
-                let mut res = res.get().init_result(vertices.len() as u32);
+            Ok(())
+        });
 
-                for (i, vertex) in vertices.into_iter().enumerate() {
-                    converters::from_vertex(&vertex, res.reborrow().get(i as u32));
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_vertices_lenient(
+        &mut self,
+        req: autogen::transaction::GetVerticesLenientParams,
+        mut res: autogen::transaction::GetVerticesLenientResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let cnp_q = pry!(pry!(req.get()).get_q());
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
+
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<(Vec<Vertex>, Vec<QueryError>), CapnpError> {
+                converters::map_capnp_err(trans.get_vertices_lenient(&q))
+            },
+        )
+        .and_then(move |(vertices, errors)| -> Result<(), CapnpError> {
+            {
+                let mut errors_builder = res.get().init_errors(errors.len() as u32);
+
+                for (i, error) in errors.iter().enumerate() {
+                    errors_builder.set(i as u32, &error.message);
                 }
+            }
+
+            let mut res = res.get().init_result(vertices.len() as u32);
 
-                Ok(())
-            });
+            for (i, vertex) in vertices.into_iter().enumerate() {
+                converters::from_vertex(&vertex, res.reborrow().get(i as u32));
+            }
 
-        Promise::from_future(f)
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn delete_vertices(
@@ -176,20 +413,18 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
     ) -> Promise<(), CapnpError> {
         let trans = self.trans.clone();
         let cnp_q = pry!(pry!(req.get()).get_q());
-        let q = pry!(converters::to_vertex_query(&cnp_q));
-
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<(), CapnpError> {
-                converters::map_capnp_err(trans.delete_vertices(&q))?;
-                Ok(())
-            })
-            .and_then(move |_| -> Result<(), CapnpError> {
-                res.get().set_result(());
-                Ok(())
-            });
-
-        Promise::from_future(f)
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<u64, CapnpError> {
+            converters::map_capnp_err(trans.delete_vertices(&q))
+        })
+        .and_then(move |count| -> Result<(), CapnpError> {
+            res.get().set_result(count);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn get_vertex_count(
@@ -199,15 +434,36 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
     ) -> Promise<(), CapnpError> {
         let trans = self.trans.clone();
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<u64, CapnpError> { converters::map_capnp_err(trans.get_vertex_count()) })
-            .and_then(move |count| -> Result<(), CapnpError> {
-                res.get().set_result(count);
-                Ok(())
-            });
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<u64, CapnpError> {
+            converters::map_capnp_err(trans.get_vertex_count())
+        })
+        .and_then(move |count| -> Result<(), CapnpError> {
+            res.get().set_result(count);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
 
-        Promise::from_future(f)
+    fn estimate_vertex_count(
+        &mut self,
+        req: autogen::transaction::EstimateVertexCountParams,
+        mut res: autogen::transaction::EstimateVertexCountResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let cnp_q = pry!(pry!(req.get()).get_q());
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<u64, CapnpError> {
+            converters::map_capnp_err(trans.estimate_vertex_count(&q))
+        })
+        .and_then(move |count| -> Result<(), CapnpError> {
+            res.get().set_result(count);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn create_edge(
@@ -219,15 +475,60 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
         let cnp_edge_key = pry!(pry!(req.get()).get_key());
         let edge_key = pry!(converters::to_edge_key(&cnp_edge_key));
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<bool, CapnpError> { converters::map_capnp_err(trans.create_edge(&edge_key)) })
-            .and_then(move |created| -> Result<(), CapnpError> {
-                res.get().set_result(created);
-                Ok(())
-            });
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<bool, CapnpError> {
+            converters::map_capnp_err(trans.create_edge(&edge_key))
+        })
+        .and_then(move |created| -> Result<(), CapnpError> {
+            res.get().set_result(created);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
 
-        Promise::from_future(f)
+    fn create_edge_auto(
+        &mut self,
+        req: autogen::transaction::CreateEdgeAutoParams,
+        mut res: autogen::transaction::CreateEdgeAutoResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_edge_key = pry!(params.get_key());
+        let edge_key = pry!(converters::to_edge_key(&cnp_edge_key));
+        let cnp_default_type = pry!(params.get_default_type());
+        let default_type = pry!(converters::map_capnp_err(indradb::Type::new(
+            cnp_default_type.to_string()
+        )));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<bool, CapnpError> {
+            converters::map_capnp_err(trans.create_edge_auto(&edge_key, &default_type))
+        })
+        .and_then(move |created| -> Result<(), CapnpError> {
+            res.get().set_result(created);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn update_edge(
+        &mut self,
+        req: autogen::transaction::UpdateEdgeParams,
+        mut res: autogen::transaction::UpdateEdgeResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let cnp_edge_key = pry!(pry!(req.get()).get_key());
+        let edge_key = pry!(converters::to_edge_key(&cnp_edge_key));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<bool, CapnpError> {
+            converters::map_capnp_err(trans.update_edge(&edge_key))
+        })
+        .and_then(move |updated| -> Result<(), CapnpError> {
+            res.get().set_result(updated);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn get_edges(
@@ -237,22 +538,24 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
     ) -> Promise<(), CapnpError> {
         let trans = self.trans.clone();
         let cnp_q = pry!(pry!(req.get()).get_q());
-        let q = pry!(converters::to_edge_query(&cnp_q));
+        let mut q = pry!(converters::to_edge_query(&cnp_q));
+        let clamped = self.clamp_edge_query(&mut q);
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<Vec<Edge>, CapnpError> { converters::map_capnp_err(trans.get_edges(&q)) })
-            .and_then(move |edges| -> Result<(), CapnpError> {
-                let mut res = res.get().init_result(edges.len() as u32);
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<Vec<Edge>, CapnpError> {
+            converters::map_capnp_err(trans.get_edges(&q))
+        })
+        .and_then(move |edges| -> Result<(), CapnpError> {
+            res.get().set_clamped(clamped);
+            let mut res = res.get().init_result(edges.len() as u32);
 
-                for (i, edge) in edges.into_iter().enumerate() {
-                    converters::from_edge(&edge, res.reborrow().get(i as u32))?;
-                }
+            for (i, edge) in edges.into_iter().enumerate() {
+                converters::from_edge(&edge, res.reborrow().get(i as u32))?;
+            }
 
-                Ok(())
-            });
+            Ok(())
+        });
 
-        Promise::from_future(f)
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn delete_edges(
@@ -262,20 +565,18 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
     ) -> Promise<(), CapnpError> {
         let trans = self.trans.clone();
         let cnp_q = pry!(pry!(req.get()).get_q());
-        let q = pry!(converters::to_edge_query(&cnp_q));
-
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<(), CapnpError> {
-                converters::map_capnp_err(trans.delete_edges(&q))?;
-                Ok(())
-            })
-            .and_then(move |_| -> Result<(), CapnpError> {
-                res.get().set_result(());
-                Ok(())
-            });
-
-        Promise::from_future(f)
+        let mut q = pry!(converters::to_edge_query(&cnp_q));
+        self.clamp_edge_query(&mut q);
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<u64, CapnpError> {
+            converters::map_capnp_err(trans.delete_edges(&q))
+        })
+        .and_then(move |count| -> Result<(), CapnpError> {
+            res.get().set_result(count);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn get_edge_count(
@@ -285,24 +586,60 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
     ) -> Promise<(), CapnpError> {
         let trans = self.trans.clone();
         let params = pry!(req.get());
-        let id = pry!(converters::map_capnp_err(Uuid::from_slice(pry!(params.get_id()))));
+        let id = pry!(converters::to_uuid(pry!(params.get_id())));
+        let type_filter = match pry!(params.get_type_filter()) {
+            "" => None,
+            value => Some(pry!(converters::map_capnp_err(Type::new(value.to_string())))),
+        };
+        let converter = converters::to_edge_direction(pry!(params.get_direction()));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<u64, CapnpError> {
+            converters::map_capnp_err(trans.get_edge_count(id, type_filter.as_ref(), converter))
+        })
+        .and_then(move |count| -> Result<(), CapnpError> {
+            res.get().set_result(count);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_edge_counts(
+        &mut self,
+        req: autogen::transaction::GetEdgeCountsParams,
+        mut res: autogen::transaction::GetEdgeCountsResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let ids: Result<Vec<Uuid>, CapnpError> = pry!(params.get_ids())
+            .into_iter()
+            .map(|bytes| converters::to_uuid(bytes?))
+            .collect();
+        let ids = pry!(ids);
         let type_filter = match pry!(params.get_type_filter()) {
             "" => None,
             value => Some(pry!(converters::map_capnp_err(Type::new(value.to_string())))),
         };
         let converter = converters::to_edge_direction(pry!(params.get_direction()));
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<u64, CapnpError> {
-                converters::map_capnp_err(trans.get_edge_count(id, type_filter.as_ref(), converter))
-            })
-            .and_then(move |count| -> Result<(), CapnpError> {
-                res.get().set_result(count);
-                Ok(())
-            });
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<Vec<(Uuid, u64)>, CapnpError> {
+                converters::map_capnp_err(trans.get_edge_counts(&ids, type_filter.as_ref(), converter))
+            },
+        )
+        .and_then(move |counts| -> Result<(), CapnpError> {
+            let mut res = res.get().init_result(counts.len() as u32);
+
+            for (i, (_, count)) in counts.into_iter().enumerate() {
+                res.set(i as u32, count);
+            }
+
+            Ok(())
+        });
 
-        Promise::from_future(f)
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn get_vertex_properties(
@@ -313,25 +650,28 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
         let trans = self.trans.clone();
         let params = pry!(req.get());
         let cnp_q = pry!(params.get_q());
-        let q = pry!(converters::to_vertex_query(&cnp_q));
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
         let name = pry!(params.get_name()).to_string();
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<Vec<VertexProperty>, CapnpError> {
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<Vec<VertexProperty>, CapnpError> {
                 converters::map_capnp_err(trans.get_vertex_properties(&q, &name))
-            })
-            .and_then(move |properties| -> Result<(), CapnpError> {
-                let mut res = res.get().init_result(properties.len() as u32);
+            },
+        )
+        .and_then(move |properties| -> Result<(), CapnpError> {
+            let mut res = res.get().init_result(properties.len() as u32);
 
-                for (i, property) in properties.into_iter().enumerate() {
-                    converters::from_vertex_property(&property, res.reborrow().get(i as u32));
-                }
+            for (i, property) in properties.into_iter().enumerate() {
+                converters::from_vertex_property(&property, res.reborrow().get(i as u32));
+            }
 
-                Ok(())
-            });
+            Ok(())
+        });
 
-        Promise::from_future(f)
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn set_vertex_properties(
@@ -342,22 +682,50 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
         let trans = self.trans.clone();
         let params = pry!(req.get());
         let cnp_q = pry!(params.get_q());
-        let q = pry!(converters::to_vertex_query(&cnp_q));
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
         let name = pry!(params.get_name()).to_string();
-        let cnp_value = pry!(params.get_value());
-        let value = pry!(converters::map_capnp_err(serde_json::from_str(cnp_value)));
-
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<(), CapnpError> {
-                converters::map_capnp_err(trans.set_vertex_properties(&q, &name, &value))
-            })
-            .and_then(move |_| -> Result<(), CapnpError> {
-                res.get().set_result(());
-                Ok(())
-            });
+        let value = pry!(converters::to_property_value(
+            pry!(params.get_value()),
+            pry!(params.get_packed_value())
+        ));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<u64, CapnpError> {
+            converters::map_capnp_err(trans.set_vertex_properties(&q, &name, &value))
+        })
+        .and_then(move |count| -> Result<(), CapnpError> {
+            res.get().set_result(count);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
 
-        Promise::from_future(f)
+    fn patch_vertex_property(
+        &mut self,
+        req: autogen::transaction::PatchVertexPropertyParams,
+        mut res: autogen::transaction::PatchVertexPropertyResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_q = pry!(params.get_q());
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
+        let name = pry!(params.get_name()).to_string();
+        let patch = pry!(converters::to_property_value(
+            pry!(params.get_value()),
+            pry!(params.get_packed_value())
+        ));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(trans.patch_vertex_property(&q, &name, &patch))
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn delete_vertex_properties(
@@ -368,20 +736,41 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
         let trans = self.trans.clone();
         let params = pry!(req.get());
         let cnp_q = pry!(params.get_q());
-        let q = pry!(converters::to_vertex_query(&cnp_q));
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
         let name = pry!(params.get_name()).to_string();
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<(), CapnpError> {
-                converters::map_capnp_err(trans.delete_vertex_properties(&q, &name))
-            })
-            .and_then(move |_| -> Result<(), CapnpError> {
-                res.get().set_result(());
-                Ok(())
-            });
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(trans.delete_vertex_properties(&q, &name))
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
 
-        Promise::from_future(f)
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn delete_all_vertex_properties(
+        &mut self,
+        req: autogen::transaction::DeleteAllVertexPropertiesParams,
+        mut res: autogen::transaction::DeleteAllVertexPropertiesResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_q = pry!(params.get_q());
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(trans.delete_all_vertex_properties(&q))
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn get_edge_properties(
@@ -392,25 +781,28 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
         let trans = self.trans.clone();
         let params = pry!(req.get());
         let cnp_q = pry!(params.get_q());
-        let q = pry!(converters::to_edge_query(&cnp_q));
+        let mut q = pry!(converters::to_edge_query(&cnp_q));
+        self.clamp_edge_query(&mut q);
         let name = pry!(params.get_name()).to_string();
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<Vec<EdgeProperty>, CapnpError> {
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<Vec<EdgeProperty>, CapnpError> {
                 converters::map_capnp_err(trans.get_edge_properties(&q, &name))
-            })
-            .and_then(move |properties| -> Result<(), CapnpError> {
-                let mut res = res.get().init_result(properties.len() as u32);
+            },
+        )
+        .and_then(move |properties| -> Result<(), CapnpError> {
+            let mut res = res.get().init_result(properties.len() as u32);
 
-                for (i, property) in properties.into_iter().enumerate() {
-                    converters::from_edge_property(&property, res.reborrow().get(i as u32));
-                }
+            for (i, property) in properties.into_iter().enumerate() {
+                converters::from_edge_property(&property, res.reborrow().get(i as u32));
+            }
 
-                Ok(())
-            });
+            Ok(())
+        });
 
-        Promise::from_future(f)
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn set_edge_properties(
@@ -421,22 +813,50 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
         let trans = self.trans.clone();
         let params = pry!(req.get());
         let cnp_q = pry!(params.get_q());
-        let q = pry!(converters::to_edge_query(&cnp_q));
+        let mut q = pry!(converters::to_edge_query(&cnp_q));
+        self.clamp_edge_query(&mut q);
         let name = pry!(params.get_name()).to_string();
-        let cnp_value = pry!(params.get_value());
-        let value = pry!(converters::map_capnp_err(serde_json::from_str(cnp_value)));
-
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<(), CapnpError> {
-                converters::map_capnp_err(trans.set_edge_properties(&q, &name, &value))
-            })
-            .and_then(move |_| -> Result<(), CapnpError> {
-                res.get().set_result(());
-                Ok(())
-            });
+        let value = pry!(converters::to_property_value(
+            pry!(params.get_value()),
+            pry!(params.get_packed_value())
+        ));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(trans.set_edge_properties(&q, &name, &value))
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
 
-        Promise::from_future(f)
+    fn patch_edge_property(
+        &mut self,
+        req: autogen::transaction::PatchEdgePropertyParams,
+        mut res: autogen::transaction::PatchEdgePropertyResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_q = pry!(params.get_q());
+        let mut q = pry!(converters::to_edge_query(&cnp_q));
+        self.clamp_edge_query(&mut q);
+        let name = pry!(params.get_name()).to_string();
+        let patch = pry!(converters::to_property_value(
+            pry!(params.get_value()),
+            pry!(params.get_packed_value())
+        ));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(trans.patch_edge_property(&q, &name, &patch))
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 
     fn delete_edge_properties(
@@ -447,24 +867,405 @@ impl<T: IndraDbTransaction + Send + Sync + 'static> autogen::transaction::Server
         let trans = self.trans.clone();
         let params = pry!(req.get());
         let cnp_q = pry!(params.get_q());
-        let q = pry!(converters::to_edge_query(&cnp_q));
+        let mut q = pry!(converters::to_edge_query(&cnp_q));
+        self.clamp_edge_query(&mut q);
+        let name = pry!(params.get_name()).to_string();
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(trans.delete_edge_properties(&q, &name))
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn delete_all_edge_properties(
+        &mut self,
+        req: autogen::transaction::DeleteAllEdgePropertiesParams,
+        mut res: autogen::transaction::DeleteAllEdgePropertiesResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_q = pry!(params.get_q());
+        let mut q = pry!(converters::to_edge_query(&cnp_q));
+        self.clamp_edge_query(&mut q);
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<(), CapnpError> {
+            converters::map_capnp_err(trans.delete_all_edge_properties(&q))
+        })
+        .and_then(move |_| -> Result<(), CapnpError> {
+            res.get().set_result(());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_edges_sorted_by_property(
+        &mut self,
+        req: autogen::transaction::GetEdgesSortedByPropertyParams,
+        mut res: autogen::transaction::GetEdgesSortedByPropertyResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_q = pry!(params.get_q());
+        let mut q = pry!(converters::to_edge_query(&cnp_q));
+        self.clamp_edge_query(&mut q);
         let name = pry!(params.get_name()).to_string();
+        let direction = converters::to_sort_direction(pry!(params.get_direction()));
+        let limit = self.clamp_limit(params.get_limit());
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<Vec<Edge>, CapnpError> {
+            converters::map_capnp_err(trans.get_edges_sorted_by_property(&q, &name, direction, limit))
+        })
+        .and_then(move |edges| -> Result<(), CapnpError> {
+            let mut res = res.get().init_result(edges.len() as u32);
 
-        let f = self
-            .pool
-            .spawn_fn(move || -> Result<(), CapnpError> {
-                converters::map_capnp_err(trans.delete_edge_properties(&q, &name))
-            })
-            .and_then(move |_| -> Result<(), CapnpError> {
-                res.get().set_result(());
-                Ok(())
-            });
+            for (i, edge) in edges.into_iter().enumerate() {
+                converters::from_edge(&edge, res.reborrow().get(i as u32))?;
+            }
 
-        Promise::from_future(f)
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_induced_edges(
+        &mut self,
+        req: autogen::transaction::GetInducedEdgesParams,
+        mut res: autogen::transaction::GetInducedEdgesResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let ids: Result<Vec<Uuid>, CapnpError> = pry!(params.get_ids())
+            .into_iter()
+            .map(|bytes| converters::to_uuid(bytes?))
+            .collect();
+        let ids = pry!(ids);
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<Vec<Edge>, CapnpError> {
+            converters::map_capnp_err(trans.get_induced_edges(&ids))
+        })
+        .and_then(move |edges| -> Result<(), CapnpError> {
+            let mut res = res.get().init_result(edges.len() as u32);
+
+            for (i, edge) in edges.into_iter().enumerate() {
+                converters::from_edge(&edge, res.reborrow().get(i as u32))?;
+            }
+
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_vertex_types(
+        &mut self,
+        _: autogen::transaction::GetVertexTypesParams,
+        mut res: autogen::transaction::GetVertexTypesResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<Vec<Type>, CapnpError> {
+            converters::map_capnp_err(trans.get_vertex_types())
+        })
+        .and_then(move |types| -> Result<(), CapnpError> {
+            let mut res = res.get().init_result(types.len() as u32);
+
+            for (i, t) in types.iter().enumerate() {
+                res.set(i as u32, &t.0);
+            }
+
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn set_vertex_property_where(
+        &mut self,
+        req: autogen::transaction::SetVertexPropertyWhereParams,
+        mut res: autogen::transaction::SetVertexPropertyWhereResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_filter = pry!(params.get_filter());
+        let filter = pry!(converters::to_property_condition(&cnp_filter));
+        let name = pry!(params.get_name()).to_string();
+        let cnp_value = pry!(params.get_value());
+        let value = pry!(converters::to_property_value(cnp_value, &[]));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<u64, CapnpError> {
+            converters::map_capnp_err(trans.set_vertex_property_where(&filter, &name, &value))
+        })
+        .and_then(move |count| -> Result<(), CapnpError> {
+            res.get().set_result(count);
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_full_record(
+        &mut self,
+        req: autogen::transaction::GetFullRecordParams,
+        mut res: autogen::transaction::GetFullRecordResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let id = pry!(converters::to_uuid(pry!(params.get_id())));
+        let edge_limit = self.clamp_limit(params.get_edge_limit());
+
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<Option<FullRecord>, CapnpError> {
+                converters::map_capnp_err(trans.get_full_record(id, edge_limit))
+            },
+        )
+        .and_then(move |record| -> Result<(), CapnpError> {
+            if let Some(record) = record {
+                converters::from_full_record(&record, res.get().init_result())?;
+            }
+
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_vertex_properties_multi(
+        &mut self,
+        req: autogen::transaction::GetVertexPropertiesMultiParams,
+        mut res: autogen::transaction::GetVertexPropertiesMultiResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_q = pry!(params.get_q());
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
+        let names: Result<Vec<String>, CapnpError> = pry!(params.get_names())
+            .into_iter()
+            .map(|name| Ok(name?.to_string()))
+            .collect();
+        let names = pry!(names);
+
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<Vec<VertexProperty>, CapnpError> {
+                converters::map_capnp_err(trans.get_vertex_properties_multi(&q, &names))
+            },
+        )
+        .and_then(move |properties| -> Result<(), CapnpError> {
+            let mut res = res.get().init_result(properties.len() as u32);
+
+            for (i, property) in properties.into_iter().enumerate() {
+                converters::from_vertex_property(&property, res.reborrow().get(i as u32));
+            }
+
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_vertex_properties_bounded(
+        &mut self,
+        req: autogen::transaction::GetVertexPropertiesBoundedParams,
+        mut res: autogen::transaction::GetVertexPropertiesBoundedResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_q = pry!(params.get_q());
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
+        let names: Result<Vec<String>, CapnpError> = pry!(params.get_names())
+            .into_iter()
+            .map(|name| Ok(name?.to_string()))
+            .collect();
+        let names = pry!(names);
+        let max_bytes = params.get_max_bytes();
+
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<(Vec<VertexProperty>, bool), CapnpError> {
+                converters::map_capnp_err(trans.get_vertex_properties_bounded(&q, &names, max_bytes))
+            },
+        )
+        .and_then(move |(properties, truncated)| -> Result<(), CapnpError> {
+            res.get().set_truncated(truncated);
+            let mut res = res.get().init_result(properties.len() as u32);
+
+            for (i, property) in properties.into_iter().enumerate() {
+                converters::from_vertex_property(&property, res.reborrow().get(i as u32));
+            }
+
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_all_vertex_properties_bounded(
+        &mut self,
+        req: autogen::transaction::GetAllVertexPropertiesBoundedParams,
+        mut res: autogen::transaction::GetAllVertexPropertiesBoundedResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_q = pry!(params.get_q());
+        let mut q = pry!(converters::to_vertex_query(&cnp_q));
+        self.clamp_vertex_query(&mut q);
+        let max_bytes = params.get_max_bytes();
+
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<(Vec<VertexProperty>, bool), CapnpError> {
+                converters::map_capnp_err(trans.get_all_vertex_properties_bounded(&q, max_bytes))
+            },
+        )
+        .and_then(move |(properties, truncated)| -> Result<(), CapnpError> {
+            res.get().set_truncated(truncated);
+            let mut res = res.get().init_result(properties.len() as u32);
+
+            for (i, property) in properties.into_iter().enumerate() {
+                converters::from_vertex_property(&property, res.reborrow().get(i as u32));
+            }
+
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn aggregate_edge_property(
+        &mut self,
+        req: autogen::transaction::AggregateEdgePropertyParams,
+        mut res: autogen::transaction::AggregateEdgePropertyResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let params = pry!(req.get());
+        let cnp_q = pry!(params.get_q());
+        let mut q = pry!(converters::to_edge_query(&cnp_q));
+        self.clamp_edge_query(&mut q);
+        let name = pry!(params.get_name()).to_string();
+        let op = converters::to_aggregate_op(pry!(params.get_op()));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<Option<f64>, CapnpError> {
+            converters::map_capnp_err(trans.aggregate_edge_property(&q, &name, op))
+        })
+        .and_then(move |result| -> Result<(), CapnpError> {
+            converters::from_aggregate_result(result, res.get().init_result());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_graph_stats(
+        &mut self,
+        _: autogen::transaction::GetGraphStatsParams,
+        mut res: autogen::transaction::GetGraphStatsResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<GraphStats, CapnpError> {
+            converters::map_capnp_err(trans.get_graph_stats())
+        })
+        .and_then(move |stats| -> Result<(), CapnpError> {
+            converters::from_graph_stats(&stats, res.get().init_result());
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn count_vertices_by_type(
+        &mut self,
+        _: autogen::transaction::CountVerticesByTypeParams,
+        mut res: autogen::transaction::CountVerticesByTypeResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+
+        let f = spawn_with_deadline(
+            &self.pool,
+            self.timeout,
+            move || -> Result<BTreeMap<Type, u64>, CapnpError> {
+                converters::map_capnp_err(trans.count_vertices_by_type())
+            },
+        )
+        .and_then(move |counts| -> Result<(), CapnpError> {
+            converters::from_type_counts(&counts, res.get().init_result(counts.len() as u32));
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_vertex_property_names(
+        &mut self,
+        req: autogen::transaction::GetVertexPropertyNamesParams,
+        mut res: autogen::transaction::GetVertexPropertyNamesResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let id = pry!(converters::to_uuid(pry!(pry!(req.get()).get_id())));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<Vec<String>, CapnpError> {
+            converters::map_capnp_err(trans.get_vertex_property_names(id))
+        })
+        .and_then(move |names| -> Result<(), CapnpError> {
+            let mut res = res.get().init_result(names.len() as u32);
+
+            for (i, name) in names.iter().enumerate() {
+                res.set(i as u32, name);
+            }
+
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
+    }
+
+    fn get_edge_property_names(
+        &mut self,
+        req: autogen::transaction::GetEdgePropertyNamesParams,
+        mut res: autogen::transaction::GetEdgePropertyNamesResults,
+    ) -> Promise<(), CapnpError> {
+        let trans = self.trans.clone();
+        let cnp_edge_key = pry!(pry!(req.get()).get_key());
+        let edge_key = pry!(converters::to_edge_key(&cnp_edge_key));
+
+        let f = spawn_with_deadline(&self.pool, self.timeout, move || -> Result<Vec<String>, CapnpError> {
+            converters::map_capnp_err(trans.get_edge_property_names(&edge_key))
+        })
+        .and_then(move |names| -> Result<(), CapnpError> {
+            let mut res = res.get().init_result(names.len() as u32);
+
+            for (i, name) in names.iter().enumerate() {
+                res.set(i as u32, name);
+            }
+
+            Ok(())
+        });
+
+        with_timeout(&self.handle, self.timeout, f)
     }
 }
 
-fn run<D, T>(addr: SocketAddr, datastore: D, worker_count: usize) -> Result<(), errors::Error>
+fn run<D, T>(
+    addr: SocketAddr,
+    datastore: D,
+    worker_count: usize,
+    timeout: Option<Duration>,
+    max_query_limit: Option<u32>,
+) -> Result<(), errors::Error>
 where
     D: IndraDbDatastore<Trans = T> + Send + Sync + 'static,
     T: IndraDbTransaction + Send + Sync + 'static,
@@ -473,7 +1274,14 @@ where
     let handle = core.handle();
     let socket = TcpListener::bind(&addr, &handle)?;
 
-    let service = autogen::service::ToClient::new(Service::new(datastore, worker_count)).from_server::<Server>();
+    let service = autogen::service::ToClient::new(Service::new(
+        datastore,
+        worker_count,
+        handle.clone(),
+        timeout,
+        max_query_limit,
+    ))
+    .from_server::<Server>();
 
     let done = socket.incoming().for_each(move |(socket, _)| {
         socket.set_nodelay(true)?;
@@ -488,12 +1296,30 @@ where
     Ok(())
 }
 
-pub fn start(binding: &str, connection_string: &str, worker_count: usize) -> Result<(), errors::Error> {
+pub fn start(
+    binding: &str,
+    connection_string: &str,
+    worker_count: usize,
+    timeout: Option<Duration>,
+    max_query_limit: Option<u32>,
+) -> Result<(), errors::Error> {
     let addr = binding
         .to_socket_addrs()?
         .next()
         .ok_or_else(|| -> errors::Error { "Could not parse binding".into() })?;
 
+    let max_property_size_bytes = match env::var("MAX_PROPERTY_SIZE_BYTES") {
+        Ok(value) => Some(
+            value
+                .parse::<usize>()
+                .expect("Could not parse environment variable `MAX_PROPERTY_SIZE_BYTES`"),
+        ),
+        Err(_) => None,
+    };
+
+    let reject_dangling_bulk_edges =
+        env::var("REJECT_DANGLING_BULK_EDGES").unwrap_or_else(|_| "".to_string()) == "true";
+
     if connection_string.starts_with("rocksdb://") {
         let path = &connection_string[10..connection_string.len()];
 
@@ -505,13 +1331,25 @@ pub fn start(binding: &str, connection_string: &str, worker_count: usize) -> Res
 
         let bulk_load_optimized = env::var("ROCKSDB_BULK_LOAD_OPTIMIZED").unwrap_or_else(|_| "".to_string()) == "true";
 
+        let sync_writes = env::var("ROCKSDB_SYNC_WRITES").unwrap_or_else(|_| "".to_string()) == "true";
+        let use_wal = env::var("ROCKSDB_DISABLE_WAL").unwrap_or_else(|_| "".to_string()) != "true";
+
         let datastore = RocksdbDatastore::new(path, Some(max_open_files), bulk_load_optimized)
-            .expect("Expected to be able to create the RocksDB datastore");
+            .expect("Expected to be able to create the RocksDB datastore")
+            .with_max_property_size_bytes(max_property_size_bytes)
+            .with_reject_dangling_bulk_edges(reject_dangling_bulk_edges)
+            .with_sync_writes(sync_writes)
+            .with_wal(use_wal);
 
-        run(addr, datastore, worker_count)
+        run(addr, datastore, worker_count, timeout, max_query_limit)
     } else if connection_string == "memory://" {
-        let datastore = MemoryDatastore::default();
-        run(addr, datastore, worker_count)
+        let reject_duplicate_bulk_edges =
+            env::var("MEMORY_REJECT_DUPLICATE_BULK_EDGES").unwrap_or_else(|_| "".to_string()) == "true";
+        let datastore = MemoryDatastore::default()
+            .with_max_property_size_bytes(max_property_size_bytes)
+            .with_reject_dangling_bulk_edges(reject_dangling_bulk_edges)
+            .with_reject_duplicate_bulk_edges(reject_duplicate_bulk_edges);
+        run(addr, datastore, worker_count, timeout, max_query_limit)
     } else {
         panic!("Cannot parse environment variable `DATABASE_URL`");
     }
@@ -1,11 +1,20 @@
+use autogen;
+use capnp;
+use chrono::{TimeZone, Utc};
 use client_datastore::ClientDatastore;
+use converters;
+use indradb;
 use indradb::util::generate_temporary_path;
-use indradb::{Datastore, Transaction};
+use indradb::{Datastore, ReadTransaction, Type, VertexQuery, WriteTransaction};
+use serde_json;
+use serde_json::Value as JsonValue;
 use server;
 use std::panic::catch_unwind;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::thread::spawn;
+use std::time::Duration;
+use uuid::Uuid;
 
 const START_PORT: u16 = 27616;
 
@@ -15,7 +24,7 @@ lazy_static! {
 
 full_test_impl!({
     let port = (*CURRENT_PORT).fetch_add(1, Ordering::SeqCst);
-    spawn(move || server::start(&format!("127.0.0.1:{}", port), "memory://", 1));
+    spawn(move || server::start(&format!("127.0.0.1:{}", port), "memory://", 1, None, None));
     ClientDatastore::new(port as u16)
 });
 
@@ -25,7 +34,7 @@ fn should_create_rocksdb_datastore() {
 
     spawn(move || {
         let connection_string = format!("rocksdb://{}", generate_temporary_path());
-        server::start(&format!("127.0.0.1:{}", port), &connection_string, 1)
+        server::start(&format!("127.0.0.1:{}", port), &connection_string, 1, None, None)
     });
 
     // Just make sure we can run a command
@@ -38,6 +47,130 @@ fn should_create_rocksdb_datastore() {
 
 #[test]
 fn should_panic_on_bad_connection_string() {
-    let result = catch_unwind(|| server::start("127.0.0.1:9999", "foo://", 1));
+    let result = catch_unwind(|| server::start("127.0.0.1:9999", "foo://", 1, None, None));
     assert!(result.is_err());
 }
+
+#[test]
+fn should_time_out_slow_operations() {
+    let port = (*CURRENT_PORT).fetch_add(1, Ordering::SeqCst);
+    spawn(move || {
+        server::start(
+            &format!("127.0.0.1:{}", port),
+            "memory://",
+            1,
+            Some(Duration::from_millis(0)),
+            None,
+        )
+    });
+
+    // Every operation takes longer than the zero-length timeout
+    let datastore = ClientDatastore::new(port as u16);
+    let trans = datastore.transaction().unwrap();
+    let err = trans.get_vertex_count().unwrap_err();
+    assert!(format!("{}", err).contains("[timed_out] Operation timed out"));
+}
+
+#[test]
+fn should_send_error_codes() {
+    let err: indradb::Error = indradb::ErrorKind::TraversalLimitExceeded("10 vertices".to_string()).into();
+    let err = converters::map_capnp_err::<(), _>(Err(err)).unwrap_err();
+    assert_eq!(converters::error_code(&err), Some("traversal_limit_exceeded"));
+
+    // Errors from the server are prefixed by the RPC system
+    let remote_err = capnp::Error::failed(format!("remote exception: {}", err.description));
+    assert_eq!(converters::error_code(&remote_err), Some("traversal_limit_exceeded"));
+
+    match *converters::to_indradb_error(remote_err).kind() {
+        indradb::ErrorKind::TraversalLimitExceeded(ref details) => assert_eq!(details, "10 vertices"),
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+
+    let err = converters::map_capnp_err(Type::new("$".to_string())).unwrap_err();
+    assert_eq!(converters::error_code(&err), Some("validation"));
+
+    match *converters::to_indradb_error(err).kind() {
+        indradb::ErrorKind::Validation(_) => (),
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+
+    let err = capnp::Error::failed("no code here".to_string());
+    assert_eq!(converters::error_code(&err), None);
+    assert_eq!(format!("{}", converters::to_indradb_error(err)), "no code here");
+}
+
+#[test]
+fn should_clamp_query_limits() {
+    let port = (*CURRENT_PORT).fetch_add(1, Ordering::SeqCst);
+    spawn(move || server::start(&format!("127.0.0.1:{}", port), "memory://", 1, None, Some(2)));
+
+    let datastore = ClientDatastore::new(port as u16);
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_vertex_type".to_string()).unwrap();
+
+    for _ in 0..5 {
+        trans.create_vertex_from_type(t.clone()).unwrap();
+    }
+
+    let q = VertexQuery::All {
+        start_id: None,
+        end_id: None,
+        high_filter: None,
+        low_filter: None,
+        limit: u32::MAX,
+    };
+    assert_eq!(trans.get_vertices(&q).unwrap().len(), 2);
+    assert_eq!(trans.get_vertex_count().unwrap(), 5);
+}
+
+#[test]
+fn should_read_property_values_in_either_encoding() {
+    let value: JsonValue =
+        serde_json::from_str(r#"{"name": "alice", "scores": [1, 2.5, -3], "active": true, "extra": null}"#).unwrap();
+    let packed_value = converters::pack_property_value(&value);
+    assert_eq!(converters::to_property_value("", &packed_value).unwrap(), value);
+    assert_eq!(converters::to_property_value(&value.to_string(), &[]).unwrap(), value);
+    assert!(converters::to_property_value("", &[0xc1]).is_err());
+}
+
+#[test]
+fn should_report_missing_property_values() {
+    let err = converters::to_property_value("", &[]).unwrap_err();
+    assert_eq!(err.description, "[validation] property value missing");
+    assert_eq!(converters::to_property_value("null", &[]).unwrap(), JsonValue::Null);
+}
+
+#[test]
+fn should_report_malformed_uuids() {
+    let err = converters::to_uuid(&[0; 15]).unwrap_err();
+    assert_eq!(err.description, "[validation] expected 16-byte UUID, got 15 bytes");
+    let err = converters::to_uuid(&[]).unwrap_err();
+    assert_eq!(err.description, "[validation] expected 16-byte UUID, got 0 bytes");
+    assert_eq!(converters::to_uuid(&[0; 16]).unwrap(), Uuid::default());
+}
+
+#[test]
+fn should_send_epoch_edge_filters() {
+    let epoch = Utc.timestamp(0, 0);
+    let q = VertexQuery::All {
+        start_id: None,
+        end_id: None,
+        high_filter: None,
+        low_filter: None,
+        limit: 10,
+    }
+    .outbound_edges(None, Some(epoch), None, 10);
+
+    let mut message = capnp::message::Builder::new_default();
+    let mut builder = message.init_root::<autogen::edge_query::Builder>();
+    converters::from_edge_query(&q, builder.reborrow());
+    assert_eq!(converters::to_edge_query(&builder.into_reader()).unwrap(), q);
+
+    // Older clients only leave unset filters as zero
+    assert_eq!(converters::to_optional_datetime(0, false), None);
+    assert_eq!(converters::to_optional_datetime(0, true), Some(epoch));
+    assert_eq!(
+        converters::to_optional_datetime(1_000_000_000, false),
+        Some(Utc.timestamp(1, 0))
+    );
+}
@@ -13,6 +13,7 @@ extern crate serde_json;
 extern crate uuid;
 
 use std::env;
+use std::time::Duration;
 
 const DEFAULT_PORT: u16 = 27615;
 
@@ -35,5 +36,24 @@ fn main() {
         Err(_) => num_cpus::get() * 2,
     };
 
-    common::server::start(&binding, &connection_string, worker_count).expect("Expected to be able to start the server");
+    let timeout = match env::var("REQUEST_TIMEOUT") {
+        Ok(value) => Some(Duration::from_millis(
+            value
+                .parse::<u64>()
+                .expect("Could not parse environment variable `REQUEST_TIMEOUT`"),
+        )),
+        Err(_) => None,
+    };
+
+    let max_query_limit = match env::var("MAX_QUERY_LIMIT") {
+        Ok(value) => Some(
+            value
+                .parse::<u32>()
+                .expect("Could not parse environment variable `MAX_QUERY_LIMIT`"),
+        ),
+        Err(_) => None,
+    };
+
+    common::server::start(&binding, &connection_string, worker_count, timeout, max_query_limit)
+        .expect("Expected to be able to start the server");
 }
@@ -0,0 +1,337 @@
+use errors::{Error, Result};
+use futures::Future;
+use futures_cpupool::CpuPool;
+use models;
+use serde_json::value::Value as JsonValue;
+use std::sync::Arc;
+use traits::{Datastore, Transaction};
+use uuid::Uuid;
+
+/// The future returned by async datastore and transaction methods.
+pub type DatastoreFuture<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+/// Specifies an async datastore implementation, i.e. a `Datastore` whose
+/// methods return futures rather than blocking.
+///
+/// # Errors
+/// All futures may resolve to an error if something unexpected happens -
+/// e.g. if there was a problem connecting to the underlying database.
+pub trait AsyncDatastore {
+    type Trans: AsyncTransaction;
+
+    /// Creates a new transaction.
+    fn transaction(&self) -> DatastoreFuture<Self::Trans>;
+}
+
+/// Specifies the operations of an async transaction. These mirror the core
+/// operations of `ReadTransaction` and `WriteTransaction`, but take their
+/// arguments by value, since the futures can outlive the call.
+pub trait AsyncTransaction {
+    /// Gets a range of vertices specified by a query.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    fn get_vertices(&self, q: models::VertexQuery) -> DatastoreFuture<Vec<models::Vertex>>;
+
+    /// Gets the number of vertices in the datastore.
+    fn get_vertex_count(&self) -> DatastoreFuture<u64>;
+
+    /// Gets a range of edges specified by a query.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    fn get_edges(&self, q: models::EdgeQuery) -> DatastoreFuture<Vec<models::Edge>>;
+
+    /// Gets the number of edges associated with a vertex.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    /// * `type_filter` - Only get the count for a specified edge type.
+    /// * `direction`: The direction of edges to get.
+    fn get_edge_count(
+        &self,
+        id: Uuid,
+        type_filter: Option<models::Type>,
+        direction: models::EdgeDirection,
+    ) -> DatastoreFuture<u64>;
+
+    /// Gets vertex properties.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    fn get_vertex_properties(
+        &self,
+        q: models::VertexQuery,
+        name: String,
+    ) -> DatastoreFuture<Vec<models::VertexProperty>>;
+
+    /// Gets edge properties.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    fn get_edge_properties(&self, q: models::EdgeQuery, name: String) -> DatastoreFuture<Vec<models::EdgeProperty>>;
+
+    /// Creates a new vertex. Resolves to whether the vertex was created;
+    /// see `WriteTransaction::create_vertex`.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create.
+    fn create_vertex(&self, vertex: models::Vertex) -> DatastoreFuture<bool>;
+
+    /// Creates a new vertex with just a type specification. Resolves to the
+    /// new vertex's UUID.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    fn create_vertex_from_type(&self, t: models::Type) -> DatastoreFuture<Uuid>;
+
+    /// Deletes existing vertices specified by a query. Resolves to the
+    /// number of vertices that were deleted.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    fn delete_vertices(&self, q: models::VertexQuery) -> DatastoreFuture<u64>;
+
+    /// Creates a new edge. Resolves to whether the edge was created; see
+    /// `WriteTransaction::create_edge`.
+    ///
+    /// # Arguments
+    /// * `key`: The edge to create.
+    fn create_edge(&self, key: models::EdgeKey) -> DatastoreFuture<bool>;
+
+    /// Deletes a set of edges specified by a query. Resolves to the number
+    /// of edges that were deleted.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    fn delete_edges(&self, q: models::EdgeQuery) -> DatastoreFuture<u64>;
+
+    /// Sets vertex properties. Resolves to the number of properties that
+    /// were set.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    /// * `value` - The property value.
+    fn set_vertex_properties(&self, q: models::VertexQuery, name: String, value: JsonValue) -> DatastoreFuture<u64>;
+
+    /// Deletes vertex properties.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    fn delete_vertex_properties(&self, q: models::VertexQuery, name: String) -> DatastoreFuture<()>;
+
+    /// Sets edge properties.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    /// * `value` - The property value.
+    fn set_edge_properties(&self, q: models::EdgeQuery, name: String, value: JsonValue) -> DatastoreFuture<()>;
+
+    /// Deletes edge properties.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    fn delete_edge_properties(&self, q: models::EdgeQuery, name: String) -> DatastoreFuture<()>;
+}
+
+/// An async datastore that wraps a synchronous one, running each call on a
+/// thread pool so that it doesn't block the caller.
+///
+/// Datastores like the in-memory one guard their data with locks, so they
+/// can't be made truly async without a rewrite; offloading their calls is
+/// the pragmatic alternative.
+#[derive(Clone, Debug)]
+pub struct BlockingDatastore<D: Datastore> {
+    datastore: Arc<D>,
+    pool: CpuPool,
+}
+
+impl<D: Datastore> BlockingDatastore<D> {
+    /// Creates a new blocking datastore.
+    ///
+    /// # Arguments
+    /// * `datastore` - The datastore to wrap.
+    /// * `pool` - The thread pool to run calls on.
+    pub fn new(datastore: D, pool: CpuPool) -> Self {
+        BlockingDatastore {
+            datastore: Arc::new(datastore),
+            pool,
+        }
+    }
+}
+
+impl<D> AsyncDatastore for BlockingDatastore<D>
+where
+    D: Datastore + Send + Sync + 'static,
+    D::Trans: Send + Sync + 'static,
+{
+    type Trans = BlockingTransaction<D::Trans>;
+
+    fn transaction(&self) -> DatastoreFuture<Self::Trans> {
+        let datastore = self.datastore.clone();
+        let pool = self.pool.clone();
+
+        Box::new(self.pool.spawn_fn(move || {
+            let trans = datastore.transaction()?;
+            Ok(BlockingTransaction::new(trans, pool))
+        }))
+    }
+}
+
+/// A transaction of a `BlockingDatastore`, which runs each call to the
+/// transaction it wraps on a thread pool.
+#[derive(Debug)]
+pub struct BlockingTransaction<T: Transaction> {
+    trans: Arc<T>,
+    pool: CpuPool,
+}
+
+impl<T: Transaction + Send + Sync + 'static> BlockingTransaction<T> {
+    fn new(trans: T, pool: CpuPool) -> Self {
+        BlockingTransaction {
+            trans: Arc::new(trans),
+            pool,
+        }
+    }
+
+    fn spawn<F, R>(&self, f: F) -> DatastoreFuture<R>
+    where
+        F: FnOnce(&T) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let trans = self.trans.clone();
+        Box::new(self.pool.spawn_fn(move || f(&trans)))
+    }
+}
+
+impl<T: Transaction + Send + Sync + 'static> AsyncTransaction for BlockingTransaction<T> {
+    fn get_vertices(&self, q: models::VertexQuery) -> DatastoreFuture<Vec<models::Vertex>> {
+        self.spawn(move |trans| trans.get_vertices(&q))
+    }
+
+    fn get_vertex_count(&self) -> DatastoreFuture<u64> {
+        self.spawn(|trans| trans.get_vertex_count())
+    }
+
+    fn get_edges(&self, q: models::EdgeQuery) -> DatastoreFuture<Vec<models::Edge>> {
+        self.spawn(move |trans| trans.get_edges(&q))
+    }
+
+    fn get_edge_count(
+        &self,
+        id: Uuid,
+        type_filter: Option<models::Type>,
+        direction: models::EdgeDirection,
+    ) -> DatastoreFuture<u64> {
+        self.spawn(move |trans| trans.get_edge_count(id, type_filter.as_ref(), direction))
+    }
+
+    fn get_vertex_properties(
+        &self,
+        q: models::VertexQuery,
+        name: String,
+    ) -> DatastoreFuture<Vec<models::VertexProperty>> {
+        self.spawn(move |trans| trans.get_vertex_properties(&q, &name))
+    }
+
+    fn get_edge_properties(&self, q: models::EdgeQuery, name: String) -> DatastoreFuture<Vec<models::EdgeProperty>> {
+        self.spawn(move |trans| trans.get_edge_properties(&q, &name))
+    }
+
+    fn create_vertex(&self, vertex: models::Vertex) -> DatastoreFuture<bool> {
+        self.spawn(move |trans| trans.create_vertex(&vertex))
+    }
+
+    fn create_vertex_from_type(&self, t: models::Type) -> DatastoreFuture<Uuid> {
+        self.spawn(move |trans| trans.create_vertex_from_type(t))
+    }
+
+    fn delete_vertices(&self, q: models::VertexQuery) -> DatastoreFuture<u64> {
+        self.spawn(move |trans| trans.delete_vertices(&q))
+    }
+
+    fn create_edge(&self, key: models::EdgeKey) -> DatastoreFuture<bool> {
+        self.spawn(move |trans| trans.create_edge(&key))
+    }
+
+    fn delete_edges(&self, q: models::EdgeQuery) -> DatastoreFuture<u64> {
+        self.spawn(move |trans| trans.delete_edges(&q))
+    }
+
+    fn set_vertex_properties(&self, q: models::VertexQuery, name: String, value: JsonValue) -> DatastoreFuture<u64> {
+        self.spawn(move |trans| trans.set_vertex_properties(&q, &name, &value))
+    }
+
+    fn delete_vertex_properties(&self, q: models::VertexQuery, name: String) -> DatastoreFuture<()> {
+        self.spawn(move |trans| trans.delete_vertex_properties(&q, &name))
+    }
+
+    fn set_edge_properties(&self, q: models::EdgeQuery, name: String, value: JsonValue) -> DatastoreFuture<()> {
+        self.spawn(move |trans| trans.set_edge_properties(&q, &name, &value))
+    }
+
+    fn delete_edge_properties(&self, q: models::EdgeQuery, name: String) -> DatastoreFuture<()> {
+        self.spawn(move |trans| trans.delete_edge_properties(&q, &name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncDatastore, AsyncTransaction, BlockingDatastore};
+    use futures::Future;
+    use futures_cpupool::CpuPool;
+    use memory::MemoryDatastore;
+    use models;
+    use serde_json::Value as JsonValue;
+
+    #[test]
+    fn should_run_blocking_datastore_calls_on_a_pool() {
+        let datastore = BlockingDatastore::new(MemoryDatastore::default(), CpuPool::new(2));
+        let trans = datastore.transaction().wait().unwrap();
+        let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+        let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+
+        let outbound_id = trans.create_vertex_from_type(vertex_t.clone()).wait().unwrap();
+        let inbound_id = trans.create_vertex_from_type(vertex_t).wait().unwrap();
+        let key = models::EdgeKey::new(outbound_id, edge_t.clone(), inbound_id);
+        assert!(trans.create_edge(key.clone()).wait().unwrap());
+        assert_eq!(trans.get_vertex_count().wait().unwrap(), 2);
+
+        let q = models::VertexQuery::single(outbound_id);
+        let count = trans
+            .set_vertex_properties(q.clone(), "foo".to_string(), JsonValue::Bool(true))
+            .wait()
+            .unwrap();
+        assert_eq!(count, 1);
+        let properties = trans
+            .get_vertex_properties(q.clone(), "foo".to_string())
+            .wait()
+            .unwrap();
+        assert_eq!(properties.len(), 1);
+        assert_eq!(properties[0].value, JsonValue::Bool(true));
+
+        let edge_count = trans
+            .get_edge_count(outbound_id, Some(edge_t), models::EdgeDirection::Outbound)
+            .wait()
+            .unwrap();
+        assert_eq!(edge_count, 1);
+
+        // Futures from several calls can be in flight at once
+        let edges = trans
+            .get_edges(models::EdgeQuery::single(key))
+            .join(trans.get_vertices(q.clone()))
+            .wait()
+            .unwrap();
+        assert_eq!(edges.0.len(), 1);
+        assert_eq!(edges.1.len(), 1);
+
+        assert_eq!(trans.delete_vertices(q).wait().unwrap(), 1);
+        assert_eq!(trans.get_vertex_count().wait().unwrap(), 1);
+    }
+}
@@ -1,6 +1,7 @@
 use models::{EdgeDirection, EdgeKey, EdgeQuery, Type, Vertex, VertexQuery};
+use serde_json::Value as JsonValue;
 use test::Bencher;
-use traits::{Datastore, Transaction};
+use traits::{Datastore, ReadTransaction, WriteTransaction};
 
 pub fn bench_create_vertex<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
     let t = Type::new("bench_create_vertex".to_string()).unwrap();
@@ -91,3 +92,23 @@ pub fn bench_get_edge_count<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
             .unwrap();
     });
 }
+
+pub fn bench_get_large_vertex_property<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
+    let t = Type::new("bench_get_large_vertex_property".to_string()).unwrap();
+    // Roughly 100KB once encoded as JSON
+    let value = JsonValue::Array((0..7_000).map(|i| JsonValue::from(f64::from(i) / 3.0)).collect());
+
+    let q = {
+        let trans = datastore.transaction().unwrap();
+        let v = Vertex::new(t);
+        trans.create_vertex(&v).unwrap();
+        let q = VertexQuery::Vertices { ids: vec![v.id] };
+        trans.set_vertex_properties(&q, "data", &value).unwrap();
+        q
+    };
+
+    b.iter(|| {
+        let trans = datastore.transaction().unwrap();
+        trans.get_vertex_properties(&q, "data").unwrap();
+    });
+}
@@ -21,5 +21,6 @@ macro_rules! full_bench_impl {
         define_bench!(bench_create_edge, $code);
         define_bench!(bench_get_edges, $code);
         define_bench!(bench_get_edge_count, $code);
+        define_bench!(bench_get_large_vertex_property, $code);
     };
 }
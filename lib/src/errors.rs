@@ -3,6 +3,7 @@ use bincode::Error as BincodeError;
 #[cfg(feature = "rocksdb-datastore")]
 use rocksdb::Error as RocksDbError;
 use serde_json::Error as JsonError;
+use std::io::Error as IoError;
 
 error_chain!{
     types {
@@ -11,8 +12,63 @@ error_chain!{
 
     foreign_links {
         Json(JsonError);
+        Io(IoError);
         RocksDb(RocksDbError) #[cfg(feature = "rocksdb-datastore")];
         Bincode(BincodeError) #[cfg(feature = "rocksdb-datastore")];
+        Validation(ValidationError);
+    }
+
+    errors {
+        TraversalLimitExceeded(limit: String) {
+            description("traversal limit exceeded")
+            display("traversal limit exceeded: {}", limit)
+        }
+        BulkInsertConflict(item: String) {
+            description("bulk insert item already exists")
+            display("bulk insert item already exists: {}", item)
+        }
+        DuplicateBulkInsertEdges(keys: String) {
+            description("bulk insert has duplicate edges")
+            display("bulk insert has duplicate edges: {}", keys)
+        }
+        DanglingBulkInsertEdges(keys: String) {
+            description("bulk insert has edges between vertices that don't exist")
+            display("bulk insert has edges between vertices that don't exist: {}", keys)
+        }
+        UnsupportedKeyVersion(version: u8) {
+            description("unsupported key version")
+            display("unsupported key version: {}", version)
+        }
+        LockPoisoned {
+            description("datastore lock poisoned")
+            display("datastore lock poisoned: a thread panicked while holding it")
+        }
+    }
+}
+
+impl ErrorKind {
+    /// Gets a stable, machine-readable code for the kind of error, so that
+    /// callers on the other side of a boundary that only carries strings,
+    /// like the RPC server, can tell kinds apart without matching on
+    /// messages. Errors that don't have a more specific code, including
+    /// ones made from plain messages, are `internal`.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            ErrorKind::Json(_) => "json",
+            ErrorKind::Io(_) => "io",
+            #[cfg(feature = "rocksdb-datastore")]
+            ErrorKind::RocksDb(_) => "rocksdb",
+            #[cfg(feature = "rocksdb-datastore")]
+            ErrorKind::Bincode(_) => "bincode",
+            ErrorKind::Validation(_) => "validation",
+            ErrorKind::TraversalLimitExceeded(_) => "traversal_limit_exceeded",
+            ErrorKind::BulkInsertConflict(_) => "bulk_insert_conflict",
+            ErrorKind::DuplicateBulkInsertEdges(_) => "duplicate_bulk_insert_edges",
+            ErrorKind::DanglingBulkInsertEdges(_) => "dangling_bulk_insert_edges",
+            ErrorKind::UnsupportedKeyVersion(_) => "unsupported_key_version",
+            ErrorKind::LockPoisoned => "lock_poisoned",
+            _ => "internal",
+        }
     }
 }
 
@@ -21,3 +77,19 @@ error_chain! {
         ValidationError, ValidationErrorKind, ValidationResultExt, ValidationResult;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, ErrorKind, ValidationError};
+
+    #[test]
+    fn should_get_error_codes() {
+        let err: Error = ValidationError::from("bad type").into();
+        assert_eq!(err.kind().code(), "validation");
+        let err: Error = ErrorKind::TraversalLimitExceeded("10 vertices".to_string()).into();
+        assert_eq!(err.kind().code(), "traversal_limit_exceeded");
+        assert_eq!(ErrorKind::LockPoisoned.code(), "lock_poisoned");
+        let err: Error = "something went wrong".into();
+        assert_eq!(err.kind().code(), "internal");
+    }
+}
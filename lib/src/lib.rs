@@ -35,6 +35,16 @@ extern crate bincode;
 #[cfg(feature = "rocksdb-datastore")]
 extern crate rocksdb;
 
+#[cfg(feature = "compression")]
+extern crate flate2;
+#[cfg(feature = "compression")]
+extern crate zstd;
+
+#[cfg(feature = "async-datastore")]
+extern crate futures;
+#[cfg(feature = "async-datastore")]
+extern crate futures_cpupool;
+
 #[cfg(feature = "test-suite")]
 #[macro_use]
 pub mod tests;
@@ -50,11 +60,22 @@ mod traits;
 pub mod util;
 
 pub use errors::*;
-pub use memory::{MemoryDatastore, MemoryTransaction};
+pub use memory::{
+    ChangeEvent, Clock, IntegrityReport, MemoryDatastore, MemoryEdgePager, MemoryReadOnlyTransaction,
+    MemoryTransaction, MockClock, ShardedMemoryDatastore, ShardedMemoryTransaction, SnapshotCompression, SystemClock,
+};
 pub use models::*;
 pub use traits::*;
 
+#[cfg(feature = "lock-stats")]
+pub use memory::DatastoreStats;
+
 #[cfg(feature = "rocksdb-datastore")]
 mod rdb;
 #[cfg(feature = "rocksdb-datastore")]
 pub use rdb::{RocksdbDatastore, RocksdbTransaction};
+
+#[cfg(feature = "async-datastore")]
+mod async_traits;
+#[cfg(feature = "async-datastore")]
+pub use async_traits::{AsyncDatastore, AsyncTransaction, BlockingDatastore, BlockingTransaction, DatastoreFuture};
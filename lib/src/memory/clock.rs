@@ -0,0 +1,61 @@
+use chrono::offset::Utc;
+use chrono::{DateTime, Duration};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A source of the current time. The memory datastore reads it whenever it
+/// timestamps a vertex, edge or property, so swapping it out lets tests
+/// control those timestamps. See `MemoryDatastore::with_clock`.
+pub trait Clock: Debug + Send + Sync {
+    /// Gets the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// A clock that reads the system time. This is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock whose time only changes when it's told to, for deterministic
+/// tests of time-based queries. Clones share the same time, so a test can
+/// keep a clone to move the time of a datastore that uses it.
+#[derive(Clone, Debug)]
+pub struct MockClock(Arc<Mutex<DateTime<Utc>>>);
+
+impl MockClock {
+    /// Creates a new mock clock.
+    ///
+    /// # Arguments
+    /// * `now` - The time the clock starts at.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        MockClock(Arc::new(Mutex::new(now)))
+    }
+
+    /// Sets the time.
+    ///
+    /// # Arguments
+    /// * `now` - The new time.
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+
+    /// Moves the time forward, or backward for a negative duration.
+    ///
+    /// # Arguments
+    /// * `duration` - How far to move the time.
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        *now += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -1,75 +1,175 @@
-use super::super::{Datastore, EdgeQuery, Transaction, VertexQuery};
+use super::super::{insert_bulk_insert_item, Datastore, EdgeQuery, ReadTransaction, VertexQuery, WriteTransaction};
+use super::clock::{Clock, SystemClock};
 use chrono::offset::Utc;
 use chrono::DateTime;
-use errors::Result;
+use chrono::Duration as ChronoDuration;
+use errors::{Error, ErrorKind, Result, ValidationError};
+#[cfg(feature = "compression")]
+use flate2::write::GzEncoder;
+#[cfg(feature = "compression")]
+use flate2::Compression;
 use models;
+use serde_json;
 use serde_json::Value as JsonValue;
-use std::collections::{BTreeMap, HashSet};
-use std::sync::{Arc, RwLock};
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, Bound, HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::iter;
+#[cfg(feature = "lock-stats")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+#[cfg(feature = "lock-stats")]
+use std::time::Instant;
+use util;
 use uuid::Uuid;
+#[cfg(feature = "compression")]
+use zstd;
+
+// The maximum number of vertices a `MemoryVertexIterator` fetches while
+// holding the read lock.
+const VERTEX_ITERATOR_CHUNK_SIZE: usize = 1000;
+
+// An edge's key, along with its update datetime and weight.
+type EdgeValue = (models::EdgeKey, DateTime<Utc>, Option<f64>);
 
 // All of the data is actually stored in this struct, which is stored
 // internally to the datastore itself. This way, we can wrap an rwlock around
 // the entire datastore, rather than on a per-data structure basis, as the
 // latter approach would risk deadlocking without extreme care.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct InternalMemoryDatastore {
-    edge_properties: BTreeMap<(models::EdgeKey, String), JsonValue>,
-    edges: BTreeMap<models::EdgeKey, DateTime<Utc>>,
-    vertex_properties: BTreeMap<(Uuid, String), JsonValue>,
-    vertices: BTreeMap<Uuid, models::Type>,
+    allow_self_loops: bool,
+    case_insensitive_types: bool,
+    check_edge_times: bool,
+    clock: Arc<dyn Clock>,
+    // When each edge created with a TTL expires
+    edge_expirations: BTreeMap<models::EdgeKey, DateTime<Utc>>,
+    edge_properties: BTreeMap<(models::EdgeKey, String), (JsonValue, DateTime<Utc>)>,
+    edges: BTreeMap<models::EdgeKey, (DateTime<Utc>, Option<f64>)>,
+    // The edges touching each vertex, in either direction, ordered by their
+    // update datetime
+    edge_timelines: HashMap<Uuid, BTreeSet<(DateTime<Utc>, models::EdgeKey)>>,
+    // The edges created with a TTL, ordered by when they expire
+    expiring_edges: BTreeSet<(DateTime<Utc>, models::EdgeKey)>,
+    in_degrees: HashMap<Uuid, u64>,
+    // Edges whose update datetime predates the creation of either of their
+    // vertices, as recorded when `check_edge_times` is set
+    inconsistent_edges: BTreeSet<models::EdgeKey>,
+    max_property_size_bytes: Option<usize>,
+    out_degrees: HashMap<Uuid, u64>,
+    property_indexes: HashMap<String, HashMap<String, BTreeSet<Uuid>>>,
+    property_types: HashMap<String, models::PropertyValueType>,
+    reject_dangling_bulk_edges: bool,
+    reject_duplicate_bulk_edges: bool,
+    subscribers: Vec<Sender<ChangeEvent>>,
+    traversal_limits: models::TraversalLimits,
+    // The canonical keys of undirected edges, and the same keys reversed,
+    // so that the undirected edges touching a vertex can be found with a
+    // range scan whichever end of them it's on
+    undirected_edges: BTreeSet<models::EdgeKey>,
+    reversed_undirected_edges: BTreeSet<models::EdgeKey>,
+    vertex_properties: BTreeMap<(Uuid, String), (JsonValue, DateTime<Utc>)>,
+    // Each vertex's type, and when it was created
+    vertices: BTreeMap<Uuid, (models::Type, DateTime<Utc>)>,
 }
 
 impl InternalMemoryDatastore {
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
     fn get_vertex_values_by_query(&self, q: &VertexQuery) -> Result<Vec<(Uuid, models::Type)>> {
+        q.validate()?;
+
         match *q {
-            VertexQuery::All { start_id, limit } => {
-                if let Some(start_id) = start_id {
-                    Ok(self
-                        .vertices
-                        .range(start_id..)
-                        .take(limit as usize)
-                        .map(|(k, v)| (*k, v.clone()))
-                        .collect())
-                } else {
-                    Ok(self
-                        .vertices
-                        .iter()
-                        .take(limit as usize)
-                        .map(|(k, v)| (*k, v.clone()))
-                        .collect())
-                }
-            }
+            VertexQuery::All {
+                start_id,
+                end_id,
+                high_filter,
+                low_filter,
+                limit,
+            } => match vertex_id_bounds(start_id, end_id) {
+                Some(bounds) => Ok(self
+                    .vertices
+                    .range(bounds)
+                    .filter(|&(_, &(_, created_datetime))| {
+                        high_filter.is_none_or(|high_filter| created_datetime <= high_filter)
+                            && low_filter.is_none_or(|low_filter| created_datetime >= low_filter)
+                    })
+                    .take(limit as usize)
+                    .map(|(k, (t, _))| (*k, t.clone()))
+                    .collect()),
+                None => Ok(Vec::new()),
+            },
             VertexQuery::Vertices { ref ids } => {
                 let mut results = Vec::new();
 
                 for id in ids {
                     let value = self.vertices.get(id);
 
-                    if let Some(value) = value {
-                        results.push((*id, value.clone()));
+                    if let Some((t, _)) = value {
+                        results.push((*id, t.clone()));
                     }
                 }
 
                 Ok(results)
             }
+            VertexQuery::Types { ref types, limit } => Ok(self
+                .vertices
+                .iter()
+                .filter(|&(_, (t, _))| types.contains(t))
+                .take(limit as usize)
+                .map(|(k, (t, _))| (*k, t.clone()))
+                .collect()),
+            VertexQuery::PropertyContains {
+                ref name,
+                ref substring,
+                limit,
+            } => Ok(self
+                .vertices
+                .iter()
+                .filter(|&(id, _)| {
+                    self.vertex_properties
+                        .get(&(*id, name.clone()))
+                        .is_some_and(|(value, _)| util::json_string_contains(value, substring))
+                })
+                .take(limit as usize)
+                .map(|(k, (t, _))| (*k, t.clone()))
+                .collect()),
             VertexQuery::Pipe {
                 ref edge_query,
                 converter,
+                offset,
                 limit,
             } => {
-                let edge_values = self.get_edge_values_by_query(&*edge_query)?;
+                let edge_values = self
+                    .get_edge_values_by_query(edge_query)?
+                    .into_iter()
+                    .skip(offset as usize);
 
                 let ids: Vec<Uuid> = match converter {
                     models::EdgeDirection::Outbound => edge_values
-                        .into_iter()
                         .take(limit as usize)
-                        .map(|(key, _)| key.outbound_id)
+                        .map(|(key, _, _)| key.outbound_id)
                         .collect(),
                     models::EdgeDirection::Inbound => edge_values
-                        .into_iter()
                         .take(limit as usize)
-                        .map(|(key, _)| key.inbound_id)
+                        .map(|(key, _, _)| key.inbound_id)
+                        .collect(),
+                    models::EdgeDirection::Both => edge_values
+                        .flat_map(|(key, _, _)| {
+                            if key.outbound_id == key.inbound_id {
+                                vec![key.outbound_id]
+                            } else {
+                                vec![key.outbound_id, key.inbound_id]
+                            }
+                        })
+                        .take(limit as usize)
                         .collect(),
                 };
 
@@ -77,122 +177,464 @@ impl InternalMemoryDatastore {
 
                 for id in ids {
                     let value = self.vertices.get(&id);
-                    if let Some(value) = value {
-                        results.push((id, value.clone()));
+                    if let Some((t, _)) = value {
+                        results.push((id, t.clone()));
+                    }
+                }
+
+                Ok(results)
+            }
+            VertexQuery::Traverse {
+                ref start,
+                direction,
+                ref type_filter,
+                depth,
+                limit,
+            } => {
+                let mut results = Vec::new();
+
+                if limit == 0 {
+                    return Ok(results);
+                }
+
+                self.traversal_limits.check_hops(depth)?;
+
+                let mut visited = HashSet::new();
+                let mut frontier = Vec::new();
+                let mut visited_vertices = 0;
+                let mut followed_edges = 0;
+
+                for (id, _) in self.get_vertex_values_by_query(start)? {
+                    if visited.insert(id) {
+                        frontier.push(id);
+                    }
+                }
+
+                for _ in 0..depth {
+                    if frontier.is_empty() {
+                        break;
+                    }
+
+                    let mut next_frontier = Vec::new();
+
+                    for id in self.get_neighbor_ids(&frontier, direction, type_filter.as_ref()) {
+                        followed_edges += 1;
+                        self.traversal_limits.check_edges(followed_edges)?;
+
+                        if !visited.insert(id) {
+                            continue;
+                        }
+
+                        visited_vertices += 1;
+                        self.traversal_limits.check_vertices(visited_vertices)?;
+
+                        if let Some((t, _)) = self.vertices.get(&id) {
+                            results.push((id, t.clone()));
+
+                            if results.len() == limit as usize {
+                                return Ok(results);
+                            }
+                        }
+
+                        next_frontier.push(id);
                     }
+
+                    frontier = next_frontier;
                 }
 
                 Ok(results)
             }
+            VertexQuery::DegreeAtLeast {
+                ref start,
+                direction,
+                ref type_filter,
+                min,
+                limit,
+            } => {
+                let candidates = match *start {
+                    Some(ref start) => self.get_vertex_values_by_query(start)?,
+                    None => self.vertices.iter().map(|(id, (t, _))| (*id, t.clone())).collect(),
+                };
+
+                Ok(candidates
+                    .into_iter()
+                    .filter(|&(id, _)| self.count_edges(id, type_filter.as_ref(), direction) >= min)
+                    .take(limit as usize)
+                    .collect())
+            }
+        }
+    }
+
+    fn count_edges(&self, id: Uuid, type_filter: Option<&models::Type>, direction: models::EdgeDirection) -> u64 {
+        match direction {
+            models::EdgeDirection::Both => {
+                // Every edge touching the vertex is stored under a single key
+                // with the vertex on either end, undirected edges included,
+                // so a self-loop or undirected edge is only counted once
+                self.edges
+                    .keys()
+                    .filter(|k| (k.outbound_id == id || k.inbound_id == id) && type_filter.is_none_or(|t| &k.t == t))
+                    .count() as u64
+            }
+            models::EdgeDirection::Outbound => self.count_outbound_edges(id, type_filter),
+            models::EdgeDirection::Inbound => self.count_inbound_edges(id, type_filter),
+        }
+    }
+
+    // Counts the edges of each of several vertices. Outbound edges are
+    // counted with a range scan per vertex, but inbound edges are tallied
+    // in a single scan over all edges, rather than one scan per vertex.
+    fn count_edges_for_many(
+        &self,
+        ids: &[Uuid],
+        type_filter: Option<&models::Type>,
+        direction: models::EdgeDirection,
+    ) -> Vec<(Uuid, u64)> {
+        if direction == models::EdgeDirection::Outbound {
+            return ids
+                .iter()
+                .map(|&id| (id, self.count_outbound_edges(id, type_filter)))
+                .collect();
+        }
+
+        let mut counts: HashMap<Uuid, u64> = ids.iter().map(|&id| (id, 0)).collect();
+
+        for key in self.edges.keys() {
+            if type_filter.is_some_and(|t| &key.t != t) {
+                continue;
+            }
+
+            if let Some(count) = counts.get_mut(&key.inbound_id) {
+                *count += 1;
+            }
+
+            // As in `count_edges`, an edge is only counted once per vertex
+            // when counting in both directions
+            if direction == models::EdgeDirection::Both && key.outbound_id != key.inbound_id {
+                if let Some(count) = counts.get_mut(&key.outbound_id) {
+                    *count += 1;
+                }
+            }
+        }
+
+        if direction == models::EdgeDirection::Inbound && !self.undirected_edges.is_empty() {
+            for (id, count) in &mut counts {
+                *count += self
+                    .scan_mirrored_edge_keys(*id, models::EdgeDirection::Inbound, type_filter)
+                    .count() as u64;
+            }
+        }
+
+        ids.iter().map(|id| (*id, counts[id])).collect()
+    }
+
+    // Counts the outbound edges of a vertex with a range scan, including
+    // undirected edges mirrored onto it.
+    fn count_outbound_edges(&self, id: Uuid, type_filter: Option<&models::Type>) -> u64 {
+        let lower_bound = match type_filter {
+            Some(type_filter) => models::EdgeKey::new(id, type_filter.clone(), Uuid::default()),
+            None => {
+                let empty_type = models::Type::default();
+                models::EdgeKey::new(id, empty_type, Uuid::default())
+            }
+        };
+        let range = self.edges.range(lower_bound..);
+
+        let range = range.take_while(|&(k, _)| {
+            if let Some(type_filter) = type_filter {
+                k.outbound_id == id && &k.t == type_filter
+            } else {
+                k.outbound_id == id
+            }
+        });
+
+        let mirrored_count = self
+            .scan_mirrored_edge_keys(id, models::EdgeDirection::Outbound, type_filter)
+            .count();
+        (range.count() + mirrored_count) as u64
+    }
+
+    // Counts the inbound edges of a vertex. Edges are keyed by their
+    // outbound vertex, so this has to scan all of them.
+    fn count_inbound_edges(&self, id: Uuid, type_filter: Option<&models::Type>) -> u64 {
+        let range = self.edges.iter().filter(|&(k, _)| {
+            if let Some(type_filter) = type_filter {
+                k.inbound_id == id && &k.t == type_filter
+            } else {
+                k.inbound_id == id
+            }
+        });
+
+        let mirrored_count = self
+            .scan_mirrored_edge_keys(id, models::EdgeDirection::Inbound, type_filter)
+            .count();
+        (range.count() + mirrored_count) as u64
+    }
+
+    fn count_vertices_by_type(&self) -> BTreeMap<models::Type, u64> {
+        let mut counts = BTreeMap::new();
+
+        for (t, _) in self.vertices.values() {
+            *counts.entry(t.clone()).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    fn get_neighbor_ids(
+        &self,
+        ids: &[Uuid],
+        direction: models::EdgeDirection,
+        type_filter: Option<&models::Type>,
+    ) -> Vec<Uuid> {
+        let mut neighbor_ids = Vec::new();
+
+        match direction {
+            models::EdgeDirection::Outbound => {
+                for id in ids {
+                    let lower_bound = match type_filter {
+                        Some(type_filter) => models::EdgeKey::new(*id, type_filter.clone(), Uuid::default()),
+                        None => models::EdgeKey::new(*id, models::Type::default(), Uuid::default()),
+                    };
+
+                    for key in self.edges.range(lower_bound..).map(|(key, _)| key) {
+                        if key.outbound_id != *id {
+                            break;
+                        }
+
+                        if let Some(type_filter) = type_filter {
+                            if &key.t != type_filter {
+                                break;
+                            }
+                        }
+
+                        neighbor_ids.push(key.inbound_id);
+                    }
+
+                    for key in self.scan_mirrored_edge_keys(*id, direction, type_filter) {
+                        neighbor_ids.push(key.inbound_id);
+                    }
+                }
+            }
+            models::EdgeDirection::Inbound => {
+                let candidate_ids: BTreeSet<&Uuid> = ids.iter().collect();
+
+                for key in self.edges.keys() {
+                    if !candidate_ids.contains(&key.inbound_id) {
+                        continue;
+                    }
+
+                    if let Some(type_filter) = type_filter {
+                        if &key.t != type_filter {
+                            continue;
+                        }
+                    }
+
+                    neighbor_ids.push(key.outbound_id);
+                }
+
+                for id in ids {
+                    for key in self.scan_mirrored_edge_keys(*id, direction, type_filter) {
+                        neighbor_ids.push(key.outbound_id);
+                    }
+                }
+            }
+            models::EdgeDirection::Both => {
+                neighbor_ids.extend(self.get_neighbor_ids(ids, models::EdgeDirection::Outbound, type_filter));
+                neighbor_ids.extend(self.get_neighbor_ids(ids, models::EdgeDirection::Inbound, type_filter));
+            }
         }
+
+        neighbor_ids
     }
 
-    fn get_edge_values_by_query(&self, q: &EdgeQuery) -> Result<Vec<(models::EdgeKey, DateTime<Utc>)>> {
+    fn get_edge_values_by_query(&self, q: &EdgeQuery) -> Result<Vec<EdgeValue>> {
+        q.validate()?;
+
         match *q {
             EdgeQuery::Edges { ref keys } => {
                 let mut results = Vec::new();
 
                 for key in keys {
-                    let value = self.edges.get(key);
+                    let value = self.edges.get(&self.resolve_edge_key(key));
 
-                    if let Some(update_datetime) = value {
-                        results.push((key.clone(), *update_datetime));
+                    if let Some(&(update_datetime, weight)) = value {
+                        results.push((key.clone(), update_datetime, weight));
                     }
                 }
 
                 Ok(results)
             }
+            EdgeQuery::All {
+                ref type_filter,
+                high_filter,
+                low_filter,
+                limit,
+            } => Ok(self
+                .edges
+                .iter()
+                .filter(|&(key, &(update_datetime, _))| {
+                    matches_type_filters(&key.t, type_filter.as_ref(), &[])
+                        && high_filter.is_none_or(|high_filter| update_datetime <= high_filter)
+                        && low_filter.is_none_or(|low_filter| update_datetime >= low_filter)
+                })
+                .take(limit as usize)
+                .map(|(key, &(update_datetime, weight))| (key.clone(), update_datetime, weight))
+                .collect()),
             EdgeQuery::Pipe {
                 ref vertex_query,
                 converter,
                 ref type_filter,
+                ref type_filters,
                 high_filter,
                 low_filter,
+                after_inbound_id,
+                offset,
                 limit,
             } => {
                 let vertex_values = self.get_vertex_values_by_query(&*vertex_query)?;
                 let mut results = Vec::new();
+                let mut skipped = 0;
 
                 if limit == 0 {
                     return Ok(results);
                 }
 
-                match converter {
-                    models::EdgeDirection::Outbound => {
-                        for (id, _) in vertex_values {
-                            let lower_bound = match *type_filter {
-                                Some(ref type_filter) => models::EdgeKey::new(id, type_filter.clone(), Uuid::default()),
-                                None => {
-                                    let empty_type = models::Type::default();
-                                    models::EdgeKey::new(id, empty_type, Uuid::default())
-                                }
-                            };
+                let matches_filters = |update_datetime: &DateTime<Utc>| {
+                    if let Some(high_filter) = high_filter {
+                        if *update_datetime > high_filter {
+                            return false;
+                        }
+                    }
 
-                            for (key, update_datetime) in self.edges.range(lower_bound..) {
-                                if key.outbound_id != id {
-                                    break;
-                                }
+                    if let Some(low_filter) = low_filter {
+                        if *update_datetime < low_filter {
+                            return false;
+                        }
+                    }
 
-                                if let Some(ref type_filter) = *type_filter {
-                                    if &key.t != type_filter {
-                                        break;
-                                    }
-                                }
+                    true
+                };
 
-                                if let Some(high_filter) = high_filter {
-                                    if *update_datetime > high_filter {
-                                        continue;
-                                    }
-                                }
+                // Edges are keyed by their outbound vertex, so finding
+                // inbound edges means scanning all of them. Matches are
+                // bucketed by inbound vertex so that, like with outbound
+                // queries, results are grouped in the order the vertex
+                // query produced the vertices. The buckets live in a
+                // `BTreeMap` so that anything iterating over them does so
+                // in a stable order.
+                let mut candidate_edges: BTreeMap<Uuid, Vec<EdgeValue>> = BTreeMap::new();
+
+                if converter != models::EdgeDirection::Outbound {
+                    for &(id, _) in &vertex_values {
+                        candidate_edges.insert(id, Vec::new());
+                    }
 
-                                if let Some(low_filter) = low_filter {
-                                    if *update_datetime < low_filter {
-                                        continue;
-                                    }
-                                }
+                    for (key, &(update_datetime, weight)) in &self.edges {
+                        if !matches_type_filters(&key.t, type_filter.as_ref(), type_filters) {
+                            continue;
+                        }
 
-                                results.push((key.clone(), *update_datetime));
+                        if !matches_filters(&update_datetime) {
+                            continue;
+                        }
 
-                                if results.len() == limit as usize {
-                                    return Ok(results);
-                                }
-                            }
+                        if let Some(edges) = candidate_edges.get_mut(&key.inbound_id) {
+                            edges.push((key.clone(), update_datetime, weight));
                         }
                     }
-                    models::EdgeDirection::Inbound => {
-                        let mut candidate_ids = HashSet::new();
-                        for (id, _) in vertex_values {
-                            candidate_ids.insert(id);
-                        }
 
-                        for (key, update_datetime) in &self.edges {
-                            if !candidate_ids.contains(&key.inbound_id) {
-                                continue;
-                            }
+                    if !self.undirected_edges.is_empty() {
+                        for (id, edges) in &mut candidate_edges {
+                            let mirrored_edges =
+                                self.get_mirrored_edges(*id, models::EdgeDirection::Inbound, type_filter.as_ref());
+                            edges.extend(mirrored_edges.into_iter().filter(|(key, update_datetime, _)| {
+                                matches_type_filters(&key.t, None, type_filters) && matches_filters(update_datetime)
+                            }));
+                            edges.sort_by(|first, second| first.0.cmp(&second.0));
+                        }
+                    }
+                }
 
-                            if let Some(ref type_filter) = *type_filter {
-                                if &key.t != type_filter {
-                                    continue;
+                // When piping in both directions, a vertex's outbound edges
+                // come before its inbound edges. An edge between two of the
+                // vertices, or a self-loop, would be found from both ends,
+                // so track what has been returned already. Keys are resolved
+                // so that an undirected edge is only returned once.
+                let mut seen_keys = HashSet::new();
+
+                for (id, _) in vertex_values {
+                    let mut edges: Vec<EdgeValue> = Vec::new();
+
+                    if converter != models::EdgeDirection::Inbound {
+                        // Edges are keyed by type after their outbound
+                        // vertex, so each filtered type is a tight range
+                        // scan. Scanning the types in sorted order keeps the
+                        // edges in key order.
+                        match scanned_edge_types(type_filter.as_ref(), type_filters) {
+                            Some(types) => {
+                                for t in types {
+                                    edges.extend(
+                                        self.edges
+                                            .range((outbound_range_start(id, t, after_inbound_id), Bound::Unbounded))
+                                            .take_while(|&(key, _)| key.outbound_id == id && &key.t == t)
+                                            .map(|(key, &(update_datetime, weight))| {
+                                                (key.clone(), update_datetime, weight)
+                                            }),
+                                    );
                                 }
                             }
-
-                            if let Some(high_filter) = high_filter {
-                                if *update_datetime > high_filter {
-                                    continue;
-                                }
+                            None => {
+                                let lower_bound = models::EdgeKey::new(id, models::Type::default(), Uuid::default());
+
+                                edges.extend(
+                                    self.edges
+                                        .range(lower_bound..)
+                                        .take_while(|&(key, _)| key.outbound_id == id)
+                                        .map(|(key, &(update_datetime, weight))| {
+                                            (key.clone(), update_datetime, weight)
+                                        }),
+                                );
                             }
+                        }
 
-                            if let Some(low_filter) = low_filter {
-                                if *update_datetime < low_filter {
-                                    continue;
-                                }
-                            }
+                        if !self.undirected_edges.is_empty() {
+                            let mirrored_edges =
+                                self.get_mirrored_edges(id, models::EdgeDirection::Outbound, type_filter.as_ref());
+                            edges.extend(
+                                mirrored_edges
+                                    .into_iter()
+                                    .filter(|(key, _, _)| matches_type_filters(&key.t, None, type_filters)),
+                            );
+                            edges.sort_by(|first, second| first.0.cmp(&second.0));
+                        }
 
-                            results.push((key.clone(), *update_datetime));
+                        // Unfiltered scans and mirrored edges cover every
+                        // type, so they can't seek past the cursor
+                        edges.retain(|(key, update_datetime, _)| {
+                            matches_filters(update_datetime)
+                                && after_inbound_id.is_none_or(|after_inbound_id| key.inbound_id > after_inbound_id)
+                        });
+                    }
 
-                            if results.len() == limit as usize {
-                                return Ok(results);
-                            }
+                    if let Some(inbound_edges) = candidate_edges.get(&id) {
+                        edges.extend(inbound_edges.iter().cloned());
+                    }
+
+                    for (key, update_datetime, weight) in edges {
+                        if converter == models::EdgeDirection::Both && !seen_keys.insert(self.resolve_edge_key(&key)) {
+                            continue;
+                        }
+
+                        if skipped < offset {
+                            skipped += 1;
+                            continue;
+                        }
+
+                        results.push((key, update_datetime, weight));
+
+                        if results.len() == limit as usize {
+                            return Ok(results);
                         }
                     }
                 }
@@ -202,26 +644,90 @@ impl InternalMemoryDatastore {
         }
     }
 
-    fn delete_vertices(&mut self, vertices: Vec<Uuid>) {
-        for vertex_id in vertices {
-            self.vertices.remove(&vertex_id);
-
-            let mut deletable_vertex_properties: Vec<(Uuid, String)> = Vec::new();
+    // Gets the keys of the undirected edges that are stored pointing the
+    // other way from how a vertex would see them in the given direction,
+    // reversed to match that direction. These are the edges that aren't
+    // found by looking at the stored keys alone. Self-loops are excluded,
+    // since they read the same both ways. Looking in both directions covers
+    // every stored key that touches the vertex, so nothing is mirrored then.
+    fn scan_mirrored_edge_keys<'a>(
+        &'a self,
+        id: Uuid,
+        direction: models::EdgeDirection,
+        type_filter: Option<&'a models::Type>,
+    ) -> Box<dyn Iterator<Item = models::EdgeKey> + 'a> {
+        let lower_bound = models::EdgeKey::new(id, type_filter.cloned().unwrap_or_default(), Uuid::default());
+        let in_range = move |key: &&models::EdgeKey| {
+            key.outbound_id == id && type_filter.is_none_or(|type_filter| &key.t == type_filter)
+        };
+        let is_not_self_loop = |key: &&models::EdgeKey| key.outbound_id != key.inbound_id;
+
+        match direction {
+            // Stored with the vertex as their inbound end
+            models::EdgeDirection::Outbound => Box::new(
+                self.reversed_undirected_edges
+                    .range(lower_bound..)
+                    .take_while(in_range)
+                    .filter(is_not_self_loop)
+                    .cloned(),
+            ),
+            // Stored with the vertex as their outbound end
+            models::EdgeDirection::Inbound => Box::new(
+                self.undirected_edges
+                    .range(lower_bound..)
+                    .take_while(in_range)
+                    .filter(is_not_self_loop)
+                    .map(|key| key.reversed()),
+            ),
+            models::EdgeDirection::Both => Box::new(iter::empty()),
+        }
+    }
 
-            for (property_key, _) in self.vertex_properties.range((vertex_id, "".to_string())..) {
-                let &(ref property_vertex_id, _) = property_key;
+    // Gets the undirected edges mirrored onto a vertex, as described in
+    // `scan_mirrored_edge_keys`, along with their update datetimes and
+    // weights.
+    fn get_mirrored_edges(
+        &self,
+        id: Uuid,
+        direction: models::EdgeDirection,
+        type_filter: Option<&models::Type>,
+    ) -> Vec<EdgeValue> {
+        self.scan_mirrored_edge_keys(id, direction, type_filter)
+            .map(|key| {
+                let (update_datetime, weight) = self.edges[&key.reversed()];
+                (key, update_datetime, weight)
+            })
+            .collect()
+    }
 
-                if &vertex_id != property_vertex_id {
-                    break;
-                }
+    // Maps a key to the key its edge is stored under. Undirected edges are
+    // stored once, under their canonical key, but can be addressed by their
+    // reversed key too.
+    fn resolve_edge_key(&self, key: &models::EdgeKey) -> models::EdgeKey {
+        if !self.edges.contains_key(key) {
+            let reversed_key = key.reversed();
 
-                deletable_vertex_properties.push(property_key.clone());
+            if self.undirected_edges.contains(&reversed_key) {
+                return reversed_key;
             }
+        }
 
-            for property_key in deletable_vertex_properties {
-                self.vertex_properties.remove(&property_key);
+        key.clone()
+    }
+
+    // Deletes vertices along with their properties and edges, sending a
+    // change for each vertex that existed. Returns how many existed.
+    fn delete_vertices(&mut self, vertices: Vec<Uuid>) -> u64 {
+        let mut count = 0;
+
+        for vertex_id in vertices {
+            if self.vertices.remove(&vertex_id).is_some() {
+                count += 1;
+                self.emit(|| ChangeEvent::VertexDeleted(vertex_id));
             }
 
+            self.remove_all_vertex_properties(vertex_id);
+
             let mut deletable_edges: Vec<models::EdgeKey> = Vec::new();
 
             for edge_key in self.edges.keys() {
@@ -230,257 +736,3319 @@ impl InternalMemoryDatastore {
                 }
             }
 
-            self.delete_edges(deletable_edges);
+            // The edges are deleted along with the vertex, so they don't
+            // get changes of their own
+            for edge_key in &deletable_edges {
+                self.delete_edge(edge_key);
+            }
+
+            self.out_degrees.remove(&vertex_id);
+            self.in_degrees.remove(&vertex_id);
         }
+
+        count
     }
 
-    fn delete_edges(&mut self, edges: Vec<models::EdgeKey>) {
-        for edge_key in edges {
-            self.edges.remove(&edge_key);
+    fn validate_property_value(&self, name: &str, value: &JsonValue) -> Result<()> {
+        if let Some(t) = self.property_types.get(name) {
+            if !t.matches(value) {
+                let err: ValidationError = format!("Property `{}` must be of type {:?}", name, t).into();
+                return Err(err.into());
+            }
+        }
 
-            let mut deletable_edge_properties: Vec<(models::EdgeKey, String)> = Vec::new();
+        util::validate_property_size(name, value, self.max_property_size_bytes)?;
+        Ok(())
+    }
 
-            for (property_key, _) in self.edge_properties.range((edge_key.clone(), "".to_string())..) {
-                let &(ref property_edge_key, _) = property_key;
+    fn get_edge_records(&self, q: &EdgeQuery) -> Result<Vec<models::EdgeRecord>> {
+        let edge_values = self.get_edge_values_by_query(q)?;
 
-                if &edge_key != property_edge_key {
-                    break;
-                }
+        Ok(edge_values
+            .into_iter()
+            .map(|(key, update_datetime, weight)| {
+                let stored_key = self.resolve_edge_key(&key);
+                let properties = self
+                    .edge_properties
+                    .range((stored_key.clone(), "".to_string())..)
+                    .take_while(|&((property_edge_key, _), _)| property_edge_key == &stored_key)
+                    .map(|((_, name), (value, _))| models::NamedProperty::new(name.clone(), value.clone()))
+                    .collect();
+
+                models::EdgeRecord::new(models::Edge::with_weight(key, update_datetime, weight), properties)
+            })
+            .collect())
+    }
 
-                deletable_edge_properties.push(property_key.clone());
+    // Sets a vertex property, keeping the property indexes up-to-date.
+    fn set_vertex_property(&mut self, id: Uuid, name: String, value: JsonValue, update_datetime: DateTime<Utc>) {
+        if let Some(index) = self.property_indexes.get_mut(&name) {
+            if let Some((old_value, _)) = self.vertex_properties.get(&(id, name.clone())) {
+                remove_from_property_index(index, &old_value.to_string(), id);
             }
 
-            for property_key in deletable_edge_properties {
-                self.edge_properties.remove(&property_key);
-            }
+            index.entry(value.to_string()).or_insert_with(BTreeSet::new).insert(id);
         }
+
+        self.vertex_properties.insert((id, name), (value, update_datetime));
     }
-}
 
-/// An in-memory-only datastore.
-#[derive(Debug)]
-pub struct MemoryDatastore(Arc<RwLock<InternalMemoryDatastore>>);
+    // Removes a vertex property, keeping the property indexes up-to-date.
+    // Returns whether the vertex had the property.
+    fn remove_vertex_property(&mut self, id: Uuid, name: &str) -> bool {
+        match self.vertex_properties.remove(&(id, name.to_string())) {
+            Some((old_value, _)) => {
+                if let Some(index) = self.property_indexes.get_mut(name) {
+                    remove_from_property_index(index, &old_value.to_string(), id);
+                }
 
-impl MemoryDatastore {
-    /// Creates a new in-memory datastore.
-    pub fn default() -> MemoryDatastore {
-        Self {
-            0: Arc::new(RwLock::new(InternalMemoryDatastore {
-                edge_properties: BTreeMap::new(),
-                edges: BTreeMap::new(),
-                vertex_properties: BTreeMap::new(),
-                vertices: BTreeMap::new(),
-            })),
+                true
+            }
+            None => false,
         }
     }
-}
 
-impl Datastore for MemoryDatastore {
-    type Trans = MemoryTransaction;
+    // Sends an event to every subscriber, forgetting the ones that have
+    // dropped their receiver. The event is only built if there are
+    // subscribers, so that writes don't pay for it otherwise.
+    fn emit<F: FnOnce() -> ChangeEvent>(&mut self, event: F) {
+        if self.subscribers.is_empty() {
+            return;
+        }
 
-    fn transaction(&self) -> Result<Self::Trans> {
-        Ok(MemoryTransaction {
-            datastore: Arc::clone(&self.0),
-        })
+        let event = event();
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
     }
-}
 
-/// A transaction for manipulating in-memory-only datastores.
-#[derive(Debug)]
-pub struct MemoryTransaction {
-    datastore: Arc<RwLock<InternalMemoryDatastore>>,
-}
+    // Removes all of the properties of a vertex, keeping the property
+    // indexes up-to-date.
+    fn remove_all_vertex_properties(&mut self, id: Uuid) {
+        let mut deletable_vertex_properties: Vec<(Uuid, String)> = Vec::new();
 
-impl Transaction for MemoryTransaction {
-    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
-        let mut datastore = self.datastore.write().unwrap();
-        let mut inserted = false;
+        for (property_key, _) in self.vertex_properties.range((id, "".to_string())..) {
+            let (property_vertex_id, _) = property_key;
 
-        datastore.vertices.entry(vertex.id).or_insert_with(|| {
-            inserted = true;
-            vertex.t.clone()
-        });
+            if &id != property_vertex_id {
+                break;
+            }
 
-        Ok(inserted)
-    }
+            deletable_vertex_properties.push(property_key.clone());
+        }
 
-    fn get_vertices(&self, q: &VertexQuery) -> Result<Vec<models::Vertex>> {
-        let vertex_values = self.datastore.read().unwrap().get_vertex_values_by_query(q)?;
-        let iter = vertex_values
-            .into_iter()
-            .map(|(uuid, t)| models::Vertex::with_id(uuid, t));
-        Ok(iter.collect())
+        for (_, name) in deletable_vertex_properties {
+            self.remove_vertex_property(id, &name);
+        }
     }
 
-    fn delete_vertices(&self, q: &VertexQuery) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
-        let deletable_vertices = datastore
-            .get_vertex_values_by_query(q)?
-            .into_iter()
-            .map(|(k, _)| k)
-            .collect();
-        datastore.delete_vertices(deletable_vertices);
-        Ok(())
+    // Gets the ids of the vertices whose property equals the given value,
+    // in sorted order. Uses the property's index if there is one, and
+    // otherwise scans all vertex properties.
+    fn get_vertex_ids_by_property_value(&self, name: &str, value: &JsonValue) -> Vec<Uuid> {
+        match self.property_indexes.get(name) {
+            Some(index) => match index.get(&value.to_string()) {
+                Some(ids) => ids.iter().cloned().collect(),
+                None => Vec::new(),
+            },
+            None => self
+                .vertex_properties
+                .iter()
+                .filter(|&((_, property_name), (property_value, _))| property_name == name && property_value == value)
+                .map(|(&(id, _), _)| id)
+                .collect(),
+        }
     }
 
-    fn get_vertex_count(&self) -> Result<u64> {
-        let datastore = self.datastore.read().unwrap();
+    fn contains_bulk_insert_item(&self, item: &models::BulkInsertItem) -> bool {
+        match *item {
+            models::BulkInsertItem::Vertex(ref vertex) => self.vertices.contains_key(&vertex.id),
+            models::BulkInsertItem::Edge(ref edge_key) => self.edges.contains_key(&self.resolve_edge_key(edge_key)),
+            models::BulkInsertItem::VertexProperty(id, ref name, _) => {
+                self.vertex_properties.contains_key(&(id, name.clone()))
+            }
+            models::BulkInsertItem::EdgeProperty(ref edge_key, ref name, _) => self
+                .edge_properties
+                .contains_key(&(self.resolve_edge_key(edge_key), name.clone())),
+        }
+    }
+
+    // Checks that no edge appears more than once in a bulk insert, if
+    // duplicates are rejected. Every duplicated key is listed in the error.
+    fn check_duplicate_bulk_edges(&self, items: &[models::BulkInsertItem]) -> Result<()> {
+        if !self.reject_duplicate_bulk_edges {
+            return Ok(());
+        }
+
+        let mut seen_keys = HashSet::new();
+        let mut duplicate_keys = Vec::new();
+
+        for item in items {
+            if let models::BulkInsertItem::Edge(ref key) = *item {
+                let key = self.normalize_edge_key(key);
+
+                if !seen_keys.insert(key.clone()) && !duplicate_keys.contains(&key) {
+                    duplicate_keys.push(key);
+                }
+            }
+        }
+
+        if duplicate_keys.is_empty() {
+            Ok(())
+        } else {
+            Err(ErrorKind::DuplicateBulkInsertEdges(format!("{:?}", duplicate_keys)).into())
+        }
+    }
+
+    // Checks that the vertices of every edge in a bulk insert either exist
+    // already, or are inserted by an earlier item, if the datastore is
+    // configured to reject edges whose vertices don't exist.
+    fn check_dangling_bulk_edges(&self, items: &[models::BulkInsertItem]) -> Result<()> {
+        if !self.reject_dangling_bulk_edges {
+            return Ok(());
+        }
+
+        let mut inserted_ids = HashSet::new();
+        let mut dangling_keys = Vec::new();
+
+        for item in items {
+            match *item {
+                models::BulkInsertItem::Vertex(ref vertex) => {
+                    inserted_ids.insert(vertex.id);
+                }
+                models::BulkInsertItem::Edge(ref key) => {
+                    let exists = |id| inserted_ids.contains(&id) || self.vertices.contains_key(&id);
+
+                    if !exists(key.outbound_id) || !exists(key.inbound_id) {
+                        dangling_keys.push(key);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if dangling_keys.is_empty() {
+            Ok(())
+        } else {
+            Err(ErrorKind::DanglingBulkInsertEdges(format!("{:?}", dangling_keys)).into())
+        }
+    }
+
+    // When types are case-insensitive, every type is lowercased before it's
+    // stored or queried, so that stored types and queries always agree.
+    fn normalize_type<'a>(&self, t: &'a models::Type) -> Cow<'a, models::Type> {
+        if self.case_insensitive_types {
+            Cow::Owned(t.to_lowercase())
+        } else {
+            Cow::Borrowed(t)
+        }
+    }
+
+    fn normalize_edge_key<'a>(&self, key: &'a models::EdgeKey) -> Cow<'a, models::EdgeKey> {
+        if self.case_insensitive_types {
+            Cow::Owned(models::EdgeKey::new(
+                key.outbound_id,
+                key.t.to_lowercase(),
+                key.inbound_id,
+            ))
+        } else {
+            Cow::Borrowed(key)
+        }
+    }
+
+    fn normalize_vertex_query<'a>(&self, q: &'a VertexQuery) -> Cow<'a, VertexQuery> {
+        if self.case_insensitive_types {
+            let mut q = q.clone();
+            q.lowercase_types();
+            Cow::Owned(q)
+        } else {
+            Cow::Borrowed(q)
+        }
+    }
+
+    fn normalize_edge_query<'a>(&self, q: &'a EdgeQuery) -> Cow<'a, EdgeQuery> {
+        if self.case_insensitive_types {
+            let mut q = q.clone();
+            q.lowercase_types();
+            Cow::Owned(q)
+        } else {
+            Cow::Borrowed(q)
+        }
+    }
+
+    fn normalize_bulk_insert_item(&self, item: models::BulkInsertItem) -> models::BulkInsertItem {
+        if !self.case_insensitive_types {
+            return item;
+        }
+
+        match item {
+            models::BulkInsertItem::Vertex(vertex) => {
+                models::BulkInsertItem::Vertex(models::Vertex::with_id(vertex.id, vertex.t.to_lowercase()))
+            }
+            models::BulkInsertItem::Edge(key) => {
+                models::BulkInsertItem::Edge(self.normalize_edge_key(&key).into_owned())
+            }
+            models::BulkInsertItem::VertexProperty(id, name, value) => {
+                models::BulkInsertItem::VertexProperty(id, name, value)
+            }
+            models::BulkInsertItem::EdgeProperty(key, name, value) => {
+                models::BulkInsertItem::EdgeProperty(self.normalize_edge_key(&key).into_owned(), name, value)
+            }
+        }
+    }
+
+    // Checks whether an edge can be created, i.e. both of its vertices exist
+    // and it isn't a disallowed self-loop.
+    fn is_valid_edge(&self, key: &models::EdgeKey) -> bool {
+        (self.allow_self_loops || key.outbound_id != key.inbound_id)
+            && self.vertices.contains_key(&key.outbound_id)
+            && self.vertices.contains_key(&key.inbound_id)
+    }
+
+    // Inserts or updates a vertex, sending a change if it was created or its
+    // type changed. A vertex that already exists keeps its creation datetime
+    // unless one is given.
+    fn insert_vertex(&mut self, id: Uuid, t: models::Type, created_datetime: Option<DateTime<Utc>>) {
+        let old_value = self.vertices.get(&id).cloned();
+        let created_datetime = created_datetime
+            .or_else(|| old_value.as_ref().map(|&(_, created_datetime)| created_datetime))
+            .unwrap_or_else(|| self.now());
+        self.vertices.insert(id, (t.clone(), created_datetime));
+
+        match old_value {
+            None => self.emit(|| ChangeEvent::VertexCreated(models::Vertex::with_id(id, t))),
+            Some((ref old_t, _)) if *old_t != t => {
+                self.emit(|| ChangeEvent::VertexUpdated(models::Vertex::with_id(id, t)))
+            }
+            Some(_) => (),
+        }
+    }
+
+    // Inserts or updates an edge, keeping the degree counters up-to-date,
+    // and sends a change for the key it was given. If the key addresses an
+    // existing undirected edge by its reversed key, that edge is updated
+    // instead. Updating an edge without a weight keeps the weight it
+    // already has.
+    fn insert_edge(&mut self, key: models::EdgeKey, update_datetime: DateTime<Utc>, weight: Option<f64>) {
+        let stored_key = self.resolve_edge_key(&key);
+        let weight = weight.or_else(|| self.edges.get(&stored_key).and_then(|&(_, weight)| weight));
+
+        let created = match self.edges.insert(stored_key.clone(), (update_datetime, weight)) {
+            Some((old_update_datetime, _)) => {
+                self.remove_from_edge_timelines(&stored_key, old_update_datetime);
+                false
+            }
+            None => {
+                self.increment_degrees(&stored_key);
+                true
+            }
+        };
+
+        self.add_to_edge_timelines(&stored_key, update_datetime);
+
+        if self.check_edge_times {
+            self.check_edge_time(stored_key, update_datetime);
+        }
+
+        if created {
+            self.emit(|| ChangeEvent::EdgeCreated(key));
+        } else {
+            self.emit(|| ChangeEvent::EdgeUpdated(key));
+        }
+    }
+
+    // Records whether an edge's update datetime predates the creation of
+    // either of its vertices.
+    fn check_edge_time(&mut self, key: models::EdgeKey, update_datetime: DateTime<Utc>) {
+        let predates_vertices = [key.outbound_id, key.inbound_id].iter().any(|id| {
+            self.vertices
+                .get(id)
+                .is_some_and(|&(_, created_datetime)| update_datetime < created_datetime)
+        });
+
+        if predates_vertices {
+            self.inconsistent_edges.insert(key);
+        } else {
+            self.inconsistent_edges.remove(&key);
+        }
+    }
+
+    // Checks the times of every edge, as `check_edge_time` does, if the
+    // checks are enabled. This is for after edges were inserted without
+    // going through `insert_edge`.
+    fn recheck_edge_times(&mut self) {
+        if !self.check_edge_times {
+            return;
+        }
+
+        let edges: Vec<(models::EdgeKey, DateTime<Utc>)> = self
+            .edges
+            .iter()
+            .map(|(key, &(update_datetime, _))| (key.clone(), update_datetime))
+            .collect();
+
+        for (key, update_datetime) in edges {
+            self.check_edge_time(key, update_datetime);
+        }
+    }
+
+    fn add_to_edge_timelines(&mut self, key: &models::EdgeKey, update_datetime: DateTime<Utc>) {
+        for id in &[key.outbound_id, key.inbound_id] {
+            self.edge_timelines
+                .entry(*id)
+                .or_default()
+                .insert((update_datetime, key.clone()));
+        }
+    }
+
+    fn remove_from_edge_timelines(&mut self, key: &models::EdgeKey, update_datetime: DateTime<Utc>) {
+        for id in &[key.outbound_id, key.inbound_id] {
+            let remove = match self.edge_timelines.get_mut(id) {
+                Some(timeline) => {
+                    timeline.remove(&(update_datetime, key.clone()));
+                    timeline.is_empty()
+                }
+                None => false,
+            };
+
+            if remove {
+                self.edge_timelines.remove(id);
+            }
+        }
+    }
+
+    fn rebuild_edge_timelines(&mut self) {
+        self.edge_timelines.clear();
+
+        let edges: Vec<(models::EdgeKey, DateTime<Utc>)> = self
+            .edges
+            .iter()
+            .map(|(key, &(update_datetime, _))| (key.clone(), update_datetime))
+            .collect();
+
+        for (key, update_datetime) in edges {
+            self.add_to_edge_timelines(&key, update_datetime);
+        }
+    }
+
+    // Gets the most recently updated edges touching a vertex, in either
+    // direction, newest first.
+    fn get_vertex_timeline(&self, id: Uuid, limit: u32) -> Vec<models::Edge> {
+        let timeline = match self.edge_timelines.get(&id) {
+            Some(timeline) => timeline,
+            None => return Vec::new(),
+        };
+
+        timeline
+            .iter()
+            .rev()
+            .take(limit as usize)
+            .map(|&(update_datetime, ref key)| {
+                let weight = self.edges.get(key).and_then(|&(_, weight)| weight);
+                models::Edge::with_weight(key.clone(), update_datetime, weight)
+            })
+            .collect()
+    }
+
+    // Inserts or updates an undirected edge under its canonical key. If a
+    // directed edge with that key already exists, it becomes undirected.
+    fn insert_undirected_edge(&mut self, key: models::EdgeKey, update_datetime: DateTime<Utc>, weight: Option<f64>) {
+        let key = key.canonical();
+
+        if self.undirected_edges.insert(key.clone()) {
+            self.reversed_undirected_edges.insert(key.reversed());
+
+            // The edge was already counted in its stored direction
+            if self.edges.contains_key(&key) && key.outbound_id != key.inbound_id {
+                *self.out_degrees.entry(key.inbound_id).or_insert(0) += 1;
+                *self.in_degrees.entry(key.outbound_id).or_insert(0) += 1;
+            }
+        }
+
+        self.insert_edge(key, update_datetime, weight);
+    }
+
+    // Sets or clears when an edge expires. The key must already be
+    // resolved.
+    fn set_edge_expiration(&mut self, key: models::EdgeKey, expires_datetime: Option<DateTime<Utc>>) {
+        if let Some(old_expires_datetime) = self.edge_expirations.remove(&key) {
+            self.expiring_edges.remove(&(old_expires_datetime, key.clone()));
+        }
+
+        if let Some(expires_datetime) = expires_datetime {
+            self.expiring_edges.insert((expires_datetime, key.clone()));
+            self.edge_expirations.insert(key, expires_datetime);
+        }
+    }
+
+    // Whether any edges created with a TTL have expired, and are waiting to
+    // be purged.
+    fn has_expired_edges(&self) -> bool {
+        self.expiring_edges
+            .iter()
+            .next()
+            .is_some_and(|&(expires_datetime, _)| expires_datetime <= self.now())
+    }
+
+    // Deletes the edges whose TTL has expired, returning how many there
+    // were.
+    fn purge_expired_edges(&mut self) -> u64 {
+        if !self.has_expired_edges() {
+            return 0;
+        }
+
+        let now = self.now();
+        let expired_keys: Vec<models::EdgeKey> = self
+            .expiring_edges
+            .iter()
+            .take_while(|&&(expires_datetime, _)| expires_datetime <= now)
+            .map(|(_, key)| key.clone())
+            .collect();
+
+        for key in &expired_keys {
+            self.delete_edge(key);
+            self.emit(|| ChangeEvent::EdgeDeleted(key.clone()));
+        }
+
+        expired_keys.len() as u64
+    }
+
+    // Counts an edge in the degrees of its vertices. Undirected edges count
+    // as both outbound and inbound edges of each of their vertices.
+    fn increment_degrees(&mut self, key: &models::EdgeKey) {
+        *self.out_degrees.entry(key.outbound_id).or_insert(0) += 1;
+        *self.in_degrees.entry(key.inbound_id).or_insert(0) += 1;
+
+        if self.undirected_edges.contains(key) && key.outbound_id != key.inbound_id {
+            *self.out_degrees.entry(key.inbound_id).or_insert(0) += 1;
+            *self.in_degrees.entry(key.outbound_id).or_insert(0) += 1;
+        }
+    }
+
+    // Inserts items without maintaining the degree counters or property
+    // indexes, which have to be rebuilt afterwards. Edges whose vertices
+    // don't exist and properties of missing vertices or edges are skipped.
+    fn load_items<I>(&mut self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        for item in items {
+            match self.normalize_bulk_insert_item(item) {
+                models::BulkInsertItem::Vertex(vertex) => {
+                    self.insert_vertex(vertex.id, vertex.t, None);
+                }
+                models::BulkInsertItem::Edge(edge_key) => {
+                    if self.is_valid_edge(&edge_key) {
+                        let stored_key = self.resolve_edge_key(&edge_key);
+                        let weight = self.edges.get(&stored_key).and_then(|&(_, weight)| weight);
+
+                        if self.edges.insert(stored_key, (self.now(), weight)).is_some() {
+                            self.emit(|| ChangeEvent::EdgeUpdated(edge_key));
+                        } else {
+                            self.emit(|| ChangeEvent::EdgeCreated(edge_key));
+                        }
+                    }
+                }
+                models::BulkInsertItem::VertexProperty(id, name, value) => {
+                    if self.vertices.contains_key(&id) {
+                        self.validate_property_value(&name, &value)?;
+                        self.vertex_properties
+                            .insert((id, name.clone()), (value.clone(), self.now()));
+                        self.emit(|| ChangeEvent::VertexPropertySet(models::VertexProperty::new(id, name, value)));
+                    }
+                }
+                models::BulkInsertItem::EdgeProperty(edge_key, name, value) => {
+                    let edge_key = self.resolve_edge_key(&edge_key);
+
+                    if self.edges.contains_key(&edge_key) {
+                        self.validate_property_value(&name, &value)?;
+                        self.edge_properties
+                            .insert((edge_key.clone(), name.clone()), (value.clone(), self.now()));
+                        self.emit(|| ChangeEvent::EdgePropertySet(models::EdgeProperty::new(edge_key, name, value)));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Checks a batch operation for the errors that don't depend on the
+    // data, so that they can be found before anything is changed.
+    fn check_batch_operation(&self, operation: &models::BatchOperation) -> Result<()> {
+        match *operation {
+            models::BatchOperation::CreateVertex(_) | models::BatchOperation::CreateEdge(_) => Ok(()),
+            models::BatchOperation::DeleteVertices(ref q)
+            | models::BatchOperation::DeleteVertexProperties(ref q, _) => self.check_vertex_query(q),
+            models::BatchOperation::DeleteEdges(ref q) | models::BatchOperation::DeleteEdgeProperties(ref q, _) => {
+                self.check_edge_query(q)
+            }
+            models::BatchOperation::SetVertexProperties(ref q, ref name, ref value) => {
+                self.validate_property_value(name, value)?;
+                self.check_vertex_query(q)
+            }
+            models::BatchOperation::SetEdgeProperties(ref q, ref name, ref value) => {
+                self.validate_property_value(name, value)?;
+                self.check_edge_query(q)
+            }
+        }
+    }
+
+    // Checks a vertex query, and the queries it's built from, for the errors
+    // that don't depend on the data.
+    fn check_vertex_query(&self, q: &VertexQuery) -> Result<()> {
+        match *q {
+            VertexQuery::Pipe { ref edge_query, .. } => self.check_edge_query(edge_query),
+            VertexQuery::Traverse {
+                ref start,
+                depth,
+                limit,
+                ..
+            } => {
+                if limit > 0 {
+                    self.traversal_limits.check_hops(depth)?;
+                }
+
+                self.check_vertex_query(start)
+            }
+            VertexQuery::DegreeAtLeast {
+                start: Some(ref start), ..
+            } => self.check_vertex_query(start),
+            _ => Ok(()),
+        }
+    }
+
+    // Checks an edge query, and the queries it's built from, for the errors
+    // that don't depend on the data.
+    fn check_edge_query(&self, q: &EdgeQuery) -> Result<()> {
+        q.validate()?;
+
+        match *q {
+            EdgeQuery::Pipe { ref vertex_query, .. } => self.check_vertex_query(vertex_query),
+            EdgeQuery::Edges { .. } | EdgeQuery::All { .. } => Ok(()),
+        }
+    }
+
+    fn apply_batch_operation(&mut self, operation: models::BatchOperation) -> Result<()> {
+        match operation {
+            models::BatchOperation::CreateVertex(vertex) => {
+                let t = self.normalize_type(&vertex.t).into_owned();
+
+                if !self.vertices.contains_key(&vertex.id) {
+                    self.insert_vertex(vertex.id, t, None);
+                }
+            }
+            models::BatchOperation::CreateEdge(key) => {
+                let key = self.normalize_edge_key(&key).into_owned();
+
+                if self.is_valid_edge(&key) {
+                    self.insert_edge(key, self.now(), None);
+                }
+            }
+            models::BatchOperation::DeleteVertices(q) => {
+                let deletable_vertices = self
+                    .get_vertex_values_by_query(&self.normalize_vertex_query(&q))?
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .collect();
+                self.delete_vertices(deletable_vertices);
+            }
+            models::BatchOperation::DeleteEdges(q) => {
+                let deletable_edges = self
+                    .get_edge_values_by_query(&self.normalize_edge_query(&q))?
+                    .into_iter()
+                    .map(|(key, _, _)| key)
+                    .collect();
+                self.delete_edges(deletable_edges);
+            }
+            models::BatchOperation::SetVertexProperties(q, name, value) => {
+                for (id, _) in self.get_vertex_values_by_query(&self.normalize_vertex_query(&q))? {
+                    self.set_vertex_property(id, name.clone(), value.clone(), self.now());
+                    self.emit(|| {
+                        ChangeEvent::VertexPropertySet(models::VertexProperty::new(id, name.clone(), value.clone()))
+                    });
+                }
+            }
+            models::BatchOperation::SetEdgeProperties(q, name, value) => {
+                for (key, _, _) in self.get_edge_values_by_query(&self.normalize_edge_query(&q))? {
+                    let key = self.resolve_edge_key(&key);
+                    self.edge_properties
+                        .insert((key.clone(), name.clone()), (value.clone(), self.now()));
+                    self.emit(|| {
+                        ChangeEvent::EdgePropertySet(models::EdgeProperty::new(key, name.clone(), value.clone()))
+                    });
+                }
+            }
+            models::BatchOperation::DeleteVertexProperties(q, name) => {
+                for (id, _) in self.get_vertex_values_by_query(&self.normalize_vertex_query(&q))? {
+                    if self.remove_vertex_property(id, &name) {
+                        self.emit(|| ChangeEvent::VertexPropertyDeleted(id, name.clone()));
+                    }
+                }
+            }
+            models::BatchOperation::DeleteEdgeProperties(q, name) => {
+                for (key, _, _) in self.get_edge_values_by_query(&self.normalize_edge_query(&q))? {
+                    let key = self.resolve_edge_key(&key);
+
+                    if self.edge_properties.remove(&(key.clone(), name.clone())).is_some() {
+                        self.emit(|| ChangeEvent::EdgePropertyDeleted(key, name.clone()));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn build_property_index(&self, name: &str) -> HashMap<String, BTreeSet<Uuid>> {
+        let mut index: HashMap<String, BTreeSet<Uuid>> = HashMap::new();
+
+        for (&(id, ref property_name), (value, _)) in &self.vertex_properties {
+            if property_name == name {
+                index.entry(value.to_string()).or_default().insert(id);
+            }
+        }
+
+        index
+    }
+
+    fn rebuild_property_indexes(&mut self) {
+        let names: Vec<String> = self.property_indexes.keys().cloned().collect();
+
+        for name in names {
+            let index = self.build_property_index(&name);
+            self.property_indexes.insert(name, index);
+        }
+    }
+
+    fn recompute_degrees(&mut self) {
+        self.out_degrees.clear();
+        self.in_degrees.clear();
+
+        let keys: Vec<models::EdgeKey> = self.edges.keys().cloned().collect();
+
+        for key in keys {
+            self.increment_degrees(&key);
+        }
+    }
+
+    // Deletes edges and their properties, sending a change for each edge
+    // that existed. Returns how many existed.
+    fn delete_edges(&mut self, edges: Vec<models::EdgeKey>) -> u64 {
+        let mut count = 0;
+
+        for edge_key in edges {
+            if self.delete_edge(&edge_key) {
+                count += 1;
+                self.emit(|| ChangeEvent::EdgeDeleted(edge_key));
+            }
+        }
+
+        count
+    }
+
+    // Deletes an edge and its properties, returning whether the edge
+    // existed.
+    fn delete_edge(&mut self, edge_key: &models::EdgeKey) -> bool {
+        let edge_key = self.resolve_edge_key(edge_key);
+        let deleted = match self.edges.remove(&edge_key) {
+            Some((update_datetime, _)) => {
+                self.remove_from_edge_timelines(&edge_key, update_datetime);
+                self.inconsistent_edges.remove(&edge_key);
+                self.set_edge_expiration(edge_key.clone(), None);
+                decrement_degree(&mut self.out_degrees, edge_key.outbound_id);
+                decrement_degree(&mut self.in_degrees, edge_key.inbound_id);
+
+                if self.undirected_edges.remove(&edge_key) {
+                    self.reversed_undirected_edges.remove(&edge_key.reversed());
+
+                    if edge_key.outbound_id != edge_key.inbound_id {
+                        decrement_degree(&mut self.out_degrees, edge_key.inbound_id);
+                        decrement_degree(&mut self.in_degrees, edge_key.outbound_id);
+                    }
+                }
+
+                true
+            }
+            None => false,
+        };
+
+        self.remove_all_edge_properties(&edge_key);
+        deleted
+    }
+
+    // Deletes the edges of an outbound pipe query with type filters, e.g.
+    // all of the `follows` edges of a vertex. Rather than collecting every
+    // matching edge up-front, each vertex and type is a tight range scan
+    // that's deleted before moving on to the next, so only one range's keys
+    // are buffered at a time. The edges deleted are the same as those
+    // `get_edge_values_by_query` would return. Returns `None` if the query
+    // isn't of that shape, or if there are undirected edges, whose mirrored
+    // keys aren't found by range scans.
+    fn delete_outbound_edges_by_type(&mut self, q: &EdgeQuery) -> Result<Option<u64>> {
+        let (vertex_query, types, high_filter, low_filter, after_inbound_id, limit) = match *q {
+            EdgeQuery::Pipe {
+                ref vertex_query,
+                converter: models::EdgeDirection::Outbound,
+                ref type_filter,
+                ref type_filters,
+                high_filter,
+                low_filter,
+                after_inbound_id,
+                offset: 0,
+                limit,
+            } if self.undirected_edges.is_empty() => match scanned_edge_types(type_filter.as_ref(), type_filters) {
+                Some(types) => (
+                    vertex_query,
+                    types,
+                    high_filter,
+                    low_filter,
+                    after_inbound_id,
+                    u64::from(limit),
+                ),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        q.validate()?;
+        let vertex_values = self.get_vertex_values_by_query(vertex_query)?;
+        let mut keys = Vec::new();
+        let mut count = 0;
+
+        for (id, _) in vertex_values {
+            for t in &types {
+                if count == limit {
+                    return Ok(Some(count));
+                }
+
+                keys.extend(
+                    self.edges
+                        .range((outbound_range_start(id, t, after_inbound_id), Bound::Unbounded))
+                        .take_while(|&(key, _)| key.outbound_id == id && &key.t == *t)
+                        .filter(|&(_, &(update_datetime, _))| {
+                            high_filter.is_none_or(|high_filter| update_datetime <= high_filter)
+                                && low_filter.is_none_or(|low_filter| update_datetime >= low_filter)
+                        })
+                        .take((limit - count) as usize)
+                        .map(|(key, _)| key.clone()),
+                );
+
+                for key in keys.drain(..) {
+                    self.delete_edge(&key);
+                    count += 1;
+                    self.emit(|| ChangeEvent::EdgeDeleted(key));
+                }
+            }
+        }
+
+        Ok(Some(count))
+    }
+
+    // Removes all of the properties of an edge, given the key it's stored
+    // under.
+    fn remove_all_edge_properties(&mut self, edge_key: &models::EdgeKey) {
+        let mut deletable_edge_properties: Vec<(models::EdgeKey, String)> = Vec::new();
+
+        for (property_key, _) in self.edge_properties.range((edge_key.clone(), "".to_string())..) {
+            let (property_edge_key, _) = property_key;
+
+            if edge_key != property_edge_key {
+                break;
+            }
+
+            deletable_edge_properties.push(property_key.clone());
+        }
+
+        for property_key in deletable_edge_properties {
+            self.edge_properties.remove(&property_key);
+        }
+    }
+
+    // Finds references to vertices and edges that don't exist. Edge
+    // properties are keyed by the key their edge is stored under, so they
+    // don't need resolving.
+    fn integrity_report(&self) -> IntegrityReport {
+        IntegrityReport {
+            dangling_edges: self
+                .edges
+                .keys()
+                .filter(|key| !self.is_valid_edge(key))
+                .cloned()
+                .collect(),
+            dangling_vertex_properties: self
+                .vertex_properties
+                .keys()
+                .filter(|&&(id, _)| !self.vertices.contains_key(&id))
+                .cloned()
+                .collect(),
+            dangling_edge_properties: self
+                .edge_properties
+                .keys()
+                .filter(|&(key, _)| !self.edges.contains_key(key))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+// Gets the bounds of the vertex ids covered by an `All` query, or `None` if
+// no ids are covered. `BTreeMap::range` panics if the start of a range is
+// after its end, so this has to be checked beforehand.
+fn vertex_id_bounds(start_id: Option<Uuid>, end_id: Option<Uuid>) -> Option<(Bound<Uuid>, Bound<Uuid>)> {
+    match (start_id, end_id) {
+        (Some(start_id), Some(end_id)) if start_id >= end_id => None,
+        _ => Some((
+            start_id.map_or(Bound::Unbounded, Bound::Included),
+            end_id.map_or(Bound::Unbounded, Bound::Excluded),
+        )),
+    }
+}
+
+// Checks whether an edge type passes a piped edge query's type filters.
+fn matches_type_filters(t: &models::Type, type_filter: Option<&models::Type>, type_filters: &[models::Type]) -> bool {
+    type_filter.is_none_or(|type_filter| t == type_filter) && (type_filters.is_empty() || type_filters.contains(t))
+}
+
+// Gets the edge types that a piped edge query's type filters allow, in
+// sorted order, or `None` if they allow every type.
+fn scanned_edge_types<'a>(
+    type_filter: Option<&'a models::Type>,
+    type_filters: &'a [models::Type],
+) -> Option<Vec<&'a models::Type>> {
+    match type_filter {
+        Some(type_filter) => {
+            if matches_type_filters(type_filter, None, type_filters) {
+                Some(vec![type_filter])
+            } else {
+                Some(Vec::new())
+            }
+        }
+        None if type_filters.is_empty() => None,
+        None => {
+            let mut types: Vec<&models::Type> = type_filters.iter().collect();
+            types.sort();
+            types.dedup();
+            Some(types)
+        }
+    }
+}
+
+// Gets where to start scanning the outbound edges of a vertex of a given
+// type, which is just past `after_inbound_id` if it's set.
+fn outbound_range_start(id: Uuid, t: &models::Type, after_inbound_id: Option<Uuid>) -> Bound<models::EdgeKey> {
+    match after_inbound_id {
+        Some(after_inbound_id) => Bound::Excluded(models::EdgeKey::new(id, t.clone(), after_inbound_id)),
+        None => Bound::Included(models::EdgeKey::new(id, t.clone(), Uuid::default())),
+    }
+}
+
+// Decrements a vertex's degree counter, dropping it once it reaches zero so
+// that the counter maps only track vertices that have edges.
+fn decrement_degree(degrees: &mut HashMap<Uuid, u64>, id: Uuid) {
+    let remove = match degrees.get_mut(&id) {
+        Some(degree) => {
+            *degree -= 1;
+            *degree == 0
+        }
+        None => false,
+    };
+
+    if remove {
+        degrees.remove(&id);
+    }
+}
+
+// Removes a vertex from the entry of a property index for a value, dropping
+// the entry once it's empty.
+fn remove_from_property_index(index: &mut HashMap<String, BTreeSet<Uuid>>, value_key: &str, id: Uuid) {
+    let remove = match index.get_mut(value_key) {
+        Some(ids) => {
+            ids.remove(&id);
+            ids.is_empty()
+        }
+        None => false,
+    };
+
+    if remove {
+        index.remove(value_key);
+    }
+}
+
+// Wraps the lock around the internal datastore. With the `lock-stats`
+// feature, it also counts lock acquisitions and the time spent waiting for
+// them; otherwise it adds no overhead.
+#[derive(Debug)]
+struct DatastoreLock {
+    lock: RwLock<InternalMemoryDatastore>,
+    #[cfg(feature = "lock-stats")]
+    stats: LockStats,
+}
+
+impl DatastoreLock {
+    fn new(datastore: InternalMemoryDatastore) -> Self {
+        DatastoreLock {
+            lock: RwLock::new(datastore),
+            #[cfg(feature = "lock-stats")]
+            stats: LockStats::default(),
+        }
+    }
+
+    // The lock is poisoned if a thread panicked while holding the write
+    // lock, in which case the datastore may have been left half-updated.
+    // Rather than panicking too, every later operation fails with an error.
+    #[cfg(not(feature = "lock-stats"))]
+    fn acquire_read<'a>(&'a self) -> Result<RwLockReadGuard<'a, InternalMemoryDatastore>> {
+        self.lock.read().map_err(|_| Error::from(ErrorKind::LockPoisoned))
+    }
+
+    #[cfg(not(feature = "lock-stats"))]
+    fn acquire_write<'a>(&'a self) -> Result<RwLockWriteGuard<'a, InternalMemoryDatastore>> {
+        self.lock.write().map_err(|_| Error::from(ErrorKind::LockPoisoned))
+    }
+
+    #[cfg(feature = "lock-stats")]
+    fn acquire_read<'a>(&'a self) -> Result<RwLockReadGuard<'a, InternalMemoryDatastore>> {
+        let start = Instant::now();
+        let guard = self.lock.read().map_err(|_| Error::from(ErrorKind::LockPoisoned))?;
+        self.stats
+            .record(&self.stats.read_acquisitions, &self.stats.read_wait_nanos, start);
+        Ok(guard)
+    }
+
+    #[cfg(feature = "lock-stats")]
+    fn acquire_write<'a>(&'a self) -> Result<RwLockWriteGuard<'a, InternalMemoryDatastore>> {
+        let start = Instant::now();
+        let guard = self.lock.write().map_err(|_| Error::from(ErrorKind::LockPoisoned))?;
+        self.stats
+            .record(&self.stats.write_acquisitions, &self.stats.write_wait_nanos, start);
+        Ok(guard)
+    }
+
+    // Expired edges are purged lazily, whenever the lock is taken. A read
+    // lock is only handed out once no edges have expired as of taking it;
+    // until then, the write lock is taken to purge them. Edges that expire
+    // while the read lock is held are only purged the next time around.
+    fn read<'a>(&'a self) -> Result<RwLockReadGuard<'a, InternalMemoryDatastore>> {
+        loop {
+            {
+                let datastore = self.acquire_read()?;
+
+                if !datastore.has_expired_edges() {
+                    return Ok(datastore);
+                }
+            }
+
+            drop(self.write()?);
+        }
+    }
+
+    fn write<'a>(&'a self) -> Result<RwLockWriteGuard<'a, InternalMemoryDatastore>> {
+        let mut datastore = self.acquire_write()?;
+        datastore.purge_expired_edges();
+        Ok(datastore)
+    }
+
+    // Gets the write lock even if it's poisoned. This is only for changing
+    // settings and subscribers, which are never left half-updated by a
+    // panic.
+    fn write_settings<'a>(&'a self) -> RwLockWriteGuard<'a, InternalMemoryDatastore> {
+        self.lock.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "lock-stats")]
+#[derive(Debug, Default)]
+struct LockStats {
+    read_acquisitions: AtomicUsize,
+    read_wait_nanos: AtomicUsize,
+    write_acquisitions: AtomicUsize,
+    write_wait_nanos: AtomicUsize,
+}
+
+#[cfg(feature = "lock-stats")]
+impl LockStats {
+    fn record(&self, acquisitions: &AtomicUsize, wait_nanos: &AtomicUsize, start: Instant) {
+        let elapsed = start.elapsed();
+        let nanos = elapsed.as_secs() as usize * 1_000_000_000 + elapsed.subsec_nanos() as usize;
+        acquisitions.fetch_add(1, Ordering::Relaxed);
+        wait_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// References to vertices and edges that don't exist, as found by
+/// `MemoryDatastore::integrity_check`. Deleting a vertex or edge deletes
+/// everything that refers to it, so these only turn up if that went wrong.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IntegrityReport {
+    /// Edges whose outbound or inbound vertex doesn't exist.
+    pub dangling_edges: Vec<models::EdgeKey>,
+    /// Vertex properties, as `(vertex id, property name)`, whose vertex
+    /// doesn't exist.
+    pub dangling_vertex_properties: Vec<(Uuid, String)>,
+    /// Edge properties, as `(edge key, property name)`, whose edge doesn't
+    /// exist.
+    pub dangling_edge_properties: Vec<(models::EdgeKey, String)>,
+}
+
+impl IntegrityReport {
+    /// Checks whether no dangling references were found.
+    pub fn is_ok(&self) -> bool {
+        self.dangling_edges.is_empty()
+            && self.dangling_vertex_properties.is_empty()
+            && self.dangling_edge_properties.is_empty()
+    }
+}
+
+/// Statistics on the lock around a memory datastore, which help tell
+/// whether it's a bottleneck. Only available with the `lock-stats` feature.
+#[cfg(feature = "lock-stats")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatastoreStats {
+    /// The number of times the read lock was acquired.
+    pub read_acquisitions: u64,
+    /// The total time spent waiting to acquire the read lock.
+    pub read_wait: Duration,
+    /// The number of times the write lock was acquired.
+    pub write_acquisitions: u64,
+    /// The total time spent waiting to acquire the write lock.
+    pub write_wait: Duration,
+}
+
+/// A change made to a memory datastore, as sent to subscribers. See
+/// `MemoryDatastore::subscribe`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangeEvent {
+    /// A vertex was created.
+    VertexCreated(models::Vertex),
+    /// An existing vertex was given a new type, e.g. by
+    /// `rename_vertex_type`, or by a bulk insert or import that overwrote
+    /// it.
+    VertexUpdated(models::Vertex),
+    /// A vertex was deleted, along with its properties and edges.
+    VertexDeleted(Uuid),
+    /// An edge was created.
+    EdgeCreated(models::EdgeKey),
+    /// An existing edge's update datetime was bumped, either with
+    /// `update_edge` or by creating it again.
+    EdgeUpdated(models::EdgeKey),
+    /// An edge was rekeyed from the first key to the second by
+    /// `rename_edge_type`, keeping its update datetime and properties.
+    EdgeRenamed(models::EdgeKey, models::EdgeKey),
+    /// An edge was deleted, along with its properties.
+    EdgeDeleted(models::EdgeKey),
+    /// A vertex property was set.
+    VertexPropertySet(models::VertexProperty),
+    /// A vertex property was deleted.
+    VertexPropertyDeleted(Uuid, String),
+    /// All of the properties of a vertex were deleted.
+    AllVertexPropertiesDeleted(Uuid),
+    /// An edge property was set.
+    EdgePropertySet(models::EdgeProperty),
+    /// An edge property was deleted.
+    EdgePropertyDeleted(models::EdgeKey, String),
+    /// All of the properties of an edge were deleted.
+    AllEdgePropertiesDeleted(models::EdgeKey),
+}
+
+// Zstd's own default level, which already compresses the repetitive ids in
+// snapshots well, at a fraction of the CPU cost of the higher levels.
+#[cfg(feature = "compression")]
+const SNAPSHOT_ZSTD_LEVEL: i32 = 3;
+
+/// How a snapshot written by `MemoryDatastore::save_to_path` is compressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotCompression {
+    /// The snapshot is written as plain newline-delimited JSON.
+    None,
+    /// The snapshot is gzip compressed.
+    #[cfg(feature = "compression")]
+    Gzip,
+    /// The snapshot is zstd compressed.
+    #[cfg(feature = "compression")]
+    Zstd,
+}
+
+/// An in-memory-only datastore.
+#[derive(Debug)]
+pub struct MemoryDatastore(Arc<DatastoreLock>);
+
+impl MemoryDatastore {
+    /// Creates a new in-memory datastore.
+    pub fn default() -> MemoryDatastore {
+        Self {
+            0: Arc::new(DatastoreLock::new(InternalMemoryDatastore {
+                allow_self_loops: true,
+                case_insensitive_types: false,
+                check_edge_times: false,
+                clock: Arc::new(SystemClock),
+                edge_expirations: BTreeMap::new(),
+                edge_properties: BTreeMap::new(),
+                edges: BTreeMap::new(),
+                edge_timelines: HashMap::new(),
+                expiring_edges: BTreeSet::new(),
+                in_degrees: HashMap::new(),
+                inconsistent_edges: BTreeSet::new(),
+                max_property_size_bytes: None,
+                out_degrees: HashMap::new(),
+                property_indexes: HashMap::new(),
+                property_types: HashMap::new(),
+                reject_dangling_bulk_edges: false,
+                reject_duplicate_bulk_edges: false,
+                subscribers: Vec::new(),
+                traversal_limits: models::TraversalLimits::default(),
+                undirected_edges: BTreeSet::new(),
+                reversed_undirected_edges: BTreeSet::new(),
+                vertex_properties: BTreeMap::new(),
+                vertices: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Sets the limits that bound how much work a single traversal may do.
+    ///
+    /// # Arguments
+    /// * `limits` - The traversal limits.
+    pub fn with_traversal_limits(self, limits: models::TraversalLimits) -> MemoryDatastore {
+        self.0.write_settings().traversal_limits = limits;
+        self
+    }
+
+    /// Sets whether edges may start and end at the same vertex. Self-loops
+    /// are allowed by default. When they're not, `create_edge` returns
+    /// `false` for them, bulk inserts skip them, and merging vertices drops
+    /// edges that would become self-loops.
+    ///
+    /// # Arguments
+    /// * `allow_self_loops` - Whether to allow self-loops.
+    pub fn with_allow_self_loops(self, allow_self_loops: bool) -> MemoryDatastore {
+        self.0.write_settings().allow_self_loops = allow_self_loops;
+        self
+    }
+
+    /// Sets whether vertex and edge types are case-insensitive. Types are
+    /// case-sensitive by default. When they're not, every type is lowercased
+    /// before it's stored, and the types in queries and edge keys are
+    /// lowercased before they're run, so e.g. `User` and `user` are the same
+    /// type. Types that were stored before this was set are left as-is.
+    ///
+    /// # Arguments
+    /// * `case_insensitive_types` - Whether types are case-insensitive.
+    pub fn with_case_insensitive_types(self, case_insensitive_types: bool) -> MemoryDatastore {
+        self.0.write_settings().case_insensitive_types = case_insensitive_types;
+        self
+    }
+
+    /// Sets whether bulk inserts fail if the same edge appears in them more
+    /// than once. Duplicates are allowed by default, and just insert the
+    /// edge again. When they're not, a bulk insert with duplicate edges
+    /// fails with a `DuplicateBulkInsertEdges` error listing them, without
+    /// inserting anything. Edges that already exist in the datastore aren't
+    /// duplicates; see `bulk_insert_with_mode` for handling those.
+    ///
+    /// # Arguments
+    /// * `reject_duplicate_bulk_edges` - Whether to reject duplicate edges.
+    pub fn with_reject_duplicate_bulk_edges(self, reject_duplicate_bulk_edges: bool) -> MemoryDatastore {
+        self.0.write_settings().reject_duplicate_bulk_edges = reject_duplicate_bulk_edges;
+        self
+    }
+
+    /// Sets the maximum size of property values, measured as the length of
+    /// the value serialized as JSON. There's no maximum by default. When
+    /// there is one, setting a vertex or edge property to a larger value
+    /// fails with a validation error. Values that were set before the
+    /// maximum was set are not checked.
+    ///
+    /// # Arguments
+    /// * `max_property_size_bytes` - The maximum size in bytes, or `None`
+    ///   for no maximum.
+    pub fn with_max_property_size_bytes(self, max_property_size_bytes: Option<usize>) -> MemoryDatastore {
+        self.0.write_settings().max_property_size_bytes = max_property_size_bytes;
+        self
+    }
+
+    /// Sets whether bulk inserts fail if they have edges whose vertices
+    /// don't exist. By default, such edges are skipped, like `create_edge`
+    /// skips them. When they're not, a bulk insert with such edges fails
+    /// with a `DanglingBulkInsertEdges` error listing them, without
+    /// inserting anything. Vertices inserted by earlier items in the same
+    /// bulk insert count as existing.
+    ///
+    /// # Arguments
+    /// * `reject_dangling_bulk_edges` - Whether to reject edges whose
+    ///   vertices don't exist.
+    pub fn with_reject_dangling_bulk_edges(self, reject_dangling_bulk_edges: bool) -> MemoryDatastore {
+        self.0.write_settings().reject_dangling_bulk_edges = reject_dangling_bulk_edges;
+        self
+    }
+
+    /// Sets the clock used to timestamp vertices, edges and properties.
+    /// Defaults to the system clock; tests of time-based queries can use a
+    /// `MockClock` to control the timestamps instead.
+    ///
+    /// # Arguments
+    /// * `clock` - The clock.
+    pub fn with_clock<C: Clock + 'static>(self, clock: C) -> MemoryDatastore {
+        self.0.write_settings().clock = Arc::new(clock);
+        self
+    }
+
+    /// Sets whether to check edge times for consistency. When set, creating
+    /// or updating an edge records whether its update datetime predates the
+    /// creation of either of its vertices, which can happen when edges and
+    /// vertices are imported out of order. Such edges are still created;
+    /// they can be listed with `MemoryTransaction::get_inconsistent_edges`.
+    /// Defaults to `false`.
+    ///
+    /// # Arguments
+    /// * `check_edge_times` - Whether to check edge times.
+    pub fn with_edge_time_checks(self, check_edge_times: bool) -> MemoryDatastore {
+        self.0.write_settings().check_edge_times = check_edge_times;
+        self
+    }
+
+    /// Declares the JSON type that values of a property must have. Once
+    /// declared, setting a vertex or edge property of that name to a value of
+    /// a different type fails with a validation error. Properties without a
+    /// declared type accept any value.
+    ///
+    /// Values that were set before the type was declared are not checked.
+    ///
+    /// # Arguments
+    /// * `name` - The property name.
+    /// * `t` - The type that values must have.
+    pub fn set_property_type(&self, name: &str, t: models::PropertyValueType) {
+        self.0.write_settings().property_types.insert(name.to_string(), t);
+    }
+
+    /// Removes the declared type of a property, if any, so that it accepts
+    /// any value again.
+    ///
+    /// # Arguments
+    /// * `name` - The property name.
+    pub fn remove_property_type(&self, name: &str) {
+        self.0.write_settings().property_types.remove(name);
+    }
+
+    /// Creates an index over the values of a vertex property, built from
+    /// the existing data and maintained as properties change. The index
+    /// speeds up finding vertices whose property equals a given value, via
+    /// `MemoryTransaction::get_vertices_by_property_value` and equality
+    /// conditions in `set_vertex_property_where`. Creating an index that
+    /// already exists rebuilds it.
+    ///
+    /// Values are compared by their serialized JSON, so e.g. `1` and `1.0`
+    /// are considered different values.
+    ///
+    /// # Arguments
+    /// * `name` - The property name.
+    pub fn create_property_index(&self, name: &str) -> Result<()> {
+        let mut datastore = self.0.write()?;
+        let index = datastore.build_property_index(name);
+        datastore.property_indexes.insert(name.to_string(), index);
+        Ok(())
+    }
+
+    /// Drops the index over the values of a vertex property, if any.
+    ///
+    /// # Arguments
+    /// * `name` - The property name.
+    pub fn drop_property_index(&self, name: &str) -> Result<()> {
+        self.0.write()?.property_indexes.remove(name);
+        Ok(())
+    }
+
+    /// Loads a large number of items at once. Unlike a bulk insert, this
+    /// doesn't maintain the cached vertex degrees or the edge and property
+    /// indexes item by item; instead, they're rebuilt once all of the items are
+    /// inserted, which is much faster for large datasets. Edge times are
+    /// likewise checked once at the end, if the checks are enabled. The write
+    /// lock is held throughout.
+    ///
+    /// Existing vertices, edges and properties are overwritten. Items are
+    /// inserted in order, so vertices must come before their edges and
+    /// properties, and edges before their properties - items that reference
+    /// missing vertices or edges are skipped. Properties are checked against
+    /// their declared types, and the load stops at the first invalid value,
+    /// though the items before it stay inserted.
+    ///
+    /// # Arguments
+    /// * `items` - The items to load.
+    pub fn bulk_load<I>(&self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        let mut datastore = self.0.write()?;
+        let result = datastore.load_items(items);
+
+        // This has to happen even if the load failed partway through, since
+        // the items before the failure were inserted
+        datastore.recompute_degrees();
+        datastore.rebuild_edge_timelines();
+        datastore.rebuild_property_indexes();
+        datastore.recheck_edge_times();
+
+        result
+    }
+
+    /// Removes property entries whose vertex or edge no longer exists,
+    /// returning how many were removed. Deleting a vertex or edge deletes its
+    /// properties, so this is only a safety net for repair, like
+    /// `recompute_degrees`. Dangling edges are left alone; see
+    /// `integrity_check` to find them.
+    pub fn vacuum(&self) -> Result<u64> {
+        let mut datastore = self.0.write()?;
+        let report = datastore.integrity_report();
+        let mut count = 0;
+
+        for (id, name) in report.dangling_vertex_properties {
+            if datastore.remove_vertex_property(id, &name) {
+                count += 1;
+            }
+        }
+
+        for property_key in report.dangling_edge_properties {
+            if datastore.edge_properties.remove(&property_key).is_some() {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Checks the datastore for references to vertices and edges that don't
+    /// exist, without fixing anything. This scans all of the edges and
+    /// properties under a read lock.
+    pub fn integrity_check(&self) -> Result<IntegrityReport> {
+        Ok(self.0.read()?.integrity_report())
+    }
+
+    /// Rebuilds the cached vertex degrees from scratch. The cache is
+    /// maintained incrementally, so this is only needed for repair.
+    pub fn recompute_degrees(&self) -> Result<()> {
+        self.0.write()?.recompute_degrees();
+        Ok(())
+    }
+
+    /// Subscribes to changes made to the datastore. Every write sends the
+    /// changes it made to the returned receiver, in the order they were
+    /// made: transactions, bulk inserts and loads, batches, imports, and
+    /// purges of expired edges. Writes that don't change anything, like
+    /// creating a vertex that already exists, don't send changes, and
+    /// deleting a vertex or edge only sends one change for it rather than
+    /// one for each of its properties and edges. The only writes that never
+    /// send changes are the repairs, `vacuum` and `recompute_degrees`, since
+    /// they don't change what can be read.
+    ///
+    /// A write that fails partway through, e.g. a bulk load with an invalid
+    /// property value, still sends the changes it made before failing.
+    ///
+    /// Subscribers are sent changes while the datastore is locked, and
+    /// changes queue up in the receiver until they're received, so
+    /// subscribers that fall behind use more and more memory. Dropping the
+    /// receiver unsubscribes.
+    pub fn subscribe(&self) -> Receiver<ChangeEvent> {
+        let (sender, receiver) = channel();
+        self.0.write_settings().subscribers.push(sender);
+        receiver
+    }
+
+    /// Creates a transaction that can only read from the datastore. Like
+    /// other transactions, it only locks the datastore for the duration of
+    /// each call, and then only for reading.
+    pub fn read_transaction(&self) -> Result<MemoryReadOnlyTransaction> {
+        Ok(MemoryReadOnlyTransaction(self.transaction()?))
+    }
+
+    /// Takes a snapshot of the datastore, and creates a transaction that
+    /// reads from it. Every read through the transaction sees the datastore
+    /// as it was when the snapshot was taken, regardless of any writes made
+    /// since, so several reads can be combined into a consistent view.
+    ///
+    /// The snapshot is a full copy of the datastore, so taking one briefly
+    /// blocks writers, and uses as much memory again as the datastore does
+    /// until the transaction is dropped.
+    pub fn snapshot(&self) -> Result<MemoryReadOnlyTransaction> {
+        let mut datastore = self.0.read()?.clone();
+        datastore.subscribers.clear();
+
+        Ok(MemoryReadOnlyTransaction(MemoryTransaction {
+            datastore: Arc::new(DatastoreLock::new(datastore)),
+        }))
+    }
+
+    /// Gets statistics on the lock around the datastore, accumulated since
+    /// it was created. Only available with the `lock-stats` feature.
+    #[cfg(feature = "lock-stats")]
+    pub fn stats(&self) -> DatastoreStats {
+        let stats = &self.0.stats;
+
+        DatastoreStats {
+            read_acquisitions: stats.read_acquisitions.load(Ordering::Relaxed) as u64,
+            read_wait: Duration::from_nanos(stats.read_wait_nanos.load(Ordering::Relaxed) as u64),
+            write_acquisitions: stats.write_acquisitions.load(Ordering::Relaxed) as u64,
+            write_wait: Duration::from_nanos(stats.write_wait_nanos.load(Ordering::Relaxed) as u64),
+        }
+    }
+
+    /// Starts a paging session over all edges in the datastore.
+    ///
+    /// The session captures a point-in-time copy of the edges when it is
+    /// created, so pages are neither skipped nor repeated if edges are
+    /// created or deleted while paging.
+    pub fn edge_pager(&self) -> Result<MemoryEdgePager> {
+        Ok(MemoryEdgePager {
+            edges: self.0.read()?.edges.clone(),
+            cursor: Bound::Unbounded,
+        })
+    }
+
+    /// Exports the entire datastore as newline-delimited JSON, under a
+    /// single read lock. Each line is an object with a `kind` field, which
+    /// is one of:
+    ///
+    /// * `vertex`, with `id`, `type` and `created_datetime` fields.
+    /// * `edge`, with `outbound_id`, `type`, `inbound_id` and
+    ///   `created_datetime` fields, plus an `undirected` field set to `true`
+    ///   for undirected edges, and a `weight` field for weighted edges.
+    /// * `vertex_property`, with `id`, `name`, `value` and
+    ///   `updated_datetime` fields.
+    /// * `edge_property`, with `outbound_id`, `type`, `inbound_id`, `name`,
+    ///   `value` and `updated_datetime` fields.
+    ///
+    /// Ids are hyphenated UUID strings, datetimes are RFC 3339 strings, and
+    /// property values are arbitrary JSON. All vertices are written first,
+    /// then all edges, then all vertex properties, and finally all edge
+    /// properties, so every record only refers to records above it.
+    ///
+    /// # Arguments
+    /// * `writer` - The writer to export to.
+    pub fn export_json<W: Write>(&self, writer: W) -> Result<()> {
+        let datastore = self.0.read()?;
+        let mut writer = BufWriter::new(writer);
+
+        for (id, &(ref t, created_datetime)) in &datastore.vertices {
+            let record = models::ExportRecord::Vertex {
+                id: *id,
+                t: t.clone(),
+                created_datetime: Some(created_datetime),
+            };
+            write_json_record(&mut writer, &record)?;
+        }
+
+        for (key, &(created_datetime, weight)) in &datastore.edges {
+            let record = models::ExportRecord::Edge {
+                outbound_id: key.outbound_id,
+                t: key.t.clone(),
+                inbound_id: key.inbound_id,
+                created_datetime,
+                undirected: datastore.undirected_edges.contains(key),
+                weight,
+                expires_datetime: datastore.edge_expirations.get(key).cloned(),
+            };
+            write_json_record(&mut writer, &record)?;
+        }
+
+        for (&(id, ref name), &(ref value, updated_datetime)) in &datastore.vertex_properties {
+            let record = models::ExportRecord::VertexProperty {
+                id,
+                name: name.clone(),
+                value: value.clone(),
+                updated_datetime: Some(updated_datetime),
+            };
+            write_json_record(&mut writer, &record)?;
+        }
+
+        for ((key, name), &(ref value, updated_datetime)) in &datastore.edge_properties {
+            let record = models::ExportRecord::EdgeProperty {
+                outbound_id: key.outbound_id,
+                t: key.t.clone(),
+                inbound_id: key.inbound_id,
+                name: name.clone(),
+                value: value.clone(),
+                updated_datetime: Some(updated_datetime),
+            };
+            write_json_record(&mut writer, &record)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Imports newline-delimited JSON in the format written by
+    /// `export_json`, under a single write lock. Existing vertices, edges
+    /// and properties are overwritten. Edges and properties that refer to
+    /// vertices or edges that don't exist are ignored, and blank lines are
+    /// skipped. Properties without an `updated_datetime`, and vertices
+    /// without a `created_datetime`, as written by older versions, are
+    /// considered updated or created when they're imported.
+    ///
+    /// # Arguments
+    /// * `reader` - The reader to import from.
+    pub fn import_json<R: Read>(&self, reader: R) -> Result<()> {
+        self.import_json_reporting(reader)?;
+        Ok(())
+    }
+
+    // Imports newline-delimited JSON like `import_json`, counting the
+    // records as they're parsed.
+    fn import_json_reporting<R: Read>(&self, reader: R) -> Result<models::ImportReport> {
+        let mut datastore = self.0.write()?;
+        let mut report = models::ImportReport::default();
+
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record: models::ExportRecord = serde_json::from_str(&line)?;
+            report.record(&record);
+
+            match record {
+                models::ExportRecord::Vertex {
+                    id,
+                    t,
+                    created_datetime,
+                } => {
+                    let t = datastore.normalize_type(&t).into_owned();
+                    datastore.insert_vertex(id, t, created_datetime);
+                }
+                models::ExportRecord::Edge {
+                    outbound_id,
+                    t,
+                    inbound_id,
+                    created_datetime,
+                    undirected,
+                    weight,
+                    expires_datetime,
+                } => {
+                    if datastore.vertices.contains_key(&outbound_id) && datastore.vertices.contains_key(&inbound_id) {
+                        let key =
+                            models::EdgeKey::new(outbound_id, datastore.normalize_type(&t).into_owned(), inbound_id);
+
+                        if undirected {
+                            datastore.insert_undirected_edge(key.clone(), created_datetime, weight);
+                        } else {
+                            datastore.insert_edge(key.clone(), created_datetime, weight);
+                        }
+
+                        let key = datastore.resolve_edge_key(&key);
+                        datastore.set_edge_expiration(key, expires_datetime);
+                    }
+                }
+                models::ExportRecord::VertexProperty {
+                    id,
+                    name,
+                    value,
+                    updated_datetime,
+                } => {
+                    if datastore.vertices.contains_key(&id) {
+                        datastore.validate_property_value(&name, &value)?;
+                        let updated_datetime = updated_datetime.unwrap_or_else(|| datastore.now());
+                        datastore.set_vertex_property(id, name.clone(), value.clone(), updated_datetime);
+                        datastore.emit(|| ChangeEvent::VertexPropertySet(models::VertexProperty::new(id, name, value)));
+                    }
+                }
+                models::ExportRecord::EdgeProperty {
+                    outbound_id,
+                    t,
+                    inbound_id,
+                    name,
+                    value,
+                    updated_datetime,
+                } => {
+                    let t = datastore.normalize_type(&t).into_owned();
+                    let key = datastore.resolve_edge_key(&models::EdgeKey::new(outbound_id, t, inbound_id));
+
+                    if datastore.edges.contains_key(&key) {
+                        datastore.validate_property_value(&name, &value)?;
+                        let updated_datetime = updated_datetime.unwrap_or_else(|| datastore.now());
+                        datastore
+                            .edge_properties
+                            .insert((key.clone(), name.clone()), (value.clone(), updated_datetime));
+                        datastore.emit(|| ChangeEvent::EdgePropertySet(models::EdgeProperty::new(key, name, value)));
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Saves the datastore to a file, in the format written by
+    /// `export_json`, optionally compressed. Any existing file at the path
+    /// is overwritten.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file to save to.
+    /// * `compression` - How to compress the file.
+    pub fn save_to_path(&self, path: &str, compression: SnapshotCompression) -> Result<()> {
+        let file = File::create(path)?;
+
+        match compression {
+            SnapshotCompression::None => self.export_json(file)?,
+            #[cfg(feature = "compression")]
+            SnapshotCompression::Gzip => {
+                let mut encoder = GzEncoder::new(file, Compression::default());
+                self.export_json(&mut encoder)?;
+                encoder.finish()?;
+            }
+            #[cfg(feature = "compression")]
+            SnapshotCompression::Zstd => {
+                let mut encoder = zstd::stream::write::Encoder::new(file, SNAPSHOT_ZSTD_LEVEL)?;
+                self.export_json(&mut encoder)?;
+                encoder.finish()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads a file written by `save_to_path` into the datastore, as with
+    /// `import_json`. Gzip and zstd compressed files are recognized by their
+    /// magic bytes and decompressed, so the compression doesn't need to be
+    /// known up front.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file to load from.
+    pub fn load_from_path(&self, path: &str) -> Result<()> {
+        let file = File::open(path)?;
+
+        #[cfg(feature = "compression")]
+        let file = util::decompress(file)?;
+
+        self.import_json(file)
+    }
+
+    // Bulk inserts items, first checking for duplicate and dangling edges.
+    // The checks need every item up-front, so the items are only collected
+    // when one of them is enabled; otherwise they're streamed. If `progress`
+    // is set, its callback is called with the number of items inserted so
+    // far after every so many items, and once more at the end if the last
+    // call didn't cover every item.
+    fn bulk_insert_reporting_progress<I>(&self, items: I, progress: Option<(u64, &mut dyn FnMut(u64))>) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        let checked = {
+            let datastore = self.0.read()?;
+            datastore.reject_duplicate_bulk_edges || datastore.reject_dangling_bulk_edges
+        };
+
+        if !checked {
+            return self.insert_bulk_items(items, progress);
+        }
+
+        let items: Vec<models::BulkInsertItem> = items.collect();
+
+        {
+            let datastore = self.0.read()?;
+            datastore.check_duplicate_bulk_edges(&items)?;
+            datastore.check_dangling_bulk_edges(&items)?;
+        }
+
+        self.insert_bulk_items(items.into_iter(), progress)
+    }
+
+    // Inserts items one at a time, as the default `bulk_insert` does,
+    // reporting progress as described in `bulk_insert_reporting_progress`.
+    fn insert_bulk_items<I>(&self, items: I, mut progress: Option<(u64, &mut dyn FnMut(u64))>) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        let trans = self.transaction()?;
+        let mut count = 0;
+
+        for item in items {
+            insert_bulk_insert_item(&trans, &item)?;
+            count += 1;
+
+            if let Some((every, ref mut callback)) = progress {
+                if count % every == 0 {
+                    callback(count);
+                }
+            }
+        }
+
+        if let Some((every, callback)) = progress {
+            if count % every != 0 {
+                callback(count);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_json_record<W: Write>(writer: &mut W, record: &models::ExportRecord) -> Result<()> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// A paging session over a snapshot of a memory datastore's edges.
+#[derive(Debug)]
+pub struct MemoryEdgePager {
+    edges: BTreeMap<models::EdgeKey, (DateTime<Utc>, Option<f64>)>,
+    cursor: Bound<models::EdgeKey>,
+}
+
+impl MemoryEdgePager {
+    /// Gets the next page of edges, ordered by edge key. An empty page
+    /// signals that the session is exhausted.
+    ///
+    /// # Arguments
+    /// * `limit` - The maximum number of edges to return.
+    pub fn next_page(&mut self, limit: u32) -> Vec<models::Edge> {
+        let page: Vec<models::Edge> = self
+            .edges
+            .range((self.cursor.clone(), Bound::Unbounded))
+            .take(limit as usize)
+            .map(|(key, &(update_datetime, weight))| models::Edge::with_weight(key.clone(), update_datetime, weight))
+            .collect();
+
+        if let Some(last) = page.last() {
+            self.cursor = Bound::Excluded(last.key.clone());
+        }
+
+        page
+    }
+}
+
+impl Datastore for MemoryDatastore {
+    type Trans = MemoryTransaction;
+
+    fn transaction(&self) -> Result<Self::Trans> {
+        Ok(MemoryTransaction {
+            datastore: Arc::clone(&self.0),
+        })
+    }
+
+    // Creating a transaction never fails, so this checks that the lock isn't
+    // poisoned instead.
+    fn health_check(&self) -> Result<()> {
+        drop(self.0.read()?);
+        Ok(())
+    }
+
+    // Overridden to check for duplicate and dangling edges before anything
+    // is inserted; otherwise, this does the same as the default
+    // implementation. Progress is counted as items are inserted, rather
+    // than as they're collected up-front.
+    fn bulk_insert<I>(&self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        self.bulk_insert_reporting_progress(items, None)
+    }
+
+    fn bulk_insert_with_progress<I, F>(&self, items: I, every: u64, mut progress: F) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+        F: FnMut(u64),
+    {
+        if every == 0 {
+            let err: ValidationError = "The progress interval must be positive".into();
+            return Err(err.into());
+        }
+
+        self.bulk_insert_reporting_progress(items, Some((every, &mut progress)))
+    }
+
+    fn bulk_insert_with_mode<I>(&self, items: I, mode: models::BulkInsertMode) -> Result<u64>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        let mut datastore = self.0.write()?;
+        let items: Vec<models::BulkInsertItem> = items.map(|item| datastore.normalize_bulk_insert_item(item)).collect();
+        datastore.check_duplicate_bulk_edges(&items)?;
+        datastore.check_dangling_bulk_edges(&items)?;
+
+        // Property values are all checked before anything is inserted, so
+        // that an invalid value leaves the datastore untouched
+        for item in &items {
+            match *item {
+                models::BulkInsertItem::VertexProperty(_, ref name, ref value)
+                | models::BulkInsertItem::EdgeProperty(_, ref name, ref value) => {
+                    datastore.validate_property_value(name, value)?;
+                }
+                models::BulkInsertItem::Vertex(_) | models::BulkInsertItem::Edge(_) => (),
+            }
+        }
+
+        if mode == models::BulkInsertMode::Error {
+            if let Some(item) = items.iter().find(|item| datastore.contains_bulk_insert_item(item)) {
+                return Err(ErrorKind::BulkInsertConflict(format!("{:?}", item)).into());
+            }
+        }
+
+        let mut skipped = 0;
+
+        for item in items {
+            if mode == models::BulkInsertMode::Skip && datastore.contains_bulk_insert_item(&item) {
+                skipped += 1;
+                continue;
+            }
+
+            match item {
+                models::BulkInsertItem::Vertex(vertex) => {
+                    datastore.insert_vertex(vertex.id, vertex.t, None);
+                }
+                models::BulkInsertItem::Edge(edge_key) => {
+                    if datastore.is_valid_edge(&edge_key) {
+                        let update_datetime = datastore.now();
+                        datastore.insert_edge(edge_key, update_datetime, None);
+                    }
+                }
+                models::BulkInsertItem::VertexProperty(id, name, value) => {
+                    if datastore.vertices.contains_key(&id) {
+                        let update_datetime = datastore.now();
+                        datastore.set_vertex_property(id, name.clone(), value.clone(), update_datetime);
+                        datastore.emit(|| ChangeEvent::VertexPropertySet(models::VertexProperty::new(id, name, value)));
+                    }
+                }
+                models::BulkInsertItem::EdgeProperty(edge_key, name, value) => {
+                    let edge_key = datastore.resolve_edge_key(&edge_key);
+
+                    if datastore.edges.contains_key(&edge_key) {
+                        let update_datetime = datastore.now();
+                        datastore
+                            .edge_properties
+                            .insert((edge_key.clone(), name.clone()), (value.clone(), update_datetime));
+                        datastore
+                            .emit(|| ChangeEvent::EdgePropertySet(models::EdgeProperty::new(edge_key, name, value)));
+                    }
+                }
+            }
+        }
+
+        Ok(skipped)
+    }
+
+    /// Executes a batch of operations, in order, under a single write lock,
+    /// so no other operation can be interleaved with them or see the
+    /// datastore partway through the batch.
+    ///
+    /// Every operation is checked before any is applied - property values
+    /// against their declared types, and queries for invalid time ranges
+    /// and too many traversal hops - so a batch that fails those checks
+    /// leaves the datastore untouched. The only errors that can happen once
+    /// operations are being applied are from traversals that visit too many
+    /// vertices or edges, which depends on the data; the operations before
+    /// the failing one then stay applied.
+    ///
+    /// # Arguments
+    /// * `operations`: The operations to execute.
+    fn execute_batch<I>(&self, operations: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BatchOperation>,
+    {
+        let mut datastore = self.0.write()?;
+        let operations: Vec<models::BatchOperation> = operations.collect();
+
+        for operation in &operations {
+            datastore.check_batch_operation(operation)?;
+        }
+
+        for operation in operations {
+            datastore.apply_batch_operation(operation)?;
+        }
+
+        Ok(())
+    }
+
+    // Overridden to import the records as `import_json` does, so that edge
+    // datetimes, weights, expirations and undirected edges are kept.
+    #[cfg(feature = "compression")]
+    fn import_compressed<R: Read>(&self, reader: R) -> Result<models::ImportReport> {
+        self.import_json_reporting(util::decompress(reader)?)
+    }
+}
+
+// Lazily iterates over all vertices. Rather than holding the read lock for
+// the iterator's whole lifetime, vertices are fetched in chunks, each of which
+// briefly acquires the lock.
+struct MemoryVertexIterator {
+    datastore: Arc<DatastoreLock>,
+    lower_bound: Bound<Uuid>,
+    upper_bound: Bound<Uuid>,
+    remaining: usize,
+    buffer: VecDeque<models::Vertex>,
+}
+
+impl MemoryVertexIterator {
+    fn fill_buffer(&mut self) -> Result<()> {
+        let datastore = self.datastore.read()?;
+        let chunk_size = cmp::min(self.remaining, VERTEX_ITERATOR_CHUNK_SIZE);
+        let range = datastore.vertices.range((self.lower_bound, self.upper_bound));
+
+        for (id, (t, _)) in range.take(chunk_size) {
+            self.buffer.push_back(models::Vertex::with_id(*id, t.clone()));
+            self.lower_bound = Bound::Excluded(*id);
+        }
+
+        if self.buffer.len() < chunk_size {
+            // There's nothing left to read
+            self.remaining = 0;
+        } else {
+            self.remaining -= chunk_size;
+        }
+
+        Ok(())
+    }
+}
+
+impl Iterator for MemoryVertexIterator {
+    type Item = Result<models::Vertex>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_empty() && self.remaining > 0 {
+            if let Err(err) = self.fill_buffer() {
+                self.remaining = 0;
+                return Some(Err(err));
+            }
+        }
+
+        self.buffer.pop_front().map(Ok)
+    }
+}
+
+/// A transaction for manipulating in-memory-only datastores.
+#[derive(Debug)]
+pub struct MemoryTransaction {
+    datastore: Arc<DatastoreLock>,
+}
+
+impl MemoryTransaction {
+    /// Gets the cached outbound and inbound degree of a vertex, i.e. the
+    /// number of edges going out of and into it. Unlike `get_edge_count`,
+    /// this is a constant-time lookup. Vertices that don't exist have a
+    /// degree of zero.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    pub fn get_cached_degree(&self, id: Uuid) -> Result<(u64, u64)> {
+        let datastore = self.datastore.read()?;
+        let out_degree = datastore.out_degrees.get(&id).cloned().unwrap_or(0);
+        let in_degree = datastore.in_degrees.get(&id).cloned().unwrap_or(0);
+        Ok((out_degree, in_degree))
+    }
+
+    /// Gets the vertices whose property equals a given value, ordered by
+    /// id. This is fast for properties that have an index - see
+    /// `MemoryDatastore::create_property_index` - and otherwise scans all
+    /// vertex properties.
+    ///
+    /// # Arguments
+    /// * `name` - The property name.
+    /// * `value` - The property value.
+    pub fn get_vertices_by_property_value(&self, name: &str, value: &JsonValue) -> Result<Vec<models::Vertex>> {
+        let datastore = self.datastore.read()?;
+        let ids = datastore.get_vertex_ids_by_property_value(name, value);
+
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| {
+                datastore
+                    .vertices
+                    .get(&id)
+                    .map(|(t, _)| models::Vertex::with_id(id, t.clone()))
+            })
+            .collect())
+    }
+
+    /// Creates an undirected edge. The edge is stored once, under its
+    /// canonical key - see `EdgeKey::canonical` - but queries, edge counts
+    /// and edge properties treat it as existing in both directions, so it
+    /// can be addressed by either its key or its reversed key. Creating an
+    /// undirected edge where a directed edge with the canonical key already
+    /// exists makes that edge undirected.
+    ///
+    /// Returns whether the edge was created - if this is false, it's
+    /// because one of the vertices doesn't exist, the edge is a disallowed
+    /// self-loop, or a directed edge already exists going the opposite way
+    /// of the canonical key.
+    ///
+    /// # Arguments
+    /// * `key` - The edge to create.
+    pub fn create_undirected_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        let mut datastore = self.datastore.write()?;
+        let key = datastore.normalize_edge_key(key).canonical();
+
+        if !datastore.is_valid_edge(&key) {
+            return Ok(false);
+        }
+
+        if key.outbound_id != key.inbound_id && datastore.edges.contains_key(&key.reversed()) {
+            return Ok(false);
+        }
+
+        let update_datetime = datastore.now();
+        datastore.insert_undirected_edge(key, update_datetime, None);
+        Ok(true)
+    }
+
+    /// Creates an edge with a weight, or sets the weight of an existing
+    /// edge, updating its datetime like `create_edge` does. Weights are
+    /// returned with edges, e.g. by `get_edges`, and are kept when the edge
+    /// is later created again without one. Weights must be finite.
+    ///
+    /// Returns whether the edge was created - if this is false, it's
+    /// because one of the vertices doesn't exist, or the edge is a
+    /// disallowed self-loop.
+    ///
+    /// # Arguments
+    /// * `key` - The edge to create.
+    /// * `weight` - The weight of the edge.
+    pub fn create_weighted_edge(&self, key: &models::EdgeKey, weight: f64) -> Result<bool> {
+        if !weight.is_finite() {
+            let err: ValidationError = format!("Edge weight `{}` is not finite", weight).into();
+            return Err(err.into());
+        }
+
+        let mut datastore = self.datastore.write()?;
+        let key = datastore.normalize_edge_key(key).into_owned();
+
+        if !datastore.is_valid_edge(&key) {
+            return Ok(false);
+        }
+
+        let update_datetime = datastore.now();
+        datastore.insert_edge(key, update_datetime, Some(weight));
+        Ok(true)
+    }
+
+    /// Creates an edge that expires once a time-to-live has passed, or
+    /// updates an existing edge, resetting when it expires. Creating the
+    /// edge again with `create_edge` keeps its expiry.
+    ///
+    /// Expired edges are treated as absent: they're purged whenever the
+    /// datastore is next read from or written to, before the read or write
+    /// goes ahead, so queries, counts and records don't include edges that
+    /// had expired by the time they started. An edge that expires while a
+    /// read is in progress may still be included in that read. Use
+    /// `purge_expired` to purge them eagerly, e.g. to free their memory when
+    /// the datastore is otherwise idle.
+    ///
+    /// Returns whether the edge was created - if this is false, it's
+    /// because one of the vertices doesn't exist, or the edge is a
+    /// disallowed self-loop.
+    ///
+    /// # Arguments
+    /// * `key` - The edge to create.
+    /// * `ttl` - How long until the edge expires.
+    pub fn create_edge_with_ttl(&self, key: &models::EdgeKey, ttl: Duration) -> Result<bool> {
+        let mut datastore = self.datastore.write()?;
+        let key = datastore.normalize_edge_key(key).into_owned();
+
+        if !datastore.is_valid_edge(&key) {
+            return Ok(false);
+        }
+
+        let update_datetime = datastore.now();
+        let expires_datetime = match ChronoDuration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| update_datetime.checked_add_signed(ttl))
+        {
+            Some(expires_datetime) => expires_datetime,
+            None => {
+                let err: ValidationError = format!("Edge TTL `{:?}` is too long", ttl).into();
+                return Err(err.into());
+            }
+        };
+
+        datastore.insert_edge(key.clone(), update_datetime, None);
+        let resolved_key = datastore.resolve_edge_key(&key);
+        datastore.set_edge_expiration(resolved_key, Some(expires_datetime));
+        Ok(true)
+    }
+
+    /// Deletes every edge whose TTL has expired, along with its properties.
+    /// Returns the number of edges that were deleted, which doesn't include
+    /// expired edges that were already purged by an earlier read or write.
+    /// See `create_edge_with_ttl`.
+    pub fn purge_expired(&self) -> Result<u64> {
+        let mut datastore = self.datastore.acquire_write()?;
+        Ok(datastore.purge_expired_edges())
+    }
+
+    /// Gets the most recently updated edges touching a vertex, whether
+    /// they're outbound or inbound, newest first. Edges updated at the same
+    /// time are ordered by descending key, so the order is deterministic.
+    /// This is backed by an index of each vertex's edges by update
+    /// datetime, so it doesn't scan the vertex's other edges.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    /// * `limit` - The maximum number of edges to return.
+    pub fn get_vertex_timeline(&self, id: Uuid, limit: u32) -> Result<Vec<models::Edge>> {
+        Ok(self.datastore.read()?.get_vertex_timeline(id, limit))
+    }
+
+    /// Gets the edges whose update datetime predates the creation of either
+    /// of their vertices, ordered by key. Edges are only checked while
+    /// edge time checks are enabled; see
+    /// `MemoryDatastore::with_edge_time_checks`.
+    pub fn get_inconsistent_edges(&self) -> Result<Vec<models::EdgeKey>> {
+        Ok(self.datastore.read()?.inconsistent_edges.iter().cloned().collect())
+    }
+
+    /// Checks whether an edge is undirected. The edge can be addressed by
+    /// either its key or its reversed key. Returns false if the edge
+    /// doesn't exist.
+    ///
+    /// # Arguments
+    /// * `key` - The edge to check.
+    pub fn is_edge_undirected(&self, key: &models::EdgeKey) -> Result<bool> {
+        let datastore = self.datastore.read()?;
+        let key = datastore.normalize_edge_key(key);
+        Ok(datastore.undirected_edges.contains(&datastore.resolve_edge_key(&key)))
+    }
+
+    /// Gets the edges connecting two vertices, of any type. Outbound edges
+    /// go from `a` to `b`, and inbound edges from `b` to `a`. If no
+    /// direction is given, or the direction is `Both`, outbound edges are
+    /// returned first, followed by inbound edges.
+    ///
+    /// # Arguments
+    /// * `a` - The id of the first vertex.
+    /// * `b` - The id of the second vertex.
+    /// * `direction` - The direction of edges to get, relative to `a`.
+    pub fn get_edges_between(
+        &self,
+        a: Uuid,
+        b: Uuid,
+        direction: Option<models::EdgeDirection>,
+    ) -> Result<Vec<models::Edge>> {
+        let datastore = self.datastore.read()?;
+        let mut results = Vec::new();
+
+        let mut pairs = match direction {
+            Some(models::EdgeDirection::Outbound) => vec![(a, b)],
+            Some(models::EdgeDirection::Inbound) => vec![(b, a)],
+            Some(models::EdgeDirection::Both) | None => vec![(a, b), (b, a)],
+        };
+
+        // Self-loops would otherwise be returned in both directions
+        pairs.dedup();
+
+        for (outbound_id, inbound_id) in pairs {
+            let lower_bound = models::EdgeKey::new(outbound_id, models::Type::default(), Uuid::default());
+
+            for (key, &(update_datetime, weight)) in datastore.edges.range(lower_bound..) {
+                if key.outbound_id != outbound_id {
+                    break;
+                }
+
+                if key.inbound_id == inbound_id {
+                    results.push(models::Edge::with_weight(key.clone(), update_datetime, weight));
+                }
+            }
+
+            for (key, update_datetime, weight) in
+                datastore.get_mirrored_edges(outbound_id, models::EdgeDirection::Outbound, None)
+            {
+                if key.inbound_id == inbound_id {
+                    results.push(models::Edge::with_weight(key, update_datetime, weight));
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Clones a vertex, along with all of its properties, under a new id.
+    /// Edges are not cloned. Returns whether the vertex was successfully
+    /// cloned - if this is false, it's because the source vertex doesn't
+    /// exist, or a vertex with the new id already exists.
+    ///
+    /// # Arguments
+    /// * `source` - The id of the vertex to clone.
+    /// * `new_id` - The id of the cloned vertex.
+    pub fn clone_vertex(&self, source: Uuid, new_id: Uuid) -> Result<bool> {
+        let mut datastore = self.datastore.write()?;
+
+        if datastore.vertices.contains_key(&new_id) {
+            return Ok(false);
+        }
+
+        let t = match datastore.vertices.get(&source) {
+            Some((t, _)) => t.clone(),
+            None => return Ok(false),
+        };
+
+        let properties: Vec<(String, JsonValue)> = datastore
+            .vertex_properties
+            .range((source, "".to_string())..)
+            .take_while(|&(&(id, _), _)| id == source)
+            .map(|((_, name), (value, _))| (name.clone(), value.clone()))
+            .collect();
+
+        let created_datetime = datastore.now();
+        datastore.insert_vertex(new_id, t, Some(created_datetime));
+
+        for (name, value) in properties {
+            datastore.set_vertex_property(new_id, name.clone(), value.clone(), created_datetime);
+            datastore.emit(|| ChangeEvent::VertexPropertySet(models::VertexProperty::new(new_id, name, value)));
+        }
+
+        Ok(true)
+    }
+
+    /// Merges one vertex into another: the edges of `remove` are redirected
+    /// to `keep`, its properties are copied over, and then `remove` is
+    /// deleted.
+    ///
+    /// Conflicts are resolved in favor of `keep`:
+    /// * Properties that both vertices have keep the value from `keep`.
+    /// * Edges between `keep` and `remove` are dropped rather than turned
+    ///   into self-loops. Self-loops on `remove` become self-loops on `keep`,
+    ///   unless self-loops are disallowed, in which case they're dropped too.
+    /// * If a redirected edge collides with an existing edge of `keep`, the
+    ///   existing edge and its creation datetime are kept, and the
+    ///   redirected edge's properties are merged into it, again with the
+    ///   existing edge's values winning.
+    ///
+    /// Returns a validation error if either vertex doesn't exist, or if both
+    /// ids are the same.
+    ///
+    /// # Arguments
+    /// * `keep` - The id of the vertex to merge into.
+    /// * `remove` - The id of the vertex to merge and then delete.
+    pub fn merge_vertices(&self, keep: Uuid, remove: Uuid) -> Result<()> {
+        let mut datastore = self.datastore.write()?;
+
+        if keep == remove {
+            let err: ValidationError = "Cannot merge a vertex into itself".into();
+            return Err(err.into());
+        }
+
+        for id in &[keep, remove] {
+            if !datastore.vertices.contains_key(id) {
+                let err: ValidationError = format!("Vertex `{}` does not exist", id).into();
+                return Err(err.into());
+            }
+        }
+
+        // Each edge to move, along with whether it's undirected and its
+        // properties
+        type MergedEdge = (EdgeValue, bool, Vec<(String, JsonValue)>);
+        let mut merged_edges: Vec<MergedEdge> = Vec::new();
+
+        for (key, &(update_datetime, weight)) in &datastore.edges {
+            if key.outbound_id != remove && key.inbound_id != remove {
+                continue;
+            }
+
+            let properties = datastore
+                .edge_properties
+                .range((key.clone(), "".to_string())..)
+                .take_while(|&((property_edge_key, _), _)| property_edge_key == key)
+                .map(|((_, name), (value, _))| (name.clone(), value.clone()))
+                .collect();
+
+            let undirected = datastore.undirected_edges.contains(key);
+            merged_edges.push(((key.clone(), update_datetime, weight), undirected, properties));
+        }
+
+        // Deleting the old edges also deletes their properties and updates
+        // the degree counters
+        datastore.delete_edges(merged_edges.iter().map(|((key, _, _), _, _)| key.clone()).collect());
+
+        for ((key, update_datetime, weight), undirected, properties) in merged_edges {
+            if key.outbound_id == keep || key.inbound_id == keep {
+                continue;
+            }
+
+            let outbound_id = if key.outbound_id == remove {
+                keep
+            } else {
+                key.outbound_id
+            };
+            let inbound_id = if key.inbound_id == remove { keep } else { key.inbound_id };
+            let new_key = models::EdgeKey::new(outbound_id, key.t, inbound_id);
+
+            if outbound_id == inbound_id && !datastore.allow_self_loops {
+                continue;
+            }
+
+            if !datastore.edges.contains_key(&datastore.resolve_edge_key(&new_key)) {
+                if undirected && !datastore.edges.contains_key(&new_key.reversed()) {
+                    datastore.insert_undirected_edge(new_key.clone(), update_datetime, weight);
+                } else {
+                    datastore.insert_edge(new_key.clone(), update_datetime, weight);
+                }
+            }
+
+            let new_key = datastore.resolve_edge_key(&new_key);
+
+            let update_datetime = datastore.now();
+            for (name, value) in properties {
+                if let Entry::Vacant(entry) = datastore.edge_properties.entry((new_key.clone(), name.clone())) {
+                    entry.insert((value.clone(), update_datetime));
+                    datastore
+                        .emit(|| ChangeEvent::EdgePropertySet(models::EdgeProperty::new(new_key.clone(), name, value)));
+                }
+            }
+        }
+
+        let properties: Vec<(String, JsonValue)> = datastore
+            .vertex_properties
+            .range((remove, "".to_string())..)
+            .take_while(|&(&(id, _), _)| id == remove)
+            .map(|((_, name), (value, _))| (name.clone(), value.clone()))
+            .collect();
+
+        for (name, value) in properties {
+            if !datastore.vertex_properties.contains_key(&(keep, name.clone())) {
+                let update_datetime = datastore.now();
+                datastore.set_vertex_property(keep, name.clone(), value.clone(), update_datetime);
+                datastore.emit(|| ChangeEvent::VertexPropertySet(models::VertexProperty::new(keep, name, value)));
+            }
+        }
+
+        datastore.delete_vertices(vec![remove]);
+        Ok(())
+    }
+
+    /// Renames a vertex type: every vertex of type `from` gets type `to`.
+    /// Vertices that already have type `to` are left as-is, so afterwards
+    /// they can't be told apart from the renamed ones. Returns the number of
+    /// vertices that were renamed.
+    ///
+    /// # Arguments
+    /// * `from` - The type to rename.
+    /// * `to` - The new type.
+    pub fn rename_vertex_type(&self, from: &models::Type, to: &models::Type) -> Result<u64> {
+        let mut datastore = self.datastore.write()?;
+        let from = datastore.normalize_type(from).into_owned();
+        let to = datastore.normalize_type(to).into_owned();
+        let mut count = 0;
+
+        if from == to {
+            return Ok(count);
+        }
+
+        let mut renamed_ids = Vec::new();
+
+        for (id, &mut (ref mut t, _)) in datastore.vertices.iter_mut() {
+            if *t == from {
+                *t = to.clone();
+                renamed_ids.push(*id);
+                count += 1;
+            }
+        }
+
+        for id in renamed_ids {
+            datastore.emit(|| ChangeEvent::VertexUpdated(models::Vertex::with_id(id, to.clone())));
+        }
+
+        Ok(count)
+    }
+
+    /// Renames an edge type: every edge of type `from` is rekeyed to type
+    /// `to`, keeping its update datetime, its properties and whether it's
+    /// undirected. Returns the number of edges that were renamed.
+    ///
+    /// An edge can't be renamed if an edge already exists between the same
+    /// vertices with type `to` - or, for undirected edges, going either way
+    /// between them. In that case, a validation error is returned, and no
+    /// edges are renamed at all.
+    ///
+    /// # Arguments
+    /// * `from` - The type to rename.
+    /// * `to` - The new type.
+    pub fn rename_edge_type(&self, from: &models::Type, to: &models::Type) -> Result<u64> {
+        let mut datastore = self.datastore.write()?;
+        let from = datastore.normalize_type(from).into_owned();
+        let to = datastore.normalize_type(to).into_owned();
+
+        if from == to {
+            return Ok(0);
+        }
+
+        let renamed_keys: Vec<models::EdgeKey> = datastore.edges.keys().filter(|key| key.t == from).cloned().collect();
+
+        for key in &renamed_keys {
+            let new_key = models::EdgeKey::new(key.outbound_id, to.clone(), key.inbound_id);
+            let undirected = datastore.undirected_edges.contains(key);
+
+            if datastore.edges.contains_key(&datastore.resolve_edge_key(&new_key))
+                || (undirected && datastore.edges.contains_key(&new_key.reversed()))
+            {
+                let err: ValidationError = format!("Edge `{:?}` would collide with an existing edge", key).into();
+                return Err(err.into());
+            }
+        }
+
+        for key in &renamed_keys {
+            let new_key = models::EdgeKey::new(key.outbound_id, to.clone(), key.inbound_id);
+
+            // The vertices stay the same, so the degrees don't change, and
+            // since canonical keys only depend on the vertex ids, the new
+            // key of an undirected edge is canonical too
+            if let Some(value) = datastore.edges.remove(key) {
+                datastore.remove_from_edge_timelines(key, value.0);
+                datastore.add_to_edge_timelines(&new_key, value.0);
+                datastore.edges.insert(new_key.clone(), value);
+            }
+
+            if datastore.inconsistent_edges.remove(key) {
+                datastore.inconsistent_edges.insert(new_key.clone());
+            }
+
+            if let Some(expires_datetime) = datastore.edge_expirations.get(key).cloned() {
+                datastore.set_edge_expiration(key.clone(), None);
+                datastore.set_edge_expiration(new_key.clone(), Some(expires_datetime));
+            }
+
+            if datastore.undirected_edges.remove(key) {
+                datastore.reversed_undirected_edges.remove(&key.reversed());
+                datastore.undirected_edges.insert(new_key.clone());
+                datastore.reversed_undirected_edges.insert(new_key.reversed());
+            }
+
+            let properties: Vec<(String, (JsonValue, DateTime<Utc>))> = datastore
+                .edge_properties
+                .range((key.clone(), "".to_string())..)
+                .take_while(|&((property_edge_key, _), _)| property_edge_key == key)
+                .map(|((_, name), value)| (name.clone(), value.clone()))
+                .collect();
+
+            datastore.remove_all_edge_properties(key);
+
+            for (name, value) in properties {
+                datastore.edge_properties.insert((new_key.clone(), name), value);
+            }
+
+            datastore.emit(|| ChangeEvent::EdgeRenamed(key.clone(), new_key));
+        }
+
+        Ok(renamed_keys.len() as u64)
+    }
+
+    /// Gets the vertex properties with a given name that were last set at or
+    /// after `since`, ordered by vertex id. Deleted properties aren't
+    /// tracked, so they're never returned.
+    ///
+    /// # Arguments
+    /// * `name` - The property name.
+    /// * `since` - The earliest update datetime to include.
+    pub fn get_vertex_properties_updated_since(
+        &self,
+        name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<models::VertexProperty>> {
+        let datastore = self.datastore.read()?;
+
+        Ok(datastore
+            .vertex_properties
+            .iter()
+            .filter(|&((_, property_name), &(_, update_datetime))| property_name == name && update_datetime >= since)
+            .map(|(&(id, ref property_name), (value, _))| {
+                models::VertexProperty::new(id, property_name.clone(), value.clone())
+            })
+            .collect())
+    }
+
+    /// Gets the edge properties with a given name that were last set at or
+    /// after `since`, ordered by edge key. Deleted properties aren't
+    /// tracked, so they're never returned.
+    ///
+    /// # Arguments
+    /// * `name` - The property name.
+    /// * `since` - The earliest update datetime to include.
+    pub fn get_edge_properties_updated_since(
+        &self,
+        name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<models::EdgeProperty>> {
+        let datastore = self.datastore.read()?;
+
+        Ok(datastore
+            .edge_properties
+            .iter()
+            .filter(|&((_, property_name), &(_, update_datetime))| property_name == name && update_datetime >= since)
+            .map(|((key, property_name), (value, _))| {
+                models::EdgeProperty::new(key.clone(), property_name.clone(), value.clone())
+            })
+            .collect())
+    }
+}
+
+impl ReadTransaction for MemoryTransaction {
+    fn get_vertices(&self, q: &VertexQuery) -> Result<Vec<models::Vertex>> {
+        let vertex_values = {
+            let datastore = self.datastore.read()?;
+            datastore.get_vertex_values_by_query(&datastore.normalize_vertex_query(q))?
+        };
+
+        let iter = vertex_values
+            .into_iter()
+            .map(|(uuid, t)| models::Vertex::with_id(uuid, t));
+        Ok(iter.collect())
+    }
+
+    fn get_vertices_iter(&self, q: &VertexQuery) -> Result<Box<dyn Iterator<Item = Result<models::Vertex>>>> {
+        match *q {
+            VertexQuery::All {
+                start_id,
+                end_id,
+                high_filter: None,
+                low_filter: None,
+                limit,
+            } => {
+                let (lower_bound, upper_bound) = match vertex_id_bounds(start_id, end_id) {
+                    Some(bounds) => bounds,
+                    None => return Ok(Box::new(Vec::new().into_iter())),
+                };
+
+                Ok(Box::new(MemoryVertexIterator {
+                    datastore: Arc::clone(&self.datastore),
+                    lower_bound,
+                    upper_bound,
+                    remaining: limit as usize,
+                    buffer: VecDeque::new(),
+                }))
+            }
+            // Other queries are bounded by their inputs, so they're cheap
+            // enough to evaluate up-front. Queries for all vertices created
+            // in a time range aren't, but they're rare enough that it isn't
+            // worth filtering lazily.
+            _ => Ok(Box::new(self.get_vertices(q)?.into_iter().map(Ok))),
+        }
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        let datastore = self.datastore.read()?;
         Ok(datastore.vertices.len() as u64)
     }
 
+    fn estimate_vertex_count(&self, q: &VertexQuery) -> Result<u64> {
+        let datastore = self.datastore.read()?;
+        Ok(cmp::min(q.max_results(), datastore.vertices.len() as u64))
+    }
+
+    fn get_vertex_types(&self) -> Result<Vec<models::Type>> {
+        let datastore = self.datastore.read()?;
+        let types: BTreeSet<&models::Type> = datastore.vertices.values().map(|(t, _)| t).collect();
+        Ok(types.into_iter().cloned().collect())
+    }
+
+    fn count_vertices_by_type(&self) -> Result<BTreeMap<models::Type, u64>> {
+        Ok(self.datastore.read()?.count_vertices_by_type())
+    }
+
+    fn vertex_exists(&self, id: Uuid) -> Result<bool> {
+        let datastore = self.datastore.read()?;
+        Ok(datastore.vertices.contains_key(&id))
+    }
+
+    fn get_edges(&self, q: &EdgeQuery) -> Result<Vec<models::Edge>> {
+        let edge_values = {
+            let datastore = self.datastore.read()?;
+            datastore.get_edge_values_by_query(&datastore.normalize_edge_query(q))?
+        };
+
+        let iter = edge_values
+            .into_iter()
+            .map(|(key, update_datetime, weight)| models::Edge::with_weight(key, update_datetime, weight));
+        Ok(iter.collect())
+    }
+
+    fn edge_exists(&self, key: &models::EdgeKey) -> Result<bool> {
+        let datastore = self.datastore.read()?;
+        let key = datastore.normalize_edge_key(key);
+        Ok(datastore.edges.contains_key(&datastore.resolve_edge_key(&key)))
+    }
+
+    fn get_induced_edges(&self, ids: &[Uuid]) -> Result<Vec<models::Edge>> {
+        let datastore = self.datastore.read()?;
+        let id_set: HashSet<&Uuid> = ids.iter().collect();
+        let mut scanned_ids = HashSet::new();
+        let mut results = Vec::new();
+
+        for id in ids {
+            if !scanned_ids.insert(id) {
+                continue;
+            }
+
+            let lower_bound = models::EdgeKey::new(*id, models::Type::default(), Uuid::default());
+
+            for (key, &(update_datetime, weight)) in datastore.edges.range(lower_bound..) {
+                if &key.outbound_id != id {
+                    break;
+                }
+
+                if id_set.contains(&key.inbound_id) {
+                    results.push(models::Edge::with_weight(key.clone(), update_datetime, weight));
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn get_edge_count(
+        &self,
+        id: Uuid,
+        type_filter: Option<&models::Type>,
+        direction: models::EdgeDirection,
+    ) -> Result<u64> {
+        let datastore = self.datastore.read()?;
+        let type_filter = type_filter.map(|t| datastore.normalize_type(t));
+        Ok(datastore.count_edges(id, type_filter.as_ref().map(|t| t.as_ref()), direction))
+    }
+
+    fn get_edge_counts(
+        &self,
+        ids: &[Uuid],
+        type_filter: Option<&models::Type>,
+        direction: models::EdgeDirection,
+    ) -> Result<Vec<(Uuid, u64)>> {
+        let datastore = self.datastore.read()?;
+        let type_filter = type_filter.map(|t| datastore.normalize_type(t));
+        Ok(datastore.count_edges_for_many(ids, type_filter.as_ref().map(|t| t.as_ref()), direction))
+    }
+
+    fn get_vertex_degree(&self, id: Uuid, type_filter: Option<&models::Type>) -> Result<(u64, u64)> {
+        let datastore = self.datastore.read()?;
+        let type_filter = type_filter.map(|t| datastore.normalize_type(t));
+        let type_filter = type_filter.as_ref().map(|t| t.as_ref());
+        Ok((
+            datastore.count_outbound_edges(id, type_filter),
+            datastore.count_inbound_edges(id, type_filter),
+        ))
+    }
+
+    fn get_vertex_properties(&self, q: &VertexQuery, name: &str) -> Result<Vec<models::VertexProperty>> {
+        let mut result = Vec::new();
+        let datastore = self.datastore.read()?;
+        let vertex_values = datastore.get_vertex_values_by_query(&datastore.normalize_vertex_query(q))?;
+
+        for (id, _) in vertex_values {
+            let property_value = datastore.vertex_properties.get(&(id, name.to_string()));
+
+            if let Some((property_value, _)) = property_value {
+                result.push(models::VertexProperty::new(
+                    id,
+                    name.to_string(),
+                    property_value.clone(),
+                ));
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn get_vertex_properties_multi(&self, q: &VertexQuery, names: &[String]) -> Result<Vec<models::VertexProperty>> {
+        let mut result = Vec::new();
+        let datastore = self.datastore.read()?;
+        let vertex_values = datastore.get_vertex_values_by_query(&datastore.normalize_vertex_query(q))?;
+
+        for (id, _) in vertex_values {
+            for name in names {
+                if let Some((property_value, _)) = datastore.vertex_properties.get(&(id, name.clone())) {
+                    result.push(models::VertexProperty::new(id, name.clone(), property_value.clone()));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn get_vertex_properties_bounded(
+        &self,
+        q: &VertexQuery,
+        names: &[String],
+        max_bytes: u64,
+    ) -> Result<(Vec<models::VertexProperty>, bool)> {
+        let mut result = Vec::new();
+        let mut total_bytes = 0;
+        let datastore = self.datastore.read()?;
+        let vertex_values = datastore.get_vertex_values_by_query(&datastore.normalize_vertex_query(q))?;
+
+        for (id, _) in vertex_values {
+            for name in names {
+                if let Some((property_value, _)) = datastore.vertex_properties.get(&(id, name.clone())) {
+                    total_bytes += util::json_size(property_value) as u64;
+
+                    if total_bytes > max_bytes {
+                        return Ok((result, true));
+                    }
+
+                    result.push(models::VertexProperty::new(id, name.clone(), property_value.clone()));
+                }
+            }
+        }
+
+        Ok((result, false))
+    }
+
+    fn get_all_vertex_properties_bounded(
+        &self,
+        q: &VertexQuery,
+        max_bytes: u64,
+    ) -> Result<(Vec<models::VertexProperty>, bool)> {
+        let mut result = Vec::new();
+        let mut total_bytes = 0;
+        let datastore = self.datastore.read()?;
+        let vertex_values = datastore.get_vertex_values_by_query(&datastore.normalize_vertex_query(q))?;
+
+        for (id, _) in vertex_values {
+            let properties = datastore
+                .vertex_properties
+                .range((id, "".to_string())..)
+                .take_while(|&(&(property_id, _), _)| property_id == id);
+
+            for ((_, name), (property_value, _)) in properties {
+                total_bytes += util::json_size(property_value) as u64;
+
+                if total_bytes > max_bytes {
+                    return Ok((result, true));
+                }
+
+                result.push(models::VertexProperty::new(id, name.clone(), property_value.clone()));
+            }
+        }
+
+        Ok((result, false))
+    }
+
+    fn get_vertex_property_names(&self, id: Uuid) -> Result<Vec<String>> {
+        let datastore = self.datastore.read()?;
+
+        Ok(datastore
+            .vertex_properties
+            .range((id, "".to_string())..)
+            .take_while(|&(&(property_id, _), _)| property_id == id)
+            .map(|((_, name), _)| name.clone())
+            .collect())
+    }
+
+    fn get_edge_properties(&self, q: &EdgeQuery, name: &str) -> Result<Vec<models::EdgeProperty>> {
+        let mut result = Vec::new();
+        let datastore = self.datastore.read()?;
+        let edge_values = datastore.get_edge_values_by_query(&datastore.normalize_edge_query(q))?;
+
+        for (key, _, _) in edge_values {
+            let property_value = datastore
+                .edge_properties
+                .get(&(datastore.resolve_edge_key(&key), name.to_string()));
+
+            if let Some((property_value, _)) = property_value {
+                result.push(models::EdgeProperty::new(key, name.to_string(), property_value.clone()));
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn get_edge_property_names(&self, key: &models::EdgeKey) -> Result<Vec<String>> {
+        let datastore = self.datastore.read()?;
+        let key = datastore.resolve_edge_key(&datastore.normalize_edge_key(key));
+
+        Ok(datastore
+            .edge_properties
+            .range((key.clone(), "".to_string())..)
+            .take_while(|&((property_key, _), _)| property_key == &key)
+            .map(|((_, name), _)| name.clone())
+            .collect())
+    }
+
+    fn aggregate_edge_property(&self, q: &EdgeQuery, name: &str, op: models::AggregateOp) -> Result<Option<f64>> {
+        let datastore = self.datastore.read()?;
+        let edge_values = datastore.get_edge_values_by_query(&datastore.normalize_edge_query(q))?;
+        let name = name.to_string();
+
+        Ok(op.aggregate(edge_values.into_iter().filter_map(|(key, _, _)| {
+            datastore
+                .edge_properties
+                .get(&(datastore.resolve_edge_key(&key), name.clone()))
+                .map(|(value, _)| value)
+        })))
+    }
+
+    fn get_full_record(&self, id: Uuid, edge_limit: u32) -> Result<Option<models::FullRecord>> {
+        let datastore = self.datastore.read()?;
+
+        let t = match datastore.vertices.get(&id) {
+            Some((t, _)) => t.clone(),
+            None => return Ok(None),
+        };
+
+        let properties = datastore
+            .vertex_properties
+            .range((id, "".to_string())..)
+            .take_while(|&(&(property_vertex_id, _), _)| property_vertex_id == id)
+            .map(|((_, name), (value, _))| models::NamedProperty::new(name.clone(), value.clone()))
+            .collect();
+
+        let q = VertexQuery::Vertices { ids: vec![id] };
+        let outbound_edges = datastore.get_edge_records(&q.clone().outbound_edges(None, None, None, edge_limit))?;
+        let inbound_edges = datastore.get_edge_records(&q.inbound_edges(None, None, None, edge_limit))?;
+
+        Ok(Some(models::FullRecord::new(
+            models::Vertex::with_id(id, t),
+            properties,
+            outbound_edges,
+            inbound_edges,
+        )))
+    }
+
+    fn get_graph_stats(&self) -> Result<models::GraphStats> {
+        let datastore = self.datastore.read()?;
+        let mut stats = models::GraphStats {
+            vertices: datastore.vertices.len() as u64,
+            edges: datastore.edges.len() as u64,
+            vertex_properties: datastore.vertex_properties.len() as u64,
+            edge_properties: datastore.edge_properties.len() as u64,
+            vertex_types: datastore.count_vertices_by_type(),
+            ..models::GraphStats::default()
+        };
+
+        let total_degree = datastore.out_degrees.values().sum::<u64>() + datastore.in_degrees.values().sum::<u64>();
+        stats.set_average_degree(total_degree);
+        Ok(stats)
+    }
+}
+
+impl WriteTransaction for MemoryTransaction {
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
+        let mut datastore = self.datastore.write()?;
+        let t = datastore.normalize_type(&vertex.t).into_owned();
+        let mut inserted = false;
+
+        let created_datetime = datastore.now();
+        datastore.vertices.entry(vertex.id).or_insert_with(|| {
+            inserted = true;
+            (t.clone(), created_datetime)
+        });
+
+        if inserted {
+            datastore.emit(|| ChangeEvent::VertexCreated(models::Vertex::with_id(vertex.id, t)));
+        }
+
+        Ok(inserted)
+    }
+
+    fn get_or_create_vertex(&self, id: Uuid, t: &models::Type) -> Result<(models::Vertex, bool)> {
+        let mut datastore = self.datastore.write()?;
+
+        if let Some((t, _)) = datastore.vertices.get(&id) {
+            return Ok((models::Vertex::with_id(id, t.clone()), false));
+        }
+
+        let vertex = models::Vertex::with_id(id, datastore.normalize_type(t).into_owned());
+        let created_datetime = datastore.now();
+        datastore.vertices.insert(id, (vertex.t.clone(), created_datetime));
+        datastore.emit(|| ChangeEvent::VertexCreated(vertex.clone()));
+        Ok((vertex, true))
+    }
+
+    fn delete_vertices(&self, q: &VertexQuery) -> Result<u64> {
+        let mut datastore = self.datastore.write()?;
+        let q = datastore.normalize_vertex_query(q);
+        let deletable_vertices: Vec<Uuid> = datastore
+            .get_vertex_values_by_query(&q)?
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        Ok(datastore.delete_vertices(deletable_vertices))
+    }
+
     fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
-        let mut datastore = self.datastore.write().unwrap();
+        let mut datastore = self.datastore.write()?;
+        let key = datastore.normalize_edge_key(key).into_owned();
 
-        if !datastore.vertices.contains_key(&key.outbound_id) || !datastore.vertices.contains_key(&key.inbound_id) {
+        if !datastore.is_valid_edge(&key) {
             return Ok(false);
         }
 
-        datastore.edges.insert(key.clone(), Utc::now());
+        let update_datetime = datastore.now();
+        datastore.insert_edge(key, update_datetime, None);
         Ok(true)
     }
 
-    fn get_edges(&self, q: &EdgeQuery) -> Result<Vec<models::Edge>> {
-        let edge_values = {
-            let datastore = self.datastore.read().unwrap();
-            datastore.get_edge_values_by_query(q)?
-        };
+    fn create_edge_auto(&self, key: &models::EdgeKey, default_type: &models::Type) -> Result<bool> {
+        let mut datastore = self.datastore.write()?;
+        let key = datastore.normalize_edge_key(key).into_owned();
 
-        let iter = edge_values
-            .into_iter()
-            .map(|(key, update_datetime)| models::Edge::new(key, update_datetime));
-        Ok(iter.collect())
+        if !datastore.allow_self_loops && key.outbound_id == key.inbound_id {
+            return Ok(false);
+        }
+
+        let t = datastore.normalize_type(default_type).into_owned();
+
+        for &id in &[key.outbound_id, key.inbound_id] {
+            if !datastore.vertices.contains_key(&id) {
+                let created_datetime = datastore.now();
+                datastore.vertices.insert(id, (t.clone(), created_datetime));
+                datastore.emit(|| ChangeEvent::VertexCreated(models::Vertex::with_id(id, t.clone())));
+            }
+        }
+
+        let created = !datastore.edges.contains_key(&datastore.resolve_edge_key(&key));
+        let update_datetime = datastore.now();
+        datastore.insert_edge(key, update_datetime, None);
+        Ok(created)
+    }
+
+    fn update_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        let mut datastore = self.datastore.write()?;
+        let key = datastore.normalize_edge_key(key).into_owned();
+
+        if !datastore.edges.contains_key(&datastore.resolve_edge_key(&key)) {
+            return Ok(false);
+        }
+
+        let update_datetime = datastore.now();
+        datastore.insert_edge(key, update_datetime, None);
+        Ok(true)
     }
 
-    fn delete_edges(&self, q: &EdgeQuery) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
+    fn delete_edges(&self, q: &EdgeQuery) -> Result<u64> {
+        let mut datastore = self.datastore.write()?;
+        let q = datastore.normalize_edge_query(q);
+
+        if let Some(count) = datastore.delete_outbound_edges_by_type(&q)? {
+            return Ok(count);
+        }
+
         let deletable_edges: Vec<models::EdgeKey> = datastore
-            .get_edge_values_by_query(q)?
+            .get_edge_values_by_query(&q)?
             .into_iter()
-            .map(|(k, _)| k)
+            .map(|(k, _, _)| k)
             .collect();
-        datastore.delete_edges(deletable_edges);
-        Ok(())
+        Ok(datastore.delete_edges(deletable_edges))
     }
 
-    fn get_edge_count(
-        &self,
-        id: Uuid,
-        type_filter: Option<&models::Type>,
-        direction: models::EdgeDirection,
-    ) -> Result<u64> {
-        let datastore = self.datastore.read().unwrap();
+    fn set_vertex_properties(&self, q: &VertexQuery, name: &str, value: &JsonValue) -> Result<u64> {
+        let mut datastore = self.datastore.write()?;
+        datastore.validate_property_value(name, value)?;
 
-        if direction == models::EdgeDirection::Outbound {
-            let lower_bound = match type_filter {
-                Some(type_filter) => models::EdgeKey::new(id, type_filter.clone(), Uuid::default()),
-                None => {
-                    let empty_type = models::Type::default();
-                    models::EdgeKey::new(id, empty_type, Uuid::default())
-                }
-            };
-            let range = datastore.edges.range(lower_bound..);
+        let vertex_values = datastore.get_vertex_values_by_query(&datastore.normalize_vertex_query(q))?;
+        let count = vertex_values.len() as u64;
+        let update_datetime = datastore.now();
 
-            let range = range.take_while(|&(k, _)| {
-                if let Some(type_filter) = type_filter {
-                    k.outbound_id == id && &k.t == type_filter
-                } else {
-                    k.outbound_id == id
-                }
+        for (id, _) in vertex_values {
+            datastore.set_vertex_property(id, name.to_string(), value.clone(), update_datetime);
+            datastore.emit(|| {
+                ChangeEvent::VertexPropertySet(models::VertexProperty::new(id, name.to_string(), value.clone()))
             });
+        }
+
+        Ok(count)
+    }
 
-            Ok(range.count() as u64)
+    fn set_vertex_property_where(
+        &self,
+        filter: &models::PropertyCondition,
+        name: &str,
+        value: &JsonValue,
+    ) -> Result<u64> {
+        let mut datastore = self.datastore.write()?;
+        datastore.validate_property_value(name, value)?;
+
+        let ids: Vec<Uuid> = if filter.operator == models::ComparisonOperator::Equal {
+            datastore.get_vertex_ids_by_property_value(&filter.name, &filter.value)
         } else {
-            let range = datastore.edges.iter().filter(|&(k, _)| {
-                if let Some(type_filter) = type_filter {
-                    k.inbound_id == id && &k.t == type_filter
-                } else {
-                    k.inbound_id == id
-                }
-            });
+            datastore
+                .vertex_properties
+                .iter()
+                .filter(|&((_, property_name), (property_value, _))| {
+                    property_name == &filter.name && filter.matches(property_value)
+                })
+                .map(|(&(id, _), _)| id)
+                .collect()
+        };
+
+        let update_datetime = datastore.now();
 
-            Ok(range.count() as u64)
+        for id in &ids {
+            datastore.set_vertex_property(*id, name.to_string(), value.clone(), update_datetime);
+            datastore.emit(|| {
+                ChangeEvent::VertexPropertySet(models::VertexProperty::new(*id, name.to_string(), value.clone()))
+            });
         }
+
+        Ok(ids.len() as u64)
     }
 
-    fn get_vertex_properties(&self, q: &VertexQuery, name: &str) -> Result<Vec<models::VertexProperty>> {
-        let mut result = Vec::new();
-        let datastore = self.datastore.read().unwrap();
-        let vertex_values = datastore.get_vertex_values_by_query(q)?;
+    fn patch_vertex_property(&self, q: &VertexQuery, name: &str, patch: &JsonValue) -> Result<()> {
+        let mut datastore = self.datastore.write()?;
+
+        let vertex_values = datastore.get_vertex_values_by_query(&datastore.normalize_vertex_query(q))?;
+        let mut patched_values = Vec::with_capacity(vertex_values.len());
 
         for (id, _) in vertex_values {
-            let property_value = datastore.vertex_properties.get(&(id, name.to_string()));
+            let mut value = datastore
+                .vertex_properties
+                .get(&(id, name.to_string()))
+                .map(|(value, _)| value.clone())
+                .unwrap_or(JsonValue::Null);
+            util::json_merge_patch(&mut value, patch);
+            datastore.validate_property_value(name, &value)?;
+            patched_values.push((id, value));
+        }
 
-            if let Some(property_value) = property_value {
-                result.push(models::VertexProperty::new(id, property_value.clone()));
-            }
+        let update_datetime = datastore.now();
+
+        for (id, value) in patched_values {
+            datastore.set_vertex_property(id, name.to_string(), value.clone(), update_datetime);
+            datastore.emit(|| ChangeEvent::VertexPropertySet(models::VertexProperty::new(id, name.to_string(), value)));
         }
 
-        Ok(result)
+        Ok(())
     }
 
-    fn set_vertex_properties(&self, q: &VertexQuery, name: &str, value: &JsonValue) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
+    fn delete_vertex_properties(&self, q: &VertexQuery, name: &str) -> Result<()> {
+        let mut datastore = self.datastore.write()?;
 
-        let vertex_values = datastore.get_vertex_values_by_query(q)?;
+        let vertex_values = datastore.get_vertex_values_by_query(&datastore.normalize_vertex_query(q))?;
 
         for (id, _) in vertex_values {
-            datastore
-                .vertex_properties
-                .insert((id, name.to_string()), value.clone());
+            if datastore.remove_vertex_property(id, name) {
+                datastore.emit(|| ChangeEvent::VertexPropertyDeleted(id, name.to_string()));
+            }
         }
 
         Ok(())
     }
 
-    fn delete_vertex_properties(&self, q: &VertexQuery, name: &str) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
+    fn delete_all_vertex_properties(&self, q: &VertexQuery) -> Result<()> {
+        let mut datastore = self.datastore.write()?;
 
-        let vertex_values = datastore.get_vertex_values_by_query(q)?;
+        let vertex_values = datastore.get_vertex_values_by_query(&datastore.normalize_vertex_query(q))?;
 
         for (id, _) in vertex_values {
-            datastore.vertex_properties.remove(&(id, name.to_string()));
+            datastore.remove_all_vertex_properties(id);
+            datastore.emit(|| ChangeEvent::AllVertexPropertiesDeleted(id));
         }
 
         Ok(())
     }
 
-    fn get_edge_properties(&self, q: &EdgeQuery, name: &str) -> Result<Vec<models::EdgeProperty>> {
-        let mut result = Vec::new();
-        let datastore = self.datastore.read().unwrap();
-        let edge_values = datastore.get_edge_values_by_query(q)?;
+    fn set_edge_properties(&self, q: &EdgeQuery, name: &str, value: &JsonValue) -> Result<()> {
+        let mut datastore = self.datastore.write()?;
+        datastore.validate_property_value(name, value)?;
 
-        for (key, _) in edge_values {
-            let property_value = datastore.edge_properties.get(&(key.clone(), name.to_string()));
+        let edge_values = datastore.get_edge_values_by_query(&datastore.normalize_edge_query(q))?;
+        let update_datetime = datastore.now();
 
-            if let Some(property_value) = property_value {
-                result.push(models::EdgeProperty::new(key, property_value.clone()));
-            }
+        for (key, _, _) in edge_values {
+            let key = datastore.resolve_edge_key(&key);
+            datastore
+                .edge_properties
+                .insert((key.clone(), name.to_string()), (value.clone(), update_datetime));
+            datastore
+                .emit(|| ChangeEvent::EdgePropertySet(models::EdgeProperty::new(key, name.to_string(), value.clone())));
         }
 
-        Ok(result)
+        Ok(())
     }
 
-    fn set_edge_properties(&self, q: &EdgeQuery, name: &str, value: &JsonValue) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
+    fn patch_edge_property(&self, q: &EdgeQuery, name: &str, patch: &JsonValue) -> Result<()> {
+        let mut datastore = self.datastore.write()?;
+
+        let edge_values = datastore.get_edge_values_by_query(&datastore.normalize_edge_query(q))?;
+        let mut patched_values = Vec::with_capacity(edge_values.len());
+
+        for (key, _, _) in edge_values {
+            let key = datastore.resolve_edge_key(&key);
+            let mut value = datastore
+                .edge_properties
+                .get(&(key.clone(), name.to_string()))
+                .map(|(value, _)| value.clone())
+                .unwrap_or(JsonValue::Null);
+            util::json_merge_patch(&mut value, patch);
+            datastore.validate_property_value(name, &value)?;
+            patched_values.push((key, value));
+        }
 
-        let edge_values = datastore.get_edge_values_by_query(q)?;
+        let update_datetime = datastore.now();
 
-        for (key, _) in edge_values {
-            datastore.edge_properties.insert((key, name.to_string()), value.clone());
+        for (key, value) in patched_values {
+            datastore
+                .edge_properties
+                .insert((key.clone(), name.to_string()), (value.clone(), update_datetime));
+            datastore.emit(|| ChangeEvent::EdgePropertySet(models::EdgeProperty::new(key, name.to_string(), value)));
         }
 
         Ok(())
     }
 
     fn delete_edge_properties(&self, q: &EdgeQuery, name: &str) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
+        let mut datastore = self.datastore.write()?;
+
+        let edge_values = datastore.get_edge_values_by_query(&datastore.normalize_edge_query(q))?;
+
+        for (key, _, _) in edge_values {
+            let key = datastore.resolve_edge_key(&key);
+
+            if datastore
+                .edge_properties
+                .remove(&(key.clone(), name.to_string()))
+                .is_some()
+            {
+                datastore.emit(|| ChangeEvent::EdgePropertyDeleted(key, name.to_string()));
+            }
+        }
+
+        Ok(())
+    }
 
-        let edge_values = datastore.get_edge_values_by_query(q)?;
+    fn delete_all_edge_properties(&self, q: &EdgeQuery) -> Result<()> {
+        let mut datastore = self.datastore.write()?;
 
-        for (key, _) in edge_values {
-            datastore.edge_properties.remove(&(key, name.to_string()));
+        let edge_values = datastore.get_edge_values_by_query(&datastore.normalize_edge_query(q))?;
+
+        for (key, _, _) in edge_values {
+            let key = datastore.resolve_edge_key(&key);
+            datastore.remove_all_edge_properties(&key);
+            datastore.emit(|| ChangeEvent::AllEdgePropertiesDeleted(key));
         }
 
         Ok(())
     }
 }
+
+/// A transaction for reading from in-memory-only datastores, which is
+/// created via `MemoryDatastore::read_transaction`, or via
+/// `MemoryDatastore::snapshot` to read from a snapshot. It only implements
+/// `ReadTransaction`, so it can be handed to code that mustn't change the
+/// datastore.
+#[derive(Debug)]
+pub struct MemoryReadOnlyTransaction(MemoryTransaction);
+
+impl MemoryReadOnlyTransaction {
+    /// Gets the most recently updated edges touching a vertex, newest
+    /// first. See `MemoryTransaction::get_vertex_timeline`.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    /// * `limit` - The maximum number of edges to return.
+    pub fn get_vertex_timeline(&self, id: Uuid, limit: u32) -> Result<Vec<models::Edge>> {
+        self.0.get_vertex_timeline(id, limit)
+    }
+
+    /// Gets the edges whose update datetime predates the creation of either
+    /// of their vertices. See `MemoryTransaction::get_inconsistent_edges`.
+    pub fn get_inconsistent_edges(&self) -> Result<Vec<models::EdgeKey>> {
+        self.0.get_inconsistent_edges()
+    }
+}
+
+impl ReadTransaction for MemoryReadOnlyTransaction {
+    fn get_vertices(&self, q: &VertexQuery) -> Result<Vec<models::Vertex>> {
+        self.0.get_vertices(q)
+    }
+
+    fn get_vertices_iter(&self, q: &VertexQuery) -> Result<Box<dyn Iterator<Item = Result<models::Vertex>>>> {
+        self.0.get_vertices_iter(q)
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        self.0.get_vertex_count()
+    }
+
+    fn estimate_vertex_count(&self, q: &VertexQuery) -> Result<u64> {
+        self.0.estimate_vertex_count(q)
+    }
+
+    fn get_vertex_types(&self) -> Result<Vec<models::Type>> {
+        self.0.get_vertex_types()
+    }
+
+    fn count_vertices_by_type(&self) -> Result<BTreeMap<models::Type, u64>> {
+        self.0.count_vertices_by_type()
+    }
+
+    fn vertex_exists(&self, id: Uuid) -> Result<bool> {
+        self.0.vertex_exists(id)
+    }
+
+    fn get_edges(&self, q: &EdgeQuery) -> Result<Vec<models::Edge>> {
+        self.0.get_edges(q)
+    }
+
+    fn edge_exists(&self, key: &models::EdgeKey) -> Result<bool> {
+        self.0.edge_exists(key)
+    }
+
+    fn get_induced_edges(&self, ids: &[Uuid]) -> Result<Vec<models::Edge>> {
+        self.0.get_induced_edges(ids)
+    }
+
+    fn get_edge_count(
+        &self,
+        id: Uuid,
+        type_filter: Option<&models::Type>,
+        direction: models::EdgeDirection,
+    ) -> Result<u64> {
+        self.0.get_edge_count(id, type_filter, direction)
+    }
+
+    fn get_edge_counts(
+        &self,
+        ids: &[Uuid],
+        type_filter: Option<&models::Type>,
+        direction: models::EdgeDirection,
+    ) -> Result<Vec<(Uuid, u64)>> {
+        self.0.get_edge_counts(ids, type_filter, direction)
+    }
+
+    fn get_vertex_degree(&self, id: Uuid, type_filter: Option<&models::Type>) -> Result<(u64, u64)> {
+        self.0.get_vertex_degree(id, type_filter)
+    }
+
+    fn get_vertex_properties(&self, q: &VertexQuery, name: &str) -> Result<Vec<models::VertexProperty>> {
+        self.0.get_vertex_properties(q, name)
+    }
+
+    fn get_vertex_properties_multi(&self, q: &VertexQuery, names: &[String]) -> Result<Vec<models::VertexProperty>> {
+        self.0.get_vertex_properties_multi(q, names)
+    }
+
+    fn get_vertex_properties_bounded(
+        &self,
+        q: &VertexQuery,
+        names: &[String],
+        max_bytes: u64,
+    ) -> Result<(Vec<models::VertexProperty>, bool)> {
+        self.0.get_vertex_properties_bounded(q, names, max_bytes)
+    }
+
+    fn get_all_vertex_properties_bounded(
+        &self,
+        q: &VertexQuery,
+        max_bytes: u64,
+    ) -> Result<(Vec<models::VertexProperty>, bool)> {
+        self.0.get_all_vertex_properties_bounded(q, max_bytes)
+    }
+
+    fn get_vertex_property_names(&self, id: Uuid) -> Result<Vec<String>> {
+        self.0.get_vertex_property_names(id)
+    }
+
+    fn get_edge_properties(&self, q: &EdgeQuery, name: &str) -> Result<Vec<models::EdgeProperty>> {
+        self.0.get_edge_properties(q, name)
+    }
+
+    fn get_edge_property_names(&self, key: &models::EdgeKey) -> Result<Vec<String>> {
+        self.0.get_edge_property_names(key)
+    }
+
+    fn aggregate_edge_property(&self, q: &EdgeQuery, name: &str, op: models::AggregateOp) -> Result<Option<f64>> {
+        self.0.aggregate_edge_property(q, name, op)
+    }
+
+    fn get_full_record(&self, id: Uuid, edge_limit: u32) -> Result<Option<models::FullRecord>> {
+        self.0.get_full_record(id, edge_limit)
+    }
+
+    fn get_graph_stats(&self) -> Result<models::GraphStats> {
+        self.0.get_graph_stats()
+    }
+}
@@ -1,15 +1,30 @@
 //! The in-memory-only datastore implementation. This is the simplest and
 //! generally fastest implementation, but it has these drawbacks:
 //!
-//! * Data is not persisted.
+//! * Data is only persisted when it's explicitly saved, with
+//!   `MemoryDatastore::save_to_path`.
 //! * Transaction changes cannot be rolled back on error.
 //! * Locking is coarse-grained; only one thread can write to the datastore at
 //!   a time. Consequently, this may actually perform worse on highly
-//!   concurrent write-heavy workloads.
+//!   concurrent write-heavy workloads. `ShardedMemoryDatastore` partitions
+//!   the data across several locks instead, at the cost of some features.
 
+mod clock;
 mod datastore;
+mod sharded;
 
-pub use self::datastore::{MemoryDatastore, MemoryTransaction};
+#[cfg(feature = "test-suite")]
+mod tests;
+
+pub use self::clock::{Clock, MockClock, SystemClock};
+pub use self::datastore::{
+    ChangeEvent, IntegrityReport, MemoryDatastore, MemoryEdgePager, MemoryReadOnlyTransaction, MemoryTransaction,
+    SnapshotCompression,
+};
+pub use self::sharded::{ShardedMemoryDatastore, ShardedMemoryTransaction};
+
+#[cfg(feature = "lock-stats")]
+pub use self::datastore::DatastoreStats;
 
 #[cfg(feature = "bench-suite")]
 full_bench_impl!(MemoryDatastore::default());
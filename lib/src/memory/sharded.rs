@@ -0,0 +1,980 @@
+use super::super::{Datastore, EdgeQuery, ReadTransaction, VertexQuery, WriteTransaction};
+use chrono::offset::Utc;
+use chrono::DateTime;
+use errors::{Error, ErrorKind, Result};
+use models;
+use serde_json::Value as JsonValue;
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, Bound, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use util;
+use uuid::Uuid;
+
+// The number of shards used by `ShardedMemoryDatastore::default`.
+const DEFAULT_SHARD_COUNT: usize = 16;
+
+// A single partition of the datastore. A shard owns the vertices whose ids
+// hash to it, along with their properties, and the edges whose outbound
+// vertex it owns, along with their properties. So that inbound edges can be
+// found without scanning every shard, each edge is also recorded in the
+// shard that owns its inbound vertex, keyed by the inbound vertex id.
+#[derive(Debug, Default)]
+struct Shard {
+    edge_properties: BTreeMap<(models::EdgeKey, String), JsonValue>,
+    edges: BTreeMap<models::EdgeKey, DateTime<Utc>>,
+    inbound_edges: BTreeSet<(Uuid, models::EdgeKey)>,
+    vertex_properties: BTreeMap<(Uuid, String), JsonValue>,
+    // Each vertex's type, and when it was created
+    vertices: BTreeMap<Uuid, (models::Type, DateTime<Utc>)>,
+}
+
+fn shard_index(id: Uuid, shard_count: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    id.hash(&mut hasher);
+    (hasher.finish() % shard_count as u64) as usize
+}
+
+// A shard's lock is poisoned if a thread panicked while holding its write
+// lock. Rather than panicking too, operations that need it fail.
+fn lock_poisoned<T>(_: PoisonError<T>) -> Error {
+    ErrorKind::LockPoisoned.into()
+}
+
+fn min_edge_key() -> models::EdgeKey {
+    models::EdgeKey::new(Uuid::default(), models::Type::default(), Uuid::default())
+}
+
+fn get_vertex_values_by_query<S: Deref<Target = Shard>>(
+    shards: &[S],
+    q: &VertexQuery,
+) -> Result<Vec<(Uuid, models::Type)>> {
+    let get_vertex = |id: Uuid| {
+        shards[shard_index(id, shards.len())]
+            .vertices
+            .get(&id)
+            .map(|(t, _)| (id, t.clone()))
+    };
+
+    q.validate()?;
+
+    match *q {
+        VertexQuery::All {
+            start_id,
+            end_id,
+            high_filter,
+            low_filter,
+            limit,
+        } => {
+            let lower_bound = start_id.unwrap_or_default();
+            let upper_bound = end_id.map_or(Bound::Unbounded, Bound::Excluded);
+
+            // `BTreeMap::range` panics if the start of a range is after its
+            // end
+            if end_id.is_some_and(|end_id| lower_bound >= end_id) {
+                return Ok(Vec::new());
+            }
+
+            // Every shard holds an ordered subset of the vertices, so the
+            // first `limit` vertices overall are among the first `limit`
+            // vertices of each shard
+            let mut results: Vec<(Uuid, models::Type)> = shards
+                .iter()
+                .flat_map(|shard| {
+                    shard
+                        .vertices
+                        .range((Bound::Included(lower_bound), upper_bound))
+                        .filter(move |&(_, &(_, created_datetime))| {
+                            high_filter.is_none_or(|high_filter| created_datetime <= high_filter)
+                                && low_filter.is_none_or(|low_filter| created_datetime >= low_filter)
+                        })
+                        .take(limit as usize)
+                        .map(|(k, (t, _))| (*k, t.clone()))
+                })
+                .collect();
+            results.sort_by_key(|&(id, _)| id);
+            results.truncate(limit as usize);
+            Ok(results)
+        }
+        VertexQuery::Vertices { ref ids } => Ok(ids.iter().filter_map(|id| get_vertex(*id)).collect()),
+        VertexQuery::Types { ref types, limit } => {
+            let mut results: Vec<(Uuid, models::Type)> = shards
+                .iter()
+                .flat_map(|shard| {
+                    shard
+                        .vertices
+                        .iter()
+                        .filter(|&(_, (t, _))| types.contains(t))
+                        .take(limit as usize)
+                        .map(|(k, (t, _))| (*k, t.clone()))
+                })
+                .collect();
+            results.sort_by_key(|&(id, _)| id);
+            results.truncate(limit as usize);
+            Ok(results)
+        }
+        VertexQuery::PropertyContains {
+            ref name,
+            ref substring,
+            limit,
+        } => {
+            let mut results: Vec<(Uuid, models::Type)> = shards
+                .iter()
+                .flat_map(|shard| {
+                    shard
+                        .vertices
+                        .iter()
+                        .filter(move |&(id, _)| {
+                            shard
+                                .vertex_properties
+                                .get(&(*id, name.clone()))
+                                .is_some_and(|value| util::json_string_contains(value, substring))
+                        })
+                        .take(limit as usize)
+                        .map(|(k, (t, _))| (*k, t.clone()))
+                })
+                .collect();
+            results.sort_by_key(|&(id, _)| id);
+            results.truncate(limit as usize);
+            Ok(results)
+        }
+        VertexQuery::Pipe {
+            ref edge_query,
+            converter,
+            offset,
+            limit,
+        } => {
+            let edge_values = get_edge_values_by_query(shards, edge_query)?;
+
+            Ok(edge_values
+                .into_iter()
+                .skip(offset as usize)
+                .flat_map(|(key, _)| match converter {
+                    models::EdgeDirection::Outbound => vec![key.outbound_id],
+                    models::EdgeDirection::Inbound => vec![key.inbound_id],
+                    models::EdgeDirection::Both if key.outbound_id == key.inbound_id => vec![key.outbound_id],
+                    models::EdgeDirection::Both => vec![key.outbound_id, key.inbound_id],
+                })
+                .take(limit as usize)
+                .filter_map(get_vertex)
+                .collect())
+        }
+        VertexQuery::Traverse {
+            ref start,
+            direction,
+            ref type_filter,
+            depth,
+            limit,
+        } => {
+            let mut results = Vec::new();
+
+            if limit == 0 {
+                return Ok(results);
+            }
+
+            let mut visited = HashSet::new();
+            let mut frontier = Vec::new();
+
+            for (id, _) in get_vertex_values_by_query(shards, start)? {
+                if visited.insert(id) {
+                    frontier.push(id);
+                }
+            }
+
+            for _ in 0..depth {
+                if frontier.is_empty() {
+                    break;
+                }
+
+                let mut next_frontier = Vec::new();
+
+                for id in &frontier {
+                    for key in get_edge_keys_for_vertex(shards, *id, direction, type_filter.as_ref()) {
+                        let neighbor_id = if key.outbound_id == *id {
+                            key.inbound_id
+                        } else {
+                            key.outbound_id
+                        };
+
+                        if !visited.insert(neighbor_id) {
+                            continue;
+                        }
+
+                        if let Some(value) = get_vertex(neighbor_id) {
+                            results.push(value);
+
+                            if results.len() == limit as usize {
+                                return Ok(results);
+                            }
+                        }
+
+                        next_frontier.push(neighbor_id);
+                    }
+                }
+
+                frontier = next_frontier;
+            }
+
+            Ok(results)
+        }
+        VertexQuery::DegreeAtLeast {
+            ref start,
+            direction,
+            ref type_filter,
+            min,
+            limit,
+        } => {
+            let candidates = match *start {
+                Some(ref start) => get_vertex_values_by_query(shards, start)?,
+                None => {
+                    let mut candidates: Vec<(Uuid, models::Type)> = shards
+                        .iter()
+                        .flat_map(|shard| shard.vertices.iter().map(|(k, (t, _))| (*k, t.clone())))
+                        .collect();
+                    candidates.sort_by_key(|&(id, _)| id);
+                    candidates
+                }
+            };
+
+            Ok(candidates
+                .into_iter()
+                .filter(|&(id, _)| {
+                    get_edge_keys_for_vertex(shards, id, direction, type_filter.as_ref()).len() as u64 >= min
+                })
+                .take(limit as usize)
+                .collect())
+        }
+    }
+}
+
+// Gets the keys of the edges going in the given direction from a vertex,
+// in key order. In both directions, the outbound keys come first, followed
+// by the inbound keys that aren't self-loops, so no key is returned twice.
+fn get_edge_keys_for_vertex<S: Deref<Target = Shard>>(
+    shards: &[S],
+    id: Uuid,
+    direction: models::EdgeDirection,
+    type_filter: Option<&models::Type>,
+) -> Vec<models::EdgeKey> {
+    let shard = &shards[shard_index(id, shards.len())];
+
+    match direction {
+        models::EdgeDirection::Outbound => {
+            let lower_bound = models::EdgeKey::new(id, type_filter.cloned().unwrap_or_default(), Uuid::default());
+
+            shard
+                .edges
+                .range(lower_bound..)
+                .map(|(key, _)| key)
+                .take_while(|key| key.outbound_id == id && type_filter.is_none_or(|t| &key.t == t))
+                .cloned()
+                .collect()
+        }
+        models::EdgeDirection::Inbound => shard
+            .inbound_edges
+            .range((id, min_edge_key())..)
+            .take_while(|&&(inbound_id, _)| inbound_id == id)
+            .map(|(_, key)| key)
+            .filter(|key| type_filter.is_none_or(|t| &key.t == t))
+            .cloned()
+            .collect(),
+        models::EdgeDirection::Both => {
+            let mut keys = get_edge_keys_for_vertex(shards, id, models::EdgeDirection::Outbound, type_filter);
+            keys.extend(
+                get_edge_keys_for_vertex(shards, id, models::EdgeDirection::Inbound, type_filter)
+                    .into_iter()
+                    .filter(|key| key.outbound_id != id),
+            );
+            keys
+        }
+    }
+}
+
+fn get_edge_values_by_query<S: Deref<Target = Shard>>(
+    shards: &[S],
+    q: &EdgeQuery,
+) -> Result<Vec<(models::EdgeKey, DateTime<Utc>)>> {
+    q.validate()?;
+
+    let get_edge = |key: models::EdgeKey| {
+        let update_datetime = shards[shard_index(key.outbound_id, shards.len())]
+            .edges
+            .get(&key)
+            .cloned();
+        update_datetime.map(|update_datetime| (key, update_datetime))
+    };
+
+    match *q {
+        EdgeQuery::Edges { ref keys } => Ok(keys.iter().cloned().filter_map(get_edge).collect()),
+        EdgeQuery::All {
+            ref type_filter,
+            high_filter,
+            low_filter,
+            limit,
+        } => {
+            // Each shard's edges are sorted, but only within that shard, so
+            // merge them before applying the limit
+            let mut results: Vec<(models::EdgeKey, DateTime<Utc>)> = shards
+                .iter()
+                .flat_map(|shard| shard.edges.iter())
+                .filter(|&(key, &update_datetime)| {
+                    type_filter.as_ref().is_none_or(|type_filter| &key.t == type_filter)
+                        && high_filter.is_none_or(|high_filter| update_datetime <= high_filter)
+                        && low_filter.is_none_or(|low_filter| update_datetime >= low_filter)
+                })
+                .map(|(key, &update_datetime)| (key.clone(), update_datetime))
+                .collect();
+            results.sort_by(|first, second| first.0.cmp(&second.0));
+            results.truncate(limit as usize);
+            Ok(results)
+        }
+        EdgeQuery::Pipe {
+            ref vertex_query,
+            converter,
+            ref type_filter,
+            ref type_filters,
+            high_filter,
+            low_filter,
+            after_inbound_id,
+            offset,
+            limit,
+        } => {
+            let vertex_values = get_vertex_values_by_query(shards, vertex_query)?;
+            let mut results = Vec::new();
+            let mut skipped = 0;
+
+            if limit == 0 {
+                return Ok(results);
+            }
+
+            // When piping in both directions, an edge between two of the
+            // vertices would be found from each end
+            let mut seen_keys = HashSet::new();
+
+            for (id, _) in vertex_values {
+                for key in get_edge_keys_for_vertex(shards, id, converter, type_filter.as_ref()) {
+                    if !type_filters.is_empty() && !type_filters.contains(&key.t) {
+                        continue;
+                    }
+
+                    if after_inbound_id.is_some_and(|after_inbound_id| key.inbound_id <= after_inbound_id) {
+                        continue;
+                    }
+
+                    if converter == models::EdgeDirection::Both && !seen_keys.insert(key.clone()) {
+                        continue;
+                    }
+
+                    let (key, update_datetime) = match get_edge(key) {
+                        Some(value) => value,
+                        None => continue,
+                    };
+
+                    if high_filter.is_some_and(|high_filter| update_datetime > high_filter) {
+                        continue;
+                    }
+
+                    if low_filter.is_some_and(|low_filter| update_datetime < low_filter) {
+                        continue;
+                    }
+
+                    if skipped < offset {
+                        skipped += 1;
+                        continue;
+                    }
+
+                    results.push((key, update_datetime));
+
+                    if results.len() == limit as usize {
+                        return Ok(results);
+                    }
+                }
+            }
+
+            Ok(results)
+        }
+    }
+}
+
+fn get_edge_records<S: Deref<Target = Shard>>(shards: &[S], q: &EdgeQuery) -> Result<Vec<models::EdgeRecord>> {
+    let edge_values = get_edge_values_by_query(shards, q)?;
+
+    Ok(edge_values
+        .into_iter()
+        .map(|(key, update_datetime)| {
+            let properties = shards[shard_index(key.outbound_id, shards.len())]
+                .edge_properties
+                .range((key.clone(), "".to_string())..)
+                .take_while(|&((property_edge_key, _), _)| property_edge_key == &key)
+                .map(|((_, name), value)| models::NamedProperty::new(name.clone(), value.clone()))
+                .collect();
+
+            models::EdgeRecord::new(models::Edge::new(key, update_datetime), properties)
+        })
+        .collect())
+}
+
+// Removes all of the properties of a vertex from the shard that owns it.
+fn remove_all_vertex_properties(shard: &mut Shard, id: Uuid) {
+    let deletable_vertex_properties: Vec<(Uuid, String)> = shard
+        .vertex_properties
+        .range((id, "".to_string())..)
+        .take_while(|&(&(property_vertex_id, _), _)| property_vertex_id == id)
+        .map(|(property_key, _)| property_key.clone())
+        .collect();
+
+    for property_key in deletable_vertex_properties {
+        shard.vertex_properties.remove(&property_key);
+    }
+}
+
+// Removes all of the properties of an edge from the shard that owns its
+// outbound vertex.
+fn remove_all_edge_properties(shard: &mut Shard, edge_key: &models::EdgeKey) {
+    let deletable_edge_properties: Vec<(models::EdgeKey, String)> = shard
+        .edge_properties
+        .range((edge_key.clone(), "".to_string())..)
+        .take_while(|&((property_edge_key, _), _)| property_edge_key == edge_key)
+        .map(|(property_key, _)| property_key.clone())
+        .collect();
+
+    for property_key in deletable_edge_properties {
+        shard.edge_properties.remove(&property_key);
+    }
+}
+
+fn delete_edges<S: DerefMut<Target = Shard>>(shards: &mut [S], edges: Vec<models::EdgeKey>) -> u64 {
+    let shard_count = shards.len();
+    let mut count = 0;
+
+    for edge_key in edges {
+        let outbound_shard = &mut shards[shard_index(edge_key.outbound_id, shard_count)];
+
+        if outbound_shard.edges.remove(&edge_key).is_some() {
+            count += 1;
+        }
+
+        remove_all_edge_properties(outbound_shard, &edge_key);
+
+        shards[shard_index(edge_key.inbound_id, shard_count)]
+            .inbound_edges
+            .remove(&(edge_key.inbound_id, edge_key));
+    }
+
+    count
+}
+
+fn delete_vertices<S: DerefMut<Target = Shard>>(shards: &mut [S], vertices: Vec<Uuid>) -> u64 {
+    let mut count = 0;
+
+    for vertex_id in vertices {
+        let mut deletable_edges = get_edge_keys_for_vertex(shards, vertex_id, models::EdgeDirection::Outbound, None);
+        deletable_edges.extend(get_edge_keys_for_vertex(
+            shards,
+            vertex_id,
+            models::EdgeDirection::Inbound,
+            None,
+        ));
+        delete_edges(shards, deletable_edges);
+
+        let shard_count = shards.len();
+        let shard = &mut shards[shard_index(vertex_id, shard_count)];
+
+        if shard.vertices.remove(&vertex_id).is_some() {
+            count += 1;
+        }
+
+        remove_all_vertex_properties(shard, vertex_id);
+    }
+
+    count
+}
+
+/// An in-memory datastore that partitions vertices across several
+/// independently locked shards by a hash of their id, so that writes to
+/// vertices in different shards don't block each other.
+///
+/// Operations on a single vertex or edge only lock the shards that own it.
+/// Operations that run a query lock every shard, always in the same order
+/// so that concurrent transactions can't deadlock. Unlike
+/// `MemoryDatastore`, this doesn't support property types, property
+/// indexes or traversal limits.
+#[derive(Debug, Clone)]
+pub struct ShardedMemoryDatastore(Arc<Vec<RwLock<Shard>>>);
+
+impl Default for ShardedMemoryDatastore {
+    /// Creates a new sharded in-memory datastore with a default number of
+    /// shards.
+    fn default() -> ShardedMemoryDatastore {
+        Self::new(DEFAULT_SHARD_COUNT)
+    }
+}
+
+impl ShardedMemoryDatastore {
+    /// Creates a new sharded in-memory datastore.
+    ///
+    /// # Arguments
+    /// * `shard_count` - The number of shards to partition vertices across.
+    ///
+    /// # Panics
+    /// Panics if `shard_count` is zero.
+    pub fn new(shard_count: usize) -> ShardedMemoryDatastore {
+        assert!(shard_count > 0, "a sharded datastore needs at least one shard");
+        let shards = (0..shard_count).map(|_| RwLock::new(Shard::default())).collect();
+        ShardedMemoryDatastore(Arc::new(shards))
+    }
+
+    /// Gets the number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl Datastore for ShardedMemoryDatastore {
+    type Trans = ShardedMemoryTransaction;
+
+    fn transaction(&self) -> Result<Self::Trans> {
+        Ok(ShardedMemoryTransaction {
+            shards: Arc::clone(&self.0),
+        })
+    }
+
+    // Creating a transaction never fails, so this checks that none of the
+    // shards' locks are poisoned instead.
+    fn health_check(&self) -> Result<()> {
+        for shard in self.0.iter() {
+            drop(shard.read().map_err(lock_poisoned)?);
+        }
+
+        Ok(())
+    }
+
+    fn bulk_insert<I>(&self, items: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        let mut shards: Vec<RwLockWriteGuard<Shard>> = self
+            .0
+            .iter()
+            .map(|shard| shard.write().map_err(lock_poisoned))
+            .collect::<Result<_>>()?;
+        let shard_count = shards.len();
+
+        for item in items {
+            match item {
+                models::BulkInsertItem::Vertex(vertex) => {
+                    // Vertices that already exist keep their creation datetime
+                    let vertices = &mut shards[shard_index(vertex.id, shard_count)].vertices;
+                    let created_datetime = vertices
+                        .get(&vertex.id)
+                        .map_or_else(Utc::now, |&(_, created_datetime)| created_datetime);
+                    vertices.insert(vertex.id, (vertex.t, created_datetime));
+                }
+                models::BulkInsertItem::Edge(edge_key) => {
+                    let outbound_index = shard_index(edge_key.outbound_id, shard_count);
+                    let inbound_index = shard_index(edge_key.inbound_id, shard_count);
+
+                    if shards[outbound_index].vertices.contains_key(&edge_key.outbound_id)
+                        && shards[inbound_index].vertices.contains_key(&edge_key.inbound_id)
+                    {
+                        shards[inbound_index]
+                            .inbound_edges
+                            .insert((edge_key.inbound_id, edge_key.clone()));
+                        shards[outbound_index].edges.insert(edge_key, Utc::now());
+                    }
+                }
+                models::BulkInsertItem::VertexProperty(id, name, value) => {
+                    let shard = &mut shards[shard_index(id, shard_count)];
+
+                    if shard.vertices.contains_key(&id) {
+                        shard.vertex_properties.insert((id, name), value);
+                    }
+                }
+                models::BulkInsertItem::EdgeProperty(edge_key, name, value) => {
+                    let shard = &mut shards[shard_index(edge_key.outbound_id, shard_count)];
+
+                    if shard.edges.contains_key(&edge_key) {
+                        shard.edge_properties.insert((edge_key, name), value);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A transaction for manipulating sharded in-memory datastores.
+#[derive(Debug)]
+pub struct ShardedMemoryTransaction {
+    shards: Arc<Vec<RwLock<Shard>>>,
+}
+
+impl ShardedMemoryTransaction {
+    fn shard_index(&self, id: Uuid) -> usize {
+        shard_index(id, self.shards.len())
+    }
+
+    fn read_shard<'a>(&'a self, id: Uuid) -> Result<RwLockReadGuard<'a, Shard>> {
+        self.shards[self.shard_index(id)].read().map_err(lock_poisoned)
+    }
+
+    fn read_all<'a>(&'a self) -> Result<Vec<RwLockReadGuard<'a, Shard>>> {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().map_err(lock_poisoned))
+            .collect()
+    }
+
+    fn write_all<'a>(&'a self) -> Result<Vec<RwLockWriteGuard<'a, Shard>>> {
+        self.shards
+            .iter()
+            .map(|shard| shard.write().map_err(lock_poisoned))
+            .collect()
+    }
+}
+
+impl ReadTransaction for ShardedMemoryTransaction {
+    fn get_vertices(&self, q: &VertexQuery) -> Result<Vec<models::Vertex>> {
+        let vertex_values = get_vertex_values_by_query(&self.read_all()?, q)?;
+        let iter = vertex_values
+            .into_iter()
+            .map(|(uuid, t)| models::Vertex::with_id(uuid, t));
+        Ok(iter.collect())
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        Ok(self.read_all()?.iter().map(|shard| shard.vertices.len() as u64).sum())
+    }
+
+    fn estimate_vertex_count(&self, q: &VertexQuery) -> Result<u64> {
+        Ok(cmp::min(q.max_results(), self.get_vertex_count()?))
+    }
+
+    fn vertex_exists(&self, id: Uuid) -> Result<bool> {
+        Ok(self.read_shard(id)?.vertices.contains_key(&id))
+    }
+
+    fn get_edges(&self, q: &EdgeQuery) -> Result<Vec<models::Edge>> {
+        let edge_values = get_edge_values_by_query(&self.read_all()?, q)?;
+        let iter = edge_values
+            .into_iter()
+            .map(|(key, update_datetime)| models::Edge::new(key, update_datetime));
+        Ok(iter.collect())
+    }
+
+    fn edge_exists(&self, key: &models::EdgeKey) -> Result<bool> {
+        Ok(self.read_shard(key.outbound_id)?.edges.contains_key(key))
+    }
+
+    fn get_edge_count(
+        &self,
+        id: Uuid,
+        type_filter: Option<&models::Type>,
+        direction: models::EdgeDirection,
+    ) -> Result<u64> {
+        // Both the outbound and inbound edges of a vertex are recorded in
+        // the shard that owns it, so that's the only one we need to lock
+        let shards = [self.read_shard(id)?];
+        Ok(get_edge_keys_for_vertex(&shards[..], id, direction, type_filter).len() as u64)
+    }
+
+    fn get_vertex_properties(&self, q: &VertexQuery, name: &str) -> Result<Vec<models::VertexProperty>> {
+        let shards = self.read_all()?;
+        let vertex_values = get_vertex_values_by_query(&shards, q)?;
+
+        Ok(vertex_values
+            .into_iter()
+            .filter_map(|(id, _)| {
+                shards[self.shard_index(id)]
+                    .vertex_properties
+                    .get(&(id, name.to_string()))
+                    .map(|value| models::VertexProperty::new(id, name.to_string(), value.clone()))
+            })
+            .collect())
+    }
+
+    fn get_edge_properties(&self, q: &EdgeQuery, name: &str) -> Result<Vec<models::EdgeProperty>> {
+        let shards = self.read_all()?;
+        let edge_values = get_edge_values_by_query(&shards, q)?;
+
+        Ok(edge_values
+            .into_iter()
+            .filter_map(|(key, _)| {
+                let value = shards[self.shard_index(key.outbound_id)]
+                    .edge_properties
+                    .get(&(key.clone(), name.to_string()))
+                    .cloned();
+                value.map(|value| models::EdgeProperty::new(key, name.to_string(), value))
+            })
+            .collect())
+    }
+
+    fn get_full_record(&self, id: Uuid, edge_limit: u32) -> Result<Option<models::FullRecord>> {
+        let shards = self.read_all()?;
+        let shard = &shards[self.shard_index(id)];
+
+        let t = match shard.vertices.get(&id) {
+            Some((t, _)) => t.clone(),
+            None => return Ok(None),
+        };
+
+        let properties = shard
+            .vertex_properties
+            .range((id, "".to_string())..)
+            .take_while(|&(&(property_vertex_id, _), _)| property_vertex_id == id)
+            .map(|((_, name), value)| models::NamedProperty::new(name.clone(), value.clone()))
+            .collect();
+
+        let q = VertexQuery::Vertices { ids: vec![id] };
+        let outbound_edges = get_edge_records(&shards, &q.clone().outbound_edges(None, None, None, edge_limit))?;
+        let inbound_edges = get_edge_records(&shards, &q.inbound_edges(None, None, None, edge_limit))?;
+
+        Ok(Some(models::FullRecord::new(
+            models::Vertex::with_id(id, t),
+            properties,
+            outbound_edges,
+            inbound_edges,
+        )))
+    }
+}
+
+impl WriteTransaction for ShardedMemoryTransaction {
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
+        let mut shard = self.shards[self.shard_index(vertex.id)]
+            .write()
+            .map_err(lock_poisoned)?;
+        let mut inserted = false;
+
+        shard.vertices.entry(vertex.id).or_insert_with(|| {
+            inserted = true;
+            (vertex.t.clone(), Utc::now())
+        });
+
+        Ok(inserted)
+    }
+
+    fn get_or_create_vertex(&self, id: Uuid, t: &models::Type) -> Result<(models::Vertex, bool)> {
+        let mut shard = self.shards[self.shard_index(id)].write().map_err(lock_poisoned)?;
+
+        if let Some((t, _)) = shard.vertices.get(&id) {
+            return Ok((models::Vertex::with_id(id, t.clone()), false));
+        }
+
+        shard.vertices.insert(id, (t.clone(), Utc::now()));
+        Ok((models::Vertex::with_id(id, t.clone()), true))
+    }
+
+    fn delete_vertices(&self, q: &VertexQuery) -> Result<u64> {
+        let mut shards = self.write_all()?;
+        let deletable_vertices = get_vertex_values_by_query(&shards, q)?
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        Ok(delete_vertices(&mut shards, deletable_vertices))
+    }
+
+    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        // Lock the two shards in index order, so that a concurrent edge
+        // going the other way can't deadlock with this one
+        let outbound_index = self.shard_index(key.outbound_id);
+        let inbound_index = self.shard_index(key.inbound_id);
+        let indexes: BTreeSet<usize> = vec![outbound_index, inbound_index].into_iter().collect();
+        let mut shards: BTreeMap<usize, RwLockWriteGuard<Shard>> = indexes
+            .into_iter()
+            .map(|index| Ok((index, self.shards[index].write().map_err(lock_poisoned)?)))
+            .collect::<Result<_>>()?;
+
+        if !shards[&outbound_index].vertices.contains_key(&key.outbound_id)
+            || !shards[&inbound_index].vertices.contains_key(&key.inbound_id)
+        {
+            return Ok(false);
+        }
+
+        shards
+            .get_mut(&outbound_index)
+            .unwrap()
+            .edges
+            .insert(key.clone(), Utc::now());
+        shards
+            .get_mut(&inbound_index)
+            .unwrap()
+            .inbound_edges
+            .insert((key.inbound_id, key.clone()));
+        Ok(true)
+    }
+
+    fn create_edge_auto(&self, key: &models::EdgeKey, default_type: &models::Type) -> Result<bool> {
+        // Lock the two shards in index order, as in `create_edge`
+        let outbound_index = self.shard_index(key.outbound_id);
+        let inbound_index = self.shard_index(key.inbound_id);
+        let indexes: BTreeSet<usize> = vec![outbound_index, inbound_index].into_iter().collect();
+        let mut shards: BTreeMap<usize, RwLockWriteGuard<Shard>> = indexes
+            .into_iter()
+            .map(|index| Ok((index, self.shards[index].write().map_err(lock_poisoned)?)))
+            .collect::<Result<_>>()?;
+
+        for &(index, id) in &[(outbound_index, key.outbound_id), (inbound_index, key.inbound_id)] {
+            shards
+                .get_mut(&index)
+                .unwrap()
+                .vertices
+                .entry(id)
+                .or_insert_with(|| (default_type.clone(), Utc::now()));
+        }
+
+        let created = shards
+            .get_mut(&outbound_index)
+            .unwrap()
+            .edges
+            .insert(key.clone(), Utc::now())
+            .is_none();
+        shards
+            .get_mut(&inbound_index)
+            .unwrap()
+            .inbound_edges
+            .insert((key.inbound_id, key.clone()));
+        Ok(created)
+    }
+
+    fn update_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        // Only the outbound shard records the update datetime
+        let mut shard = self.shards[self.shard_index(key.outbound_id)]
+            .write()
+            .map_err(lock_poisoned)?;
+
+        match shard.edges.get_mut(key) {
+            Some(update_datetime) => {
+                *update_datetime = Utc::now();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn delete_edges(&self, q: &EdgeQuery) -> Result<u64> {
+        let mut shards = self.write_all()?;
+        let deletable_edges: Vec<models::EdgeKey> = get_edge_values_by_query(&shards, q)?
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        Ok(delete_edges(&mut shards, deletable_edges))
+    }
+
+    fn set_vertex_properties(&self, q: &VertexQuery, name: &str, value: &JsonValue) -> Result<u64> {
+        let mut shards = self.write_all()?;
+        let vertex_values = get_vertex_values_by_query(&shards, q)?;
+        let count = vertex_values.len() as u64;
+
+        for (id, _) in vertex_values {
+            shards[self.shard_index(id)]
+                .vertex_properties
+                .insert((id, name.to_string()), value.clone());
+        }
+
+        Ok(count)
+    }
+
+    fn patch_vertex_property(&self, q: &VertexQuery, name: &str, patch: &JsonValue) -> Result<()> {
+        let mut shards = self.write_all()?;
+        let vertex_values = get_vertex_values_by_query(&shards, q)?;
+
+        for (id, _) in vertex_values {
+            let value = shards[self.shard_index(id)]
+                .vertex_properties
+                .entry((id, name.to_string()))
+                .or_insert(JsonValue::Null);
+            util::json_merge_patch(value, patch);
+        }
+
+        Ok(())
+    }
+
+    fn delete_vertex_properties(&self, q: &VertexQuery, name: &str) -> Result<()> {
+        let mut shards = self.write_all()?;
+        let vertex_values = get_vertex_values_by_query(&shards, q)?;
+
+        for (id, _) in vertex_values {
+            shards[self.shard_index(id)]
+                .vertex_properties
+                .remove(&(id, name.to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn delete_all_vertex_properties(&self, q: &VertexQuery) -> Result<()> {
+        let mut shards = self.write_all()?;
+        let vertex_values = get_vertex_values_by_query(&shards, q)?;
+
+        for (id, _) in vertex_values {
+            remove_all_vertex_properties(&mut shards[self.shard_index(id)], id);
+        }
+
+        Ok(())
+    }
+
+    fn set_edge_properties(&self, q: &EdgeQuery, name: &str, value: &JsonValue) -> Result<()> {
+        let mut shards = self.write_all()?;
+        let edge_values = get_edge_values_by_query(&shards, q)?;
+
+        for (key, _) in edge_values {
+            shards[self.shard_index(key.outbound_id)]
+                .edge_properties
+                .insert((key, name.to_string()), value.clone());
+        }
+
+        Ok(())
+    }
+
+    fn patch_edge_property(&self, q: &EdgeQuery, name: &str, patch: &JsonValue) -> Result<()> {
+        let mut shards = self.write_all()?;
+        let edge_values = get_edge_values_by_query(&shards, q)?;
+
+        for (key, _) in edge_values {
+            let value = shards[self.shard_index(key.outbound_id)]
+                .edge_properties
+                .entry((key, name.to_string()))
+                .or_insert(JsonValue::Null);
+            util::json_merge_patch(value, patch);
+        }
+
+        Ok(())
+    }
+
+    fn delete_edge_properties(&self, q: &EdgeQuery, name: &str) -> Result<()> {
+        let mut shards = self.write_all()?;
+        let edge_values = get_edge_values_by_query(&shards, q)?;
+
+        for (key, _) in edge_values {
+            shards[self.shard_index(key.outbound_id)]
+                .edge_properties
+                .remove(&(key, name.to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn delete_all_edge_properties(&self, q: &EdgeQuery) -> Result<()> {
+        let mut shards = self.write_all()?;
+        let edge_values = get_edge_values_by_query(&shards, q)?;
+
+        for (key, _) in edge_values {
+            remove_all_edge_properties(&mut shards[self.shard_index(key.outbound_id)], &key);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "bench-suite")]
+mod benches {
+    full_bench_impl!(super::ShardedMemoryDatastore::default());
+}
+
+#[cfg(feature = "test-suite")]
+mod tests {
+    full_test_impl!(super::ShardedMemoryDatastore::new(4));
+}
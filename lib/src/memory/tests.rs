@@ -0,0 +1,1935 @@
+#[cfg(test)]
+fn traversal_limited_datastore() -> super::MemoryDatastore {
+    use super::MemoryDatastore;
+    use tests::test_traversal_limits;
+
+    MemoryDatastore::default().with_traversal_limits(test_traversal_limits())
+}
+
+#[test]
+fn should_abort_traversal_over_vertex_limit() {
+    ::tests::should_abort_traversal_over_vertex_limit(&mut traversal_limited_datastore());
+}
+
+#[test]
+fn should_abort_traversal_over_edge_limit() {
+    ::tests::should_abort_traversal_over_edge_limit(&mut traversal_limited_datastore());
+}
+
+#[test]
+fn should_abort_traversal_over_hop_limit() {
+    ::tests::should_abort_traversal_over_hop_limit(&mut traversal_limited_datastore());
+}
+
+#[test]
+fn should_not_create_a_disallowed_self_loop() {
+    use super::MemoryDatastore;
+
+    ::tests::should_not_create_a_disallowed_self_loop(&mut MemoryDatastore::default().with_allow_self_loops(false));
+}
+
+#[test]
+fn should_page_edges_from_a_stable_snapshot() {
+    use super::MemoryDatastore;
+    use models;
+    use tests::create_edges;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let mut datastore = MemoryDatastore::default();
+    let (outbound_id, _) = create_edges(&mut datastore);
+    let mut pager = datastore.edge_pager().unwrap();
+    let first_page = pager.next_page(2);
+    assert_eq!(first_page.len(), 2);
+
+    // Delete every edge while the paging session is still open
+    let trans = datastore.transaction().unwrap();
+    let q = models::VertexQuery::Vertices { ids: vec![outbound_id] }.outbound_edges(None, None, None, 10);
+    trans.delete_edges(&q).unwrap();
+    assert_eq!(
+        trans
+            .get_edge_count(outbound_id, None, models::EdgeDirection::Outbound)
+            .unwrap(),
+        0
+    );
+
+    let second_page = pager.next_page(2);
+    let third_page = pager.next_page(2);
+    assert_eq!(second_page.len(), 2);
+    assert_eq!(third_page.len(), 1);
+    assert_eq!(pager.next_page(2).len(), 0);
+
+    let mut keys: Vec<models::EdgeKey> = first_page
+        .into_iter()
+        .chain(second_page)
+        .chain(third_page)
+        .map(|edge| edge.key)
+        .collect();
+    assert!(keys.iter().all(|key| key.outbound_id == outbound_id));
+    keys.dedup();
+    assert_eq!(keys.len(), 5);
+}
+
+#[test]
+fn should_validate_declared_property_types() {
+    use super::MemoryDatastore;
+    use errors::{Error, ErrorKind};
+    use models;
+    use serde_json::Value as JsonValue;
+    use tests::create_edges;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let mut datastore = MemoryDatastore::default();
+    let (outbound_id, _) = create_edges(&mut datastore);
+    datastore.set_property_type("age", models::PropertyValueType::Number);
+
+    let trans = datastore.transaction().unwrap();
+    let vertex_q = models::VertexQuery::Vertices { ids: vec![outbound_id] };
+    let edge_q = vertex_q.clone().outbound_edges(None, None, None, 10);
+
+    assert_eq!(
+        trans
+            .set_vertex_properties(&vertex_q, "age", &JsonValue::from(30))
+            .unwrap(),
+        1
+    );
+    trans.set_edge_properties(&edge_q, "age", &JsonValue::from(30)).unwrap();
+
+    match trans.set_vertex_properties(&vertex_q, "age", &JsonValue::from("thirty")) {
+        Err(Error(ErrorKind::Validation(_), _)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    match trans.set_edge_properties(&edge_q, "age", &JsonValue::from("thirty")) {
+        Err(Error(ErrorKind::Validation(_), _)) => (),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+
+    // The rejected writes should not have clobbered the existing values
+    let properties = trans.get_vertex_properties(&vertex_q, "age").unwrap();
+    assert_eq!(properties[0].value, JsonValue::from(30));
+    let properties = trans.get_edge_properties(&edge_q, "age").unwrap();
+    assert!(properties.iter().all(|property| property.value == 30));
+
+    // Undeclared properties remain schemaless
+    trans
+        .set_vertex_properties(&vertex_q, "name", &JsonValue::from(30))
+        .unwrap();
+    trans
+        .set_vertex_properties(&vertex_q, "name", &JsonValue::from("thirty"))
+        .unwrap();
+
+    datastore.remove_property_type("age");
+    trans
+        .set_vertex_properties(&vertex_q, "age", &JsonValue::from("thirty"))
+        .unwrap();
+}
+
+#[test]
+fn should_maintain_cached_degrees() {
+    use super::MemoryDatastore;
+    use models;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+    use uuid::Uuid;
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let mut ids: Vec<Uuid> = (0..8)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+
+    // A simple deterministic pseudo-random sequence, so failures reproduce
+    let mut seed: u64 = 42;
+    let mut next = move |bound: usize| -> usize {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        ((seed >> 33) as usize) % bound
+    };
+
+    for _ in 0..200 {
+        let outbound_id = ids[next(ids.len())];
+        let inbound_id = ids[next(ids.len())];
+        let key = models::EdgeKey::new(outbound_id, edge_t.clone(), inbound_id);
+
+        match next(10) {
+            0 => {
+                trans
+                    .delete_vertices(&models::VertexQuery::Vertices { ids: vec![outbound_id] })
+                    .unwrap();
+                ids.retain(|id| *id != outbound_id);
+                ids.push(trans.create_vertex_from_type(vertex_t.clone()).unwrap());
+            }
+            1..=3 => {
+                trans
+                    .delete_edges(&models::EdgeQuery::Edges { keys: vec![key] })
+                    .unwrap();
+            }
+            _ => {
+                trans.create_edge(&key).unwrap();
+            }
+        }
+    }
+
+    let incremental: Vec<(u64, u64)> = ids.iter().map(|id| trans.get_cached_degree(*id).unwrap()).collect();
+
+    for (id, &(out_degree, in_degree)) in ids.iter().zip(incremental.iter()) {
+        let expected_out_degree = trans
+            .get_edge_count(*id, None, models::EdgeDirection::Outbound)
+            .unwrap();
+        let expected_in_degree = trans.get_edge_count(*id, None, models::EdgeDirection::Inbound).unwrap();
+        assert_eq!(out_degree, expected_out_degree);
+        assert_eq!(in_degree, expected_in_degree);
+    }
+
+    datastore.recompute_degrees().unwrap();
+    let recomputed: Vec<(u64, u64)> = ids.iter().map(|id| trans.get_cached_degree(*id).unwrap()).collect();
+    assert_eq!(incremental, recomputed);
+}
+
+#[test]
+fn should_clone_vertex() {
+    use super::MemoryDatastore;
+    use models;
+    use serde_json::Value as JsonValue;
+    use tests::create_edges;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+    use util::generate_uuid_v1;
+    use uuid::Uuid;
+
+    let mut datastore = MemoryDatastore::default();
+    let (source_id, _) = create_edges(&mut datastore);
+    let trans = datastore.transaction().unwrap();
+    let source_q = models::VertexQuery::Vertices { ids: vec![source_id] };
+    trans
+        .set_vertex_properties(&source_q, "foo", &JsonValue::from(1))
+        .unwrap();
+    trans
+        .set_vertex_properties(&source_q, "bar", &JsonValue::from("baz"))
+        .unwrap();
+
+    let new_id = generate_uuid_v1();
+    assert!(trans.clone_vertex(source_id, new_id).unwrap());
+
+    let source = trans.get_vertices(&source_q).unwrap();
+    let new_q = models::VertexQuery::Vertices { ids: vec![new_id] };
+    let cloned = trans.get_vertices(&new_q).unwrap();
+    assert_eq!(cloned.len(), 1);
+    assert_eq!(cloned[0].t, source[0].t);
+
+    for name in &["foo", "bar"] {
+        let source_properties = trans.get_vertex_properties(&source_q, name).unwrap();
+        let cloned_properties = trans.get_vertex_properties(&new_q, name).unwrap();
+        assert_eq!(cloned_properties.len(), 1);
+        assert_eq!(cloned_properties[0].value, source_properties[0].value);
+    }
+
+    // Edges are intentionally not cloned
+    assert_eq!(trans.get_cached_degree(new_id).unwrap(), (0, 0));
+    assert_eq!(
+        trans
+            .get_edge_count(new_id, None, models::EdgeDirection::Outbound)
+            .unwrap(),
+        0
+    );
+
+    // Neither a missing source nor an existing target can be cloned
+    assert!(!trans.clone_vertex(Uuid::default(), generate_uuid_v1()).unwrap());
+    assert!(!trans.clone_vertex(source_id, new_id).unwrap());
+}
+
+#[test]
+fn should_merge_vertices() {
+    use super::MemoryDatastore;
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let keep = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let remove = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let other_outbound = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let other_inbound = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+
+    let keep_q = models::VertexQuery::Vertices { ids: vec![keep] };
+    let remove_q = models::VertexQuery::Vertices { ids: vec![remove] };
+    trans
+        .set_vertex_properties(&keep_q, "shared", &JsonValue::from(1))
+        .unwrap();
+    trans
+        .set_vertex_properties(&remove_q, "shared", &JsonValue::from(2))
+        .unwrap();
+    trans
+        .set_vertex_properties(&remove_q, "only_removed", &JsonValue::from(true))
+        .unwrap();
+
+    let edge = |outbound_id, inbound_id| models::EdgeKey::new(outbound_id, edge_t.clone(), inbound_id);
+    let set_edge_property = |key: models::EdgeKey, name: &str, value: JsonValue| {
+        let q = models::EdgeQuery::Edges { keys: vec![key] };
+        trans.set_edge_properties(&q, name, &value).unwrap();
+    };
+
+    for key in &[
+        edge(keep, other_inbound),
+        edge(remove, other_inbound),
+        edge(other_outbound, remove),
+        edge(keep, remove),
+        edge(remove, keep),
+        edge(remove, remove),
+    ] {
+        trans.create_edge(key).unwrap();
+    }
+
+    set_edge_property(edge(keep, other_inbound), "weight", JsonValue::from(0));
+    set_edge_property(edge(remove, other_inbound), "weight", JsonValue::from(1));
+    set_edge_property(edge(remove, other_inbound), "label", JsonValue::from("merged"));
+    set_edge_property(edge(other_outbound, remove), "weight", JsonValue::from(2));
+
+    trans.merge_vertices(keep, remove).unwrap();
+
+    assert!(!trans.vertex_exists(remove).unwrap());
+    assert_eq!(trans.get_vertex_count().unwrap(), 3);
+
+    // Properties of the kept vertex win
+    let shared = trans.get_vertex_properties(&keep_q, "shared").unwrap();
+    assert_eq!(shared[0].value, JsonValue::from(1));
+    let only_removed = trans.get_vertex_properties(&keep_q, "only_removed").unwrap();
+    assert_eq!(only_removed[0].value, JsonValue::from(true));
+
+    // Edges between the two vertices are dropped, while self-loops carry over
+    let all_edges_q = models::VertexQuery::All {
+        start_id: None,
+        end_id: None,
+        high_filter: None,
+        low_filter: None,
+        limit: u32::MAX,
+    }
+    .outbound_edges(None, None, None, u32::MAX);
+    let mut keys: Vec<models::EdgeKey> = trans
+        .get_edges(&all_edges_q)
+        .unwrap()
+        .into_iter()
+        .map(|edge| edge.key)
+        .collect();
+    keys.sort();
+    let mut expected_keys = vec![edge(keep, other_inbound), edge(other_outbound, keep), edge(keep, keep)];
+    expected_keys.sort();
+    assert_eq!(keys, expected_keys);
+
+    // Colliding edges keep the existing edge's properties, and gain new ones
+    let collided_q = models::EdgeQuery::Edges {
+        keys: vec![edge(keep, other_inbound)],
+    };
+    let weight = trans.get_edge_properties(&collided_q, "weight").unwrap();
+    assert_eq!(weight[0].value, JsonValue::from(0));
+    let label = trans.get_edge_properties(&collided_q, "label").unwrap();
+    assert_eq!(label[0].value, JsonValue::from("merged"));
+
+    let redirected_q = models::EdgeQuery::Edges {
+        keys: vec![edge(other_outbound, keep)],
+    };
+    let weight = trans.get_edge_properties(&redirected_q, "weight").unwrap();
+    assert_eq!(weight[0].value, JsonValue::from(2));
+
+    assert_eq!(trans.get_cached_degree(keep).unwrap(), (2, 2));
+    assert_eq!(trans.get_cached_degree(remove).unwrap(), (0, 0));
+    assert_eq!(trans.get_cached_degree(other_inbound).unwrap(), (0, 1));
+
+    // Missing vertices and self-merges are rejected
+    assert!(trans.merge_vertices(keep, remove).is_err());
+    assert!(trans.merge_vertices(keep, keep).is_err());
+}
+
+#[test]
+fn should_export_and_import_json() {
+    use super::MemoryDatastore;
+    use models;
+    use serde_json;
+    use serde_json::Value as JsonValue;
+    use tests::create_edges;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let mut datastore = MemoryDatastore::default();
+    let (outbound_id, _) = create_edges(&mut datastore);
+    let trans = datastore.transaction().unwrap();
+    let vertex_q = models::VertexQuery::Vertices { ids: vec![outbound_id] };
+    let edge_q = vertex_q.clone().outbound_edges(None, None, None, 10);
+    trans
+        .set_vertex_properties(&vertex_q, "name", &JsonValue::from("alice"))
+        .unwrap();
+    trans
+        .set_edge_properties(&edge_q, "weight", &JsonValue::from(0.5))
+        .unwrap();
+
+    let mut exported = Vec::new();
+    datastore.export_json(&mut exported).unwrap();
+
+    let lines: Vec<JsonValue> = String::from_utf8(exported.clone())
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 6 + 5 + 1 + 5);
+    assert_eq!(lines[0]["kind"], JsonValue::from("vertex"));
+    assert_eq!(lines[6]["kind"], JsonValue::from("edge"));
+    assert_eq!(lines[11]["kind"], JsonValue::from("vertex_property"));
+    assert_eq!(lines[11]["name"], JsonValue::from("name"));
+    assert_eq!(lines[12]["kind"], JsonValue::from("edge_property"));
+
+    let imported_datastore = MemoryDatastore::default();
+    imported_datastore.import_json(&exported[..]).unwrap();
+
+    let mut reexported = Vec::new();
+    imported_datastore.export_json(&mut reexported).unwrap();
+    assert_eq!(reexported, exported);
+
+    let imported_trans = imported_datastore.transaction().unwrap();
+    let edges = trans.get_edges(&edge_q).unwrap();
+    let imported_edges = imported_trans.get_edges(&edge_q).unwrap();
+    assert_eq!(imported_edges.len(), 5);
+
+    for (edge, imported_edge) in edges.iter().zip(imported_edges.iter()) {
+        assert_eq!(imported_edge.key, edge.key);
+        assert_eq!(imported_edge.created_datetime, edge.created_datetime);
+    }
+
+    assert_eq!(imported_trans.get_cached_degree(outbound_id).unwrap(), (5, 0));
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn should_save_and_load_compressed_snapshots() {
+    use super::{MemoryDatastore, SnapshotCompression};
+    use std::fs;
+    use tests::create_edges;
+    use util::generate_temporary_path;
+
+    let mut datastore = MemoryDatastore::default();
+    create_edges(&mut datastore);
+    let mut exported = Vec::new();
+    datastore.export_json(&mut exported).unwrap();
+
+    let compressions: [(SnapshotCompression, &[u8]); 3] = [
+        (SnapshotCompression::None, b"{"),
+        (SnapshotCompression::Gzip, &[0x1f, 0x8b]),
+        (SnapshotCompression::Zstd, &[0x28, 0xb5, 0x2f, 0xfd]),
+    ];
+
+    for &(compression, magic) in &compressions {
+        let path = generate_temporary_path();
+        datastore.save_to_path(&path, compression).unwrap();
+        assert!(fs::read(&path).unwrap().starts_with(magic));
+
+        // The compression is detected when loading
+        let loaded_datastore = MemoryDatastore::default();
+        loaded_datastore.load_from_path(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut reexported = Vec::new();
+        loaded_datastore.export_json(&mut reexported).unwrap();
+        assert_eq!(reexported, exported);
+    }
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn should_import_compressed_exports_losslessly() {
+    use super::MemoryDatastore;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use models;
+    use serde_json::Value as JsonValue;
+    use std::io::Write;
+    use tests::create_edges;
+    use traits::{Datastore, WriteTransaction};
+
+    let mut datastore = MemoryDatastore::default();
+    let (outbound_id, _) = create_edges(&mut datastore);
+    let trans = datastore.transaction().unwrap();
+    let inbound_v = models::Vertex::new(models::Type::new("test_vertex_type".to_string()).unwrap());
+    trans.create_vertex(&inbound_v).unwrap();
+    let key = models::EdgeKey::new(
+        outbound_id,
+        models::Type::new("test_edge_type".to_string()).unwrap(),
+        inbound_v.id,
+    );
+    trans.create_undirected_edge(&key).unwrap();
+    trans
+        .set_vertex_properties(&models::VertexQuery::single(outbound_id), "foo", &JsonValue::from(1))
+        .unwrap();
+    let mut exported = Vec::new();
+    datastore.export_json(&mut exported).unwrap();
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&exported).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    // Edge datetimes and undirected edges survive the round trip
+    let imported_datastore = MemoryDatastore::default();
+    let report = imported_datastore.import_compressed(&compressed[..]).unwrap();
+    assert_eq!(report.parsed_vertices, 7);
+    assert_eq!(report.parsed_edges, 6);
+    assert_eq!(report.parsed_vertex_properties, 1);
+    assert_eq!(report.parsed_edge_properties, 0);
+
+    let mut reexported = Vec::new();
+    imported_datastore.export_json(&mut reexported).unwrap();
+    assert_eq!(reexported, exported);
+}
+
+#[test]
+fn should_look_up_vertices_by_indexed_property_value() {
+    use super::MemoryDatastore;
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, WriteTransaction};
+    use uuid::Uuid;
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let ids: Vec<Uuid> = (0..3)
+        .map(|_| trans.create_vertex_from_type(t.clone()).unwrap())
+        .collect();
+    let email = |i: usize| JsonValue::from(format!("user{}@example.com", i));
+    let lookup = |value: &JsonValue| -> Vec<Uuid> {
+        trans
+            .get_vertices_by_property_value("email", value)
+            .unwrap()
+            .into_iter()
+            .map(|vertex| vertex.id)
+            .collect()
+    };
+
+    // Properties set before the index is created are picked up by it
+    let q = models::VertexQuery::Vertices { ids: vec![ids[0]] };
+    trans.set_vertex_properties(&q, "email", &email(0)).unwrap();
+    assert_eq!(lookup(&email(0)), vec![ids[0]]);
+    datastore.create_property_index("email").unwrap();
+    assert_eq!(lookup(&email(0)), vec![ids[0]]);
+
+    let q = models::VertexQuery::Vertices { ids: ids.clone() };
+    trans.set_vertex_properties(&q, "email", &email(1)).unwrap();
+    assert_eq!(lookup(&email(0)).len(), 0);
+    let mut expected_ids = ids.clone();
+    expected_ids.sort();
+    assert_eq!(lookup(&email(1)), expected_ids);
+
+    // Conditional writes with an equality condition use the index
+    let condition = models::PropertyCondition::new("email".to_string(), models::ComparisonOperator::Equal, email(1));
+    assert_eq!(
+        trans
+            .set_vertex_property_where(&condition, "verified", &JsonValue::Bool(true))
+            .unwrap(),
+        3
+    );
+
+    let q = models::VertexQuery::Vertices { ids: vec![ids[1]] };
+    trans.delete_vertex_properties(&q, "email").unwrap();
+    let q = models::VertexQuery::Vertices { ids: vec![ids[2]] };
+    trans.delete_vertices(&q).unwrap();
+    assert_eq!(lookup(&email(1)), vec![ids[0]]);
+
+    datastore.drop_property_index("email").unwrap();
+    assert_eq!(lookup(&email(1)), vec![ids[0]]);
+    assert_eq!(lookup(&email(2)).len(), 0);
+}
+
+#[cfg(feature = "lock-stats")]
+#[test]
+fn should_count_lock_acquisitions() {
+    use super::MemoryDatastore;
+    use models;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let initial_stats = datastore.stats();
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    trans.create_vertex_from_type(t).unwrap();
+    trans.get_vertex_count().unwrap();
+    trans.get_vertex_count().unwrap();
+
+    let stats = datastore.stats();
+    assert_eq!(stats.read_acquisitions - initial_stats.read_acquisitions, 2);
+    assert_eq!(stats.write_acquisitions - initial_stats.write_acquisitions, 1);
+    assert!(stats.read_wait >= initial_stats.read_wait);
+}
+
+#[test]
+fn should_create_edges_across_shards_concurrently() {
+    use super::ShardedMemoryDatastore;
+    use models;
+    use std::thread;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = ShardedMemoryDatastore::new(4);
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let ids: Vec<_> = (0..16)
+        .map(|_| trans.create_vertex_from_type(t.clone()).unwrap())
+        .collect();
+
+    // Every thread links each vertex to every other in its own direction,
+    // so pairs of threads lock the same two shards in opposite orders
+    let threads: Vec<_> = (0..2)
+        .map(|direction| {
+            let datastore = datastore.clone();
+            let ids = ids.clone();
+
+            thread::spawn(move || {
+                let trans = datastore.transaction().unwrap();
+                let t = models::Type::new("test_edge_type".to_string()).unwrap();
+
+                for (i, first_id) in ids.iter().enumerate() {
+                    for second_id in &ids[i + 1..] {
+                        let key = if direction == 0 {
+                            models::EdgeKey::new(*first_id, t.clone(), *second_id)
+                        } else {
+                            models::EdgeKey::new(*second_id, t.clone(), *first_id)
+                        };
+
+                        assert!(trans.create_edge(&key).unwrap());
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    for id in ids {
+        assert_eq!(
+            trans.get_edge_count(id, None, models::EdgeDirection::Outbound).unwrap(),
+            15
+        );
+        assert_eq!(
+            trans.get_edge_count(id, None, models::EdgeDirection::Inbound).unwrap(),
+            15
+        );
+        let q = models::VertexQuery::Vertices { ids: vec![id] }.inbound_edges(None, None, None, 100);
+        assert_eq!(trans.get_edges(&q).unwrap().len(), 15);
+    }
+}
+
+#[test]
+fn should_drop_self_loops_when_merging_vertices_if_disallowed() {
+    use super::MemoryDatastore;
+    use models;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let keep = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let remove = trans.create_vertex_from_type(vertex_t).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    trans
+        .create_edge(&models::EdgeKey::new(remove, edge_t.clone(), remove))
+        .unwrap();
+
+    let datastore = datastore.with_allow_self_loops(false);
+    let trans = datastore.transaction().unwrap();
+    trans.merge_vertices(keep, remove).unwrap();
+    assert!(!trans.edge_exists(&models::EdgeKey::new(keep, edge_t, keep)).unwrap());
+}
+
+#[test]
+fn should_get_edges_between_vertices() {
+    use super::MemoryDatastore;
+    use models;
+    use tests::create_edge_from;
+    use traits::{Datastore, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let a = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let b = trans.create_vertex_from_type(vertex_t).unwrap();
+    create_edge_from(&trans, a);
+
+    let keys = vec![
+        models::EdgeKey::new(a, models::Type::new("follows".to_string()).unwrap(), b),
+        models::EdgeKey::new(a, models::Type::new("likes".to_string()).unwrap(), b),
+        models::EdgeKey::new(b, models::Type::new("follows".to_string()).unwrap(), a),
+    ];
+
+    for key in &keys {
+        trans.create_edge(key).unwrap();
+    }
+
+    let get_keys = |a, b, direction| -> Vec<models::EdgeKey> {
+        trans
+            .get_edges_between(a, b, direction)
+            .unwrap()
+            .into_iter()
+            .map(|edge| edge.key)
+            .collect()
+    };
+
+    assert_eq!(
+        get_keys(a, b, Some(models::EdgeDirection::Outbound)),
+        keys[..2].to_vec()
+    );
+    assert_eq!(get_keys(a, b, Some(models::EdgeDirection::Inbound)), keys[2..].to_vec());
+    assert_eq!(get_keys(a, b, None), keys);
+    assert_eq!(
+        get_keys(b, a, Some(models::EdgeDirection::Outbound)),
+        keys[2..].to_vec()
+    );
+
+    let self_loop = models::EdgeKey::new(a, models::Type::new("follows".to_string()).unwrap(), a);
+    trans.create_edge(&self_loop).unwrap();
+    assert_eq!(get_keys(a, a, None), vec![self_loop]);
+}
+
+#[test]
+fn should_handle_undirected_edges() {
+    use super::MemoryDatastore;
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let a = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let b = trans.create_vertex_from_type(vertex_t).unwrap();
+    let t = models::Type::new("friends_with".to_string()).unwrap();
+    let key = models::EdgeKey::new(a, t.clone(), b);
+    let reversed_key = key.reversed();
+    assert!(trans.create_undirected_edge(&reversed_key).unwrap());
+
+    // The edge exists in both directions, but is only stored once
+    for k in &[&key, &reversed_key] {
+        assert!(trans.edge_exists(k).unwrap());
+        assert!(trans.is_edge_undirected(k).unwrap());
+    }
+
+    assert_eq!(datastore.edge_pager().unwrap().next_page(10).len(), 1);
+
+    for &(id, ref expected_key) in &[(a, &key), (b, &reversed_key)] {
+        let q = models::VertexQuery::Vertices { ids: vec![id] };
+        let outbound_edges = trans
+            .get_edges(&q.clone().outbound_edges(None, None, None, 10))
+            .unwrap();
+        assert_eq!(outbound_edges.len(), 1);
+        assert_eq!(&&outbound_edges[0].key, expected_key);
+        let inbound_edges = trans.get_edges(&q.clone().inbound_edges(None, None, None, 10)).unwrap();
+        assert_eq!(inbound_edges.len(), 1);
+        assert_eq!(inbound_edges[0].key, expected_key.reversed());
+
+        for direction in &[models::EdgeDirection::Outbound, models::EdgeDirection::Inbound] {
+            assert_eq!(trans.get_edge_count(id, Some(&t), *direction).unwrap(), 1);
+        }
+
+        assert_eq!(trans.get_cached_degree(id).unwrap(), (1, 1));
+        let neighbors = trans
+            .get_vertices(&q.traverse(models::EdgeDirection::Outbound, None, 1, 10))
+            .unwrap();
+        assert_eq!(neighbors.len(), 1);
+    }
+
+    // Properties are shared between both directions
+    let q = models::EdgeQuery::Edges {
+        keys: vec![reversed_key.clone()],
+    };
+    trans.set_edge_properties(&q, "since", &JsonValue::from(2010)).unwrap();
+    let q = models::EdgeQuery::Edges {
+        keys: vec![key.clone()],
+    };
+    assert_eq!(
+        trans.get_edge_properties(&q, "since").unwrap()[0].value,
+        JsonValue::from(2010)
+    );
+
+    // Directed edges can't be created the other way, nor duplicate it
+    assert!(trans.create_edge(&reversed_key).unwrap());
+    assert_eq!(datastore.edge_pager().unwrap().next_page(10).len(), 1);
+
+    // Exports keep the edge undirected
+    let mut exported = Vec::new();
+    datastore.export_json(&mut exported).unwrap();
+    let imported_datastore = MemoryDatastore::default();
+    imported_datastore.import_json(&exported[..]).unwrap();
+    let imported_trans = imported_datastore.transaction().unwrap();
+    assert!(imported_trans.is_edge_undirected(&reversed_key).unwrap());
+
+    let q = models::EdgeQuery::Edges {
+        keys: vec![reversed_key],
+    };
+    trans.delete_edges(&q).unwrap();
+    assert!(!trans.edge_exists(&key).unwrap());
+    assert_eq!(trans.get_cached_degree(a).unwrap(), (0, 0));
+    assert_eq!(trans.get_cached_degree(b).unwrap(), (0, 0));
+
+    // Deleted edges are no longer counted from either end
+    for &id in &[a, b] {
+        for direction in &[models::EdgeDirection::Outbound, models::EdgeDirection::Inbound] {
+            assert_eq!(trans.get_edge_count(id, Some(&t), *direction).unwrap(), 0);
+        }
+    }
+
+    // An undirected edge can't be created over a directed edge going the
+    // other way of its canonical key
+    let canonical_key = key.canonical();
+    assert!(trans.create_edge(&canonical_key.reversed()).unwrap());
+    assert!(!trans.create_undirected_edge(&canonical_key).unwrap());
+}
+
+#[test]
+fn should_normalize_types_when_case_insensitive() {
+    use super::MemoryDatastore;
+    use models;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let t = |name: &str| models::Type::new(name.to_string()).unwrap();
+
+    for &case_insensitive in &[true, false] {
+        let datastore = MemoryDatastore::default().with_case_insensitive_types(case_insensitive);
+        let trans = datastore.transaction().unwrap();
+        let outbound_id = trans.create_vertex_from_type(t("User")).unwrap();
+        let inbound_id = trans.create_vertex_from_type(t("user")).unwrap();
+        let key = models::EdgeKey::new(outbound_id, t("Follows"), inbound_id);
+        assert!(trans.create_edge(&key).unwrap());
+
+        let expected_types = if case_insensitive {
+            vec![t("user")]
+        } else {
+            vec![t("User"), t("user")]
+        };
+        assert_eq!(trans.get_vertex_types().unwrap(), expected_types);
+
+        let q = models::VertexQuery::Types {
+            types: vec![t("USER")],
+            limit: 10,
+        };
+        let expected_count = if case_insensitive { 2 } else { 0 };
+        assert_eq!(trans.get_vertices(&q).unwrap().len(), expected_count);
+
+        // Edge queries match the type filter regardless of its casing
+        let outbound_q = models::VertexQuery::Vertices { ids: vec![outbound_id] };
+        let inbound_q = models::VertexQuery::Vertices { ids: vec![inbound_id] };
+
+        for type_filter in &[t("follows"), t("FOLLOWS")] {
+            let expected_count = if case_insensitive { 1 } else { 0 };
+
+            let edges = trans
+                .get_edges(
+                    &outbound_q
+                        .clone()
+                        .outbound_edges(Some(type_filter.clone()), None, None, 10),
+                )
+                .unwrap();
+            assert_eq!(edges.len(), expected_count);
+            let edges = trans
+                .get_edges(
+                    &inbound_q
+                        .clone()
+                        .inbound_edges(Some(type_filter.clone()), None, None, 10),
+                )
+                .unwrap();
+            assert_eq!(edges.len(), expected_count);
+            let count = trans
+                .get_edge_count(outbound_id, Some(type_filter), models::EdgeDirection::Outbound)
+                .unwrap();
+            assert_eq!(count, expected_count as u64);
+            let count = trans
+                .get_edge_count(inbound_id, Some(type_filter), models::EdgeDirection::Inbound)
+                .unwrap();
+            assert_eq!(count, expected_count as u64);
+            let traversed = trans
+                .get_vertices(&outbound_q.clone().traverse(
+                    models::EdgeDirection::Outbound,
+                    Some(type_filter.clone()),
+                    1,
+                    10,
+                ))
+                .unwrap();
+            assert_eq!(traversed.len(), expected_count);
+            let edge_key = models::EdgeKey::new(outbound_id, type_filter.clone(), inbound_id);
+            assert_eq!(trans.edge_exists(&edge_key).unwrap(), case_insensitive);
+        }
+
+        // The casing the edge was created with always matches
+        assert!(trans.edge_exists(&key).unwrap());
+        let q = models::EdgeQuery::Edges {
+            keys: vec![models::EdgeKey::new(outbound_id, t("FOLLOWS"), inbound_id)],
+        };
+        let expected_count = if case_insensitive { 1 } else { 0 };
+        assert_eq!(trans.delete_edges(&q).unwrap(), expected_count);
+    }
+}
+
+#[test]
+fn should_not_bulk_insert_anything_with_an_invalid_property_value() {
+    use super::MemoryDatastore;
+    use errors::{Error, ErrorKind};
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, ReadTransaction};
+
+    let datastore = MemoryDatastore::default();
+    datastore.set_property_type("age", models::PropertyValueType::Number);
+    let vertex = models::Vertex::new(models::Type::new("test_vertex_type".to_string()).unwrap());
+
+    let result = datastore.bulk_insert_with_mode(
+        vec![
+            models::BulkInsertItem::Vertex(vertex.clone()),
+            models::BulkInsertItem::VertexProperty(vertex.id, "name".to_string(), JsonValue::from("alice")),
+            models::BulkInsertItem::VertexProperty(vertex.id, "age".to_string(), JsonValue::from("thirty")),
+        ]
+        .into_iter(),
+        models::BulkInsertMode::Error,
+    );
+
+    match result {
+        Err(Error(ErrorKind::Validation(_), _)) => (),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    let trans = datastore.transaction().unwrap();
+    assert!(!trans.vertex_exists(vertex.id).unwrap());
+}
+
+#[test]
+fn should_bulk_load() {
+    use super::MemoryDatastore;
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, ReadTransaction};
+    use uuid::Uuid;
+
+    let datastore = MemoryDatastore::default();
+    datastore.create_property_index("name").unwrap();
+    datastore.set_property_type("age", models::PropertyValueType::Number);
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let vertices: Vec<models::Vertex> = (0..3).map(|_| models::Vertex::new(vertex_t.clone())).collect();
+    let key = models::EdgeKey::new(vertices[0].id, edge_t.clone(), vertices[1].id);
+
+    datastore
+        .bulk_load(
+            vec![
+                models::BulkInsertItem::Vertex(vertices[0].clone()),
+                models::BulkInsertItem::Vertex(vertices[1].clone()),
+                models::BulkInsertItem::Vertex(vertices[2].clone()),
+                models::BulkInsertItem::Edge(key.clone()),
+                models::BulkInsertItem::Edge(models::EdgeKey::new(vertices[2].id, edge_t.clone(), vertices[1].id)),
+                models::BulkInsertItem::Edge(models::EdgeKey::new(vertices[0].id, edge_t.clone(), Uuid::default())),
+                models::BulkInsertItem::VertexProperty(vertices[0].id, "name".to_string(), JsonValue::from("alice")),
+                models::BulkInsertItem::VertexProperty(vertices[1].id, "name".to_string(), JsonValue::from("bob")),
+                models::BulkInsertItem::VertexProperty(Uuid::default(), "name".to_string(), JsonValue::from("bob")),
+                models::BulkInsertItem::EdgeProperty(key.clone(), "weight".to_string(), JsonValue::from(2)),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    let trans = datastore.transaction().unwrap();
+    assert_eq!(trans.get_vertex_count().unwrap(), 3);
+    assert_eq!(datastore.edge_pager().unwrap().next_page(10).len(), 2);
+    assert_eq!(trans.get_cached_degree(vertices[0].id).unwrap(), (1, 0));
+    assert_eq!(trans.get_cached_degree(vertices[1].id).unwrap(), (0, 2));
+    assert_eq!(trans.get_cached_degree(vertices[2].id).unwrap(), (1, 0));
+    let bobs = trans
+        .get_vertices_by_property_value("name", &JsonValue::from("bob"))
+        .unwrap();
+    assert_eq!(bobs, vec![vertices[1].clone()]);
+    let q = models::EdgeQuery::Edges { keys: vec![key] };
+    assert_eq!(
+        trans.get_edge_properties(&q, "weight").unwrap()[0].value,
+        JsonValue::from(2)
+    );
+
+    // Items before an invalid property value stay loaded, and are indexed
+    let vertex = models::Vertex::new(vertex_t);
+    let result = datastore.bulk_load(
+        vec![
+            models::BulkInsertItem::Vertex(vertex.clone()),
+            models::BulkInsertItem::Edge(models::EdgeKey::new(vertex.id, edge_t, vertices[0].id)),
+            models::BulkInsertItem::VertexProperty(vertex.id, "name".to_string(), JsonValue::from("bob")),
+            models::BulkInsertItem::VertexProperty(vertex.id, "age".to_string(), JsonValue::from("old")),
+        ]
+        .into_iter(),
+    );
+    assert!(result.is_err());
+    assert_eq!(trans.get_cached_degree(vertex.id).unwrap(), (1, 0));
+    assert_eq!(trans.get_cached_degree(vertices[0].id).unwrap(), (1, 1));
+    let bobs = trans
+        .get_vertices_by_property_value("name", &JsonValue::from("bob"))
+        .unwrap();
+    assert_eq!(bobs.len(), 2);
+}
+
+#[test]
+fn should_fail_instead_of_panicking_on_a_poisoned_lock() {
+    use super::MemoryDatastore;
+    use errors::{Error, ErrorKind};
+    use models;
+    use std::panic::{self, AssertUnwindSafe};
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    trans.create_vertex_from_type(t.clone()).unwrap();
+
+    // Panic while the write lock is held
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let items = (0..1).map(|_| -> models::BulkInsertItem { panic!("failed to produce an item") });
+        datastore.bulk_load(items)
+    }));
+    assert!(result.is_err());
+
+    match trans.get_vertex_count() {
+        Err(Error(ErrorKind::LockPoisoned, _)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    match trans.create_vertex_from_type(t) {
+        Err(Error(ErrorKind::LockPoisoned, _)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn should_check_a_batch_before_applying_it() {
+    use super::MemoryDatastore;
+    use errors::{Error, ErrorKind};
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, ReadTransaction};
+
+    let datastore = MemoryDatastore::default();
+    datastore.set_property_type("age", models::PropertyValueType::Number);
+    let v = models::Vertex::new(models::Type::new("test_vertex_type".to_string()).unwrap());
+
+    let operations = vec![
+        models::BatchOperation::CreateVertex(v.clone()),
+        models::BatchOperation::SetVertexProperties(
+            models::VertexQuery::single(v.id),
+            "age".to_string(),
+            JsonValue::from("old"),
+        ),
+    ];
+
+    match datastore.execute_batch(operations.into_iter()) {
+        Err(Error(ErrorKind::Validation(_), _)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // Nothing should have been applied
+    let trans = datastore.transaction().unwrap();
+    assert!(!trans.vertex_exists(v.id).unwrap());
+}
+
+#[test]
+fn should_reject_duplicate_bulk_insert_edges() {
+    use super::MemoryDatastore;
+    use errors::{Error, ErrorKind};
+    use models;
+    use traits::{Datastore, ReadTransaction};
+
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let a = models::Vertex::new(vertex_t.clone());
+    let b = models::Vertex::new(vertex_t);
+    let key = models::EdgeKey::new(a.id, models::Type::new("test_edge_type".to_string()).unwrap(), b.id);
+    let items = vec![
+        models::BulkInsertItem::Vertex(a.clone()),
+        models::BulkInsertItem::Vertex(b.clone()),
+        models::BulkInsertItem::Edge(key.clone()),
+        models::BulkInsertItem::Edge(key.clone()),
+    ];
+
+    // Duplicates are allowed by default
+    let datastore = MemoryDatastore::default();
+    datastore.bulk_insert(items.clone().into_iter()).unwrap();
+    assert!(datastore.transaction().unwrap().edge_exists(&key).unwrap());
+
+    let datastore = MemoryDatastore::default().with_reject_duplicate_bulk_edges(true);
+
+    match datastore.bulk_insert(items.clone().into_iter()) {
+        Err(Error(ErrorKind::DuplicateBulkInsertEdges(ref keys), _)) => assert_eq!(keys, &format!("{:?}", vec![&key])),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    match datastore.bulk_insert_with_mode(items.clone().into_iter(), models::BulkInsertMode::Skip) {
+        Err(Error(ErrorKind::DuplicateBulkInsertEdges(_), _)) => (),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    // Nothing was inserted
+    let trans = datastore.transaction().unwrap();
+    assert!(!trans.vertex_exists(a.id).unwrap());
+
+    // An edge that already exists isn't a duplicate
+    datastore.bulk_insert(items[..3].iter().cloned()).unwrap();
+    datastore.bulk_insert(items[2..3].iter().cloned()).unwrap();
+    assert!(trans.edge_exists(&key).unwrap());
+}
+
+#[test]
+fn should_only_collect_bulk_inserts_when_checking_edges() {
+    use super::MemoryDatastore;
+    use models;
+    use std::cell::Cell;
+    use traits::Datastore;
+
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let items: Vec<models::BulkInsertItem> = (0..10)
+        .map(|_| models::BulkInsertItem::Vertex(models::Vertex::new(vertex_t.clone())))
+        .collect();
+
+    for &(reject_duplicates, reject_dangling, streamed) in
+        &[(false, false, true), (true, false, false), (false, true, false)]
+    {
+        let datastore = MemoryDatastore::default()
+            .with_reject_duplicate_bulk_edges(reject_duplicates)
+            .with_reject_dangling_bulk_edges(reject_dangling);
+        let taken = Cell::new(0);
+
+        // When streamed, each item is inserted before the next is taken
+        datastore
+            .bulk_insert_with_progress(
+                items.iter().cloned().inspect(|_| taken.set(taken.get() + 1)),
+                1,
+                |count| {
+                    let expected_taken = if streamed { count } else { 10 };
+                    assert_eq!(taken.get(), expected_taken);
+                },
+            )
+            .unwrap();
+    }
+}
+
+#[test]
+fn should_rename_types() {
+    use super::MemoryDatastore;
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let post_t = models::Type::new("post".to_string()).unwrap();
+    let article_t = models::Type::new("article".to_string()).unwrap();
+    let a = trans.create_vertex_from_type(post_t.clone()).unwrap();
+    let b = trans.create_vertex_from_type(post_t.clone()).unwrap();
+    let c = trans.create_vertex_from_type(article_t.clone()).unwrap();
+
+    assert_eq!(trans.rename_vertex_type(&post_t, &article_t).unwrap(), 2);
+    let q = models::VertexQuery::ids(vec![a, b, c]);
+    assert!(trans.get_vertices(&q).unwrap().iter().all(|v| v.t == article_t));
+    assert_eq!(trans.rename_vertex_type(&post_t, &article_t).unwrap(), 0);
+
+    let cites_t = models::Type::new("cites".to_string()).unwrap();
+    let references_t = models::Type::new("references".to_string()).unwrap();
+    let key = models::EdgeKey::new(a, cites_t.clone(), b);
+    let undirected_key = models::EdgeKey::new(a, cites_t.clone(), c);
+    trans.create_edge(&key).unwrap();
+    trans.create_undirected_edge(&undirected_key).unwrap();
+    trans
+        .set_edge_properties(&models::EdgeQuery::single(key.clone()), "weight", &JsonValue::from(1))
+        .unwrap();
+    let update_datetime = trans.get_edges(&models::EdgeQuery::single(key.clone())).unwrap()[0].created_datetime;
+
+    // Renaming onto an existing edge fails without renaming anything
+    let colliding_key = models::EdgeKey::new(c, references_t.clone(), a);
+    trans.create_edge(&colliding_key).unwrap();
+    assert!(trans.rename_edge_type(&cites_t, &references_t).is_err());
+    assert!(trans.edge_exists(&key).unwrap());
+    trans.delete_edges(&models::EdgeQuery::single(colliding_key)).unwrap();
+
+    assert_eq!(trans.rename_edge_type(&cites_t, &references_t).unwrap(), 2);
+    let new_key = models::EdgeKey::new(a, references_t.clone(), b);
+    let new_undirected_key = models::EdgeKey::new(a, references_t.clone(), c);
+    assert!(!trans.edge_exists(&key).unwrap());
+    assert!(!trans.edge_exists(&undirected_key).unwrap());
+    let edges = trans.get_edges(&models::EdgeQuery::single(new_key.clone())).unwrap();
+    assert_eq!(edges[0].created_datetime, update_datetime);
+    assert!(trans.is_edge_undirected(&new_undirected_key.reversed()).unwrap());
+    assert_eq!(trans.get_cached_degree(a).unwrap(), (2, 1));
+
+    // The undirected edge is counted under its new type from both ends
+    for &id in &[a, c] {
+        for &(t, expected_count) in &[(&cites_t, 0), (&references_t, 1)] {
+            let count = trans
+                .get_edge_count(id, Some(t), models::EdgeDirection::Inbound)
+                .unwrap();
+            assert_eq!(count, expected_count);
+        }
+    }
+
+    let count = trans
+        .get_edge_count(c, Some(&references_t), models::EdgeDirection::Outbound)
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let properties = trans
+        .get_edge_properties(&models::EdgeQuery::single(new_key), "weight")
+        .unwrap();
+    assert_eq!(properties[0].value, JsonValue::from(1));
+    let properties = trans
+        .get_edge_properties(&models::EdgeQuery::single(key), "weight")
+        .unwrap();
+    assert_eq!(properties.len(), 0);
+}
+
+#[test]
+fn should_get_properties_updated_since() {
+    use super::MemoryDatastore;
+    use chrono::offset::Utc;
+    use models;
+    use serde_json;
+    use serde_json::Value as JsonValue;
+    use std::thread::sleep;
+    use std::time::Duration;
+    use traits::{Datastore, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("user".to_string()).unwrap();
+    let a = trans.create_vertex_from_type(t.clone()).unwrap();
+    let b = trans.create_vertex_from_type(t.clone()).unwrap();
+    let key = models::EdgeKey::new(a, models::Type::new("follows".to_string()).unwrap(), b);
+    trans.create_edge(&key).unwrap();
+    let edge_q = models::EdgeQuery::single(key.clone());
+
+    trans
+        .set_vertex_properties(&models::VertexQuery::single(a), "name", &JsonValue::from("alice"))
+        .unwrap();
+    trans
+        .set_vertex_properties(&models::VertexQuery::single(b), "name", &JsonValue::from("bob"))
+        .unwrap();
+    trans
+        .set_edge_properties(&edge_q, "weight", &JsonValue::from(1))
+        .unwrap();
+
+    sleep(Duration::from_millis(1));
+    let since = Utc::now();
+    assert_eq!(
+        trans.get_vertex_properties_updated_since("name", since).unwrap().len(),
+        0
+    );
+    assert_eq!(
+        trans.get_edge_properties_updated_since("weight", since).unwrap().len(),
+        0
+    );
+
+    trans
+        .set_vertex_properties(&models::VertexQuery::single(b), "name", &JsonValue::from("robert"))
+        .unwrap();
+    trans
+        .set_edge_properties(&edge_q, "weight", &JsonValue::from(2))
+        .unwrap();
+
+    let properties = trans.get_vertex_properties_updated_since("name", since).unwrap();
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].id, b);
+    assert_eq!(properties[0].value, JsonValue::from("robert"));
+    let properties = trans.get_edge_properties_updated_since("weight", since).unwrap();
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].key, key);
+    assert_eq!(properties[0].value, JsonValue::from(2));
+    assert_eq!(
+        trans.get_vertex_properties_updated_since("age", since).unwrap().len(),
+        0
+    );
+
+    // Update datetimes survive an export and import
+    let mut exported = Vec::new();
+    datastore.export_json(&mut exported).unwrap();
+    let imported_datastore = MemoryDatastore::default();
+    imported_datastore.import_json(&exported[..]).unwrap();
+    let imported_trans = imported_datastore.transaction().unwrap();
+    let properties = imported_trans
+        .get_vertex_properties_updated_since("name", since)
+        .unwrap();
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].id, b);
+
+    // Properties imported without an update datetime count as just updated
+    let legacy: Vec<String> = String::from_utf8(exported)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let mut record: JsonValue = serde_json::from_str(line).unwrap();
+            record.as_object_mut().unwrap().remove("updated_datetime");
+            record.to_string()
+        })
+        .collect();
+    let record = legacy.join("\n");
+    let imported_datastore = MemoryDatastore::default();
+    imported_datastore.import_json(record.as_bytes()).unwrap();
+    let imported_trans = imported_datastore.transaction().unwrap();
+    let properties = imported_trans
+        .get_vertex_properties_updated_since("name", since)
+        .unwrap();
+    assert_eq!(properties.len(), 2);
+    assert_eq!(properties[0].value, JsonValue::from("alice"));
+    let properties = imported_trans
+        .get_edge_properties_updated_since("weight", since)
+        .unwrap();
+    assert_eq!(properties.len(), 1);
+}
+
+#[test]
+fn should_reject_oversized_property_values() {
+    use super::MemoryDatastore;
+    use tests::TEST_MAX_PROPERTY_SIZE_BYTES;
+
+    let mut datastore = MemoryDatastore::default().with_max_property_size_bytes(Some(TEST_MAX_PROPERTY_SIZE_BYTES));
+    ::tests::should_reject_oversized_property_values(&mut datastore);
+}
+
+#[test]
+fn should_reject_dangling_bulk_insert_edges() {
+    use super::MemoryDatastore;
+
+    let mut datastore = MemoryDatastore::default().with_reject_dangling_bulk_edges(true);
+    ::tests::should_reject_dangling_bulk_insert_edges(&mut datastore);
+}
+
+#[test]
+fn should_filter_undirected_edges_by_several_types() {
+    use super::MemoryDatastore;
+    use models;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let friend_t = models::Type::new("friend".to_string()).unwrap();
+    let sibling_t = models::Type::new("sibling".to_string()).unwrap();
+    let a = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let b = trans.create_vertex_from_type(vertex_t).unwrap();
+    trans
+        .create_undirected_edge(&models::EdgeKey::new(a, friend_t.clone(), b))
+        .unwrap();
+    trans
+        .create_undirected_edge(&models::EdgeKey::new(a, sibling_t.clone(), b))
+        .unwrap();
+
+    for &(id, converter) in &[
+        (b, models::EdgeDirection::Outbound),
+        (a, models::EdgeDirection::Inbound),
+    ] {
+        let q = models::EdgeQuery::Pipe {
+            vertex_query: Box::new(models::VertexQuery::single(id)),
+            converter,
+            type_filter: None,
+            type_filters: vec![sibling_t.clone()],
+            high_filter: None,
+            low_filter: None,
+            after_inbound_id: None,
+            offset: 0,
+            limit: 10,
+        };
+        let edges = trans.get_edges(&q).unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].key.t, sibling_t);
+    }
+}
+
+#[test]
+fn should_send_changes_to_subscribers() {
+    use super::{ChangeEvent, MemoryDatastore};
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let a = models::Vertex::new(t.clone());
+    trans.create_vertex(&a).unwrap();
+
+    let receiver = datastore.subscribe();
+    let dropped_receiver = datastore.subscribe();
+    drop(dropped_receiver);
+
+    let b = models::Vertex::new(t.clone());
+    assert!(trans.create_vertex(&b).unwrap());
+    assert!(!trans.create_vertex(&b).unwrap());
+    let key = models::EdgeKey::new(a.id, models::Type::new("test_edge_type".to_string()).unwrap(), b.id);
+    trans.create_edge(&key).unwrap();
+    let vertex_q = models::VertexQuery::single(a.id);
+    let edge_q = models::EdgeQuery::single(key.clone());
+    trans
+        .set_vertex_properties(&vertex_q, "name", &JsonValue::from("alice"))
+        .unwrap();
+    trans.delete_vertex_properties(&vertex_q, "name").unwrap();
+    trans.delete_vertex_properties(&vertex_q, "name").unwrap();
+    trans
+        .set_edge_properties(&edge_q, "weight", &JsonValue::from(1))
+        .unwrap();
+    trans.delete_all_edge_properties(&edge_q).unwrap();
+    trans.delete_edges(&edge_q).unwrap();
+    trans.delete_vertices(&models::VertexQuery::single(b.id)).unwrap();
+
+    let events: Vec<ChangeEvent> = receiver.try_iter().collect();
+    assert_eq!(
+        events,
+        vec![
+            ChangeEvent::VertexCreated(b.clone()),
+            ChangeEvent::EdgeCreated(key.clone()),
+            ChangeEvent::VertexPropertySet(models::VertexProperty::new(
+                a.id,
+                "name".to_string(),
+                JsonValue::from("alice")
+            )),
+            ChangeEvent::VertexPropertyDeleted(a.id, "name".to_string()),
+            ChangeEvent::EdgePropertySet(models::EdgeProperty::new(
+                key.clone(),
+                "weight".to_string(),
+                JsonValue::from(1)
+            )),
+            ChangeEvent::AllEdgePropertiesDeleted(key.clone()),
+            ChangeEvent::EdgeDeleted(key),
+            ChangeEvent::VertexDeleted(b.id),
+        ]
+    );
+
+    // Changes made after dropping the receiver aren't sent anywhere
+    drop(receiver);
+    trans.create_vertex(&models::Vertex::new(t)).unwrap();
+}
+
+#[test]
+fn should_send_changes_from_every_write_path() {
+    use super::{ChangeEvent, MemoryDatastore};
+    use models;
+    use traits::{Datastore, WriteTransaction};
+    use util::generate_uuid_v1;
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let renamed_t = models::Type::new("renamed_vertex_type".to_string()).unwrap();
+    let a = models::Vertex::new(t.clone());
+    let b = models::Vertex::new(t.clone());
+    let receiver = datastore.subscribe();
+
+    datastore
+        .execute_batch(vec![models::BatchOperation::CreateVertex(a.clone())].into_iter())
+        .unwrap();
+    datastore
+        .bulk_insert_with_mode(
+            vec![models::BulkInsertItem::Vertex(b.clone())].into_iter(),
+            models::BulkInsertMode::Overwrite,
+        )
+        .unwrap();
+    let key = models::EdgeKey::new(a.id, models::Type::new("test_edge_type".to_string()).unwrap(), b.id);
+    trans.create_edge(&key).unwrap();
+    trans.create_edge(&key).unwrap();
+    let clone_id = generate_uuid_v1();
+    assert!(trans.clone_vertex(b.id, clone_id).unwrap());
+    assert_eq!(trans.rename_vertex_type(&t, &renamed_t).unwrap(), 3);
+    let q = models::VertexQuery::Vertices {
+        ids: vec![clone_id, clone_id],
+    };
+    assert_eq!(trans.delete_vertices(&q).unwrap(), 1);
+
+    let mut renamed_ids = vec![a.id, b.id, clone_id];
+    renamed_ids.sort();
+    let mut expected_events = vec![
+        ChangeEvent::VertexCreated(a.clone()),
+        ChangeEvent::VertexCreated(b.clone()),
+        ChangeEvent::EdgeCreated(key.clone()),
+        ChangeEvent::EdgeUpdated(key),
+        ChangeEvent::VertexCreated(models::Vertex::with_id(clone_id, t)),
+    ];
+    expected_events.extend(
+        renamed_ids
+            .into_iter()
+            .map(|id| ChangeEvent::VertexUpdated(models::Vertex::with_id(id, renamed_t.clone()))),
+    );
+    expected_events.push(ChangeEvent::VertexDeleted(clone_id));
+    assert_eq!(receiver.try_iter().collect::<Vec<ChangeEvent>>(), expected_events);
+}
+
+#[test]
+fn should_read_through_a_read_only_transaction() {
+    use super::MemoryDatastore;
+    use models;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    // Only compiles for transactions that can read
+    fn count_vertices<T: ReadTransaction>(trans: &T) -> u64 {
+        trans.get_vertex_count().unwrap()
+    }
+
+    let datastore = MemoryDatastore::default();
+    let read_trans = datastore.read_transaction().unwrap();
+    assert_eq!(count_vertices(&read_trans), 0);
+
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let v = models::Vertex::new(t);
+    datastore.transaction().unwrap().create_vertex(&v).unwrap();
+
+    // Writes made through other transactions are visible
+    assert_eq!(count_vertices(&read_trans), 1);
+    assert!(read_trans.vertex_exists(v.id).unwrap());
+    let vertices = read_trans.get_vertices(&models::VertexQuery::single(v.id)).unwrap();
+    assert_eq!(vertices, vec![v]);
+}
+
+#[test]
+fn should_create_weighted_edges() {
+    use super::MemoryDatastore;
+    use models;
+    use std::f64;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+    use uuid::Uuid;
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let a = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let b = trans.create_vertex_from_type(vertex_t).unwrap();
+    let weighted_key = models::EdgeKey::new(a, edge_t.clone(), b);
+    let unweighted_key = models::EdgeKey::new(b, edge_t.clone(), a);
+
+    assert!(trans.create_weighted_edge(&weighted_key, 0.5).unwrap());
+    assert!(trans.create_edge(&unweighted_key).unwrap());
+    assert!(!trans
+        .create_weighted_edge(&models::EdgeKey::new(a, edge_t, Uuid::default()), 1.0)
+        .unwrap());
+    assert!(trans.create_weighted_edge(&weighted_key, f64::NAN).is_err());
+
+    let get_weight = |key: &models::EdgeKey| {
+        let edges = trans.get_edges(&models::EdgeQuery::single(key.clone())).unwrap();
+        edges[0].weight
+    };
+
+    assert_eq!(get_weight(&weighted_key), Some(0.5));
+    assert_eq!(get_weight(&unweighted_key), None);
+
+    // Creating the edge again without a weight keeps the weight
+    assert!(trans.create_edge(&weighted_key).unwrap());
+    assert_eq!(get_weight(&weighted_key), Some(0.5));
+    assert!(trans.create_weighted_edge(&weighted_key, 2.0).unwrap());
+    assert_eq!(get_weight(&weighted_key), Some(2.0));
+
+    // Weights survive exporting and importing
+    let mut exported = Vec::new();
+    datastore.export_json(&mut exported).unwrap();
+    let imported_datastore = MemoryDatastore::default();
+    imported_datastore.import_json(&exported[..]).unwrap();
+    let imported_trans = imported_datastore.transaction().unwrap();
+    let edges = imported_trans
+        .get_edges(&models::EdgeQuery::single(weighted_key))
+        .unwrap();
+    assert_eq!(edges[0].weight, Some(2.0));
+}
+
+#[test]
+fn should_read_from_a_snapshot() {
+    use super::MemoryDatastore;
+    use models;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let first_id = trans.create_vertex_from_type(t.clone()).unwrap();
+
+    let snapshot = datastore.snapshot().unwrap();
+    let second_id = trans.create_vertex_from_type(t).unwrap();
+    trans.delete_vertices(&models::VertexQuery::single(first_id)).unwrap();
+
+    // The snapshot doesn't see writes made after it was taken
+    assert_eq!(snapshot.get_vertex_count().unwrap(), 1);
+    assert!(snapshot.vertex_exists(first_id).unwrap());
+    assert!(!snapshot.vertex_exists(second_id).unwrap());
+
+    // ...while the datastore does
+    let read_trans = datastore.read_transaction().unwrap();
+    assert_eq!(read_trans.get_vertex_count().unwrap(), 1);
+    assert!(!read_trans.vertex_exists(first_id).unwrap());
+    assert!(read_trans.vertex_exists(second_id).unwrap());
+}
+
+#[test]
+fn should_get_a_vertex_timeline() {
+    use super::{MemoryDatastore, MockClock};
+    use chrono::{Duration, TimeZone, Utc};
+    use models;
+    use traits::{Datastore, WriteTransaction};
+
+    let clock = MockClock::new(Utc.timestamp(1_500_000_000, 0));
+    let datastore = MemoryDatastore::default().with_clock(clock.clone());
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let first_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let second_id = trans.create_vertex_from_type(vertex_t).unwrap();
+
+    let outbound_key = models::EdgeKey::new(id, edge_t.clone(), first_id);
+    let inbound_key = models::EdgeKey::new(second_id, edge_t.clone(), id);
+    let other_key = models::EdgeKey::new(id, edge_t, second_id);
+
+    for key in &[&outbound_key, &inbound_key, &other_key] {
+        clock.advance(Duration::seconds(1));
+        trans.create_edge(key).unwrap();
+    }
+
+    // Creating an edge again moves it to the front
+    clock.advance(Duration::seconds(1));
+    trans.create_edge(&outbound_key).unwrap();
+
+    let get_keys = |id, limit| -> Vec<models::EdgeKey> {
+        trans
+            .get_vertex_timeline(id, limit)
+            .unwrap()
+            .into_iter()
+            .map(|edge| edge.key)
+            .collect()
+    };
+
+    assert_eq!(
+        get_keys(id, 10),
+        vec![outbound_key.clone(), other_key.clone(), inbound_key.clone()]
+    );
+    assert_eq!(get_keys(id, 2), vec![outbound_key.clone(), other_key.clone()]);
+    assert_eq!(get_keys(second_id, 10), vec![other_key.clone(), inbound_key.clone()]);
+
+    trans.delete_edges(&models::EdgeQuery::single(other_key)).unwrap();
+    assert_eq!(get_keys(id, 10), vec![outbound_key.clone(), inbound_key.clone()]);
+    assert_eq!(get_keys(second_id, 10), vec![inbound_key]);
+
+    trans.delete_vertices(&models::VertexQuery::single(id)).unwrap();
+    assert_eq!(get_keys(id, 10), vec![]);
+    assert_eq!(get_keys(first_id, 10), vec![]);
+    assert_eq!(get_keys(second_id, 10), vec![]);
+}
+
+#[test]
+fn should_record_edges_that_predate_their_vertices() {
+    use super::MemoryDatastore;
+    use models;
+    use serde_json;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, WriteTransaction};
+
+    let source_datastore = MemoryDatastore::default();
+    let source_trans = source_datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let outbound_id = source_trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let inbound_id = source_trans.create_vertex_from_type(vertex_t).unwrap();
+    let key = models::EdgeKey::new(
+        outbound_id,
+        models::Type::new("test_edge_type".to_string()).unwrap(),
+        inbound_id,
+    );
+    source_trans.create_edge(&key).unwrap();
+
+    // Backdate the edge to before its vertices were created, as can happen
+    // when importing data that was exported out of order
+    let mut exported = Vec::new();
+    source_datastore.export_json(&mut exported).unwrap();
+    let mut lines = String::new();
+
+    for line in String::from_utf8(exported).unwrap().lines() {
+        let mut record: JsonValue = serde_json::from_str(line).unwrap();
+
+        if record["kind"] == "edge" {
+            record["created_datetime"] = JsonValue::from("2000-01-01T00:00:00Z");
+        }
+
+        lines.push_str(&serde_json::to_string(&record).unwrap());
+        lines.push('\n');
+    }
+
+    let datastore = MemoryDatastore::default().with_edge_time_checks(true);
+    datastore.import_json(lines.as_bytes()).unwrap();
+    let trans = datastore.transaction().unwrap();
+    assert_eq!(trans.get_inconsistent_edges().unwrap(), vec![key.clone()]);
+
+    // Creating the edge again bumps its update datetime past its vertices'
+    trans.create_edge(&key).unwrap();
+    assert_eq!(trans.get_inconsistent_edges().unwrap(), vec![]);
+
+    // Edges aren't checked unless the checks are enabled
+    let unchecked_datastore = MemoryDatastore::default();
+    unchecked_datastore.import_json(lines.as_bytes()).unwrap();
+    let unchecked_trans = unchecked_datastore.transaction().unwrap();
+    assert_eq!(unchecked_trans.get_inconsistent_edges().unwrap(), vec![]);
+}
+
+#[test]
+fn should_check_edge_times_when_bulk_loading() {
+    use super::{MemoryDatastore, MockClock};
+    use chrono::{Duration, TimeZone, Utc};
+    use models;
+    use traits::Datastore;
+
+    let clock = MockClock::new(Utc.timestamp(1_500_000_000, 0));
+    let datastore = MemoryDatastore::default()
+        .with_clock(clock.clone())
+        .with_edge_time_checks(true);
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let outbound_v = models::Vertex::new(vertex_t.clone());
+    let inbound_v = models::Vertex::new(vertex_t);
+    let key = models::EdgeKey::new(
+        outbound_v.id,
+        models::Type::new("test_edge_type".to_string()).unwrap(),
+        inbound_v.id,
+    );
+    datastore
+        .bulk_load(
+            vec![
+                models::BulkInsertItem::Vertex(outbound_v),
+                models::BulkInsertItem::Vertex(inbound_v),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+    // Load the edge at a time before its vertices were created
+    clock.advance(Duration::days(-1));
+    datastore
+        .bulk_load(vec![models::BulkInsertItem::Edge(key.clone())].into_iter())
+        .unwrap();
+    let trans = datastore.transaction().unwrap();
+    assert_eq!(trans.get_inconsistent_edges().unwrap(), vec![key.clone()]);
+
+    // Loading it again later bumps its update datetime past its vertices'
+    clock.advance(Duration::days(2));
+    datastore
+        .bulk_load(vec![models::BulkInsertItem::Edge(key)].into_iter())
+        .unwrap();
+    assert_eq!(trans.get_inconsistent_edges().unwrap(), vec![]);
+}
+
+#[test]
+fn should_delete_outbound_edges_by_type() {
+    use super::{ChangeEvent, MemoryDatastore};
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let follows_t = models::Type::new("follows".to_string()).unwrap();
+    let likes_t = models::Type::new("likes".to_string()).unwrap();
+    let id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let other_ids: Vec<_> = (0..3)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+
+    let mut follows_keys: Vec<models::EdgeKey> = other_ids
+        .iter()
+        .map(|other_id| models::EdgeKey::new(id, follows_t.clone(), *other_id))
+        .collect();
+    follows_keys.sort();
+    let likes_key = models::EdgeKey::new(id, likes_t.clone(), other_ids[0]);
+
+    for key in follows_keys.iter().chain(Some(&likes_key)) {
+        trans.create_edge(key).unwrap();
+        trans
+            .set_edge_properties(&models::EdgeQuery::single(key.clone()), "weight", &JsonValue::from(1))
+            .unwrap();
+    }
+
+    let receiver = datastore.subscribe();
+    let q = models::VertexQuery::single(id).outbound_edges(Some(follows_t.clone()), None, None, 2);
+    assert_eq!(trans.delete_edges(&q).unwrap(), 2);
+
+    // The limit is honored, in key order
+    let events: Vec<ChangeEvent> = receiver.try_iter().collect();
+    assert_eq!(
+        events,
+        vec![
+            ChangeEvent::EdgeDeleted(follows_keys[0].clone()),
+            ChangeEvent::EdgeDeleted(follows_keys[1].clone()),
+        ]
+    );
+
+    let q = models::VertexQuery::single(id).outbound_edges(Some(follows_t.clone()), None, None, 10);
+    assert_eq!(trans.delete_edges(&q).unwrap(), 1);
+    assert!(trans.get_edges(&q).unwrap().is_empty());
+    assert_eq!(
+        trans.get_edge_count(id, None, models::EdgeDirection::Outbound).unwrap(),
+        1
+    );
+    assert!(trans.edge_exists(&likes_key).unwrap());
+
+    // The deleted edges' properties are gone, so they don't come back with
+    // the edges
+    for key in &follows_keys {
+        trans.create_edge(key).unwrap();
+    }
+
+    let properties = trans
+        .get_edge_properties(&models::EdgeQuery::Edges { keys: follows_keys }, "weight")
+        .unwrap();
+    assert_eq!(properties, vec![]);
+    let properties = trans
+        .get_edge_properties(&models::EdgeQuery::single(likes_key), "weight")
+        .unwrap();
+    assert_eq!(properties.len(), 1);
+}
+
+#[test]
+fn should_timestamp_with_the_injected_clock() {
+    use super::{MemoryDatastore, MockClock};
+    use chrono::{Duration, TimeZone, Utc};
+    use models;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let start = Utc.timestamp(1_500_000_000, 0);
+    let clock = MockClock::new(start);
+    let datastore = MemoryDatastore::default().with_clock(clock.clone());
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+
+    let mut keys = Vec::new();
+
+    for _ in 0..3 {
+        clock.advance(Duration::seconds(10));
+        let inbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+        let key = models::EdgeKey::new(id, edge_t.clone(), inbound_id);
+        trans.create_edge(&key).unwrap();
+        keys.push(key);
+    }
+
+    for (i, key) in keys.iter().enumerate() {
+        let edges = trans.get_edges(&models::EdgeQuery::single(key.clone())).unwrap();
+        assert_eq!(
+            edges[0].created_datetime,
+            start + Duration::seconds(10 * (i as i64 + 1))
+        );
+    }
+
+    // The filters are inclusive at both ends
+    let get_keys = |high_filter, low_filter| -> Vec<models::EdgeKey> {
+        let q = models::VertexQuery::single(id).outbound_edges(None, high_filter, low_filter, 10);
+        let mut keys: Vec<models::EdgeKey> = trans.get_edges(&q).unwrap().into_iter().map(|edge| edge.key).collect();
+        keys.sort_by_key(|key| key.inbound_id);
+        keys
+    };
+    let at = |seconds| Some(start + Duration::seconds(seconds));
+    let mut expected = vec![keys[0].clone(), keys[1].clone()];
+    expected.sort_by_key(|key| key.inbound_id);
+    assert_eq!(get_keys(at(20), at(10)), expected);
+    assert_eq!(get_keys(at(19), at(11)), vec![]);
+    assert_eq!(get_keys(None, at(30)), vec![keys[2].clone()]);
+
+    // Vertices are timestamped with the clock too
+    let q = models::VertexQuery::All {
+        start_id: None,
+        end_id: None,
+        high_filter: at(0),
+        low_filter: at(0),
+        limit: 10,
+    };
+    let ids: Vec<_> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(ids, vec![id]);
+}
+
+#[test]
+fn should_vacuum_and_check_integrity() {
+    use super::{IntegrityReport, MemoryDatastore};
+    use models;
+    use serde_json::Value as JsonValue;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let datastore = MemoryDatastore::default();
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let outbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let inbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let deleted_id = trans.create_vertex_from_type(vertex_t).unwrap();
+    let key = models::EdgeKey::new(outbound_id, edge_t.clone(), inbound_id);
+    let deleted_key = models::EdgeKey::new(outbound_id, edge_t, deleted_id);
+    trans.create_edge(&key).unwrap();
+    trans.create_edge(&deleted_key).unwrap();
+
+    for id in &[outbound_id, deleted_id] {
+        let q = models::VertexQuery::single(*id);
+        trans.set_vertex_properties(&q, "foo", &JsonValue::Bool(true)).unwrap();
+    }
+
+    for key in &[key.clone(), deleted_key.clone()] {
+        let q = models::EdgeQuery::single(key.clone());
+        trans.set_edge_properties(&q, "foo", &JsonValue::Bool(true)).unwrap();
+    }
+
+    trans.delete_vertices(&models::VertexQuery::single(deleted_id)).unwrap();
+
+    // Deletes clean up after themselves, so there's nothing to vacuum
+    let report = datastore.integrity_check().unwrap();
+    assert!(report.is_ok());
+    assert_eq!(report, IntegrityReport::default());
+    assert_eq!(datastore.vacuum().unwrap(), 0);
+
+    // ...and the live properties survive the vacuum
+    let q = models::VertexQuery::single(outbound_id);
+    assert_eq!(trans.get_vertex_properties(&q, "foo").unwrap().len(), 1);
+    let q = models::EdgeQuery::single(key);
+    assert_eq!(trans.get_edge_properties(&q, "foo").unwrap().len(), 1);
+    assert!(trans
+        .get_edges(&models::EdgeQuery::single(deleted_key))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn should_expire_edges_created_with_a_ttl() {
+    use super::{MemoryDatastore, MockClock};
+    use chrono::{Duration, TimeZone, Utc};
+    use models;
+    use std::time::Duration as StdDuration;
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+
+    let clock = MockClock::new(Utc.timestamp(1_500_000_000, 0));
+    let datastore = MemoryDatastore::default().with_clock(clock.clone());
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let outbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let first_inbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let second_inbound_id = trans.create_vertex_from_type(vertex_t).unwrap();
+    let expiring_key = models::EdgeKey::new(outbound_id, edge_t.clone(), first_inbound_id);
+    let permanent_key = models::EdgeKey::new(outbound_id, edge_t, second_inbound_id);
+
+    assert!(trans
+        .create_edge_with_ttl(&expiring_key, StdDuration::from_secs(10))
+        .unwrap());
+    assert!(trans.create_edge(&permanent_key).unwrap());
+
+    clock.advance(Duration::seconds(9));
+    assert!(trans.edge_exists(&expiring_key).unwrap());
+
+    // Expired edges are treated as absent by every read
+    clock.advance(Duration::seconds(1));
+    assert!(!trans.edge_exists(&expiring_key).unwrap());
+    let q = models::VertexQuery::single(outbound_id).outbound_edges(None, None, None, 10);
+    let keys: Vec<models::EdgeKey> = trans.get_edges(&q).unwrap().into_iter().map(|edge| edge.key).collect();
+    assert_eq!(keys, vec![permanent_key.clone()]);
+    assert_eq!(trans.get_cached_degree(outbound_id).unwrap(), (1, 0));
+    assert_eq!(trans.purge_expired().unwrap(), 0);
+
+    // Expired edges can be purged eagerly too
+    trans
+        .create_edge_with_ttl(&expiring_key, StdDuration::from_secs(10))
+        .unwrap();
+    clock.advance(Duration::seconds(10));
+    assert_eq!(trans.purge_expired().unwrap(), 1);
+    assert!(!trans.edge_exists(&expiring_key).unwrap());
+    assert!(trans.edge_exists(&permanent_key).unwrap());
+}
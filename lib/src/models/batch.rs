@@ -0,0 +1,18 @@
+use super::edges::EdgeKey;
+use super::queries::{EdgeQuery, VertexQuery};
+use super::vertices::Vertex;
+use serde_json::Value as JsonValue;
+
+/// A single operation of a batch. Each operation does the same as the
+/// transaction method of the same name.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum BatchOperation {
+    CreateVertex(Vertex),
+    CreateEdge(EdgeKey),
+    DeleteVertices(VertexQuery),
+    DeleteEdges(EdgeQuery),
+    SetVertexProperties(VertexQuery, String, JsonValue),
+    SetEdgeProperties(EdgeQuery, String, JsonValue),
+    DeleteVertexProperties(VertexQuery, String),
+    DeleteEdgeProperties(EdgeQuery, String),
+}
@@ -1,5 +1,8 @@
 use super::edges::EdgeKey;
+use super::types::Type;
 use super::vertices::Vertex;
+use chrono::offset::Utc;
+use chrono::DateTime;
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
@@ -10,3 +13,141 @@ pub enum BulkInsertItem {
     VertexProperty(Uuid, String, JsonValue),
     EdgeProperty(EdgeKey, String, JsonValue),
 }
+
+/// How a bulk insert handles items that already exist in the datastore.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BulkInsertMode {
+    /// Existing vertices, edges and properties are overwritten.
+    Overwrite,
+    /// Existing vertices, edges and properties are left as-is, and the
+    /// items are skipped.
+    Skip,
+    /// The bulk insert fails, without inserting anything, if any item
+    /// already exists.
+    Error,
+}
+
+/// The reason a single item of a partial bulk insert could not be inserted.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum BulkInsertItemError {
+    /// A vertex referenced by the item does not exist.
+    MissingVertex,
+    /// The edge referenced by the item does not exist.
+    MissingEdge,
+    /// The item could not be inserted due to an unexpected error.
+    Internal(String),
+}
+
+/// A single record of a serialized graph, as written by
+/// `MemoryDatastore::export_json` and read by `Datastore::import_compressed`.
+/// Each record is serialized as a JSON object on its own line, with a `kind`
+/// field of `vertex`, `edge`, `vertex_property` or `edge_property`. See
+/// `MemoryDatastore::export_json` for a description of the format.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum ExportRecord {
+    #[serde(rename = "vertex")]
+    Vertex {
+        id: Uuid,
+        #[serde(rename = "type")]
+        t: Type,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        created_datetime: Option<DateTime<Utc>>,
+    },
+    #[serde(rename = "edge")]
+    Edge {
+        outbound_id: Uuid,
+        #[serde(rename = "type")]
+        t: Type,
+        inbound_id: Uuid,
+        created_datetime: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        undirected: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        weight: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_datetime: Option<DateTime<Utc>>,
+    },
+    #[serde(rename = "vertex_property")]
+    VertexProperty {
+        id: Uuid,
+        name: String,
+        value: JsonValue,
+        // Missing from exports made before properties tracked when they
+        // were updated
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        updated_datetime: Option<DateTime<Utc>>,
+    },
+    #[serde(rename = "edge_property")]
+    EdgeProperty {
+        outbound_id: Uuid,
+        #[serde(rename = "type")]
+        t: Type,
+        inbound_id: Uuid,
+        name: String,
+        value: JsonValue,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        updated_datetime: Option<DateTime<Utc>>,
+    },
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+// Only the parts of a record that `bulk_insert` can express are kept: the
+// datetimes, weights and expirations of edges are dropped, and undirected
+// edges become directed ones.
+impl From<ExportRecord> for BulkInsertItem {
+    fn from(record: ExportRecord) -> Self {
+        match record {
+            ExportRecord::Vertex { id, t, .. } => BulkInsertItem::Vertex(Vertex::with_id(id, t)),
+            ExportRecord::Edge {
+                outbound_id,
+                t,
+                inbound_id,
+                ..
+            } => BulkInsertItem::Edge(EdgeKey::new(outbound_id, t, inbound_id)),
+            ExportRecord::VertexProperty { id, name, value, .. } => BulkInsertItem::VertexProperty(id, name, value),
+            ExportRecord::EdgeProperty {
+                outbound_id,
+                t,
+                inbound_id,
+                name,
+                value,
+                ..
+            } => BulkInsertItem::EdgeProperty(EdgeKey::new(outbound_id, t, inbound_id), name, value),
+        }
+    }
+}
+
+/// Counts of the records read from a serialized graph. Records are counted
+/// as they're parsed, so edges and properties that refer to vertices or
+/// edges that don't exist, and so are skipped, are still counted.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ImportReport {
+    /// The number of vertex records parsed.
+    pub parsed_vertices: u64,
+    /// The number of edge records parsed.
+    pub parsed_edges: u64,
+    /// The number of vertex property records parsed.
+    pub parsed_vertex_properties: u64,
+    /// The number of edge property records parsed.
+    pub parsed_edge_properties: u64,
+}
+
+impl ImportReport {
+    /// Counts a record towards the report.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The parsed record.
+    pub fn record(&mut self, record: &ExportRecord) {
+        match *record {
+            ExportRecord::Vertex { .. } => self.parsed_vertices += 1,
+            ExportRecord::Edge { .. } => self.parsed_edges += 1,
+            ExportRecord::VertexProperty { .. } => self.parsed_vertex_properties += 1,
+            ExportRecord::EdgeProperty { .. } => self.parsed_edge_properties += 1,
+        }
+    }
+}
@@ -1,9 +1,24 @@
 use super::types::Type;
 use chrono::offset::Utc;
 use chrono::DateTime;
+use errors::ValidationError;
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 
 /// Represents a uniquely identifiable key to an edge.
+///
+/// Edge keys have a total ordering: they're compared by outbound id first,
+/// then by type, and then by inbound id. Ids are compared by their bytes,
+/// and types by their names. This ordering is guaranteed to be stable, so
+/// all of the edges going out of a vertex are always adjacent when sorted.
+///
+/// Edge keys have a canonical string form, `outbound_id:type:inbound_id`,
+/// with the ids hyphenated, e.g.
+/// `8d0ef5a6-1ba4-11e9-8db6-0242ac120002:follows:911c1cb2-1ba4-11e9-8db6-0242ac120002`.
+/// Ids never contain colons, so the type is everything between the first
+/// and last colons, even if it had colons of its own. Parsing a key's string
+/// form gives back the same key, as long as its type is valid.
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub struct EdgeKey {
     /// The id of the outbound vertex.
@@ -32,6 +47,45 @@ impl EdgeKey {
             inbound_id,
         }
     }
+
+    /// Returns the key to the edge going in the opposite direction, i.e.
+    /// with the same type, but with the outbound and inbound ids swapped.
+    pub fn reversed(&self) -> EdgeKey {
+        EdgeKey::new(self.inbound_id, self.t.clone(), self.outbound_id)
+    }
+
+    /// Returns the canonical form of the key, i.e. the key itself or its
+    /// reversal, whichever has the smaller outbound id. This is the key that
+    /// undirected edges are stored under, so that an undirected edge between
+    /// two vertices has one key regardless of the order they're given in.
+    pub fn canonical(&self) -> EdgeKey {
+        if self.inbound_id < self.outbound_id {
+            self.reversed()
+        } else {
+            self.clone()
+        }
+    }
+}
+
+impl fmt::Display for EdgeKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.outbound_id, self.t.0, self.inbound_id)
+    }
+}
+
+impl FromStr for EdgeKey {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<EdgeKey, Self::Err> {
+        let (outbound_id, inbound_id, t) = match (s.find(':'), s.rfind(':')) {
+            (Some(first), Some(last)) if first < last => (&s[..first], &s[last + 1..], &s[first + 1..last]),
+            _ => return Err("Edge key should be of the form `outbound_id:type:inbound_id`".into()),
+        };
+
+        let outbound_id = Uuid::parse_str(outbound_id).map_err(|_| ValidationError::from("Invalid outbound id"))?;
+        let inbound_id = Uuid::parse_str(inbound_id).map_err(|_| ValidationError::from("Invalid inbound id"))?;
+        Ok(EdgeKey::new(outbound_id, Type::from_str(t)?, inbound_id))
+    }
 }
 
 /// An edge.
@@ -46,6 +100,11 @@ pub struct Edge {
 
     /// When the edge was created.
     pub created_datetime: DateTime<Utc>,
+
+    /// The weight of the edge, if it has one. Only some datastores support
+    /// edge weights; see the documentation of individual implementations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
 }
 
 impl Edge {
@@ -63,7 +122,21 @@ impl Edge {
     /// * `key` - The key to the edge.
     /// * `created_datetime` - When the edge was created.
     pub fn new(key: EdgeKey, created_datetime: DateTime<Utc>) -> Edge {
-        Edge { key, created_datetime }
+        Self::with_weight(key, created_datetime, None)
+    }
+
+    /// Creates a new edge with a specified datetime and weight.
+    ///
+    /// # Arguments
+    /// * `key` - The key to the edge.
+    /// * `created_datetime` - When the edge was created.
+    /// * `weight` - The weight of the edge, if it has one.
+    pub fn with_weight(key: EdgeKey, created_datetime: DateTime<Utc>, weight: Option<f64>) -> Edge {
+        Edge {
+            key,
+            created_datetime,
+            weight,
+        }
     }
 }
 
@@ -72,6 +145,8 @@ mod tests {
     use super::{Edge, EdgeKey};
     use chrono::Utc;
     use models::Type;
+    use std::str::FromStr;
+    use util::generate_uuid_v1;
     use uuid::Uuid;
 
     #[test]
@@ -85,4 +160,110 @@ mod tests {
         assert!(edge.created_datetime >= start_datetime);
         assert!(edge.created_datetime <= end_datetime);
     }
+
+    #[test]
+    fn should_reverse_edge_key() {
+        let outbound_id = generate_uuid_v1();
+        let inbound_id = generate_uuid_v1();
+        let t = Type::new("test_edge_type".to_string()).unwrap();
+        let key = EdgeKey::new(outbound_id, t.clone(), inbound_id);
+        assert_eq!(key.reversed(), EdgeKey::new(inbound_id, t, outbound_id));
+        assert_eq!(key.reversed().reversed(), key);
+    }
+
+    #[test]
+    fn should_canonicalize_edge_key() {
+        let low_id = Uuid::from_slice(&[0; 16]).unwrap();
+        let high_id = Uuid::from_slice(&[1; 16]).unwrap();
+        let t = Type::new("test_edge_type".to_string()).unwrap();
+        let key = EdgeKey::new(low_id, t.clone(), high_id);
+        assert_eq!(key.canonical(), key);
+        assert_eq!(key.reversed().canonical(), key);
+
+        let self_loop = EdgeKey::new(high_id, t, high_id);
+        assert_eq!(self_loop.canonical(), self_loop);
+    }
+
+    #[test]
+    fn should_order_edge_keys() {
+        let low_id = Uuid::from_slice(&[0; 16]).unwrap();
+        let high_id = Uuid::from_slice(&[1; 16]).unwrap();
+        let a = Type::new("a".to_string()).unwrap();
+        let b = Type::new("b".to_string()).unwrap();
+
+        let mut keys = vec![
+            EdgeKey::new(high_id, a.clone(), low_id),
+            EdgeKey::new(low_id, b.clone(), low_id),
+            EdgeKey::new(low_id, a.clone(), high_id),
+            EdgeKey::new(low_id, a.clone(), low_id),
+        ];
+        keys.sort();
+
+        assert_eq!(
+            keys,
+            vec![
+                EdgeKey::new(low_id, a.clone(), low_id),
+                EdgeKey::new(low_id, a.clone(), high_id),
+                EdgeKey::new(low_id, b, low_id),
+                EdgeKey::new(high_id, a, low_id),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_convert_edge_key_to_and_from_string() {
+        let outbound_id = Uuid::from_slice(&[0x12; 16]).unwrap();
+        let inbound_id = Uuid::from_slice(&[0xab; 16]).unwrap();
+        let t = Type::new("Test-Edge_type9".to_string()).unwrap();
+        let key = EdgeKey::new(outbound_id, t, inbound_id);
+        let s = key.to_string();
+        assert_eq!(s, format!("{}:Test-Edge_type9:{}", outbound_id, inbound_id));
+        assert_eq!(EdgeKey::from_str(&s).unwrap(), key);
+
+        // Self-loops and generated ids round-trip too
+        for key in &[
+            EdgeKey::new(outbound_id, Type::new("a".to_string()).unwrap(), outbound_id),
+            EdgeKey::new(
+                generate_uuid_v1(),
+                Type::new("b".to_string()).unwrap(),
+                generate_uuid_v1(),
+            ),
+        ] {
+            assert_eq!(&EdgeKey::from_str(&key.to_string()).unwrap(), key);
+        }
+    }
+
+    #[test]
+    fn should_not_parse_invalid_edge_keys() {
+        let id = Uuid::from_slice(&[0x12; 16]).unwrap().to_string();
+
+        for s in &[
+            "".to_string(),
+            id.clone(),
+            format!("{}:foo", id),
+            format!("{}::{}", id, id),
+            format!("{}:foo:bar", id),
+            format!("bar:foo:{}", id),
+            format!("{}:foo$:{}", id, id),
+            format!("{}:foo:{}:", id, id),
+        ] {
+            assert!(EdgeKey::from_str(s).is_err(), "parsed {:?}", s);
+        }
+    }
+
+    #[test]
+    fn should_split_edge_keys_at_the_outermost_colons() {
+        let outbound_id = Uuid::from_slice(&[0x12; 16]).unwrap();
+        let inbound_id = Uuid::from_slice(&[0xab; 16]).unwrap();
+
+        // Types can't have colons, but one that did would still be found
+        // whole between the ids rather than being mistaken for them
+        let key = EdgeKey::new(outbound_id, Type("foo:bar".to_string()), inbound_id);
+        let s = key.to_string();
+        assert_eq!(s, format!("{}:foo:bar:{}", outbound_id, inbound_id));
+        assert_eq!(
+            EdgeKey::from_str(&s).unwrap_err().to_string(),
+            Type::from_str("foo:bar").unwrap_err().to_string()
+        );
+    }
 }
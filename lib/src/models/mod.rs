@@ -1,13 +1,24 @@
+mod batch;
 mod bulk_insert;
 mod edges;
 mod properties;
 mod queries;
+mod records;
+mod stats;
+mod traversal;
 mod types;
 mod vertices;
 
-pub use self::bulk_insert::BulkInsertItem;
+pub use self::batch::BatchOperation;
+pub use self::bulk_insert::{BulkInsertItem, BulkInsertItemError, BulkInsertMode, ExportRecord, ImportReport};
 pub use self::edges::{Edge, EdgeKey};
-pub use self::properties::{EdgeProperty, VertexProperty};
-pub use self::queries::{EdgeDirection, EdgeQuery, VertexQuery};
+pub use self::properties::{
+    compare_json, AggregateOp, ComparisonOperator, EdgeProperty, NamedProperty, PropertyCondition, PropertyValueType,
+    VertexProperty,
+};
+pub use self::queries::{parse_query, EdgeDirection, EdgeQuery, Query, QueryError, SortDirection, VertexQuery};
+pub use self::records::{EdgeRecord, FullRecord};
+pub use self::stats::GraphStats;
+pub use self::traversal::TraversalLimits;
 pub use self::types::Type;
 pub use self::vertices::Vertex;
@@ -1,13 +1,17 @@
 use super::edges::EdgeKey;
 use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
 use uuid::Uuid;
 
 /// Represents a vertex property.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct VertexProperty {
-    /// The id of the vertex
+    /// The id of the vertex.
     pub id: Uuid,
 
+    /// The name of the property.
+    pub name: String,
+
     /// The property value.
     pub value: JsonValue,
 }
@@ -18,9 +22,10 @@ impl VertexProperty {
     /// # Arguments
     ///
     /// * `id` - The id of the vertex.
+    /// * `name` - The name of the property.
     /// * `value` - The property value.
-    pub fn new(id: Uuid, value: JsonValue) -> Self {
-        Self { id, value }
+    pub fn new(id: Uuid, name: String, value: JsonValue) -> Self {
+        Self { id, name, value }
     }
 }
 
@@ -30,6 +35,9 @@ pub struct EdgeProperty {
     /// The key to the edge.
     pub key: EdgeKey,
 
+    /// The name of the property.
+    pub name: String,
+
     /// The property value.
     pub value: JsonValue,
 }
@@ -40,8 +48,362 @@ impl EdgeProperty {
     /// # Arguments
     ///
     /// * `key` - The key to the edge.
+    /// * `name` - The name of the property.
+    /// * `value` - The property value.
+    pub fn new(key: EdgeKey, name: String, value: JsonValue) -> Self {
+        Self { key, name, value }
+    }
+}
+
+/// Represents a property along with its name.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NamedProperty {
+    /// The name of the property.
+    pub name: String,
+
+    /// The property value.
+    pub value: JsonValue,
+}
+
+impl NamedProperty {
+    /// Creates a new named property.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the property.
     /// * `value` - The property value.
-    pub fn new(key: EdgeKey, value: JsonValue) -> Self {
-        Self { key, value }
+    pub fn new(name: String, value: JsonValue) -> Self {
+        Self { name, value }
+    }
+}
+
+/// The JSON type that the values of a property are required to have.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum PropertyValueType {
+    #[serde(rename = "null")]
+    Null,
+    #[serde(rename = "bool")]
+    Bool,
+    #[serde(rename = "number")]
+    Number,
+    #[serde(rename = "string")]
+    String,
+    #[serde(rename = "array")]
+    Array,
+    #[serde(rename = "object")]
+    Object,
+}
+
+impl PropertyValueType {
+    /// Checks whether a value is of this type.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to check.
+    pub fn matches(self, value: &JsonValue) -> bool {
+        matches!(
+            (self, value),
+            (PropertyValueType::Null, &JsonValue::Null)
+                | (PropertyValueType::Bool, &JsonValue::Bool(_))
+                | (PropertyValueType::Number, &JsonValue::Number(_))
+                | (PropertyValueType::String, &JsonValue::String(_))
+                | (PropertyValueType::Array, &JsonValue::Array(_))
+                | (PropertyValueType::Object, &JsonValue::Object(_))
+        )
+    }
+}
+
+/// An operator for comparing a property value against another value.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ComparisonOperator {
+    #[serde(rename = "eq")]
+    Equal,
+    #[serde(rename = "ne")]
+    NotEqual,
+    #[serde(rename = "gt")]
+    GreaterThan,
+    #[serde(rename = "gte")]
+    GreaterThanOrEqual,
+    #[serde(rename = "lt")]
+    LessThan,
+    #[serde(rename = "lte")]
+    LessThanOrEqual,
+}
+
+/// A condition on the value of a named property, e.g. `spend > 1000`.
+///
+/// Equality compares JSON values structurally. The ordering operators
+/// compare values with `compare_json`, but only apply to values of the same
+/// kind, e.g. two numbers or two strings; a value of a different kind does
+/// not match. Items that don't have the property never match.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PropertyCondition {
+    /// The property name.
+    pub name: String,
+
+    /// The operator to compare with.
+    pub operator: ComparisonOperator,
+
+    /// The value to compare against.
+    pub value: JsonValue,
+}
+
+impl PropertyCondition {
+    /// Creates a new property condition.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The property name.
+    /// * `operator` - The operator to compare with.
+    /// * `value` - The value to compare against.
+    pub fn new(name: String, operator: ComparisonOperator, value: JsonValue) -> Self {
+        Self { name, operator, value }
+    }
+
+    /// Checks whether a property value satisfies the condition.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The property value to check.
+    pub fn matches(&self, value: &JsonValue) -> bool {
+        let ordering = if json_kind_rank(value) == json_kind_rank(&self.value) {
+            Some(compare_json(value, &self.value))
+        } else {
+            None
+        };
+
+        match self.operator {
+            ComparisonOperator::Equal => value == &self.value,
+            ComparisonOperator::NotEqual => value != &self.value,
+            ComparisonOperator::GreaterThan => ordering == Some(Ordering::Greater),
+            ComparisonOperator::GreaterThanOrEqual => ordering.is_some_and(|o| o != Ordering::Less),
+            ComparisonOperator::LessThan => ordering == Some(Ordering::Less),
+            ComparisonOperator::LessThanOrEqual => ordering.is_some_and(|o| o != Ordering::Greater),
+        }
+    }
+}
+
+/// An operation for aggregating numeric property values.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum AggregateOp {
+    #[serde(rename = "sum")]
+    Sum,
+    #[serde(rename = "avg")]
+    Avg,
+    #[serde(rename = "min")]
+    Min,
+    #[serde(rename = "max")]
+    Max,
+    #[serde(rename = "count")]
+    Count,
+}
+
+impl AggregateOp {
+    /// Aggregates property values. Values that aren't numbers are skipped.
+    /// Returns `None` if there are no numeric values, except for `Count`,
+    /// which returns zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The property values to aggregate.
+    pub fn aggregate<'a, I: IntoIterator<Item = &'a JsonValue>>(self, values: I) -> Option<f64> {
+        let mut count = 0;
+        let mut sum = 0.0;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+
+        for value in values.into_iter().filter_map(|value| value.as_f64()) {
+            count += 1;
+            sum += value;
+            min = min.min(value);
+            max = max.max(value);
+        }
+
+        match self {
+            AggregateOp::Count => Some(f64::from(count)),
+            _ if count == 0 => None,
+            AggregateOp::Sum => Some(sum),
+            AggregateOp::Avg => Some(sum / f64::from(count)),
+            AggregateOp::Min => Some(min),
+            AggregateOp::Max => Some(max),
+        }
+    }
+}
+
+/// Compares two JSON values, using the total ordering that range filters
+/// and sorts on property values rely on. Values of different kinds are
+/// ordered by kind: null < bool < number < string < array < object.
+/// Within a kind:
+///
+/// * `false` is less than `true`.
+/// * Numbers are compared numerically, so `1` equals `1.0`. Integers are
+///   compared exactly, even beyond the precision of a float.
+/// * Strings are compared lexically, by their bytes.
+/// * Arrays are compared element by element, and a shorter array is less
+///   than a longer one that it's a prefix of.
+/// * Objects are compared by their entries, in key order, as if they were
+///   arrays of key/value pairs.
+///
+/// # Arguments
+///
+/// * `a` - The first value.
+/// * `b` - The second value.
+pub fn compare_json(a: &JsonValue, b: &JsonValue) -> Ordering {
+    match (a, b) {
+        (&JsonValue::Bool(a), &JsonValue::Bool(b)) => a.cmp(&b),
+        (JsonValue::Number(a), JsonValue::Number(b)) => {
+            if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+                a.cmp(&b)
+            } else if let (Some(a), Some(b)) = (a.as_u64(), b.as_u64()) {
+                a.cmp(&b)
+            } else {
+                // JSON numbers are never NaN, so this always has an ordering
+                let a = a.as_f64().unwrap_or(0.0);
+                let b = b.as_f64().unwrap_or(0.0);
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            }
+        }
+        (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            for (a, b) in a.iter().zip(b.iter()) {
+                let ordering = compare_json(a, b);
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+
+            a.len().cmp(&b.len())
+        }
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            let mut a_entries: Vec<(&String, &JsonValue)> = a.iter().collect();
+            let mut b_entries: Vec<(&String, &JsonValue)> = b.iter().collect();
+            a_entries.sort_by(|first, second| first.0.cmp(second.0));
+            b_entries.sort_by(|first, second| first.0.cmp(second.0));
+
+            for (&(a_key, a_value), &(b_key, b_value)) in a_entries.iter().zip(b_entries.iter()) {
+                let ordering = a_key.cmp(b_key).then_with(|| compare_json(a_value, b_value));
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+
+            a_entries.len().cmp(&b_entries.len())
+        }
+        _ => json_kind_rank(a).cmp(&json_kind_rank(b)),
+    }
+}
+
+// The position of a value's kind in the ordering used by `compare_json`.
+fn json_kind_rank(value: &JsonValue) -> u8 {
+    match *value {
+        JsonValue::Null => 0,
+        JsonValue::Bool(_) => 1,
+        JsonValue::Number(_) => 2,
+        JsonValue::String(_) => 3,
+        JsonValue::Array(_) => 4,
+        JsonValue::Object(_) => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare_json, AggregateOp, ComparisonOperator, PropertyCondition, PropertyValueType};
+    use serde_json;
+    use serde_json::Value as JsonValue;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn should_match_property_value_types() {
+        assert!(PropertyValueType::Number.matches(&JsonValue::from(30)));
+        assert!(PropertyValueType::String.matches(&JsonValue::from("thirty")));
+        assert!(PropertyValueType::Bool.matches(&JsonValue::Bool(true)));
+        assert!(PropertyValueType::Null.matches(&JsonValue::Null));
+        assert!(!PropertyValueType::Number.matches(&JsonValue::from("thirty")));
+        assert!(!PropertyValueType::Number.matches(&JsonValue::Null));
+        assert!(!PropertyValueType::Object.matches(&JsonValue::Array(vec![])));
+    }
+
+    #[test]
+    fn should_match_property_conditions() {
+        let condition = |operator| PropertyCondition::new("spend".to_string(), operator, JsonValue::from(1000));
+        assert!(condition(ComparisonOperator::GreaterThan).matches(&JsonValue::from(1500.5)));
+        assert!(!condition(ComparisonOperator::GreaterThan).matches(&JsonValue::from(1000)));
+        assert!(condition(ComparisonOperator::GreaterThanOrEqual).matches(&JsonValue::from(1000)));
+        assert!(condition(ComparisonOperator::LessThan).matches(&JsonValue::from(-5)));
+        assert!(condition(ComparisonOperator::LessThanOrEqual).matches(&JsonValue::from(1000)));
+        assert!(condition(ComparisonOperator::Equal).matches(&JsonValue::from(1000)));
+        assert!(condition(ComparisonOperator::NotEqual).matches(&JsonValue::from("1000")));
+
+        // Ordering comparisons between different kinds of values never match
+        assert!(!condition(ComparisonOperator::GreaterThan).matches(&JsonValue::from("2000")));
+        assert!(!condition(ComparisonOperator::LessThan).matches(&JsonValue::Null));
+
+        let condition = PropertyCondition::new("name".to_string(), ComparisonOperator::LessThan, JsonValue::from("m"));
+        assert!(condition.matches(&JsonValue::from("alice")));
+        assert!(!condition.matches(&JsonValue::from("zed")));
+    }
+
+    #[test]
+    fn should_compare_json_values() {
+        let parse = |s| serde_json::from_str::<JsonValue>(s).unwrap();
+        let ordered = vec![
+            JsonValue::Null,
+            JsonValue::Bool(false),
+            JsonValue::Bool(true),
+            JsonValue::from(-5),
+            JsonValue::from(1.5),
+            JsonValue::from(u64::MAX),
+            JsonValue::from(""),
+            JsonValue::from("alice"),
+            JsonValue::from("bob"),
+            parse("[]"),
+            parse("[1, 2]"),
+            parse("[1, 2, 3]"),
+            parse("[1, 3]"),
+            parse("{}"),
+            parse(r#"{"a": 1}"#),
+            parse(r#"{"a": 2}"#),
+            parse(r#"{"b": 1}"#),
+        ];
+
+        for (i, a) in ordered.iter().enumerate() {
+            for (j, b) in ordered.iter().enumerate() {
+                assert_eq!(compare_json(a, b), i.cmp(&j), "comparing {} to {}", a, b);
+            }
+        }
+
+        assert_eq!(
+            compare_json(&JsonValue::from(1), &JsonValue::from(1.0)),
+            Ordering::Equal
+        );
+        assert_eq!(
+            compare_json(&JsonValue::from(i64::MAX), &JsonValue::from(i64::MAX - 1)),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn should_aggregate_numeric_values() {
+        let values = vec![
+            JsonValue::from(2),
+            JsonValue::from(4.5),
+            JsonValue::from("10"),
+            JsonValue::Null,
+            JsonValue::from(-1),
+        ];
+        assert_eq!(AggregateOp::Sum.aggregate(&values), Some(5.5));
+        assert_eq!(AggregateOp::Avg.aggregate(&values), Some(5.5 / 3.0));
+        assert_eq!(AggregateOp::Min.aggregate(&values), Some(-1.0));
+        assert_eq!(AggregateOp::Max.aggregate(&values), Some(4.5));
+        assert_eq!(AggregateOp::Count.aggregate(&values), Some(3.0));
+
+        let values = vec![JsonValue::from("10")];
+        assert_eq!(AggregateOp::Sum.aggregate(&values), None);
+        assert_eq!(AggregateOp::Avg.aggregate(&values), None);
+        assert_eq!(AggregateOp::Min.aggregate(&values), None);
+        assert_eq!(AggregateOp::Max.aggregate(&values), None);
+        assert_eq!(AggregateOp::Count.aggregate(&values), Some(0.0));
     }
 }
@@ -3,7 +3,9 @@ use super::types::Type;
 use chrono::offset::Utc;
 use chrono::DateTime;
 use errors;
-use std::str::FromStr;
+use std::cmp;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
 use uuid::Uuid;
 
 /// Specifies what kind of items should be piped from one type of query to
@@ -12,14 +14,16 @@ use uuid::Uuid;
 /// Edge and vertex queries can build off of one another via pipes - e.g. you
 /// can get the outbound edges of a set of vertices by piping from a vertex
 /// query to an edge query. `EdgeDirection`s are used to specify which
-/// end of things you want to pipe - either the outbound items or the inbound
-/// items.
+/// end of things you want to pipe - either the outbound items, the inbound
+/// items, or both.
 #[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize, Hash, Copy)]
 pub enum EdgeDirection {
     #[serde(rename = "outbound")]
     Outbound,
     #[serde(rename = "inbound")]
     Inbound,
+    #[serde(rename = "both")]
+    Both,
 }
 
 impl FromStr for EdgeDirection {
@@ -29,6 +33,7 @@ impl FromStr for EdgeDirection {
         match s {
             "outbound" => Ok(EdgeDirection::Outbound),
             "inbound" => Ok(EdgeDirection::Inbound),
+            "both" => Ok(EdgeDirection::Both),
             _ => Err("invalid value".into()),
         }
     }
@@ -39,6 +44,37 @@ impl From<EdgeDirection> for String {
         match d {
             EdgeDirection::Outbound => "outbound".to_string(),
             EdgeDirection::Inbound => "inbound".to_string(),
+            EdgeDirection::Both => "both".to_string(),
+        }
+    }
+}
+
+/// Specifies the order in which sorted results should be returned.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize, Hash, Copy)]
+pub enum SortDirection {
+    #[serde(rename = "ascending")]
+    Ascending,
+    #[serde(rename = "descending")]
+    Descending,
+}
+
+impl FromStr for SortDirection {
+    type Err = errors::ValidationError;
+
+    fn from_str(s: &str) -> Result<SortDirection, Self::Err> {
+        match s {
+            "ascending" => Ok(SortDirection::Ascending),
+            "descending" => Ok(SortDirection::Descending),
+            _ => Err("invalid value".into()),
+        }
+    }
+}
+
+impl From<SortDirection> for String {
+    fn from(d: SortDirection) -> Self {
+        match d {
+            SortDirection::Ascending => "ascending".to_string(),
+            SortDirection::Descending => "descending".to_string(),
         }
     }
 }
@@ -47,24 +83,116 @@ impl From<EdgeDirection> for String {
 ///
 /// This is used by transactions to get, set and delete vertices and vertex
 /// properties.
+///
+/// Every `limit` in vertex and edge queries is a hard cap on the number of
+/// results, so a limit of `0` always yields no results; it never means
+/// "unlimited". To get everything, use `u32::MAX`.
+///
+/// Limits are `u32`s everywhere, including over the wire, so a single query
+/// never returns more than `u32::MAX` results, even on 64-bit platforms.
+/// Larger result sets have to be paged through, e.g. by setting `start_id`
+/// of `VertexQuery::All` to just past the last vertex of the previous page.
 #[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize, Hash)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum VertexQuery {
+    /// Gets vertices ordered by id, starting from `start_id` if it's set.
+    /// If `end_id` is set, only vertices whose id is less than it are
+    /// included, so that a range of ids can be fetched without
+    /// over-fetching. `high_filter` and `low_filter` restrict the vertices
+    /// to those created at or before, and at or after, the given datetimes.
     All {
         start_id: Option<Uuid>,
+        end_id: Option<Uuid>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        high_filter: Option<DateTime<Utc>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        low_filter: Option<DateTime<Utc>>,
         limit: u32,
     },
     Vertices {
         ids: Vec<Uuid>,
     },
+    /// Gets the vertices whose type is any of `types`, ordered by id.
+    Types {
+        types: Vec<Type>,
+        limit: u32,
+    },
+    /// Gets the vertices at the given end of the edges of `edge_query`,
+    /// skipping the first `offset` edges.
     Pipe {
         edge_query: Box<EdgeQuery>,
         converter: EdgeDirection,
+        #[serde(default, skip_serializing_if = "is_zero")]
+        offset: u32,
+        limit: u32,
+    },
+    /// Gets the vertices whose property `name` is a string containing
+    /// `substring`, compared case-insensitively, ordered by id. Vertices
+    /// whose property isn't a string are skipped. This scans every vertex.
+    PropertyContains {
+        name: String,
+        substring: String,
+        limit: u32,
+    },
+    /// Gets the vertices reachable from the vertices of `start` within
+    /// `depth` hops, following edges in the given `direction`. Each vertex is
+    /// visited at most once, so cycles are handled; the starting vertices
+    /// themselves are not included in the results.
+    Traverse {
+        start: Box<VertexQuery>,
+        direction: EdgeDirection,
+        type_filter: Option<Type>,
+        depth: u32,
+        limit: u32,
+    },
+    /// Gets the vertices of `start`, or of every vertex if it isn't set,
+    /// that have at least `min` edges in the given `direction`, optionally
+    /// only counting edges of type `type_filter`.
+    ///
+    /// The candidate vertices are all fetched up front and have their edges
+    /// counted one at a time, so this can be expensive, especially without a
+    /// `start` query. Counting stops once `limit` vertices have been found.
+    DegreeAtLeast {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start: Option<Box<VertexQuery>>,
+        direction: EdgeDirection,
+        type_filter: Option<Type>,
+        min: u64,
         limit: u32,
     },
 }
 
 impl VertexQuery {
+    /// Creates a query for all vertices, ordered by id.
+    ///
+    /// # Arguments
+    /// * `limit` - The maximum number of vertices to get.
+    pub fn all(limit: u32) -> VertexQuery {
+        VertexQuery::All {
+            start_id: None,
+            end_id: None,
+            high_filter: None,
+            low_filter: None,
+            limit,
+        }
+    }
+
+    /// Creates a query for a single vertex.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    pub fn single(id: Uuid) -> VertexQuery {
+        VertexQuery::Vertices { ids: vec![id] }
+    }
+
+    /// Creates a query for specific vertices.
+    ///
+    /// # Arguments
+    /// * `ids` - The ids of the vertices.
+    pub fn ids(ids: Vec<Uuid>) -> VertexQuery {
+        VertexQuery::Vertices { ids }
+    }
+
     pub fn outbound_edges(
         self,
         type_filter: Option<Type>,
@@ -76,8 +204,11 @@ impl VertexQuery {
             vertex_query: Box::new(self),
             converter: EdgeDirection::Outbound,
             type_filter,
+            type_filters: Vec::new(),
             high_filter,
             low_filter,
+            after_inbound_id: None,
+            offset: 0,
             limit,
         }
     }
@@ -93,38 +224,280 @@ impl VertexQuery {
             vertex_query: Box::new(self),
             converter: EdgeDirection::Inbound,
             type_filter,
+            type_filters: Vec::new(),
             high_filter,
             low_filter,
+            after_inbound_id: None,
+            offset: 0,
             limit,
         }
     }
+
+    pub fn traverse(self, direction: EdgeDirection, type_filter: Option<Type>, depth: u32, limit: u32) -> VertexQuery {
+        VertexQuery::Traverse {
+            start: Box::new(self),
+            direction,
+            type_filter,
+            depth,
+            limit,
+        }
+    }
+
+    pub fn degree_at_least(
+        self,
+        direction: EdgeDirection,
+        type_filter: Option<Type>,
+        min: u64,
+        limit: u32,
+    ) -> VertexQuery {
+        VertexQuery::DegreeAtLeast {
+            start: Some(Box::new(self)),
+            direction,
+            type_filter,
+            min,
+            limit,
+        }
+    }
+
+    /// Lowercases every type in the query, including the types of the
+    /// queries it's built from.
+    pub fn lowercase_types(&mut self) {
+        match *self {
+            VertexQuery::All { .. } | VertexQuery::Vertices { .. } | VertexQuery::PropertyContains { .. } => {}
+            VertexQuery::Types { ref mut types, .. } => {
+                for t in types.iter_mut() {
+                    *t = t.to_lowercase();
+                }
+            }
+            VertexQuery::Pipe { ref mut edge_query, .. } => edge_query.lowercase_types(),
+            VertexQuery::Traverse {
+                ref mut start,
+                ref mut type_filter,
+                ..
+            } => {
+                start.lowercase_types();
+                *type_filter = type_filter.as_ref().map(Type::to_lowercase);
+            }
+            VertexQuery::DegreeAtLeast {
+                ref mut start,
+                ref mut type_filter,
+                ..
+            } => {
+                if let Some(ref mut start) = *start {
+                    start.lowercase_types();
+                }
+
+                *type_filter = type_filter.as_ref().map(Type::to_lowercase);
+            }
+        }
+    }
+
+    /// Gets a mutable reference to the query's own limit, or `None` if it
+    /// doesn't have one.
+    pub fn limit_mut(&mut self) -> Option<&mut u32> {
+        match *self {
+            VertexQuery::All { ref mut limit, .. }
+            | VertexQuery::Types { ref mut limit, .. }
+            | VertexQuery::Pipe { ref mut limit, .. }
+            | VertexQuery::PropertyContains { ref mut limit, .. }
+            | VertexQuery::Traverse { ref mut limit, .. }
+            | VertexQuery::DegreeAtLeast { ref mut limit, .. } => Some(limit),
+            VertexQuery::Vertices { .. } => None,
+        }
+    }
+
+    /// Lowers every limit in the query, including the limits of the queries
+    /// it's built from, to at most `max`. Returns whether any limit was
+    /// lowered.
+    ///
+    /// # Arguments
+    /// * `max` - The maximum limit.
+    pub fn clamp_limits(&mut self, max: u32) -> bool {
+        match *self {
+            VertexQuery::All { ref mut limit, .. } => clamp_limit(limit, max),
+            VertexQuery::Vertices { .. } => false,
+            VertexQuery::Types { ref mut limit, .. } => clamp_limit(limit, max),
+            VertexQuery::PropertyContains { ref mut limit, .. } => clamp_limit(limit, max),
+            VertexQuery::Pipe {
+                ref mut edge_query,
+                ref mut limit,
+                ..
+            } => {
+                let clamped = edge_query.clamp_limits(max);
+                clamp_limit(limit, max) || clamped
+            }
+            VertexQuery::Traverse {
+                ref mut start,
+                ref mut limit,
+                ..
+            } => {
+                let clamped = start.clamp_limits(max);
+                clamp_limit(limit, max) || clamped
+            }
+            VertexQuery::DegreeAtLeast {
+                ref mut start,
+                ref mut limit,
+                ..
+            } => {
+                let clamped = start.as_mut().is_some_and(|start| start.clamp_limits(max));
+                clamp_limit(limit, max) || clamped
+            }
+        }
+    }
+
+    /// Gets an upper bound on the number of vertices the query can return,
+    /// based only on the structure of the query: its limits, and the number
+    /// of ids it lists.
+    pub fn max_results(&self) -> u64 {
+        match *self {
+            VertexQuery::All { limit, .. }
+            | VertexQuery::Types { limit, .. }
+            | VertexQuery::PropertyContains { limit, .. }
+            | VertexQuery::Traverse { limit, .. } => u64::from(limit),
+            VertexQuery::Vertices { ref ids } => ids.len() as u64,
+            VertexQuery::Pipe {
+                ref edge_query,
+                converter,
+                offset,
+                limit,
+            } => {
+                // Piping both ends of the edges gets up to two vertices for
+                // each edge
+                let max_vertices = if converter == EdgeDirection::Both {
+                    edge_query.max_results().saturating_mul(2)
+                } else {
+                    edge_query.max_results()
+                };
+
+                cmp::min(u64::from(limit), max_vertices.saturating_sub(u64::from(offset)))
+            }
+            VertexQuery::DegreeAtLeast { ref start, limit, .. } => match *start {
+                Some(ref start) => cmp::min(u64::from(limit), start.max_results()),
+                None => u64::from(limit),
+            },
+        }
+    }
+
+    /// Validates the query.
+    ///
+    /// # Errors
+    /// Returns a `ValidationError` if the query has a `high_filter` that is
+    /// earlier than its `low_filter`, since no vertices could ever match it.
+    pub fn validate(&self) -> errors::ValidationResult<()> {
+        if let VertexQuery::All {
+            high_filter: Some(high_filter),
+            low_filter: Some(low_filter),
+            ..
+        } = *self
+        {
+            if high_filter < low_filter {
+                return Err("High filter is earlier than the low filter".into());
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// A query for edges.
 ///
 /// This is used by transactions to get, set and delete edges and edge
-/// properties.
+/// properties. Piped queries return edges grouped by the vertex they were
+/// piped from, in the order that the vertex query produced those vertices,
+/// regardless of the direction being piped. As with vertex queries, a
+/// `limit` of `0` yields no results.
+///
+/// Piped queries can be restricted to edges of a single type with
+/// `type_filter`, or to edges of any of several types with `type_filters`.
+/// If both are set, edges have to match both.
+///
+/// Piped queries can skip their first `offset` edges, e.g. to page through
+/// the edges of a vertex. Skipped edges don't count towards the limit.
+///
+/// Outbound piped queries can resume from a cursor with `after_inbound_id`,
+/// in which case only edges whose inbound vertex ID is greater than it are
+/// returned, and each vertex's edges of a type are returned in inbound
+/// vertex ID order. Passing the inbound vertex ID of the last edge of one
+/// page gets the next page. Without a cursor, the order depends on the
+/// datastore, so start from the nil UUID (`Uuid::default()`) rather than no
+/// cursor at all. Paging is only stable when the query has a single vertex
+/// and is restricted to a single type.
+///
+/// `All` queries scan every edge in the datastore rather than starting from
+/// a set of vertices, and return edges ordered by their outbound vertex ID.
+/// This is a full scan, so prefer piped queries when the vertices are known.
 #[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize, Hash)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum EdgeQuery {
     Edges {
         keys: Vec<EdgeKey>,
     },
+    All {
+        type_filter: Option<Type>,
+        high_filter: Option<DateTime<Utc>>,
+        low_filter: Option<DateTime<Utc>>,
+        limit: u32,
+    },
     Pipe {
         vertex_query: Box<VertexQuery>,
         converter: EdgeDirection,
         type_filter: Option<Type>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        type_filters: Vec<Type>,
         high_filter: Option<DateTime<Utc>>,
         low_filter: Option<DateTime<Utc>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        after_inbound_id: Option<Uuid>,
+        #[serde(default, skip_serializing_if = "is_zero")]
+        offset: u32,
         limit: u32,
     },
 }
 
 impl EdgeQuery {
+    /// Creates a query for a single edge.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the edge.
+    pub fn single(key: EdgeKey) -> EdgeQuery {
+        EdgeQuery::Edges { keys: vec![key] }
+    }
+
+    /// Creates a query for specific edges.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys of the edges.
+    pub fn keys(keys: Vec<EdgeKey>) -> EdgeQuery {
+        EdgeQuery::Edges { keys }
+    }
+
+    /// Creates a query for all edges, optionally restricted to a type and a
+    /// range of update datetimes.
+    ///
+    /// # Arguments
+    /// * `type_filter` - Only get edges of this type.
+    /// * `high_filter` - Only get edges updated at or before this datetime.
+    /// * `low_filter` - Only get edges updated at or after this datetime.
+    /// * `limit` - Limits the number of returned edges.
+    pub fn all(
+        type_filter: Option<Type>,
+        high_filter: Option<DateTime<Utc>>,
+        low_filter: Option<DateTime<Utc>>,
+        limit: u32,
+    ) -> EdgeQuery {
+        EdgeQuery::All {
+            type_filter,
+            high_filter,
+            low_filter,
+            limit,
+        }
+    }
+
     pub fn outbound_vertices(self, limit: u32) -> VertexQuery {
         VertexQuery::Pipe {
             edge_query: Box::new(self),
             converter: EdgeDirection::Outbound,
+            offset: 0,
             limit,
         }
     }
@@ -133,20 +506,444 @@ impl EdgeQuery {
         VertexQuery::Pipe {
             edge_query: Box::new(self),
             converter: EdgeDirection::Inbound,
+            offset: 0,
             limit,
         }
     }
+
+    /// Lowercases every type in the query, including the types of the
+    /// queries it's built from.
+    pub fn lowercase_types(&mut self) {
+        match *self {
+            EdgeQuery::Edges { ref mut keys } => {
+                for key in keys.iter_mut() {
+                    key.t = key.t.to_lowercase();
+                }
+            }
+            EdgeQuery::All {
+                ref mut type_filter, ..
+            } => {
+                *type_filter = type_filter.as_ref().map(Type::to_lowercase);
+            }
+            EdgeQuery::Pipe {
+                ref mut vertex_query,
+                ref mut type_filter,
+                ref mut type_filters,
+                ..
+            } => {
+                vertex_query.lowercase_types();
+                *type_filter = type_filter.as_ref().map(Type::to_lowercase);
+
+                for t in type_filters.iter_mut() {
+                    *t = t.to_lowercase();
+                }
+            }
+        }
+    }
+
+    /// Lowers every limit in the query, including the limits of the queries
+    /// it's built from, to at most `max`. Returns whether any limit was
+    /// lowered.
+    ///
+    /// # Arguments
+    /// * `max` - The maximum limit.
+    pub fn clamp_limits(&mut self, max: u32) -> bool {
+        match *self {
+            EdgeQuery::Edges { .. } => false,
+            EdgeQuery::All { ref mut limit, .. } => clamp_limit(limit, max),
+            EdgeQuery::Pipe {
+                ref mut vertex_query,
+                ref mut limit,
+                ..
+            } => {
+                let clamped = vertex_query.clamp_limits(max);
+                clamp_limit(limit, max) || clamped
+            }
+        }
+    }
+
+    /// Gets an upper bound on the number of edges the query can return,
+    /// based only on the structure of the query: its limits, and the number
+    /// of keys it lists.
+    pub fn max_results(&self) -> u64 {
+        match *self {
+            EdgeQuery::Edges { ref keys } => keys.len() as u64,
+            EdgeQuery::All { limit, .. } => u64::from(limit),
+            EdgeQuery::Pipe {
+                ref vertex_query,
+                limit,
+                ..
+            } => {
+                if vertex_query.max_results() == 0 {
+                    0
+                } else {
+                    u64::from(limit)
+                }
+            }
+        }
+    }
+
+    /// Validates the query.
+    ///
+    /// # Errors
+    /// Returns a `ValidationError` if the query has a `high_filter` that is
+    /// earlier than its `low_filter`, since no edges could ever match it, or
+    /// if it has an `after_inbound_id` but doesn't pipe outbound edges.
+    pub fn validate(&self) -> errors::ValidationResult<()> {
+        if let EdgeQuery::Pipe {
+            converter,
+            after_inbound_id: Some(_),
+            ..
+        } = *self
+        {
+            if converter != EdgeDirection::Outbound {
+                return Err("An inbound ID cursor can only be used with outbound edges".into());
+            }
+        }
+
+        let filters = match *self {
+            EdgeQuery::Edges { .. } => None,
+            EdgeQuery::All {
+                high_filter,
+                low_filter,
+                ..
+            }
+            | EdgeQuery::Pipe {
+                high_filter,
+                low_filter,
+                ..
+            } => Some((high_filter, low_filter)),
+        };
+
+        if let Some((Some(high_filter), Some(low_filter))) = filters {
+            if high_filter < low_filter {
+                return Err("High filter is earlier than the low filter".into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An error for a single item of a query that was skipped rather than
+/// failing the whole query, e.g. a record that couldn't be decoded.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct QueryError {
+    /// A description of what went wrong.
+    pub message: String,
+}
+
+impl QueryError {
+    /// Creates a new query error.
+    ///
+    /// # Arguments
+    /// * `message` - A description of what went wrong.
+    pub fn new(message: String) -> Self {
+        QueryError { message }
+    }
+}
+
+/// A vertex or edge query, as returned by `parse_query`.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Query {
+    Vertex(VertexQuery),
+    Edge(EdgeQuery),
+}
+
+/// Parses a query written in a compact, chained syntax, e.g.
+/// `V.all(100).outE('follows').inV(50)`.
+///
+/// Queries start from `V.all(limit)` for all vertices, `V('id', ...)` for
+/// specific vertices, or `E.all('type', limit)` for all edges. Vertex
+/// queries are piped to edge queries with `outE('type', limit)` and
+/// `inE('type', limit)`, and edge queries to vertex queries with
+/// `outV(limit)` and `inV(limit)`. Types and limits are optional, and a
+/// missing limit means `u32::MAX`. Strings can be single or double quoted.
+///
+/// # Arguments
+/// * `s` - The query to parse.
+///
+/// # Errors
+/// Returns a `ValidationError` saying what's wrong and where if the query
+/// is malformed.
+pub fn parse_query(s: &str) -> errors::ValidationResult<Query> {
+    QueryParser::new(s)?.parse()
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+enum QueryToken {
+    Name(String),
+    Str(String),
+    Int(String),
+    Dot,
+    Open,
+    Close,
+    Comma,
+}
+
+// Splits a query into tokens, along with the position each starts at.
+fn tokenize_query(s: &str) -> errors::ValidationResult<Vec<(usize, QueryToken)>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((pos, c)) = chars.next() {
+        let token = match c {
+            '.' => QueryToken::Dot,
+            '(' => QueryToken::Open,
+            ')' => QueryToken::Close,
+            ',' => QueryToken::Comma,
+            '\'' | '"' => {
+                let mut value = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some((_, next)) if next == c => break,
+                        Some((_, next)) => value.push(next),
+                        None => return Err(format!("Unterminated string at position {}", pos).into()),
+                    }
+                }
+
+                QueryToken::Str(value)
+            }
+            _ if c.is_whitespace() => continue,
+            _ if c.is_ascii_digit() => QueryToken::Int(take_while_chars(c, &mut chars, |c| c.is_ascii_digit())),
+            _ if c.is_alphabetic() => QueryToken::Name(take_while_chars(c, &mut chars, char::is_alphanumeric)),
+            _ => return Err(format!("Unexpected character `{}` at position {}", c, pos).into()),
+        };
+
+        tokens.push((pos, token));
+    }
+
+    Ok(tokens)
+}
+
+fn take_while_chars<F: Fn(char) -> bool>(first: char, chars: &mut Peekable<CharIndices>, f: F) -> String {
+    let mut value = first.to_string();
+
+    while let Some(&(_, c)) = chars.peek() {
+        if !f(c) {
+            break;
+        }
+
+        value.push(c);
+        chars.next();
+    }
+
+    value
+}
+
+struct QueryParser {
+    tokens: Vec<(usize, QueryToken)>,
+    index: usize,
+    end: usize,
+}
+
+impl QueryParser {
+    fn new(s: &str) -> errors::ValidationResult<Self> {
+        Ok(QueryParser {
+            tokens: tokenize_query(s)?,
+            index: 0,
+            end: s.len(),
+        })
+    }
+
+    fn parse(mut self) -> errors::ValidationResult<Query> {
+        let (pos, start) = self.name("`V` or `E`")?;
+
+        let mut q = match &start[..] {
+            "V" if self.peek() == Some(&QueryToken::Open) => {
+                let ids: errors::ValidationResult<Vec<Uuid>> = self
+                    .args()?
+                    .into_iter()
+                    .map(|(pos, arg)| match arg {
+                        QueryToken::Str(ref id) => Uuid::from_str(id)
+                            .map_err(|_| format!("Invalid vertex id `{}` at position {}", id, pos).into()),
+                        _ => Err(format!("Expected a quoted vertex id at position {}", pos).into()),
+                    })
+                    .collect();
+                Query::Vertex(VertexQuery::ids(ids?))
+            }
+            "V" => {
+                self.all_step()?;
+                Query::Vertex(VertexQuery::all(limit_arg("all", self.args()?)?))
+            }
+            "E" => {
+                self.all_step()?;
+                let (type_filter, limit) = type_and_limit_args("all", self.args()?)?;
+                Query::Edge(EdgeQuery::all(type_filter, None, None, limit))
+            }
+            _ => return Err(format!("Expected `V` or `E` at position {}, found `{}`", pos, start).into()),
+        };
+
+        while self.index < self.tokens.len() {
+            self.expect(&QueryToken::Dot, "`.`")?;
+            let (pos, step) = self.name("a step")?;
+            let args = self.args()?;
+
+            q = match (q, &step[..]) {
+                (Query::Vertex(q), "outE") => {
+                    let (type_filter, limit) = type_and_limit_args(&step, args)?;
+                    Query::Edge(q.outbound_edges(type_filter, None, None, limit))
+                }
+                (Query::Vertex(q), "inE") => {
+                    let (type_filter, limit) = type_and_limit_args(&step, args)?;
+                    Query::Edge(q.inbound_edges(type_filter, None, None, limit))
+                }
+                (Query::Edge(q), "outV") => Query::Vertex(q.outbound_vertices(limit_arg(&step, args)?)),
+                (Query::Edge(q), "inV") => Query::Vertex(q.inbound_vertices(limit_arg(&step, args)?)),
+                (Query::Vertex(_), _) => {
+                    return Err(format!(
+                        "Expected `outE` or `inE` after a vertex query at position {}, found `{}`",
+                        pos, step
+                    )
+                    .into());
+                }
+                (Query::Edge(_), _) => {
+                    return Err(format!(
+                        "Expected `outV` or `inV` after an edge query at position {}, found `{}`",
+                        pos, step
+                    )
+                    .into());
+                }
+            };
+        }
+
+        Ok(q)
+    }
+
+    fn peek(&self) -> Option<&QueryToken> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.index).map_or(self.end, |&(pos, _)| pos)
+    }
+
+    fn next(&mut self, expected: &str) -> errors::ValidationResult<(usize, QueryToken)> {
+        match self.tokens.get(self.index) {
+            Some(&(pos, ref token)) => {
+                self.index += 1;
+                Ok((pos, token.clone()))
+            }
+            None => Err(format!("Expected {} at the end of the query", expected).into()),
+        }
+    }
+
+    fn expect(&mut self, token: &QueryToken, expected: &str) -> errors::ValidationResult<()> {
+        let pos = self.position();
+
+        if &self.next(expected)?.1 == token {
+            Ok(())
+        } else {
+            Err(format!("Expected {} at position {}", expected, pos).into())
+        }
+    }
+
+    fn name(&mut self, expected: &str) -> errors::ValidationResult<(usize, String)> {
+        match self.next(expected)? {
+            (pos, QueryToken::Name(name)) => Ok((pos, name)),
+            (pos, _) => Err(format!("Expected {} at position {}", expected, pos).into()),
+        }
+    }
+
+    fn all_step(&mut self) -> errors::ValidationResult<()> {
+        self.expect(&QueryToken::Dot, "`.`")?;
+
+        match self.name("`all`")? {
+            (_, ref name) if name == "all" => Ok(()),
+            (pos, name) => Err(format!("Expected `all` at position {}, found `{}`", pos, name).into()),
+        }
+    }
+
+    // Parses a parenthesized, comma-separated list of strings and integers.
+    fn args(&mut self) -> errors::ValidationResult<Vec<(usize, QueryToken)>> {
+        self.expect(&QueryToken::Open, "`(`")?;
+        let mut args = Vec::new();
+
+        if self.peek() == Some(&QueryToken::Close) {
+            self.index += 1;
+            return Ok(args);
+        }
+
+        loop {
+            match self.next("an argument")? {
+                (pos, arg @ QueryToken::Str(_)) | (pos, arg @ QueryToken::Int(_)) => args.push((pos, arg)),
+                (pos, _) => return Err(format!("Expected an argument at position {}", pos).into()),
+            }
+
+            match self.next("`,` or `)`")? {
+                (_, QueryToken::Comma) => continue,
+                (_, QueryToken::Close) => return Ok(args),
+                (pos, _) => return Err(format!("Expected `,` or `)` at position {}", pos).into()),
+            }
+        }
+    }
+}
+
+// Gets the optional limit of a step.
+fn limit_arg(step: &str, args: Vec<(usize, QueryToken)>) -> errors::ValidationResult<u32> {
+    let mut args = args.into_iter();
+
+    match (args.next(), args.next()) {
+        (None, _) => Ok(u32::MAX),
+        (Some((pos, QueryToken::Int(limit))), None) => parse_limit(pos, &limit),
+        _ => Err(format!("`{}` only takes an optional limit", step).into()),
+    }
+}
+
+// Gets the optional type and optional limit of a step, in that order.
+fn type_and_limit_args(step: &str, args: Vec<(usize, QueryToken)>) -> errors::ValidationResult<(Option<Type>, u32)> {
+    let mut args = args.into_iter().peekable();
+
+    let type_filter = match args.peek() {
+        Some(&(_, QueryToken::Str(ref t))) => Some(Type::new(t.clone())?),
+        _ => None,
+    };
+
+    if type_filter.is_some() {
+        args.next();
+    }
+
+    match (args.next(), args.next()) {
+        (None, _) => Ok((type_filter, u32::MAX)),
+        (Some((pos, QueryToken::Int(limit))), None) => Ok((type_filter, parse_limit(pos, &limit)?)),
+        _ => Err(format!("`{}` only takes an optional type and an optional limit", step).into()),
+    }
+}
+
+fn parse_limit(pos: usize, limit: &str) -> errors::ValidationResult<u32> {
+    limit
+        .parse()
+        .map_err(|_| format!("Limit `{}` at position {} is too large", limit, pos).into())
+}
+
+// Used to leave offsets out of serialized queries when they aren't set.
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+fn clamp_limit(limit: &mut u32, max: u32) -> bool {
+    if *limit > max {
+        *limit = max;
+        true
+    } else {
+        false
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::EdgeDirection;
+    use super::{parse_query, EdgeDirection, EdgeKey, EdgeQuery, Query, SortDirection, Type, VertexQuery};
+    use chrono::offset::Utc;
+    use chrono::Duration;
     use std::str::FromStr;
+    use uuid::Uuid;
 
     #[test]
     fn should_convert_str_to_edge_direction() {
         assert_eq!(EdgeDirection::from_str("outbound").unwrap(), EdgeDirection::Outbound);
         assert_eq!(EdgeDirection::from_str("inbound").unwrap(), EdgeDirection::Inbound);
+        assert_eq!(EdgeDirection::from_str("both").unwrap(), EdgeDirection::Both);
         assert!(EdgeDirection::from_str("foo").is_err());
     }
 
@@ -156,5 +953,255 @@ mod tests {
         assert_eq!(s, "outbound".to_string());
         let s: String = EdgeDirection::Inbound.into();
         assert_eq!(s, "inbound".to_string());
+        let s: String = EdgeDirection::Both.into();
+        assert_eq!(s, "both".to_string());
+    }
+
+    #[test]
+    fn should_get_max_results() {
+        assert_eq!(VertexQuery::all(10).max_results(), 10);
+        assert_eq!(VertexQuery::ids(vec![Uuid::default(); 3]).max_results(), 3);
+
+        let q = VertexQuery::ids(vec![Uuid::default(); 2])
+            .outbound_edges(None, None, None, 5)
+            .inbound_vertices(10);
+        assert_eq!(q.max_results(), 5);
+
+        let q = VertexQuery::ids(vec![]).outbound_edges(None, None, None, 5);
+        assert_eq!(q.max_results(), 0);
+
+        let key = EdgeKey::new(Uuid::default(), Type::default(), Uuid::default());
+        let q = VertexQuery::Pipe {
+            edge_query: Box::new(EdgeQuery::single(key)),
+            converter: EdgeDirection::Both,
+            offset: 0,
+            limit: 10,
+        };
+        assert_eq!(q.max_results(), 2);
+    }
+
+    #[test]
+    fn should_convert_str_to_sort_direction() {
+        assert_eq!(SortDirection::from_str("ascending").unwrap(), SortDirection::Ascending);
+        assert_eq!(
+            SortDirection::from_str("descending").unwrap(),
+            SortDirection::Descending
+        );
+        assert!(SortDirection::from_str("foo").is_err());
+    }
+
+    #[test]
+    fn should_convert_sort_direction_to_string() {
+        let s: String = SortDirection::Ascending.into();
+        assert_eq!(s, "ascending".to_string());
+        let s: String = SortDirection::Descending.into();
+        assert_eq!(s, "descending".to_string());
+    }
+
+    #[test]
+    fn should_validate_edge_query_time_range() {
+        let now = Utc::now();
+        let earlier = now - Duration::hours(1);
+        let q = VertexQuery::Vertices {
+            ids: vec![Uuid::default()],
+        };
+        assert!(q
+            .clone()
+            .outbound_edges(None, Some(now), Some(earlier), 10)
+            .validate()
+            .is_ok());
+        assert!(q
+            .clone()
+            .outbound_edges(None, Some(now), Some(now), 10)
+            .validate()
+            .is_ok());
+        assert!(q.clone().outbound_edges(None, Some(now), None, 10).validate().is_ok());
+        assert!(q
+            .clone()
+            .inbound_edges(None, Some(earlier), Some(now), 10)
+            .validate()
+            .is_err());
+        assert!(EdgeQuery::Edges { keys: vec![] }.validate().is_ok());
+        assert!(VertexQuery::all(10).validate().is_ok());
+
+        let q = VertexQuery::All {
+            start_id: None,
+            end_id: None,
+            high_filter: Some(earlier),
+            low_filter: Some(now),
+            limit: 10,
+        };
+        assert!(q.validate().is_err());
+        assert!(EdgeQuery::all(None, Some(now), Some(earlier), 10).validate().is_ok());
+        assert!(EdgeQuery::all(None, Some(earlier), Some(now), 10).validate().is_err());
+    }
+
+    #[test]
+    fn should_clamp_query_limits() {
+        let mut q = VertexQuery::All {
+            start_id: None,
+            end_id: None,
+            high_filter: None,
+            low_filter: None,
+            limit: 100,
+        }
+        .outbound_edges(None, None, None, 5)
+        .inbound_vertices(1000)
+        .traverse(EdgeDirection::Outbound, None, 2, 10);
+        assert!(q.clamp_limits(10));
+
+        let expected = VertexQuery::All {
+            start_id: None,
+            end_id: None,
+            high_filter: None,
+            low_filter: None,
+            limit: 10,
+        }
+        .outbound_edges(None, None, None, 5)
+        .inbound_vertices(10)
+        .traverse(EdgeDirection::Outbound, None, 2, 10);
+        assert_eq!(q, expected);
+        assert!(!q.clamp_limits(10));
+
+        let mut q = VertexQuery::Vertices { ids: vec![] }.outbound_edges(None, None, None, 5);
+        assert!(!q.clamp_limits(10));
+        assert!(!EdgeQuery::Edges { keys: vec![] }.clamp_limits(0));
+
+        let mut q = EdgeQuery::all(None, None, None, 100);
+        assert!(q.clamp_limits(10));
+        assert_eq!(q, EdgeQuery::all(None, None, None, 10));
+    }
+
+    #[test]
+    fn should_build_queries_fluently() {
+        let id = Uuid::default();
+        let key = EdgeKey::new(id, Type::default(), id);
+
+        assert_eq!(
+            VertexQuery::all(10),
+            VertexQuery::All {
+                start_id: None,
+                end_id: None,
+                high_filter: None,
+                low_filter: None,
+                limit: 10
+            }
+        );
+        assert_eq!(VertexQuery::single(id), VertexQuery::Vertices { ids: vec![id] });
+        assert_eq!(
+            VertexQuery::ids(vec![id, id]),
+            VertexQuery::Vertices { ids: vec![id, id] }
+        );
+        assert_eq!(
+            EdgeQuery::single(key.clone()),
+            EdgeQuery::Edges {
+                keys: vec![key.clone()]
+            }
+        );
+        assert_eq!(
+            EdgeQuery::all(Some(Type::default()), None, None, 5),
+            EdgeQuery::All {
+                type_filter: Some(Type::default()),
+                high_filter: None,
+                low_filter: None,
+                limit: 5
+            }
+        );
+        assert_eq!(
+            EdgeQuery::keys(vec![key.clone()]),
+            EdgeQuery::Edges {
+                keys: vec![key.clone()]
+            }
+        );
+
+        let q = VertexQuery::single(id)
+            .outbound_edges(None, None, None, 10)
+            .inbound_vertices(10);
+        let expected = VertexQuery::Pipe {
+            edge_query: Box::new(EdgeQuery::Pipe {
+                vertex_query: Box::new(VertexQuery::Vertices { ids: vec![id] }),
+                converter: EdgeDirection::Outbound,
+                type_filter: None,
+                type_filters: Vec::new(),
+                high_filter: None,
+                low_filter: None,
+                after_inbound_id: None,
+                offset: 0,
+                limit: 10,
+            }),
+            converter: EdgeDirection::Inbound,
+            offset: 0,
+            limit: 10,
+        };
+        assert_eq!(q, expected);
+    }
+
+    #[test]
+    fn should_parse_query_chains() {
+        let follows = Type::new("follows".to_string()).unwrap();
+
+        assert_eq!(parse_query("V.all(100)").unwrap(), Query::Vertex(VertexQuery::all(100)));
+        assert_eq!(
+            parse_query("V.all(100).outE('follows').inV(50)").unwrap(),
+            Query::Vertex(
+                VertexQuery::all(100)
+                    .outbound_edges(Some(follows.clone()), None, None, u32::MAX)
+                    .inbound_vertices(50)
+            )
+        );
+        assert_eq!(
+            parse_query(" V.all() . inE(\"follows\", 5).outV().outE(7) ").unwrap(),
+            Query::Edge(
+                VertexQuery::all(u32::MAX)
+                    .inbound_edges(Some(follows.clone()), None, None, 5)
+                    .outbound_vertices(u32::MAX)
+                    .outbound_edges(None, None, None, 7)
+            )
+        );
+        assert_eq!(
+            parse_query("E.all('follows', 3).inV()").unwrap(),
+            Query::Vertex(EdgeQuery::all(Some(follows), None, None, 3).inbound_vertices(u32::MAX))
+        );
+
+        let id = Uuid::default();
+        assert_eq!(
+            parse_query(&format!("V('{}', '{}')", id, id)).unwrap(),
+            Query::Vertex(VertexQuery::ids(vec![id, id]))
+        );
+    }
+
+    #[test]
+    fn should_not_parse_malformed_queries() {
+        let error = |s: &str| parse_query(s).unwrap_err().to_string();
+
+        assert_eq!(error(""), "Expected `V` or `E` at the end of the query");
+        assert_eq!(error("X.all(1)"), "Expected `V` or `E` at position 0, found `X`");
+        assert_eq!(error("V.some(1)"), "Expected `all` at position 2, found `some`");
+        assert_eq!(error("V.all(1"), "Expected `,` or `)` at the end of the query");
+        assert_eq!(
+            error("V.all(1).inV()"),
+            "Expected `outE` or `inE` after a vertex query at position 9, found `inV`"
+        );
+        assert_eq!(
+            error("E.all().outE()"),
+            "Expected `outV` or `inV` after an edge query at position 8, found `outE`"
+        );
+        assert_eq!(error("V.all(1) outE()"), "Expected `.` at position 9");
+        assert_eq!(
+            error("V.all(1).outE('a', 'b')"),
+            "`outE` only takes an optional type and an optional limit"
+        );
+        assert_eq!(error("V.all('a')"), "`all` only takes an optional limit");
+        assert_eq!(
+            error("V.all(99999999999)"),
+            "Limit `99999999999` at position 6 is too large"
+        );
+        assert_eq!(error("V('nope')"), "Invalid vertex id `nope` at position 2");
+        assert_eq!(
+            error("V.all(1).outE('unterminated)"),
+            "Unterminated string at position 14"
+        );
+        assert_eq!(error("V.all(1)!"), "Unexpected character `!` at position 8");
+        assert!(parse_query("V.all(1).outE('bad type')").is_err());
     }
 }
@@ -0,0 +1,66 @@
+use super::edges::Edge;
+use super::properties::NamedProperty;
+use super::vertices::Vertex;
+
+/// An edge along with all of its properties.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EdgeRecord {
+    /// The edge.
+    pub edge: Edge,
+
+    /// All of the properties of the edge.
+    pub properties: Vec<NamedProperty>,
+}
+
+impl EdgeRecord {
+    /// Creates a new edge record.
+    ///
+    /// # Arguments
+    ///
+    /// * `edge` - The edge.
+    /// * `properties` - All of the properties of the edge.
+    pub fn new(edge: Edge, properties: Vec<NamedProperty>) -> Self {
+        Self { edge, properties }
+    }
+}
+
+/// A vertex along with all of its properties and its incident edges, i.e.
+/// everything needed to export a single vertex.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FullRecord {
+    /// The vertex.
+    pub vertex: Vertex,
+
+    /// All of the properties of the vertex.
+    pub properties: Vec<NamedProperty>,
+
+    /// Edges going out of the vertex.
+    pub outbound_edges: Vec<EdgeRecord>,
+
+    /// Edges coming into the vertex.
+    pub inbound_edges: Vec<EdgeRecord>,
+}
+
+impl FullRecord {
+    /// Creates a new full record.
+    ///
+    /// # Arguments
+    ///
+    /// * `vertex` - The vertex.
+    /// * `properties` - All of the properties of the vertex.
+    /// * `outbound_edges` - Edges going out of the vertex.
+    /// * `inbound_edges` - Edges coming into the vertex.
+    pub fn new(
+        vertex: Vertex,
+        properties: Vec<NamedProperty>,
+        outbound_edges: Vec<EdgeRecord>,
+        inbound_edges: Vec<EdgeRecord>,
+    ) -> Self {
+        Self {
+            vertex,
+            properties,
+            outbound_edges,
+            inbound_edges,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use super::types::Type;
+use std::collections::BTreeMap;
+
+/// A summary of the size and shape of a graph.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GraphStats {
+    /// The number of vertices.
+    pub vertices: u64,
+    /// The number of edges.
+    pub edges: u64,
+    /// The number of vertex properties.
+    pub vertex_properties: u64,
+    /// The number of edge properties.
+    pub edge_properties: u64,
+    /// The number of vertices of each type.
+    pub vertex_types: BTreeMap<Type, u64>,
+    /// The mean number of outbound and inbound edges per vertex, or zero if
+    /// there are no vertices.
+    pub average_degree: f64,
+}
+
+impl GraphStats {
+    /// Sets `average_degree` from the total degree of all vertices, i.e. the
+    /// sum of their outbound and inbound edge counts.
+    ///
+    /// # Arguments
+    ///
+    /// * `total_degree` - The total degree of all vertices.
+    pub fn set_average_degree(&mut self, total_degree: u64) {
+        self.average_degree = if self.vertices == 0 {
+            0.0
+        } else {
+            total_degree as f64 / self.vertices as f64
+        };
+    }
+}
@@ -0,0 +1,78 @@
+use errors::{ErrorKind, Result};
+
+/// Bounds on how much work a single traversal is allowed to do.
+///
+/// Traversals over dense hubs can expand to a large portion of the graph.
+/// Datastores consult these limits while expanding a traversal, and abort
+/// with a `TraversalLimitExceeded` error as soon as one is crossed. A value of
+/// `None` means the respective dimension is unbounded.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct TraversalLimits {
+    /// The maximum number of vertices a traversal may visit, excluding the
+    /// vertices it starts from.
+    pub max_vertices: Option<u64>,
+    /// The maximum number of edges a traversal may follow.
+    pub max_edges: Option<u64>,
+    /// The maximum depth a traversal may request.
+    pub max_hops: Option<u32>,
+}
+
+impl TraversalLimits {
+    /// Checks whether a traversal of the given depth is allowed.
+    pub fn check_hops(&self, hops: u32) -> Result<()> {
+        match self.max_hops {
+            Some(max_hops) if hops > max_hops => Err(ErrorKind::TraversalLimitExceeded("max_hops".to_string()).into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks whether a traversal that has visited `count` vertices may
+    /// continue.
+    pub fn check_vertices(&self, count: u64) -> Result<()> {
+        match self.max_vertices {
+            Some(max_vertices) if count > max_vertices => {
+                Err(ErrorKind::TraversalLimitExceeded("max_vertices".to_string()).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks whether a traversal that has followed `count` edges may
+    /// continue.
+    pub fn check_edges(&self, count: u64) -> Result<()> {
+        match self.max_edges {
+            Some(max_edges) if count > max_edges => {
+                Err(ErrorKind::TraversalLimitExceeded("max_edges".to_string()).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TraversalLimits;
+
+    #[test]
+    fn should_allow_anything_by_default() {
+        let limits = TraversalLimits::default();
+        assert!(limits.check_hops(u32::MAX).is_ok());
+        assert!(limits.check_vertices(u64::MAX).is_ok());
+        assert!(limits.check_edges(u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn should_reject_counts_over_the_limits() {
+        let limits = TraversalLimits {
+            max_vertices: Some(2),
+            max_edges: Some(3),
+            max_hops: Some(1),
+        };
+        assert!(limits.check_hops(1).is_ok());
+        assert!(limits.check_hops(2).is_err());
+        assert!(limits.check_vertices(2).is_ok());
+        assert!(limits.check_vertices(3).is_err());
+        assert!(limits.check_edges(3).is_ok());
+        assert!(limits.check_edges(4).is_err());
+    }
+}
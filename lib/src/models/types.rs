@@ -32,6 +32,12 @@ impl Type {
             Ok(Type(t))
         }
     }
+
+    /// Returns a copy of this type with every letter lowercased. This is
+    /// always a valid type.
+    pub fn to_lowercase(&self) -> Self {
+        Type(self.0.to_lowercase())
+    }
 }
 
 impl Default for Type {
@@ -60,6 +66,12 @@ mod tests {
         assert!(Type::new("$".to_string()).is_err());
     }
 
+    #[test]
+    fn should_lowercase_types() {
+        let t = Type::new("Foo-BAR_baz9".to_string()).unwrap();
+        assert_eq!(t.to_lowercase(), Type::new("foo-bar_baz9".to_string()).unwrap());
+    }
+
     #[test]
     fn should_convert_str_to_type() {
         assert_eq!(Type::from_str("foo").unwrap(), Type::new("foo".to_string()).unwrap());
@@ -1,5 +1,6 @@
 use super::types::Type;
-use util::generate_uuid_v1;
+use errors::Result;
+use util::{generate_uuid_v1, generate_uuid_v7};
 use uuid::Uuid;
 
 /// A vertex.
@@ -29,6 +30,25 @@ impl Vertex {
         Self::with_id(generate_uuid_v1(), t)
     }
 
+    /// Creates a new vertex with an ID generated via UUIDv7. These IDs are
+    /// ordered by creation time, so range scans via `VertexQuery::All`
+    /// traverse vertices in the order they were created, and recently
+    /// created vertices stay close together in the index.
+    ///
+    /// Note that the ID embeds the creation time with millisecond precision,
+    /// so anyone who can see the ID can tell when the vertex was created. Use
+    /// `new` if that shouldn't be exposed.
+    ///
+    /// # Arguments
+    ///
+    /// * `t` - The type of the vertex.
+    ///
+    /// # Errors
+    /// Returns an IO error if the OS random number generator is unavailable.
+    pub fn new_sequential(t: Type) -> Result<Self> {
+        Ok(Self::with_id(generate_uuid_v7()?, t))
+    }
+
     /// Creates a new vertex with a specified id.
     ///
     /// # Arguments
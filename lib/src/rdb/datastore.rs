@@ -1,18 +1,40 @@
-use super::super::{Datastore, EdgeDirection, EdgeQuery, Transaction, VertexQuery};
+use super::super::{Datastore, EdgeDirection, EdgeQuery, ReadTransaction, VertexQuery, WriteTransaction};
 use super::managers::*;
 use chrono::offset::Utc;
-use errors::Result;
+use errors::{ErrorKind, Result};
 use models;
 use rocksdb::{DBCompactionStyle, Options, WriteBatch, DB, WriteOptions};
 use serde_json::Value as JsonValue;
+use std::collections::HashSet;
 use std::i32;
 use std::sync::Arc;
 use std::u64;
 use std::usize;
-use util::next_uuid;
+use util::{json_merge_patch, json_string_contains, next_uuid, validate_property_size};
 use uuid::Uuid;
 
+// Each kind of data lives in its own column family, so that e.g. edge range
+// scans aren't slowed down by property values living in the same LSM tree.
+// Keys within a column family are built via `build_key`. Since the column
+// family already says what kind of data a key is for, the only prefix a key
+// has is its key version. The `v1` column families held keys from before
+// they started with a key version, which can't be told apart from versioned
+// keys, so versioned keys live in new column families. Every column family
+// is opened with the same options from `get_options`; they aren't tuned
+// separately.
 const CF_NAMES: [&str; 6] = [
+    "vertices:v2",
+    "edges:v2",
+    "edge_ranges:v2",
+    "reversed_edge_ranges:v2",
+    "vertex_properties:v2",
+    "edge_properties:v2",
+];
+
+// The column families of databases created before keys started with a key
+// version. Their keys have no version to check, so these databases are
+// rejected as a whole when they're opened.
+const UNVERSIONED_CF_NAMES: [&str; 6] = [
     "vertices:v1",
     "edges:v1",
     "edge_ranges:v1",
@@ -61,7 +83,13 @@ fn get_options(max_open_files: Option<i32>, bulk_load_optimized: bool) -> Option
 /// A datastore that is backed by rocksdb.
 #[derive(Debug)]
 pub struct RocksdbDatastore {
+    allow_self_loops: bool,
     db: Arc<DB>,
+    max_property_size_bytes: Option<usize>,
+    reject_dangling_bulk_edges: bool,
+    sync_writes: bool,
+    traversal_limits: models::TraversalLimits,
+    use_wal: bool,
 }
 
 impl RocksdbDatastore {
@@ -74,12 +102,21 @@ impl RocksdbDatastore {
     /// * `bulk_load_optimized` - Whether to configure the database to
     ///   optimize for bulk loading, based off of suggestions from the RocksDB
     ///   FAQ.
+    ///
+    /// # Errors
+    /// Returns an `UnsupportedKeyVersion` error with version `0` if the
+    /// database was created by a version of IndraDB from before keys were
+    /// versioned.
     pub fn new(path: &str, max_open_files: Option<i32>, bulk_load_optimized: bool) -> Result<RocksdbDatastore> {
         let opts = get_options(max_open_files, bulk_load_optimized);
 
         let db = match DB::open_cf(&opts, path, &CF_NAMES) {
             Ok(db) => db,
             Err(_) => {
+                if DB::open_cf(&opts, path, &UNVERSIONED_CF_NAMES).is_ok() {
+                    return Err(ErrorKind::UnsupportedKeyVersion(0).into());
+                }
+
                 let mut db = DB::open(&opts, path)?;
 
                 for cf_name in &CF_NAMES {
@@ -90,7 +127,90 @@ impl RocksdbDatastore {
             }
         };
 
-        Ok(RocksdbDatastore { db: Arc::new(db) })
+        Ok(RocksdbDatastore {
+            allow_self_loops: true,
+            db: Arc::new(db),
+            max_property_size_bytes: None,
+            reject_dangling_bulk_edges: false,
+            sync_writes: false,
+            traversal_limits: models::TraversalLimits::default(),
+            use_wal: true,
+        })
+    }
+
+    /// Sets the limits that bound how much work a single traversal may do.
+    ///
+    /// # Arguments
+    /// * `limits` - The traversal limits.
+    pub fn with_traversal_limits(mut self, limits: models::TraversalLimits) -> RocksdbDatastore {
+        self.traversal_limits = limits;
+        self
+    }
+
+    /// Sets whether edges may start and end at the same vertex. Self-loops
+    /// are allowed by default. When they're not, `create_edge` returns
+    /// `false` for them and bulk inserts skip them.
+    ///
+    /// # Arguments
+    /// * `allow_self_loops` - Whether to allow self-loops.
+    pub fn with_allow_self_loops(mut self, allow_self_loops: bool) -> RocksdbDatastore {
+        self.allow_self_loops = allow_self_loops;
+        self
+    }
+
+    /// Sets the maximum size of property values, measured as the length of
+    /// the value serialized as JSON. There's no maximum by default. When
+    /// there is one, setting a vertex or edge property to a larger value
+    /// fails with a validation error, as does bulk inserting one.
+    ///
+    /// # Arguments
+    /// * `max_property_size_bytes` - The maximum size in bytes, or `None`
+    ///   for no maximum.
+    pub fn with_max_property_size_bytes(mut self, max_property_size_bytes: Option<usize>) -> RocksdbDatastore {
+        self.max_property_size_bytes = max_property_size_bytes;
+        self
+    }
+
+    /// Sets whether bulk inserts check that the vertices of every edge
+    /// exist. They aren't checked by default, so that bulk inserts stay
+    /// fast, but edges whose vertices don't exist get inserted anyway. When
+    /// they're checked, a bulk insert with such edges fails with a
+    /// `DanglingBulkInsertEdges` error listing them, without inserting
+    /// anything. Vertices inserted by earlier items in the same bulk insert
+    /// count as existing.
+    ///
+    /// # Arguments
+    /// * `reject_dangling_bulk_edges` - Whether to reject edges whose
+    ///   vertices don't exist.
+    pub fn with_reject_dangling_bulk_edges(mut self, reject_dangling_bulk_edges: bool) -> RocksdbDatastore {
+        self.reject_dangling_bulk_edges = reject_dangling_bulk_edges;
+        self
+    }
+
+    /// Sets whether transaction writes are synced to disk before they
+    /// return. Writes aren't synced by default, so while they survive the
+    /// process crashing, the most recent ones may be lost if the machine
+    /// itself crashes. Syncing makes every write durable, at the cost of
+    /// making writes much slower.
+    ///
+    /// # Arguments
+    /// * `sync_writes` - Whether to sync writes.
+    pub fn with_sync_writes(mut self, sync_writes: bool) -> RocksdbDatastore {
+        self.sync_writes = sync_writes;
+        self
+    }
+
+    /// Sets whether transaction writes go through the write-ahead log. The
+    /// write-ahead log is used by default. Skipping it makes writes faster,
+    /// e.g. for ingesting data that could be loaded again, but any writes
+    /// that haven't been flushed from memory yet are lost if the process
+    /// crashes. Syncing has no effect on writes that skip the log.
+    ///
+    /// # Arguments
+    /// * `use_wal` - Whether to use the write-ahead log.
+    pub fn with_wal(mut self, use_wal: bool) -> RocksdbDatastore {
+        self.use_wal = use_wal;
+        self
     }
 
     /// Runs a repair operation on the rocksdb database.
@@ -120,26 +240,56 @@ impl Datastore for RocksdbDatastore {
         let vertex_property_manager = VertexPropertyManager::new(self.db.clone());
         let edge_property_manager = EdgePropertyManager::new(self.db.clone());
         let mut batch = WriteBatch::default();
+        let mut inserted_ids = HashSet::new();
+        let mut dangling_keys = Vec::new();
 
         for item in items {
             match item {
                 models::BulkInsertItem::Vertex(ref vertex) => {
-                    vertex_manager.create(&mut batch, vertex)?;
+                    if self.reject_dangling_bulk_edges {
+                        inserted_ids.insert(vertex.id);
+                    }
+
+                    // A vertex that already exists keeps its creation datetime
+                    let created_datetime = vertex_manager.get_created_datetime(vertex.id)?.unwrap_or_else(Utc::now);
+                    vertex_manager.create(&mut batch, vertex, created_datetime)?;
                 }
                 models::BulkInsertItem::Edge(ref key) => {
+                    if !self.allow_self_loops && key.outbound_id == key.inbound_id {
+                        continue;
+                    }
+
+                    if self.reject_dangling_bulk_edges {
+                        let outbound_exists =
+                            inserted_ids.contains(&key.outbound_id) || vertex_manager.exists(key.outbound_id)?;
+                        let inbound_exists =
+                            inserted_ids.contains(&key.inbound_id) || vertex_manager.exists(key.inbound_id)?;
+
+                        if !outbound_exists || !inbound_exists {
+                            dangling_keys.push(key.clone());
+                            continue;
+                        }
+                    }
+
                     edge_manager.set(&mut batch, key.outbound_id, &key.t, key.inbound_id, Utc::now())?;
                 }
                 models::BulkInsertItem::VertexProperty(id, ref name, ref value) => {
+                    validate_property_size(name, value, self.max_property_size_bytes)?;
                     vertex_property_manager.set(&mut batch, id, name, value)?;
                 }
                 models::BulkInsertItem::EdgeProperty(ref key, ref name, ref value) => {
+                    validate_property_size(name, value, self.max_property_size_bytes)?;
                     edge_property_manager.set(&mut batch, key.outbound_id, &key.t, key.inbound_id, name, value)?;
                 }
             }
         }
 
+        if !dangling_keys.is_empty() {
+            return Err(ErrorKind::DanglingBulkInsertEdges(format!("{:?}", dangling_keys)).into());
+        }
+
         // NOTE: syncing and WAL are disabled for bulk inserts to maximimze
-        // performance
+        // performance, regardless of how transaction writes are configured
         let mut opts = WriteOptions::default();
         opts.set_sync(false);
         opts.disable_wal(true);
@@ -149,26 +299,92 @@ impl Datastore for RocksdbDatastore {
     }
 
     fn transaction(&self) -> Result<Self::Trans> {
-        RocksdbTransaction::new(self.db.clone())
+        RocksdbTransaction::new(
+            self.db.clone(),
+            self.traversal_limits,
+            self.allow_self_loops,
+            self.max_property_size_bytes,
+            self.sync_writes,
+            self.use_wal,
+        )
+    }
+
+    // Pings the database with a point lookup, which fails if rocksdb can't
+    // read from disk.
+    fn health_check(&self) -> Result<()> {
+        VertexManager::new(self.db.clone()).exists(Uuid::default())?;
+        Ok(())
     }
 }
 
 /// A transaction that is backed by rocksdb.
 #[derive(Debug)]
 pub struct RocksdbTransaction {
+    allow_self_loops: bool,
     db: Arc<DB>,
+    max_property_size_bytes: Option<usize>,
+    sync_writes: bool,
+    traversal_limits: models::TraversalLimits,
+    use_wal: bool,
 }
 
 impl RocksdbTransaction {
-    fn new(db: Arc<DB>) -> Result<Self> {
-        Ok(RocksdbTransaction { db })
+    fn new(
+        db: Arc<DB>,
+        traversal_limits: models::TraversalLimits,
+        allow_self_loops: bool,
+        max_property_size_bytes: Option<usize>,
+        sync_writes: bool,
+        use_wal: bool,
+    ) -> Result<Self> {
+        Ok(RocksdbTransaction {
+            allow_self_loops,
+            db,
+            max_property_size_bytes,
+            sync_writes,
+            traversal_limits,
+            use_wal,
+        })
+    }
+
+    // Writes a batch with the write options the datastore was configured
+    // with.
+    fn write(&self, batch: WriteBatch) -> Result<()> {
+        let mut opts = WriteOptions::default();
+        opts.set_sync(self.sync_writes);
+        opts.disable_wal(!self.use_wal);
+        self.db.write_opt(batch, &opts)?;
+        Ok(())
+    }
+
+    // Gets the edge range managers that need to be scanned to find the
+    // edges going in the given direction from a vertex, along with the
+    // direction each one covers. The ranges a manager yields always start
+    // with the id of the vertex being scanned.
+    fn edge_range_managers(&self, direction: EdgeDirection) -> Vec<(EdgeDirection, EdgeRangeManager)> {
+        match direction {
+            EdgeDirection::Outbound => vec![(EdgeDirection::Outbound, EdgeRangeManager::new(self.db.clone()))],
+            EdgeDirection::Inbound => vec![(EdgeDirection::Inbound, EdgeRangeManager::new_reversed(self.db.clone()))],
+            EdgeDirection::Both => vec![
+                (EdgeDirection::Outbound, EdgeRangeManager::new(self.db.clone())),
+                (EdgeDirection::Inbound, EdgeRangeManager::new_reversed(self.db.clone())),
+            ],
+        }
     }
 
     fn vertex_query_to_iterator(&self, q: VertexQuery) -> Result<Box<dyn Iterator<Item = Result<VertexItem>>>> {
+        q.validate()?;
+
         let vertex_manager = VertexManager::new(self.db.clone());
 
         match q {
-            VertexQuery::All { start_id, limit } => {
+            VertexQuery::All {
+                start_id,
+                end_id,
+                high_filter,
+                low_filter,
+                limit,
+            } => {
                 let next_uuid = match start_id {
                     Some(start_id) => {
                         match next_uuid(start_id) {
@@ -184,38 +400,189 @@ impl RocksdbTransaction {
                     None => Uuid::default(),
                 };
 
-                let iterator = vertex_manager.iterate_for_range(next_uuid)?;
-                Ok(Box::new(iterator.take(limit as usize)))
+                let iterator = vertex_manager.iterate_for_range_with_created_datetime(next_uuid)?;
+
+                let bounded = iterator.take_while(move |item| match (item, end_id) {
+                    (&Ok((id, _, _)), Some(end_id)) => id < end_id,
+                    _ => true,
+                });
+
+                let filtered = bounded.filter_map(move |item| match item {
+                    Ok((id, t, created_datetime)) => {
+                        if high_filter.map_or(false, |high_filter| created_datetime > high_filter)
+                            || low_filter.map_or(false, |low_filter| created_datetime < low_filter)
+                        {
+                            None
+                        } else {
+                            Some(Ok((id, t)))
+                        }
+                    }
+                    Err(err) => Some(Err(err)),
+                });
+
+                Ok(Box::new(filtered.take(limit as usize)))
             }
             VertexQuery::Vertices { ref ids } => {
                 let vertices: Vec<Result<Uuid>> = ids.into_iter().map(|id| Ok(*id)).collect();
                 let iterator = vertices.into_iter();
                 Ok(Box::new(self.handle_vertex_id_iterator(iterator)))
             }
+            VertexQuery::Types { types, limit } => {
+                let iterator = vertex_manager.iterate_for_range(Uuid::default())?;
+
+                let filtered = iterator.filter(move |item| match *item {
+                    Ok((_, ref t)) => types.contains(t),
+                    Err(_) => true,
+                });
+
+                Ok(Box::new(filtered.take(limit as usize)))
+            }
+            VertexQuery::PropertyContains { name, substring, limit } => {
+                let iterator = vertex_manager.iterate_for_range(Uuid::default())?;
+                let vertex_property_manager = VertexPropertyManager::new(self.db.clone());
+
+                let filtered = iterator.filter_map(move |item| match item {
+                    Ok((id, t)) => match vertex_property_manager.get(id, &name) {
+                        Ok(Some(ref value)) if json_string_contains(value, &substring) => Some(Ok((id, t))),
+                        Ok(_) => None,
+                        Err(err) => Some(Err(err)),
+                    },
+                    Err(err) => Some(Err(err)),
+                });
+
+                Ok(Box::new(filtered.take(limit as usize)))
+            }
             VertexQuery::Pipe {
                 edge_query,
                 converter,
+                offset,
                 limit,
             } => {
                 let edge_iterator = self.edge_query_to_iterator(*edge_query)?;
 
-                let vertex_id_iterator = edge_iterator.map(move |item| {
-                    let (outbound_id, _, _, inbound_id) = item?;
-
-                    match converter {
-                        EdgeDirection::Outbound => Ok(outbound_id),
-                        EdgeDirection::Inbound => Ok(inbound_id),
-                    }
+                let vertex_id_iterator = edge_iterator.skip(offset as usize).flat_map(move |item| match item {
+                    Ok((outbound_id, _, _, inbound_id)) => match converter {
+                        EdgeDirection::Outbound => vec![Ok(outbound_id)],
+                        EdgeDirection::Inbound => vec![Ok(inbound_id)],
+                        EdgeDirection::Both if outbound_id == inbound_id => vec![Ok(outbound_id)],
+                        EdgeDirection::Both => vec![Ok(outbound_id), Ok(inbound_id)],
+                    },
+                    Err(err) => vec![Err(err)],
                 });
 
                 Ok(Box::new(
                     self.handle_vertex_id_iterator(vertex_id_iterator).take(limit as usize),
                 ))
             }
+            VertexQuery::Traverse {
+                start,
+                direction,
+                type_filter,
+                depth,
+                limit,
+            } => {
+                let edge_range_managers = self.edge_range_managers(direction);
+
+                // Like edge pipes, this is collected into a vector rather
+                // than being lazily evaluated, since the visited set has to
+                // be maintained across the whole traversal anyway.
+                let mut results: Vec<Result<VertexItem>> = Vec::new();
+
+                if limit == 0 {
+                    return Ok(Box::new(results.into_iter()));
+                }
+
+                self.traversal_limits.check_hops(depth)?;
+
+                let mut visited = HashSet::new();
+                let mut frontier = Vec::new();
+                let mut visited_vertices = 0;
+                let mut followed_edges = 0;
+
+                for item in self.vertex_query_to_iterator(*start)? {
+                    let (id, _) = item?;
+
+                    if visited.insert(id) {
+                        frontier.push(id);
+                    }
+                }
+
+                'traversal: for _ in 0..depth {
+                    let mut next_frontier = Vec::new();
+
+                    for id in frontier {
+                        for &(_, ref edge_range_manager) in &edge_range_managers {
+                            for item in edge_range_manager.iterate_for_range(id, type_filter.as_ref(), None)? {
+                                let (_, _, _, neighbor_id) = item?;
+                                followed_edges += 1;
+                                self.traversal_limits.check_edges(followed_edges)?;
+
+                                if !visited.insert(neighbor_id) {
+                                    continue;
+                                }
+
+                                visited_vertices += 1;
+                                self.traversal_limits.check_vertices(visited_vertices)?;
+
+                                if let Some(value) = vertex_manager.get(neighbor_id)? {
+                                    results.push(Ok((neighbor_id, value)));
+
+                                    if results.len() == limit as usize {
+                                        break 'traversal;
+                                    }
+                                }
+
+                                next_frontier.push(neighbor_id);
+                            }
+                        }
+                    }
+
+                    if next_frontier.is_empty() {
+                        break;
+                    }
+
+                    frontier = next_frontier;
+                }
+
+                Ok(Box::new(results.into_iter()))
+            }
+            VertexQuery::DegreeAtLeast {
+                start,
+                direction,
+                type_filter,
+                min,
+                limit,
+            } => {
+                let candidates = match start {
+                    Some(start) => self.vertex_query_to_iterator(*start)?,
+                    None => Box::new(vertex_manager.iterate_for_range(Uuid::default())?),
+                };
+
+                // Like traversals, the matches are collected rather than
+                // lazily evaluated, since the returned iterator can't borrow
+                // the transaction to count edges
+                let mut results: Vec<Result<VertexItem>> = Vec::new();
+
+                for item in candidates {
+                    if results.len() >= limit as usize {
+                        break;
+                    }
+
+                    let (id, t) = item?;
+
+                    if self.get_edge_count(id, type_filter.as_ref(), direction)? >= min {
+                        results.push(Ok((id, t)));
+                    }
+                }
+
+                Ok(Box::new(results.into_iter()))
+            }
         }
     }
 
     fn edge_query_to_iterator(&self, q: EdgeQuery) -> Result<Box<dyn Iterator<Item = Result<EdgeRangeItem>>>> {
+        q.validate()?;
+
         match q {
             EdgeQuery::Edges { keys } => {
                 let edge_manager = EdgeManager::new(self.db.clone());
@@ -232,19 +599,57 @@ impl RocksdbTransaction {
                 let iterator = self.remove_nones_from_iterator(edges);
                 Ok(Box::new(iterator))
             }
+            EdgeQuery::All {
+                type_filter,
+                high_filter,
+                low_filter,
+                limit,
+            } => {
+                let edge_manager = EdgeManager::new(self.db.clone());
+
+                let edges = edge_manager.iterate_for_all()?.filter(move |item| match *item {
+                    Ok((_, ref t, update_datetime, _)) => {
+                        type_filter.as_ref().map_or(true, |type_filter| t == type_filter)
+                            && high_filter.map_or(true, |high_filter| update_datetime <= high_filter)
+                            && low_filter.map_or(true, |low_filter| update_datetime >= low_filter)
+                    }
+                    Err(_) => true,
+                });
+
+                Ok(Box::new(edges.take(limit as usize)))
+            }
             EdgeQuery::Pipe {
                 vertex_query,
                 converter,
                 type_filter,
+                mut type_filters,
                 high_filter,
                 low_filter,
+                after_inbound_id,
+                offset,
                 limit,
             } => {
                 let vertex_iterator = self.vertex_query_to_iterator(*vertex_query)?;
 
-                let edge_range_manager = match converter {
-                    EdgeDirection::Outbound => EdgeRangeManager::new(self.db.clone()),
-                    EdgeDirection::Inbound => EdgeRangeManager::new_reversed(self.db.clone()),
+                let edge_range_managers = self.edge_range_managers(converter);
+
+                // Edge ranges are keyed by type after their first vertex, so
+                // each filtered type is scanned separately, in sorted order,
+                // rather than scanning every edge of the vertex.
+                let scanned_types: Vec<Option<models::Type>> = match type_filter {
+                    Some(type_filter) => {
+                        if type_filters.is_empty() || type_filters.contains(&type_filter) {
+                            vec![Some(type_filter)]
+                        } else {
+                            Vec::new()
+                        }
+                    }
+                    None if type_filters.is_empty() => vec![None],
+                    None => {
+                        type_filters.sort();
+                        type_filters.dedup();
+                        type_filters.into_iter().map(Some).collect()
+                    }
                 };
 
                 // Ideally we'd use iterators all the way down, but things
@@ -254,44 +659,94 @@ impl RocksdbTransaction {
                 // just resort to building a vector.
                 let mut edges: Vec<Result<EdgeRangeItem>> = Vec::new();
 
-                for item in vertex_iterator {
+                if limit == 0 {
+                    return Ok(Box::new(edges.into_iter()));
+                }
+
+                // When piping in both directions, an edge between two of
+                // the vertices, or a self-loop, would be found from both
+                // ends, so track what has been returned already.
+                let mut seen_items = HashSet::new();
+                let mut skipped = 0;
+
+                'vertices: for item in vertex_iterator {
                     let (id, _) = item?;
-                    let edge_iterator = edge_range_manager.iterate_for_range(id, type_filter.as_ref(), high_filter)?;
-
-                    for item in edge_iterator {
-                        match item {
-                            Ok((
-                                edge_range_first_id,
-                                edge_range_t,
-                                edge_range_update_datetime,
-                                edge_range_second_id,
-                            )) => {
-                                if let Some(low_filter) = low_filter {
-                                    if edge_range_update_datetime < low_filter {
-                                        break;
+
+                    for &(direction, ref edge_range_manager) in &edge_range_managers {
+                        for scanned_type in &scanned_types {
+                            let mut edge_iterator =
+                                edge_range_manager.iterate_for_range(id, scanned_type.as_ref(), high_filter)?;
+
+                            // Edge ranges are ordered by update datetime, so
+                            // to resume from an inbound ID cursor, the rest
+                            // of the range is sorted by inbound ID instead,
+                            // matching the order of the memory datastore.
+                            // Only outbound queries can have a cursor, so the
+                            // second ID is always the inbound ID.
+                            if let Some(after_inbound_id) = after_inbound_id {
+                                let mut items: Vec<EdgeRangeItem> = Vec::new();
+
+                                for item in edge_iterator {
+                                    let item = item?;
+
+                                    if item.3 > after_inbound_id
+                                        && low_filter.map_or(true, |low_filter| item.2 >= low_filter)
+                                    {
+                                        items.push(item);
                                     }
                                 }
 
-                                edges.push(match converter {
-                                    EdgeDirection::Outbound => Ok((
+                                items.sort_by(|first, second| (&first.1, first.3).cmp(&(&second.1, second.3)));
+                                edge_iterator = Box::new(items.into_iter().map(Ok));
+                            }
+
+                            for item in edge_iterator {
+                                match item {
+                                    Ok((
                                         edge_range_first_id,
                                         edge_range_t,
                                         edge_range_update_datetime,
                                         edge_range_second_id,
-                                    )),
-                                    EdgeDirection::Inbound => Ok((
-                                        edge_range_second_id,
-                                        edge_range_t,
-                                        edge_range_update_datetime,
-                                        edge_range_first_id,
-                                    )),
-                                })
-                            }
-                            Err(_) => edges.push(item),
-                        }
+                                    )) => {
+                                        if let Some(low_filter) = low_filter {
+                                            if edge_range_update_datetime < low_filter {
+                                                break;
+                                            }
+                                        }
+
+                                        let item = match direction {
+                                            EdgeDirection::Inbound => (
+                                                edge_range_second_id,
+                                                edge_range_t,
+                                                edge_range_update_datetime,
+                                                edge_range_first_id,
+                                            ),
+                                            _ => (
+                                                edge_range_first_id,
+                                                edge_range_t,
+                                                edge_range_update_datetime,
+                                                edge_range_second_id,
+                                            ),
+                                        };
+
+                                        if converter == EdgeDirection::Both && !seen_items.insert(item.clone()) {
+                                            continue;
+                                        }
+
+                                        if skipped < offset {
+                                            skipped += 1;
+                                            continue;
+                                        }
+
+                                        edges.push(Ok(item))
+                                    }
+                                    Err(_) => edges.push(item),
+                                }
 
-                        if edges.len() == limit as usize {
-                            break;
+                                if edges.len() == limit as usize {
+                                    break 'vertices;
+                                }
+                            }
                         }
                     }
                 }
@@ -337,22 +792,30 @@ impl RocksdbTransaction {
 
         self.remove_nones_from_iterator(Box::new(mapped))
     }
-}
 
-impl Transaction for RocksdbTransaction {
-    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
-        let vertex_manager = VertexManager::new(self.db.clone());
+    fn get_edge_records(&self, q: EdgeQuery) -> Result<Vec<models::EdgeRecord>> {
+        let manager = EdgePropertyManager::new(self.db.clone());
+        let mut records = Vec::new();
 
-        if vertex_manager.exists(vertex.id)? {
-            Ok(false)
-        } else {
-            let mut batch = WriteBatch::default();
-            vertex_manager.create(&mut batch, vertex)?;
-            self.db.write(batch)?;
-            Ok(true)
+        for item in self.edge_query_to_iterator(q)? {
+            let (outbound_id, t, update_datetime, inbound_id) = item?;
+            let mut properties = Vec::new();
+
+            for property_item in manager.iterate_for_owner(outbound_id, &t, inbound_id)? {
+                let ((_, _, _, name), value) = property_item?;
+                properties.push(models::NamedProperty::new(name, value));
+            }
+
+            let key = models::EdgeKey::new(outbound_id, t, inbound_id);
+            let edge = models::Edge::new(key, update_datetime);
+            records.push(models::EdgeRecord::new(edge, properties));
         }
+
+        Ok(records)
     }
+}
 
+impl ReadTransaction for RocksdbTransaction {
     fn get_vertices(&self, q: &VertexQuery) -> Result<Vec<models::Vertex>> {
         let iterator = self.vertex_query_to_iterator(q.clone())?;
 
@@ -365,18 +828,16 @@ impl Transaction for RocksdbTransaction {
         mapped.collect()
     }
 
-    fn delete_vertices(&self, q: &VertexQuery) -> Result<()> {
+    fn get_vertices_iter(&self, q: &VertexQuery) -> Result<Box<dyn Iterator<Item = Result<models::Vertex>>>> {
         let iterator = self.vertex_query_to_iterator(q.clone())?;
-        let vertex_manager = VertexManager::new(self.db.clone());
-        let mut batch = WriteBatch::default();
 
-        for item in iterator {
-            let (id, _) = item?;
-            vertex_manager.delete(&mut batch, id)?;
-        }
+        let mapped = iterator.map(move |item| {
+            let (id, t) = item?;
+            let vertex = models::Vertex::with_id(id, t);
+            Ok(vertex)
+        });
 
-        self.db.write(batch)?;
-        Ok(())
+        Ok(Box::new(mapped))
     }
 
     fn get_vertex_count(&self) -> Result<u64> {
@@ -385,18 +846,9 @@ impl Transaction for RocksdbTransaction {
         Ok(iterator.count() as u64)
     }
 
-    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+    fn vertex_exists(&self, id: Uuid) -> Result<bool> {
         let vertex_manager = VertexManager::new(self.db.clone());
-
-        if !vertex_manager.exists(key.outbound_id)? || !vertex_manager.exists(key.inbound_id)? {
-            Ok(false)
-        } else {
-            let edge_manager = EdgeManager::new(self.db.clone());
-            let mut batch = WriteBatch::default();
-            edge_manager.set(&mut batch, key.outbound_id, &key.t, key.inbound_id, Utc::now())?;
-            self.db.write(batch)?;
-            Ok(true)
-        }
+        vertex_manager.exists(id)
     }
 
     fn get_edges(&self, q: &EdgeQuery) -> Result<Vec<models::Edge>> {
@@ -412,22 +864,9 @@ impl Transaction for RocksdbTransaction {
         mapped.collect()
     }
 
-    fn delete_edges(&self, q: &EdgeQuery) -> Result<()> {
+    fn edge_exists(&self, key: &models::EdgeKey) -> Result<bool> {
         let edge_manager = EdgeManager::new(self.db.clone());
-        let vertex_manager = VertexManager::new(self.db.clone());
-        let iterator = self.edge_query_to_iterator(q.clone())?;
-        let mut batch = WriteBatch::default();
-
-        for item in iterator {
-            let (outbound_id, t, update_datetime, inbound_id) = item?;
-
-            if vertex_manager.get(outbound_id)?.is_some() {
-                edge_manager.delete(&mut batch, outbound_id, &t, inbound_id, update_datetime)?;
-            };
-        }
-
-        self.db.write(batch)?;
-        Ok(())
+        edge_manager.exists(key.outbound_id, &key.t, key.inbound_id)
     }
 
     fn get_edge_count(
@@ -436,14 +875,23 @@ impl Transaction for RocksdbTransaction {
         type_filter: Option<&models::Type>,
         direction: models::EdgeDirection,
     ) -> Result<u64> {
-        let edge_range_manager = match direction {
-            EdgeDirection::Outbound => EdgeRangeManager::new(self.db.clone()),
-            EdgeDirection::Inbound => EdgeRangeManager::new_reversed(self.db.clone()),
-        };
+        let mut count = 0;
 
-        let count = edge_range_manager.iterate_for_range(id, type_filter, None)?.count();
+        for (range_direction, edge_range_manager) in self.edge_range_managers(direction) {
+            for item in edge_range_manager.iterate_for_range(id, type_filter, None)? {
+                let (_, _, _, other_id) = item?;
 
-        Ok(count as u64)
+                // When counting in both directions, self-loops have already
+                // been counted with the outbound edges
+                if direction == EdgeDirection::Both && range_direction == EdgeDirection::Inbound && other_id == id {
+                    continue;
+                }
+
+                count += 1;
+            }
+        }
+
+        Ok(count)
     }
 
     fn get_vertex_properties(&self, q: &VertexQuery, name: &str) -> Result<Vec<models::VertexProperty>> {
@@ -455,23 +903,227 @@ impl Transaction for RocksdbTransaction {
             let value = manager.get(id, &name[..])?;
 
             if let Some(value) = value {
-                properties.push(models::VertexProperty::new(id, value));
+                properties.push(models::VertexProperty::new(id, name.to_string(), value));
             }
         }
 
         Ok(properties)
     }
 
-    fn set_vertex_properties(&self, q: &VertexQuery, name: &str, value: &JsonValue) -> Result<()> {
+    fn get_vertex_property_names(&self, id: Uuid) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+
+        for item in VertexPropertyManager::new(self.db.clone()).iterate_for_owner(id)? {
+            let ((_, name), _) = item?;
+            names.push(name);
+        }
+
+        Ok(names)
+    }
+
+    fn get_edge_properties(&self, q: &EdgeQuery, name: &str) -> Result<Vec<models::EdgeProperty>> {
+        let manager = EdgePropertyManager::new(self.db.clone());
+        let mut properties = Vec::new();
+
+        for item in self.edge_query_to_iterator(q.clone())? {
+            let (outbound_id, t, _, inbound_id) = item?;
+            let value = manager.get(outbound_id, &t, inbound_id, &name[..])?;
+
+            if let Some(value) = value {
+                let key = models::EdgeKey::new(outbound_id, t, inbound_id);
+                properties.push(models::EdgeProperty::new(key, name.to_string(), value));
+            }
+        }
+
+        Ok(properties)
+    }
+
+    fn get_edge_property_names(&self, key: &models::EdgeKey) -> Result<Vec<String>> {
+        let manager = EdgePropertyManager::new(self.db.clone());
+        let mut names = Vec::new();
+
+        for item in manager.iterate_for_owner(key.outbound_id, &key.t, key.inbound_id)? {
+            let ((_, _, _, name), _) = item?;
+            names.push(name);
+        }
+
+        Ok(names)
+    }
+
+    fn get_full_record(&self, id: Uuid, edge_limit: u32) -> Result<Option<models::FullRecord>> {
+        let t = match VertexManager::new(self.db.clone()).get(id)? {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let mut properties = Vec::new();
+
+        for item in VertexPropertyManager::new(self.db.clone()).iterate_for_owner(id)? {
+            let ((_, name), value) = item?;
+            properties.push(models::NamedProperty::new(name, value));
+        }
+
+        let q = VertexQuery::Vertices { ids: vec![id] };
+        let outbound_edges = self.get_edge_records(q.clone().outbound_edges(None, None, None, edge_limit))?;
+        let inbound_edges = self.get_edge_records(q.inbound_edges(None, None, None, edge_limit))?;
+
+        Ok(Some(models::FullRecord::new(
+            models::Vertex::with_id(id, t),
+            properties,
+            outbound_edges,
+            inbound_edges,
+        )))
+    }
+}
+
+impl WriteTransaction for RocksdbTransaction {
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool> {
+        let vertex_manager = VertexManager::new(self.db.clone());
+
+        if vertex_manager.exists(vertex.id)? {
+            Ok(false)
+        } else {
+            let mut batch = WriteBatch::default();
+            vertex_manager.create(&mut batch, vertex, Utc::now())?;
+            self.write(batch)?;
+            Ok(true)
+        }
+    }
+
+    fn get_or_create_vertex(&self, id: Uuid, t: &models::Type) -> Result<(models::Vertex, bool)> {
+        let vertex_manager = VertexManager::new(self.db.clone());
+
+        if let Some(t) = vertex_manager.get(id)? {
+            return Ok((models::Vertex::with_id(id, t), false));
+        }
+
+        let vertex = models::Vertex::with_id(id, t.clone());
+        let mut batch = WriteBatch::default();
+        vertex_manager.create(&mut batch, &vertex, Utc::now())?;
+        self.write(batch)?;
+        Ok((vertex, true))
+    }
+
+    fn delete_vertices(&self, q: &VertexQuery) -> Result<u64> {
+        let iterator = self.vertex_query_to_iterator(q.clone())?;
+        let vertex_manager = VertexManager::new(self.db.clone());
+        let mut batch = WriteBatch::default();
+        let mut deleted_ids = HashSet::new();
+
+        for item in iterator {
+            let (id, _) = item?;
+
+            // The query may yield the same vertex more than once, but it
+            // should only be counted once
+            if deleted_ids.insert(id) {
+                vertex_manager.delete(&mut batch, id)?;
+            }
+        }
+
+        self.write(batch)?;
+        Ok(deleted_ids.len() as u64)
+    }
+
+    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        let vertex_manager = VertexManager::new(self.db.clone());
+
+        if !self.allow_self_loops && key.outbound_id == key.inbound_id {
+            Ok(false)
+        } else if !vertex_manager.exists(key.outbound_id)? || !vertex_manager.exists(key.inbound_id)? {
+            Ok(false)
+        } else {
+            let edge_manager = EdgeManager::new(self.db.clone());
+            let mut batch = WriteBatch::default();
+            edge_manager.set(&mut batch, key.outbound_id, &key.t, key.inbound_id, Utc::now())?;
+            self.write(batch)?;
+            Ok(true)
+        }
+    }
+
+    fn create_edge_auto(&self, key: &models::EdgeKey, default_type: &models::Type) -> Result<bool> {
+        if !self.allow_self_loops && key.outbound_id == key.inbound_id {
+            return Ok(false);
+        }
+
+        let vertex_manager = VertexManager::new(self.db.clone());
+        let edge_manager = EdgeManager::new(self.db.clone());
+        let mut batch = WriteBatch::default();
+        let now = Utc::now();
+
+        for &id in &[key.outbound_id, key.inbound_id] {
+            if !vertex_manager.exists(id)? {
+                vertex_manager.create(&mut batch, &models::Vertex::with_id(id, default_type.clone()), now)?;
+            }
+        }
+
+        let created = !edge_manager.exists(key.outbound_id, &key.t, key.inbound_id)?;
+        edge_manager.set(&mut batch, key.outbound_id, &key.t, key.inbound_id, now)?;
+        self.write(batch)?;
+        Ok(created)
+    }
+
+    fn update_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        let edge_manager = EdgeManager::new(self.db.clone());
+
+        if !edge_manager.exists(key.outbound_id, &key.t, key.inbound_id)? {
+            return Ok(false);
+        }
+
+        let mut batch = WriteBatch::default();
+        edge_manager.set(&mut batch, key.outbound_id, &key.t, key.inbound_id, Utc::now())?;
+        self.write(batch)?;
+        Ok(true)
+    }
+
+    fn delete_edges(&self, q: &EdgeQuery) -> Result<u64> {
+        let edge_manager = EdgeManager::new(self.db.clone());
+        let vertex_manager = VertexManager::new(self.db.clone());
+        let iterator = self.edge_query_to_iterator(q.clone())?;
+        let mut batch = WriteBatch::default();
+        let mut deleted_keys = HashSet::new();
+
+        for item in iterator {
+            let (outbound_id, t, update_datetime, inbound_id) = item?;
+
+            // As with vertices, only count each edge once
+            if vertex_manager.get(outbound_id)?.is_some() && deleted_keys.insert((outbound_id, t.clone(), inbound_id)) {
+                edge_manager.delete(&mut batch, outbound_id, &t, inbound_id, update_datetime)?;
+            };
+        }
+
+        self.write(batch)?;
+        Ok(deleted_keys.len() as u64)
+    }
+
+    fn set_vertex_properties(&self, q: &VertexQuery, name: &str, value: &JsonValue) -> Result<u64> {
+        validate_property_size(name, value, self.max_property_size_bytes)?;
         let manager = VertexPropertyManager::new(self.db.clone());
         let mut batch = WriteBatch::default();
+        let mut count = 0;
 
         for item in self.vertex_query_to_iterator(q.clone())? {
             let (id, _) = item?;
             manager.set(&mut batch, id, &name[..], value)?;
+            count += 1;
         }
 
-        self.db.write(batch)?;
+        self.write(batch)?;
+        Ok(count)
+    }
+
+    fn patch_vertex_property(&self, q: &VertexQuery, name: &str, patch: &JsonValue) -> Result<()> {
+        let manager = VertexPropertyManager::new(self.db.clone());
+        let mut batch = WriteBatch::default();
+
+        for item in self.vertex_query_to_iterator(q.clone())? {
+            let (id, _) = item?;
+            let mut value = manager.get(id, &name[..])?.unwrap_or(JsonValue::Null);
+            json_merge_patch(&mut value, patch);
+            validate_property_size(name, &value, self.max_property_size_bytes)?;
+            manager.set(&mut batch, id, &name[..], &value)?;
+        }
+
+        self.write(batch)?;
         Ok(())
     }
 
@@ -484,28 +1136,29 @@ impl Transaction for RocksdbTransaction {
             manager.delete(&mut batch, id, &name[..])?;
         }
 
-        self.db.write(batch)?;
+        self.write(batch)?;
         Ok(())
     }
 
-    fn get_edge_properties(&self, q: &EdgeQuery, name: &str) -> Result<Vec<models::EdgeProperty>> {
-        let manager = EdgePropertyManager::new(self.db.clone());
-        let mut properties = Vec::new();
+    fn delete_all_vertex_properties(&self, q: &VertexQuery) -> Result<()> {
+        let manager = VertexPropertyManager::new(self.db.clone());
+        let mut batch = WriteBatch::default();
 
-        for item in self.edge_query_to_iterator(q.clone())? {
-            let (outbound_id, t, _, inbound_id) = item?;
-            let value = manager.get(outbound_id, &t, inbound_id, &name[..])?;
+        for item in self.vertex_query_to_iterator(q.clone())? {
+            let (id, _) = item?;
 
-            if let Some(value) = value {
-                let key = models::EdgeKey::new(outbound_id, t, inbound_id);
-                properties.push(models::EdgeProperty::new(key, value));
+            for item in manager.iterate_for_owner(id)? {
+                let ((_, name), _) = item?;
+                manager.delete(&mut batch, id, &name[..])?;
             }
         }
 
-        Ok(properties)
+        self.write(batch)?;
+        Ok(())
     }
 
     fn set_edge_properties(&self, q: &EdgeQuery, name: &str, value: &JsonValue) -> Result<()> {
+        validate_property_size(name, value, self.max_property_size_bytes)?;
         let manager = EdgePropertyManager::new(self.db.clone());
         let mut batch = WriteBatch::default();
 
@@ -514,7 +1167,25 @@ impl Transaction for RocksdbTransaction {
             manager.set(&mut batch, outbound_id, &t, inbound_id, &name[..], value)?;
         }
 
-        self.db.write(batch)?;
+        self.write(batch)?;
+        Ok(())
+    }
+
+    fn patch_edge_property(&self, q: &EdgeQuery, name: &str, patch: &JsonValue) -> Result<()> {
+        let manager = EdgePropertyManager::new(self.db.clone());
+        let mut batch = WriteBatch::default();
+
+        for item in self.edge_query_to_iterator(q.clone())? {
+            let (outbound_id, t, _, inbound_id) = item?;
+            let mut value = manager
+                .get(outbound_id, &t, inbound_id, &name[..])?
+                .unwrap_or(JsonValue::Null);
+            json_merge_patch(&mut value, patch);
+            validate_property_size(name, &value, self.max_property_size_bytes)?;
+            manager.set(&mut batch, outbound_id, &t, inbound_id, &name[..], &value)?;
+        }
+
+        self.write(batch)?;
         Ok(())
     }
 
@@ -527,7 +1198,24 @@ impl Transaction for RocksdbTransaction {
             manager.delete(&mut batch, outbound_id, &t, inbound_id, &name[..])?;
         }
 
-        self.db.write(batch)?;
+        self.write(batch)?;
+        Ok(())
+    }
+
+    fn delete_all_edge_properties(&self, q: &EdgeQuery) -> Result<()> {
+        let manager = EdgePropertyManager::new(self.db.clone());
+        let mut batch = WriteBatch::default();
+
+        for item in self.edge_query_to_iterator(q.clone())? {
+            let (outbound_id, t, _, inbound_id) = item?;
+
+            for item in manager.iterate_for_owner(outbound_id, &t, inbound_id)? {
+                let ((_, _, _, name), _) = item?;
+                manager.delete(&mut batch, outbound_id, &t, inbound_id, &name[..])?;
+            }
+        }
+
+        self.write(batch)?;
         Ok(())
     }
 }
@@ -1,7 +1,8 @@
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use chrono::offset::Utc;
-use chrono::{DateTime, NaiveDateTime};
-use chrono::{Duration, Timelike};
+use chrono::Timelike;
+use chrono::{DateTime, NaiveDateTime, TimeZone};
+use errors;
 use models;
 use std::i32;
 use std::i64;
@@ -10,9 +11,16 @@ use std::io::Write;
 use std::io::{Cursor, Error as IoError};
 use std::str;
 use std::u8;
-use util::nanos_since_epoch;
 use uuid::Uuid;
 
+// The version of the key layout, which is the first byte of every key. It
+// has to be bumped whenever the layout of any key changes, so that keys
+// written with an older layout are rejected rather than misread.
+pub const KEY_VERSION: u8 = 1;
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+const SIGN_BIT: u64 = 1 << 63;
+
 lazy_static! {
     pub static ref MAX_DATETIME: DateTime<Utc> =
         DateTime::from_utc(NaiveDateTime::from_timestamp(i64::from(i32::MAX), 0), Utc)
@@ -50,8 +58,7 @@ impl<'a> KeyComponent<'a> {
                 cursor.write_all(t.0.as_bytes())?;
             }
             KeyComponent::DateTime(datetime) => {
-                let time_to_end = nanos_since_epoch(&MAX_DATETIME) - nanos_since_epoch(&datetime);
-                cursor.write_u64::<BigEndian>(time_to_end)?;
+                cursor.write_u64::<BigEndian>(encode_datetime(datetime))?;
             }
         };
 
@@ -59,10 +66,43 @@ impl<'a> KeyComponent<'a> {
     }
 }
 
+// Datetimes are stored as signed nanoseconds since the unix epoch, which
+// covers the years 1678 through 2261. Datetimes outside of that range, like
+// a far-future bound of a range query, are clamped to it. Flipping the sign
+// bit makes the big-endian bytes sort in chronological order across the
+// full `i64` range, including pre-epoch datetimes. All bits are then
+// inverted so that, when iterating over keys, the most recent datetimes come
+// first.
+fn encode_datetime(datetime: DateTime<Utc>) -> u64 {
+    let nanos = datetime
+        .timestamp()
+        .checked_mul(NANOS_PER_SEC)
+        .and_then(|nanos| nanos.checked_add(i64::from(datetime.timestamp_subsec_nanos())))
+        .unwrap_or_else(|| if datetime.timestamp() < 0 { i64::MIN } else { i64::MAX });
+    !((nanos as u64) ^ SIGN_BIT)
+}
+
+fn decode_datetime(value: u64) -> DateTime<Utc> {
+    let nanos = (!value ^ SIGN_BIT) as i64;
+    let mut secs = nanos / NANOS_PER_SEC;
+    let mut subsec_nanos = nanos % NANOS_PER_SEC;
+
+    if subsec_nanos < 0 {
+        secs -= 1;
+        subsec_nanos += NANOS_PER_SEC;
+    }
+
+    Utc.timestamp(secs, subsec_nanos as u32)
+}
+
 pub fn build_key(components: &[KeyComponent]) -> Box<[u8]> {
-    let len = components.iter().fold(0, |len, component| len + component.len());
+    let len = components.iter().fold(1, |len, component| len + component.len());
     let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::with_capacity(len));
 
+    if let Err(err) = cursor.write_all(&[KEY_VERSION]) {
+        panic!("Could not build key: {}", err);
+    }
+
     for component in components {
         if let Err(err) = component.write(&mut cursor) {
             panic!("Could not build key: {}", err);
@@ -72,6 +112,19 @@ pub fn build_key(components: &[KeyComponent]) -> Box<[u8]> {
     cursor.into_inner().into_boxed_slice()
 }
 
+// Reads the version a key was built with, which has to be read before any
+// of its components.
+pub fn read_key_version(cursor: &mut Cursor<Box<[u8]>>) -> errors::Result<()> {
+    let mut buf: [u8; 1] = [0; 1];
+    cursor.read_exact(&mut buf)?;
+
+    if buf[0] != KEY_VERSION {
+        return Err(errors::ErrorKind::UnsupportedKeyVersion(buf[0]).into());
+    }
+
+    Ok(())
+}
+
 pub fn read_uuid(cursor: &mut Cursor<Box<[u8]>>) -> Uuid {
     let mut buf: [u8; 16] = [0; 16];
     cursor.read_exact(&mut buf).unwrap();
@@ -98,7 +151,5 @@ pub fn read_unsized_string(cursor: &mut Cursor<Box<[u8]>>) -> String {
 }
 
 pub fn read_datetime(cursor: &mut Cursor<Box<[u8]>>) -> DateTime<Utc> {
-    let time_to_end = cursor.read_u64::<BigEndian>().unwrap();
-    assert!(time_to_end <= i64::MAX as u64);
-    *MAX_DATETIME - Duration::nanoseconds(time_to_end as i64)
+    decode_datetime(cursor.read_u64::<BigEndian>().unwrap())
 }
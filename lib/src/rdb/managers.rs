@@ -15,6 +15,7 @@ use uuid::Uuid;
 
 pub type OwnedPropertyItem = ((Uuid, String), JsonValue);
 pub type VertexItem = (Uuid, models::Type);
+pub type VertexCreatedItem = (Uuid, models::Type, DateTime<Utc>);
 pub type EdgeRangeItem = (Uuid, models::Type, DateTime<Utc>, Uuid);
 pub type EdgePropertyItem = ((Uuid, models::Type, Uuid, String), JsonValue);
 
@@ -60,7 +61,7 @@ pub struct VertexManager {
 impl VertexManager {
     pub fn new(db: Arc<DB>) -> Self {
         VertexManager {
-            cf: db.cf_handle("vertices:v1").unwrap(),
+            cf: db.cf_handle("vertices:v2").unwrap(),
             db,
         }
     }
@@ -73,29 +74,41 @@ impl VertexManager {
         Ok(self.db.get_cf(self.cf, &self.key(id))?.is_some())
     }
 
-    pub fn get(&self, id: Uuid) -> Result<Option<models::Type>> {
+    fn get_value(&self, id: Uuid) -> Result<Option<(models::Type, DateTime<Utc>)>> {
         match self.db.get_cf(self.cf, &self.key(id))? {
             Some(value_bytes) => Ok(Some(bincode::deserialize(&value_bytes)?)),
             None => Ok(None),
         }
     }
 
-    fn iterate(&self, iterator: DBIterator) -> Result<impl Iterator<Item = Result<VertexItem>>> {
-        Ok(iterator.map(|item| -> Result<VertexItem> {
+    pub fn get(&self, id: Uuid) -> Result<Option<models::Type>> {
+        Ok(self.get_value(id)?.map(|(t, _)| t))
+    }
+
+    pub fn get_created_datetime(&self, id: Uuid) -> Result<Option<DateTime<Utc>>> {
+        Ok(self.get_value(id)?.map(|(_, created_datetime)| created_datetime))
+    }
+
+    fn iterate(&self, iterator: DBIterator) -> Result<impl Iterator<Item = Result<VertexCreatedItem>>> {
+        Ok(iterator.map(|item| -> Result<VertexCreatedItem> {
             let (k, v) = item;
 
             let id = {
-                debug_assert_eq!(k.len(), 16);
+                debug_assert_eq!(k.len(), 17);
                 let mut cursor = Cursor::new(k);
+                read_key_version(&mut cursor)?;
                 read_uuid(&mut cursor)
             };
 
-            let value: models::Type = bincode::deserialize(&v.to_owned()[..])?;
-            Ok((id, value))
+            let (t, created_datetime): (models::Type, DateTime<Utc>) = bincode::deserialize(&v.to_owned()[..])?;
+            Ok((id, t, created_datetime))
         }))
     }
 
-    pub fn iterate_for_range(&self, id: Uuid) -> Result<impl Iterator<Item = Result<VertexItem>>> {
+    pub fn iterate_for_range_with_created_datetime(
+        &self,
+        id: Uuid,
+    ) -> Result<impl Iterator<Item = Result<VertexCreatedItem>>> {
         let low_key = build_key(&[KeyComponent::Uuid(id)]);
         let iterator = self
             .db
@@ -103,8 +116,18 @@ impl VertexManager {
         self.iterate(iterator)
     }
 
-    pub fn create(&self, batch: &mut WriteBatch, vertex: &models::Vertex) -> Result<()> {
-        set_bincode(batch, self.cf, &self.key(vertex.id), &vertex.t)
+    pub fn iterate_for_range(&self, id: Uuid) -> Result<impl Iterator<Item = Result<VertexItem>>> {
+        let iterator = self.iterate_for_range_with_created_datetime(id)?;
+        Ok(iterator.map(|item| item.map(|(id, t, _)| (id, t))))
+    }
+
+    pub fn create(
+        &self,
+        batch: &mut WriteBatch,
+        vertex: &models::Vertex,
+        created_datetime: DateTime<Utc>,
+    ) -> Result<()> {
+        set_bincode(batch, self.cf, &self.key(vertex.id), &(&vertex.t, created_datetime))
     }
 
     pub fn delete(&self, mut batch: &mut WriteBatch, id: Uuid) -> Result<()> {
@@ -165,7 +188,7 @@ pub struct EdgeManager {
 impl EdgeManager {
     pub fn new(db: Arc<DB>) -> Self {
         EdgeManager {
-            cf: db.cf_handle("edges:v1").unwrap(),
+            cf: db.cf_handle("edges:v2").unwrap(),
             db,
         }
     }
@@ -178,6 +201,13 @@ impl EdgeManager {
         ])
     }
 
+    pub fn exists(&self, outbound_id: Uuid, t: &models::Type, inbound_id: Uuid) -> Result<bool> {
+        Ok(self
+            .db
+            .get_cf(self.cf, &self.key(outbound_id, t, inbound_id))?
+            .is_some())
+    }
+
     pub fn get(&self, outbound_id: Uuid, t: &models::Type, inbound_id: Uuid) -> Result<Option<DateTime<Utc>>> {
         match self.db.get_cf(self.cf, &self.key(outbound_id, t, inbound_id))? {
             Some(value_bytes) => Ok(Some(bincode::deserialize(&value_bytes)?)),
@@ -185,6 +215,21 @@ impl EdgeManager {
         }
     }
 
+    pub fn iterate_for_all(&self) -> Result<impl Iterator<Item = Result<EdgeRangeItem>>> {
+        let iterator = self.db.iterator_cf(self.cf, IteratorMode::Start)?;
+
+        Ok(iterator.map(|item| -> Result<EdgeRangeItem> {
+            let (k, v) = item;
+            let mut cursor = Cursor::new(k);
+            read_key_version(&mut cursor)?;
+            let outbound_id = read_uuid(&mut cursor);
+            let t = read_type(&mut cursor);
+            let inbound_id = read_uuid(&mut cursor);
+            let update_datetime: DateTime<Utc> = bincode::deserialize(&v)?;
+            Ok((outbound_id, t, update_datetime, inbound_id))
+        }))
+    }
+
     pub fn set(
         &self,
         mut batch: &mut WriteBatch,
@@ -249,14 +294,14 @@ pub struct EdgeRangeManager {
 impl EdgeRangeManager {
     pub fn new(db: Arc<DB>) -> Self {
         EdgeRangeManager {
-            cf: db.cf_handle("edge_ranges:v1").unwrap(),
+            cf: db.cf_handle("edge_ranges:v2").unwrap(),
             db,
         }
     }
 
     pub fn new_reversed(db: Arc<DB>) -> Self {
         EdgeRangeManager {
-            cf: db.cf_handle("reversed_edge_ranges:v1").unwrap(),
+            cf: db.cf_handle("reversed_edge_ranges:v2").unwrap(),
             db,
         }
     }
@@ -276,6 +321,7 @@ impl EdgeRangeManager {
         Ok(filtered.map(move |item| -> Result<EdgeRangeItem> {
             let (k, _) = item;
             let mut cursor = Cursor::new(k);
+            read_key_version(&mut cursor)?;
             let first_id = read_uuid(&mut cursor);
             let t = read_type(&mut cursor);
             let update_datetime = read_datetime(&mut cursor);
@@ -374,7 +420,7 @@ pub struct VertexPropertyManager {
 impl VertexPropertyManager {
     pub fn new(db: Arc<DB>) -> Self {
         VertexPropertyManager {
-            cf: db.cf_handle("vertex_properties:v1").unwrap(),
+            cf: db.cf_handle("vertex_properties:v2").unwrap(),
             db,
         }
     }
@@ -393,6 +439,7 @@ impl VertexPropertyManager {
         Ok(filtered.map(move |item| -> Result<OwnedPropertyItem> {
             let (k, v) = item;
             let mut cursor = Cursor::new(k);
+            read_key_version(&mut cursor)?;
             let owner_id = read_uuid(&mut cursor);
             debug_assert_eq!(vertex_id, owner_id);
             let name = read_unsized_string(&mut cursor);
@@ -426,7 +473,7 @@ pub struct EdgePropertyManager {
 impl EdgePropertyManager {
     pub fn new(db: Arc<DB>) -> Self {
         EdgePropertyManager {
-            cf: db.cf_handle("edge_properties:v1").unwrap(),
+            cf: db.cf_handle("edge_properties:v2").unwrap(),
             db,
         }
     }
@@ -460,6 +507,7 @@ impl EdgePropertyManager {
         let mapped = filtered.map(move |item| -> Result<EdgePropertyItem> {
             let (k, v) = item;
             let mut cursor = Cursor::new(k);
+            read_key_version(&mut cursor)?;
 
             let edge_property_outbound_id = read_uuid(&mut cursor);
             debug_assert_eq!(edge_property_outbound_id, outbound_id);
@@ -1,3 +1,26 @@
+#[test]
+fn should_reject_oversized_property_values() {
+    use super::RocksdbDatastore;
+    use tests::TEST_MAX_PROPERTY_SIZE_BYTES;
+    use util::generate_temporary_path;
+
+    let mut datastore = RocksdbDatastore::new(&generate_temporary_path(), Some(1), false)
+        .unwrap()
+        .with_max_property_size_bytes(Some(TEST_MAX_PROPERTY_SIZE_BYTES));
+    ::tests::should_reject_oversized_property_values(&mut datastore);
+}
+
+#[test]
+fn should_reject_dangling_bulk_insert_edges() {
+    use super::RocksdbDatastore;
+    use util::generate_temporary_path;
+
+    let mut datastore = RocksdbDatastore::new(&generate_temporary_path(), Some(1), false)
+        .unwrap()
+        .with_reject_dangling_bulk_edges(true);
+    ::tests::should_reject_dangling_bulk_insert_edges(&mut datastore);
+}
+
 #[test]
 fn should_repair() {
     use super::RocksdbDatastore;
@@ -11,3 +34,228 @@ fn should_repair() {
     // Now try to repair
     RocksdbDatastore::repair(&path, Some(1)).unwrap();
 }
+
+#[cfg(test)]
+fn traversal_limited_datastore() -> super::RocksdbDatastore {
+    use super::RocksdbDatastore;
+    use tests::test_traversal_limits;
+    use util::generate_temporary_path;
+
+    RocksdbDatastore::new(&generate_temporary_path(), Some(1), false)
+        .unwrap()
+        .with_traversal_limits(test_traversal_limits())
+}
+
+#[test]
+fn should_abort_traversal_over_vertex_limit() {
+    ::tests::should_abort_traversal_over_vertex_limit(&mut traversal_limited_datastore());
+}
+
+#[test]
+fn should_abort_traversal_over_edge_limit() {
+    ::tests::should_abort_traversal_over_edge_limit(&mut traversal_limited_datastore());
+}
+
+#[test]
+fn should_abort_traversal_over_hop_limit() {
+    ::tests::should_abort_traversal_over_hop_limit(&mut traversal_limited_datastore());
+}
+
+#[test]
+fn should_not_create_a_disallowed_self_loop() {
+    use super::RocksdbDatastore;
+    use util::generate_temporary_path;
+
+    let mut datastore = RocksdbDatastore::new(&generate_temporary_path(), Some(1), false)
+        .unwrap()
+        .with_allow_self_loops(false);
+    ::tests::should_not_create_a_disallowed_self_loop(&mut datastore);
+}
+
+#[test]
+fn should_write_with_sync_and_without_wal() {
+    use super::RocksdbDatastore;
+    use util::generate_temporary_path;
+
+    let mut datastore = RocksdbDatastore::new(&generate_temporary_path(), Some(1), false)
+        .unwrap()
+        .with_sync_writes(true);
+    ::tests::should_handle_vertex_properties(&mut datastore);
+
+    let mut datastore = RocksdbDatastore::new(&generate_temporary_path(), Some(1), false)
+        .unwrap()
+        .with_wal(false);
+    ::tests::should_handle_vertex_properties(&mut datastore);
+}
+
+#[test]
+fn should_order_datetime_keys_across_the_epoch() {
+    use super::keys::{build_key, read_datetime, read_key_version, KeyComponent};
+    use chrono::{TimeZone, Utc};
+    use std::io::Cursor;
+
+    let mut datetimes = vec![
+        Utc.timestamp(0, 0),
+        Utc.timestamp(-1, 999_999_999),
+        Utc.timestamp(1_500_000_000, 123_456_789),
+        Utc.timestamp(-1_500_000_000, 1),
+        Utc.timestamp(-1, 0),
+        Utc.timestamp(0, 1),
+        Utc.timestamp(-5_000_000_000, 0),
+        Utc.timestamp(5_000_000_000, 999_999_999),
+    ];
+
+    let mut keys: Vec<Box<[u8]>> = datetimes
+        .iter()
+        .map(|datetime| build_key(&[KeyComponent::DateTime(*datetime)]))
+        .collect();
+
+    // Keys sort with the most recent datetimes first
+    keys.sort();
+    datetimes.sort();
+    datetimes.reverse();
+
+    let decoded: Vec<_> = keys
+        .into_iter()
+        .map(|key| {
+            let mut cursor = Cursor::new(key);
+            read_key_version(&mut cursor).unwrap();
+            read_datetime(&mut cursor)
+        })
+        .collect();
+    assert_eq!(decoded, datetimes);
+}
+
+#[test]
+fn should_clamp_out_of_range_datetime_keys() {
+    use super::keys::{build_key, read_datetime, read_key_version, KeyComponent};
+    use chrono::{TimeZone, Utc};
+    use std::io::Cursor;
+
+    // The earliest and latest datetimes that fit in `i64` nanoseconds
+    let earliest = Utc.timestamp(-9_223_372_037, 145_224_192);
+    let latest = Utc.timestamp(9_223_372_036, 854_775_807);
+
+    for &(datetime, clamped_datetime) in &[
+        (Utc.ymd(1000, 1, 1).and_hms(0, 0, 0), earliest),
+        (Utc.ymd(9999, 12, 31).and_hms(0, 0, 0), latest),
+    ] {
+        let key = build_key(&[KeyComponent::DateTime(datetime)]);
+        assert_eq!(key, build_key(&[KeyComponent::DateTime(clamped_datetime)]));
+
+        let mut cursor = Cursor::new(key);
+        read_key_version(&mut cursor).unwrap();
+        assert_eq!(read_datetime(&mut cursor), clamped_datetime);
+    }
+}
+
+#[test]
+fn should_reject_keys_with_an_unsupported_version() {
+    use super::keys::{build_key, read_key_version, read_uuid, KeyComponent, KEY_VERSION};
+    use errors::{Error, ErrorKind};
+    use std::io::Cursor;
+    use util::generate_uuid_v1;
+
+    let id = generate_uuid_v1();
+    let key = build_key(&[KeyComponent::Uuid(id)]);
+    assert_eq!(key[0], KEY_VERSION);
+
+    let mut cursor = Cursor::new(key.clone());
+    read_key_version(&mut cursor).unwrap();
+    assert_eq!(read_uuid(&mut cursor), id);
+
+    // Simulate a key written with an older layout
+    let mut old_key = key.to_vec();
+    old_key[0] = KEY_VERSION - 1;
+
+    match read_key_version(&mut Cursor::new(old_key.into_boxed_slice())) {
+        Err(Error(ErrorKind::UnsupportedKeyVersion(version), _)) => assert_eq!(version, KEY_VERSION - 1),
+        other => panic!("Unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn should_reject_databases_with_unversioned_keys() {
+    use super::RocksdbDatastore;
+    use errors::{Error, ErrorKind};
+    use rocksdb::{Options, DB};
+    use util::generate_temporary_path;
+
+    let path = generate_temporary_path();
+
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let mut db = DB::open(&opts, &path).unwrap();
+
+        for cf_name in &[
+            "vertices:v1",
+            "edges:v1",
+            "edge_ranges:v1",
+            "reversed_edge_ranges:v1",
+            "vertex_properties:v1",
+            "edge_properties:v1",
+        ] {
+            db.create_cf(cf_name, &opts).unwrap();
+        }
+    }
+
+    match RocksdbDatastore::new(&path, None, false) {
+        Err(Error(ErrorKind::UnsupportedKeyVersion(version), _)) => assert_eq!(version, 0),
+        other => panic!("Unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn should_get_vertices_leniently_past_a_corrupt_record() {
+    use super::keys::{build_key, KeyComponent};
+    use super::RocksdbDatastore;
+    use models;
+    use rocksdb::{Options, DB};
+    use traits::{Datastore, ReadTransaction, WriteTransaction};
+    use util::generate_temporary_path;
+    use uuid::Uuid;
+
+    let path = generate_temporary_path();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+
+    let ids: Vec<Uuid> = {
+        let datastore = RocksdbDatastore::new(&path, Some(1), false).unwrap();
+        let trans = datastore.transaction().unwrap();
+        (0..3)
+            .map(|_| trans.create_vertex_from_type(t.clone()).unwrap())
+            .collect()
+    };
+
+    // Overwrite one of the vertices with a value that can't be deserialized
+    {
+        let db = DB::open_cf(
+            &Options::default(),
+            &path,
+            &[
+                "vertices:v2",
+                "edges:v2",
+                "edge_ranges:v2",
+                "reversed_edge_ranges:v2",
+                "vertex_properties:v2",
+                "edge_properties:v2",
+            ],
+        )
+        .unwrap();
+        let cf = db.cf_handle("vertices:v2").unwrap();
+        db.put_cf(cf, &build_key(&[KeyComponent::Uuid(ids[1])]), &[0xffu8])
+            .unwrap();
+    }
+
+    let datastore = RocksdbDatastore::new(&path, Some(1), false).unwrap();
+    let trans = datastore.transaction().unwrap();
+    let q = models::VertexQuery::all(10);
+
+    // The whole query fails normally, but is only missing the corrupt
+    // vertex when lenient
+    assert!(trans.get_vertices(&q).is_err());
+    let (vertices, errors) = trans.get_vertices_lenient(&q).unwrap();
+    assert_eq!(vertices.len(), 2);
+    assert!(vertices.iter().all(|vertex| vertex.id != ids[1] && vertex.t == t));
+    assert_eq!(errors.len(), 1);
+}
@@ -0,0 +1,67 @@
+use super::super::{BatchOperation, Datastore, EdgeKey, EdgeQuery, ReadTransaction, Type, Vertex, VertexQuery};
+use serde_json::Value as JsonValue;
+
+pub fn should_execute_batch<D: Datastore>(datastore: &mut D) {
+    let vertex_t = Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = Type::new("test_edge_type".to_string()).unwrap();
+    let first_v = Vertex::new(vertex_t.clone());
+    let second_v = Vertex::new(vertex_t.clone());
+    let third_v = Vertex::new(vertex_t.clone());
+    let key = EdgeKey::new(first_v.id, edge_t.clone(), second_v.id);
+    let deleted_key = EdgeKey::new(second_v.id, edge_t.clone(), third_v.id);
+
+    // Later operations should see the effects of earlier ones
+    let operations = vec![
+        BatchOperation::CreateVertex(first_v.clone()),
+        BatchOperation::CreateVertex(second_v.clone()),
+        BatchOperation::CreateVertex(third_v.clone()),
+        BatchOperation::CreateEdge(key.clone()),
+        BatchOperation::CreateEdge(deleted_key.clone()),
+        BatchOperation::SetVertexProperties(
+            VertexQuery::single(first_v.id)
+                .outbound_edges(None, None, None, 10)
+                .inbound_vertices(10),
+            "name".to_string(),
+            JsonValue::from("bob"),
+        ),
+        BatchOperation::SetVertexProperties(
+            VertexQuery::single(third_v.id),
+            "name".to_string(),
+            JsonValue::from("carol"),
+        ),
+        BatchOperation::SetEdgeProperties(EdgeQuery::single(key.clone()), "weight".to_string(), JsonValue::from(1)),
+        BatchOperation::SetEdgeProperties(
+            EdgeQuery::single(key.clone()),
+            "color".to_string(),
+            JsonValue::from("red"),
+        ),
+        BatchOperation::DeleteVertexProperties(VertexQuery::single(third_v.id), "name".to_string()),
+        BatchOperation::DeleteEdgeProperties(EdgeQuery::single(key.clone()), "color".to_string()),
+        BatchOperation::DeleteEdges(EdgeQuery::single(deleted_key.clone())),
+        BatchOperation::DeleteVertices(VertexQuery::single(third_v.id)),
+    ];
+
+    datastore.execute_batch(operations.into_iter()).unwrap();
+
+    let trans = datastore.transaction().unwrap();
+    let q = VertexQuery::ids(vec![first_v.id, second_v.id, third_v.id]);
+    let ids: Vec<_> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&first_v.id));
+    assert!(ids.contains(&second_v.id));
+
+    assert!(trans.edge_exists(&key).unwrap());
+    assert!(!trans.edge_exists(&deleted_key).unwrap());
+
+    let names = trans.get_vertex_properties(&q, "name").unwrap();
+    assert_eq!(names.len(), 1);
+    assert_eq!(names[0].id, second_v.id);
+    assert_eq!(names[0].value, JsonValue::from("bob"));
+
+    let q = EdgeQuery::single(key);
+    assert_eq!(
+        trans.get_edge_properties(&q, "weight").unwrap()[0].value,
+        JsonValue::from(1)
+    );
+    assert_eq!(trans.get_edge_properties(&q, "color").unwrap().len(), 0);
+}
@@ -1,7 +1,11 @@
-use super::super::{BulkInsertItem, Datastore, EdgeKey, EdgeQuery, Transaction, Type, Vertex, VertexQuery};
+use super::super::{
+    BulkInsertItem, BulkInsertItemError, BulkInsertMode, Datastore, EdgeKey, EdgeQuery, Error, ErrorKind,
+    ReadTransaction, Type, Vertex, VertexQuery, WriteTransaction,
+};
 use chrono::offset::Utc;
 use chrono::Timelike;
 use serde_json::Value as JsonValue;
+use uuid::Uuid;
 
 pub fn should_bulk_insert<D: Datastore>(datastore: &mut D) {
     let vertex_t = Type::new("test_vertex_type".to_string()).unwrap();
@@ -129,3 +133,288 @@ pub fn should_bulk_insert_an_invalid_edge<D: Datastore>(datastore: &mut D) {
     let items = vec![BulkInsertItem::Edge(EdgeKey::new(v2.id, edge_t.clone(), v1.id))];
     assert!(datastore.bulk_insert(items.into_iter()).is_ok());
 }
+
+pub fn should_bulk_insert_with_progress<D: Datastore>(datastore: &mut D) {
+    let vertex_t = Type::new("test_vertex_type".to_string()).unwrap();
+    let vertices: Vec<Vertex> = (0..5).map(|_| Vertex::new(vertex_t.clone())).collect();
+    let items: Vec<BulkInsertItem> = vertices.iter().cloned().map(BulkInsertItem::Vertex).collect();
+
+    let mut counts = Vec::new();
+    datastore
+        .bulk_insert_with_progress(items.clone().into_iter(), 2, |count| counts.push(count))
+        .unwrap();
+    assert_eq!(counts, vec![2, 4, 5]);
+
+    let trans = datastore.transaction().unwrap();
+    let ids: Vec<Uuid> = vertices.iter().map(|vertex| vertex.id).collect();
+    assert_eq!(trans.get_vertices(&VertexQuery::Vertices { ids }).unwrap().len(), 5);
+
+    let mut counts = Vec::new();
+    datastore
+        .bulk_insert_with_progress(items.into_iter(), 5, |count| counts.push(count))
+        .unwrap();
+    assert_eq!(counts, vec![5]);
+
+    let result = datastore.bulk_insert_with_progress(Vec::new().into_iter(), 0, |_| {});
+    match result {
+        Err(Error(ErrorKind::Validation(_), _)) => (),
+        _ => panic!("Expected a validation error, got {:?}", result),
+    }
+}
+
+pub fn should_bulk_insert_partially<D: Datastore>(datastore: &mut D) {
+    let vertex_t = Type::new("test_vertex_type".to_string()).unwrap();
+    let outbound_v = Vertex::new(vertex_t.clone());
+    let inbound_v = Vertex::new(vertex_t.clone());
+    let edge_t = Type::new("test_edge_type".to_string()).unwrap();
+    let key = EdgeKey::new(outbound_v.id, edge_t.clone(), inbound_v.id);
+    let dangling_key = EdgeKey::new(outbound_v.id, edge_t.clone(), Uuid::default());
+
+    let items = vec![
+        BulkInsertItem::Vertex(outbound_v.clone()),
+        BulkInsertItem::Vertex(inbound_v.clone()),
+        BulkInsertItem::Edge(dangling_key.clone()),
+        BulkInsertItem::Edge(key.clone()),
+        BulkInsertItem::EdgeProperty(
+            key.clone(),
+            "edge_property_name".to_string(),
+            JsonValue::String("edge_property_value".to_string()),
+        ),
+    ];
+
+    let results = datastore.bulk_insert_partial(items.into_iter()).unwrap();
+    assert_eq!(
+        results,
+        vec![Ok(()), Ok(()), Err(BulkInsertItemError::MissingVertex), Ok(()), Ok(()),]
+    );
+
+    let trans = datastore.transaction().unwrap();
+
+    let edges = trans
+        .get_edges(&EdgeQuery::Edges {
+            keys: vec![key.clone(), dangling_key],
+        })
+        .unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].key, key);
+
+    let edge_properties = trans
+        .get_edge_properties(&EdgeQuery::Edges { keys: vec![key] }, "edge_property_name")
+        .unwrap();
+    assert_eq!(edge_properties.len(), 1);
+}
+
+#[cfg(feature = "compression")]
+pub fn should_import_compressed<D: Datastore>(datastore: &mut D) {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use memory::MemoryDatastore;
+    use models::ImportReport;
+    use std::io::Write;
+    use zstd;
+
+    let vertex_t = Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = Type::new("test_edge_type".to_string()).unwrap();
+    let outbound_v = Vertex::new(vertex_t.clone());
+    let inbound_v = Vertex::new(vertex_t.clone());
+    let key = EdgeKey::new(outbound_v.id, edge_t.clone(), inbound_v.id);
+
+    // Export the graph from a memory datastore, which writes the format
+    // that's imported
+    let source_datastore = MemoryDatastore::default();
+    let source_trans = source_datastore.transaction().unwrap();
+    source_trans.create_vertex(&outbound_v).unwrap();
+    source_trans.create_vertex(&inbound_v).unwrap();
+    source_trans.create_edge(&key).unwrap();
+    source_trans
+        .set_vertex_properties(
+            &VertexQuery::Vertices {
+                ids: vec![outbound_v.id],
+            },
+            "vertex_property_name",
+            &JsonValue::from(1),
+        )
+        .unwrap();
+    source_trans
+        .set_edge_properties(
+            &EdgeQuery::Edges {
+                keys: vec![key.clone()],
+            },
+            "edge_property_name",
+            &JsonValue::from(2),
+        )
+        .unwrap();
+    let mut serialized = Vec::new();
+    source_datastore.export_json(&mut serialized).unwrap();
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&serialized).unwrap();
+    let gzipped = encoder.finish().unwrap();
+    let zstd_compressed = zstd::stream::encode_all(&serialized[..], 1).unwrap();
+
+    let expected_report = ImportReport {
+        parsed_vertices: 2,
+        parsed_edges: 1,
+        parsed_vertex_properties: 1,
+        parsed_edge_properties: 1,
+    };
+
+    // Importing the same graph repeatedly should be idempotent, regardless of
+    // its compression
+    for content in &[gzipped, zstd_compressed, serialized] {
+        let report = datastore.import_compressed(&content[..]).unwrap();
+        assert_eq!(report, expected_report);
+
+        let trans = datastore.transaction().unwrap();
+        let vertices = trans
+            .get_vertices(&VertexQuery::Vertices {
+                ids: vec![outbound_v.id, inbound_v.id],
+            })
+            .unwrap();
+        assert_eq!(vertices.len(), 2);
+        assert!(vertices.iter().all(|vertex| vertex.t == vertex_t));
+
+        let edges = trans
+            .get_edges(&EdgeQuery::Edges {
+                keys: vec![key.clone()],
+            })
+            .unwrap();
+        assert_eq!(edges.len(), 1);
+
+        let vertex_properties = trans
+            .get_vertex_properties(
+                &VertexQuery::Vertices {
+                    ids: vec![outbound_v.id],
+                },
+                "vertex_property_name",
+            )
+            .unwrap();
+        assert_eq!(vertex_properties.len(), 1);
+        assert_eq!(vertex_properties[0].value, JsonValue::from(1));
+
+        let edge_properties = trans
+            .get_edge_properties(
+                &EdgeQuery::Edges {
+                    keys: vec![key.clone()],
+                },
+                "edge_property_name",
+            )
+            .unwrap();
+        assert_eq!(edge_properties.len(), 1);
+        assert_eq!(edge_properties[0].value, JsonValue::from(2));
+    }
+}
+
+// Inserts a vertex with a property, and returns it along with bulk insert
+// items that conflict with it, followed by one item that doesn't.
+fn create_bulk_insert_conflicts<D: Datastore>(datastore: &mut D) -> (Vertex, Vec<BulkInsertItem>) {
+    let vertex = Vertex::new(Type::new("test_vertex_type".to_string()).unwrap());
+    let items = vec![
+        BulkInsertItem::Vertex(vertex.clone()),
+        BulkInsertItem::VertexProperty(vertex.id, "name".to_string(), JsonValue::from("original")),
+    ];
+    datastore.bulk_insert(items.into_iter()).unwrap();
+
+    let conflicting_vertex = Vertex::with_id(vertex.id, Type::new("other_vertex_type".to_string()).unwrap());
+    let items = vec![
+        BulkInsertItem::Vertex(conflicting_vertex),
+        BulkInsertItem::VertexProperty(vertex.id, "name".to_string(), JsonValue::from("conflicting")),
+        BulkInsertItem::VertexProperty(vertex.id, "age".to_string(), JsonValue::from(30)),
+    ];
+
+    (vertex, items)
+}
+
+pub fn should_bulk_insert_with_overwrite_mode<D: Datastore>(datastore: &mut D) {
+    let (vertex, items) = create_bulk_insert_conflicts(datastore);
+    let skipped = datastore
+        .bulk_insert_with_mode(items.into_iter(), BulkInsertMode::Overwrite)
+        .unwrap();
+    assert_eq!(skipped, 0);
+
+    let trans = datastore.transaction().unwrap();
+    let q = VertexQuery::Vertices { ids: vec![vertex.id] };
+    let vertices = trans.get_vertices(&q).unwrap();
+    assert_eq!(vertices[0].t, Type::new("other_vertex_type".to_string()).unwrap());
+    let names = trans.get_vertex_properties(&q, "name").unwrap();
+    assert_eq!(names[0].value, JsonValue::from("conflicting"));
+    let ages = trans.get_vertex_properties(&q, "age").unwrap();
+    assert_eq!(ages[0].value, JsonValue::from(30));
+}
+
+pub fn should_bulk_insert_with_skip_mode<D: Datastore>(datastore: &mut D) {
+    let (vertex, items) = create_bulk_insert_conflicts(datastore);
+    let skipped = datastore
+        .bulk_insert_with_mode(items.into_iter(), BulkInsertMode::Skip)
+        .unwrap();
+    assert_eq!(skipped, 2);
+
+    let trans = datastore.transaction().unwrap();
+    let q = VertexQuery::Vertices { ids: vec![vertex.id] };
+    let vertices = trans.get_vertices(&q).unwrap();
+    assert_eq!(vertices[0].t, vertex.t);
+    let names = trans.get_vertex_properties(&q, "name").unwrap();
+    assert_eq!(names[0].value, JsonValue::from("original"));
+    let ages = trans.get_vertex_properties(&q, "age").unwrap();
+    assert_eq!(ages[0].value, JsonValue::from(30));
+}
+
+pub fn should_bulk_insert_with_error_mode<D: Datastore>(datastore: &mut D) {
+    let (vertex, items) = create_bulk_insert_conflicts(datastore);
+
+    match datastore.bulk_insert_with_mode(items.into_iter(), BulkInsertMode::Error) {
+        Err(Error(ErrorKind::BulkInsertConflict(_), _)) => (),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    // Nothing should have been inserted, including non-conflicting items
+    let trans = datastore.transaction().unwrap();
+    let q = VertexQuery::Vertices { ids: vec![vertex.id] };
+    let names = trans.get_vertex_properties(&q, "name").unwrap();
+    assert_eq!(names[0].value, JsonValue::from("original"));
+    let ages = trans.get_vertex_properties(&q, "age").unwrap();
+    assert_eq!(ages.len(), 0);
+
+    let key = EdgeKey::new(vertex.id, Type::new("test_edge_type".to_string()).unwrap(), vertex.id);
+    let items = vec![BulkInsertItem::Edge(key.clone())];
+    let skipped = datastore
+        .bulk_insert_with_mode(items.into_iter(), BulkInsertMode::Error)
+        .unwrap();
+    assert_eq!(skipped, 0);
+    assert!(trans.edge_exists(&key).unwrap());
+}
+
+/// Checks that bulk inserts fail on edges whose vertices don't exist.
+/// Datastores passed to this must be configured to reject such edges.
+pub fn should_reject_dangling_bulk_insert_edges<D: Datastore>(datastore: &mut D) {
+    let vertex_t = Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = Type::new("test_edge_type".to_string()).unwrap();
+    let existing_v = Vertex::new(vertex_t.clone());
+    let inserted_v = Vertex::new(vertex_t);
+    datastore.transaction().unwrap().create_vertex(&existing_v).unwrap();
+
+    let valid_key = EdgeKey::new(existing_v.id, edge_t.clone(), inserted_v.id);
+    let dangling_key = EdgeKey::new(inserted_v.id, edge_t, Uuid::default());
+
+    let items = vec![
+        BulkInsertItem::Vertex(inserted_v.clone()),
+        BulkInsertItem::Edge(valid_key.clone()),
+        BulkInsertItem::Edge(dangling_key.clone()),
+    ];
+
+    match datastore.bulk_insert(items.clone().into_iter()) {
+        Err(Error(ErrorKind::DanglingBulkInsertEdges(ref keys), _)) => {
+            assert_eq!(keys, &format!("{:?}", vec![&dangling_key]))
+        }
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    // Nothing was inserted
+    let trans = datastore.transaction().unwrap();
+    assert!(!trans.vertex_exists(inserted_v.id).unwrap());
+
+    // Edges to vertices inserted earlier in the same bulk insert are fine
+    datastore.bulk_insert(items[..2].iter().cloned()).unwrap();
+    let trans = datastore.transaction().unwrap();
+    assert!(trans.edge_exists(&valid_key).unwrap());
+}
@@ -0,0 +1,12 @@
+use super::super::{Datastore, Type, WriteTransaction};
+
+pub fn should_pass_health_check<D: Datastore>(datastore: &mut D) {
+    datastore.health_check().unwrap();
+
+    // The check should still pass once the datastore has been used
+    let trans = datastore.transaction().unwrap();
+    trans
+        .create_vertex_from_type(Type::new("test_vertex_type".to_string()).unwrap())
+        .unwrap();
+    datastore.health_check().unwrap();
+}
@@ -1,9 +1,15 @@
-use super::super::{Datastore, EdgeDirection, EdgeKey, EdgeQuery, Transaction, VertexQuery};
+use super::super::{
+    Datastore, EdgeDirection, EdgeKey, EdgeQuery, ReadTransaction, SortDirection, VertexQuery, WriteTransaction,
+};
 use super::util::{create_edge_from, create_edges, create_time_range_queryable_edges};
 use chrono::offset::Utc;
-use chrono::Timelike;
+use chrono::{TimeZone, Timelike};
 use models;
+use serde_json::Value as JsonValue;
 use std::collections::HashSet;
+use std::thread;
+use std::time::Duration;
+use util::generate_random_secret;
 use uuid::Uuid;
 
 pub fn should_get_a_valid_edge<D: Datastore>(datastore: &mut D) {
@@ -108,6 +114,69 @@ pub fn should_create_a_valid_edge<D: Datastore>(datastore: &mut D) {
     assert_eq!(key, e[0].key);
 }
 
+pub fn should_update_an_edge<D: Datastore>(datastore: &mut D) {
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let trans = datastore.transaction().unwrap();
+    let outbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let inbound_id = trans.create_vertex_from_type(vertex_t).unwrap();
+    let key = EdgeKey::new(outbound_id, edge_t, inbound_id);
+
+    // Updating never creates the edge
+    assert!(!trans.update_edge(&key).unwrap());
+    assert!(!trans.edge_exists(&key).unwrap());
+
+    trans.create_edge(&key).unwrap();
+    let created_datetime = trans.get_edges(&EdgeQuery::single(key.clone())).unwrap()[0].created_datetime;
+    thread::sleep(Duration::from_millis(2));
+    assert!(trans.update_edge(&key).unwrap());
+
+    let e = trans
+        .get_edges(&VertexQuery::single(outbound_id).outbound_edges(None, None, None, 10))
+        .unwrap();
+    assert_eq!(e.len(), 1);
+    assert_eq!(e[0].key, key);
+    assert!(e[0].created_datetime > created_datetime);
+    assert_eq!(
+        trans.get_edge_count(inbound_id, None, EdgeDirection::Inbound).unwrap(),
+        1
+    );
+
+    trans.delete_edges(&EdgeQuery::single(key.clone())).unwrap();
+    assert!(!trans.update_edge(&key).unwrap());
+    assert!(!trans.edge_exists(&key).unwrap());
+}
+
+pub fn should_create_an_edge_with_missing_vertices<D: Datastore>(datastore: &mut D) {
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let default_t = models::Type::new("test_default_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let trans = datastore.transaction().unwrap();
+    let outbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let inbound_id = models::Vertex::new(vertex_t.clone()).id;
+    let key = EdgeKey::new(outbound_id, edge_t, inbound_id);
+
+    assert!(trans.create_edge_auto(&key, &default_t).unwrap());
+    assert!(trans.edge_exists(&key).unwrap());
+
+    // Only the missing vertex is created with the default type
+    let vertices = trans
+        .get_vertices(&VertexQuery::ids(vec![outbound_id, inbound_id]))
+        .unwrap();
+    assert_eq!(vertices.len(), 2);
+    assert!(vertices.iter().any(|v| v.id == outbound_id && v.t == vertex_t));
+    assert!(vertices.iter().any(|v| v.id == inbound_id && v.t == default_t));
+
+    // Creating the edge again just updates it
+    assert!(!trans.create_edge_auto(&key, &default_t).unwrap());
+    assert_eq!(
+        trans
+            .get_edge_count(outbound_id, None, EdgeDirection::Outbound)
+            .unwrap(),
+        1
+    );
+}
+
 pub fn should_not_create_an_invalid_edge<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
@@ -119,6 +188,38 @@ pub fn should_not_create_an_invalid_edge<D: Datastore>(datastore: &mut D) {
     assert_eq!(result.unwrap(), false);
 }
 
+pub fn should_create_a_self_loop<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let id = trans.create_vertex_from_type(vertex_t).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let key = models::EdgeKey::new(id, edge_t, id);
+    assert!(trans.create_edge(&key).unwrap());
+    assert!(trans.edge_exists(&key).unwrap());
+}
+
+/// Checks that self-loops are rejected. The datastore must be configured
+/// to disallow them.
+pub fn should_not_create_a_disallowed_self_loop<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let id = trans.create_vertex_from_type(vertex_t).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let key = models::EdgeKey::new(id, edge_t, id);
+    assert!(!trans.create_edge(&key).unwrap());
+    assert!(!trans.edge_exists(&key).unwrap());
+
+    datastore
+        .bulk_insert(vec![models::BulkInsertItem::Edge(key.clone())].into_iter())
+        .unwrap();
+    let trans = datastore.transaction().unwrap();
+    assert!(!trans.edge_exists(&key).unwrap());
+
+    // Edges between distinct vertices are unaffected
+    let other_id = create_edge_from(&trans, id);
+    assert_eq!(trans.get_edge_count(other_id, None, EdgeDirection::Inbound).unwrap(), 1);
+}
+
 pub fn should_delete_a_valid_edge<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let vertex_t = models::Type::new("test_edge_type".to_string()).unwrap();
@@ -130,11 +231,12 @@ pub fn should_delete_a_valid_edge<D: Datastore>(datastore: &mut D) {
     let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
     let key = models::EdgeKey::new(outbound_v.id, edge_t.clone(), inbound_v.id);
     trans.create_edge(&key).unwrap();
-    trans
+    let count = trans
         .delete_edges(&EdgeQuery::Edges {
-            keys: vec![key.clone()],
+            keys: vec![key.clone(), key.clone()],
         })
         .unwrap();
+    assert_eq!(count, 1);
     let e = trans.get_edges(&EdgeQuery::Edges { keys: vec![key] }).unwrap();
     assert_eq!(e.len(), 0);
 }
@@ -145,11 +247,12 @@ pub fn should_not_delete_an_invalid_edge<D: Datastore>(datastore: &mut D) {
     let outbound_v = models::Vertex::new(vertex_t.clone());
     trans.create_vertex(&outbound_v).unwrap();
     let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
-    trans
+    let count = trans
         .delete_edges(&EdgeQuery::Edges {
             keys: vec![EdgeKey::new(outbound_v.id, edge_t, Uuid::default())],
         })
         .unwrap();
+    assert_eq!(count, 0);
 }
 
 pub fn should_get_an_edge_count<D: Datastore>(datastore: &mut D) {
@@ -189,6 +292,51 @@ pub fn should_get_an_inbound_edge_count<D: Datastore>(datastore: &mut D) {
     assert_eq!(count, 1);
 }
 
+pub fn should_get_a_vertex_degree<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let other_t = models::Type::new("test_other_edge_type".to_string()).unwrap();
+    trans
+        .create_edge(&models::EdgeKey::new(inbound_ids[0], other_t.clone(), outbound_id))
+        .unwrap();
+
+    assert_eq!(trans.get_vertex_degree(outbound_id, None).unwrap(), (5, 1));
+    assert_eq!(trans.get_vertex_degree(outbound_id, Some(&t)).unwrap(), (5, 0));
+    assert_eq!(trans.get_vertex_degree(outbound_id, Some(&other_t)).unwrap(), (0, 1));
+    assert_eq!(trans.get_vertex_degree(inbound_ids[0], None).unwrap(), (1, 1));
+    assert_eq!(trans.get_vertex_degree(Uuid::default(), None).unwrap(), (0, 0));
+}
+
+pub fn should_get_edge_counts<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let other_t = models::Type::new("test_other_edge_type".to_string()).unwrap();
+    trans
+        .create_edge(&models::EdgeKey::new(inbound_ids[0], other_t.clone(), outbound_id))
+        .unwrap();
+    let ids = vec![outbound_id, inbound_ids[0], inbound_ids[1], Uuid::default()];
+
+    assert_eq!(
+        trans.get_edge_counts(&ids, None, EdgeDirection::Outbound).unwrap(),
+        vec![(ids[0], 5), (ids[1], 1), (ids[2], 0), (ids[3], 0)]
+    );
+    assert_eq!(
+        trans.get_edge_counts(&ids, None, EdgeDirection::Inbound).unwrap(),
+        vec![(ids[0], 1), (ids[1], 1), (ids[2], 1), (ids[3], 0)]
+    );
+    assert_eq!(
+        trans
+            .get_edge_counts(&ids, Some(&other_t), EdgeDirection::Inbound)
+            .unwrap(),
+        vec![(ids[0], 1), (ids[1], 0), (ids[2], 0), (ids[3], 0)]
+    );
+    assert_eq!(
+        trans.get_edge_counts(&ids, None, EdgeDirection::Both).unwrap(),
+        vec![(ids[0], 6), (ids[1], 2), (ids[2], 1), (ids[3], 0)]
+    );
+}
+
 pub fn should_get_an_edge_range<D: Datastore>(datastore: &mut D) {
     let (outbound_id, start_time, end_time, _) = create_time_range_queryable_edges(datastore);
     let trans = datastore.transaction().unwrap();
@@ -226,6 +374,16 @@ pub fn should_get_edges_with_no_high<D: Datastore>(datastore: &mut D) {
     check_edge_range(&range, outbound_id, 10);
 }
 
+pub fn should_get_edges_with_a_far_future_high<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, start_time, _, _) = create_time_range_queryable_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let high = Utc.ymd(9999, 12, 31).and_hms(0, 0, 0);
+    let q = VertexQuery::Vertices { ids: vec![outbound_id] }.outbound_edges(Some(t), Some(high), Some(start_time), 10);
+    let range = trans.get_edges(&q).unwrap();
+    check_edge_range(&range, outbound_id, 10);
+}
+
 pub fn should_get_edges_with_no_low<D: Datastore>(datastore: &mut D) {
     let (outbound_id, _, end_time, _) = create_time_range_queryable_edges(datastore);
     let trans = datastore.transaction().unwrap();
@@ -244,14 +402,13 @@ pub fn should_get_edges_with_no_time<D: Datastore>(datastore: &mut D) {
     check_edge_range(&range, outbound_id, 15);
 }
 
-pub fn should_get_no_edges_for_reversed_time<D: Datastore>(datastore: &mut D) {
+pub fn should_not_get_edges_for_reversed_time<D: Datastore>(datastore: &mut D) {
     let (outbound_id, start_time, end_time, _) = create_time_range_queryable_edges(datastore);
     let trans = datastore.transaction().unwrap();
     let t = models::Type::new("test_edge_type".to_string()).unwrap();
     let q =
         VertexQuery::Vertices { ids: vec![outbound_id] }.outbound_edges(Some(t), Some(start_time), Some(end_time), 10);
-    let range = trans.get_edges(&q).unwrap();
-    check_edge_range(&range, outbound_id, 0);
+    assert!(trans.get_edges(&q).is_err());
 }
 
 pub fn should_get_edges<D: Datastore>(datastore: &mut D) {
@@ -317,6 +474,473 @@ pub fn should_get_edges_piped<D: Datastore>(datastore: &mut D) {
     );
 }
 
+pub fn should_get_edges_piped_with_an_offset<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let with_offset = |offset: u32, limit: u32| {
+        let mut q = VertexQuery::single(outbound_id).outbound_edges(None, None, None, limit);
+
+        if let EdgeQuery::Pipe { offset: ref mut o, .. } = q {
+            *o = offset;
+        }
+
+        q
+    };
+
+    let all_keys: Vec<EdgeKey> = trans
+        .get_edges(&with_offset(0, 10))
+        .unwrap()
+        .into_iter()
+        .map(|edge| edge.key)
+        .collect();
+    assert_eq!(all_keys.len(), 5);
+
+    let page_keys: Vec<EdgeKey> = trans
+        .get_edges(&with_offset(2, 2))
+        .unwrap()
+        .into_iter()
+        .map(|edge| edge.key)
+        .collect();
+    assert_eq!(page_keys, all_keys[2..4].to_vec());
+    assert_eq!(trans.get_edges(&with_offset(5, 10)).unwrap().len(), 0);
+
+    // Vertex pipes skip edges before converting them
+    let q = VertexQuery::Pipe {
+        edge_query: Box::new(with_offset(0, 10)),
+        converter: EdgeDirection::Inbound,
+        offset: 3,
+        limit: 10,
+    };
+    let ids: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    let expected_ids: Vec<Uuid> = all_keys[3..].iter().map(|key| key.inbound_id).collect();
+    assert_eq!(ids, expected_ids);
+}
+
+pub fn should_get_inbound_edges_grouped_by_vertex<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let ids: Vec<Uuid> = (0..4)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+
+    // `ids[0]` and `ids[1]` are targeted by edges from `ids[2]` and `ids[3]`
+    for &outbound_id in &[ids[2], ids[3]] {
+        for &inbound_id in &[ids[0], ids[1]] {
+            trans
+                .create_edge(&EdgeKey::new(outbound_id, edge_t.clone(), inbound_id))
+                .unwrap();
+        }
+    }
+
+    for order in &[[ids[1], ids[0]], [ids[0], ids[1]]] {
+        let q = VertexQuery::Vertices { ids: order.to_vec() }.inbound_edges(None, None, None, 10);
+        let range = trans.get_edges(&q).unwrap();
+        let inbound_ids: Vec<Uuid> = range.into_iter().map(|edge| edge.key.inbound_id).collect();
+        assert_eq!(inbound_ids, vec![order[0], order[0], order[1], order[1]]);
+    }
+}
+
+pub fn should_get_inbound_edges_in_a_stable_order<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let ids: Vec<Uuid> = (0..8)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+
+    // Every vertex in the first half targets every vertex in the second half
+    for &outbound_id in &ids[..4] {
+        for &inbound_id in &ids[4..] {
+            trans
+                .create_edge(&EdgeKey::new(outbound_id, edge_t.clone(), inbound_id))
+                .unwrap();
+        }
+    }
+
+    let q = VertexQuery::Vertices { ids: ids[4..].to_vec() }.inbound_edges(None, None, None, 100);
+    let first_keys: Vec<EdgeKey> = trans.get_edges(&q).unwrap().into_iter().map(|edge| edge.key).collect();
+    let second_keys: Vec<EdgeKey> = trans.get_edges(&q).unwrap().into_iter().map(|edge| edge.key).collect();
+    assert_eq!(first_keys.len(), 16);
+    assert_eq!(first_keys, second_keys);
+
+    let q = q.outbound_vertices(100);
+    let first_ids: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    let second_ids: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(first_ids, second_ids);
+}
+
+pub fn should_get_edges_in_both_directions<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let a = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let b = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let c = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let d = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+
+    let keys = vec![
+        EdgeKey::new(a, edge_t.clone(), b),
+        EdgeKey::new(c, edge_t.clone(), a),
+        EdgeKey::new(a, edge_t.clone(), a),
+        EdgeKey::new(b, edge_t.clone(), c),
+        EdgeKey::new(c, edge_t.clone(), d),
+    ];
+
+    for key in &keys {
+        trans.create_edge(key).unwrap();
+    }
+
+    let get_keys = |ids: Vec<Uuid>| -> Vec<EdgeKey> {
+        let q = EdgeQuery::Pipe {
+            vertex_query: Box::new(VertexQuery::Vertices { ids }),
+            converter: EdgeDirection::Both,
+            type_filter: None,
+            type_filters: Vec::new(),
+            high_filter: None,
+            low_filter: None,
+            after_inbound_id: None,
+            offset: 0,
+            limit: 100,
+        };
+
+        trans.get_edges(&q).unwrap().into_iter().map(|edge| edge.key).collect()
+    };
+
+    // The self-loop should only be returned once
+    let a_keys = get_keys(vec![a]);
+    assert_eq!(a_keys.len(), 3);
+    let a_keys: HashSet<EdgeKey> = a_keys.into_iter().collect();
+    assert_eq!(a_keys, keys[..3].iter().cloned().collect());
+
+    // As should the edges between vertices that are both in the query
+    let ab_keys = get_keys(vec![a, b]);
+    assert_eq!(ab_keys.len(), 4);
+    let ab_keys: HashSet<EdgeKey> = ab_keys.into_iter().collect();
+    assert_eq!(ab_keys, keys[..4].iter().cloned().collect());
+
+    assert_eq!(trans.get_edge_count(a, None, EdgeDirection::Both).unwrap(), 3);
+    assert_eq!(trans.get_edge_count(c, None, EdgeDirection::Both).unwrap(), 3);
+    assert_eq!(trans.get_edge_count(d, None, EdgeDirection::Both).unwrap(), 1);
+
+    let q = VertexQuery::Vertices { ids: vec![b] }.traverse(EdgeDirection::Both, None, 1, 100);
+    let neighbor_ids: HashSet<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(neighbor_ids, vec![a, c].into_iter().collect());
+
+    let q = VertexQuery::Pipe {
+        edge_query: Box::new(EdgeQuery::Edges {
+            keys: vec![keys[1].clone(), keys[2].clone()],
+        }),
+        converter: EdgeDirection::Both,
+        offset: 0,
+        limit: 100,
+    };
+    let ids: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(ids, vec![c, a, a]);
+}
+
+pub fn should_get_edges_of_any_of_several_types<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let a_t = models::Type::new("test_edge_type_a".to_string()).unwrap();
+    let b_t = models::Type::new("test_edge_type_b".to_string()).unwrap();
+    let c_t = models::Type::new("test_edge_type_c".to_string()).unwrap();
+    let id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let other_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+
+    for t in &[&a_t, &b_t, &c_t] {
+        trans.create_edge(&EdgeKey::new(id, (*t).clone(), other_id)).unwrap();
+        trans.create_edge(&EdgeKey::new(other_id, (*t).clone(), id)).unwrap();
+    }
+
+    let get_types = |converter: EdgeDirection, type_filter: Option<models::Type>, type_filters: Vec<models::Type>| {
+        let q = EdgeQuery::Pipe {
+            vertex_query: Box::new(VertexQuery::Vertices { ids: vec![id] }),
+            converter,
+            type_filter,
+            type_filters,
+            high_filter: None,
+            low_filter: None,
+            after_inbound_id: None,
+            offset: 0,
+            limit: 100,
+        };
+
+        trans
+            .get_edges(&q)
+            .unwrap()
+            .into_iter()
+            .map(|edge| edge.key.t)
+            .collect::<Vec<models::Type>>()
+    };
+
+    // Edges are still ordered by type, whatever order the types are given in
+    let types = get_types(
+        EdgeDirection::Outbound,
+        None,
+        vec![c_t.clone(), a_t.clone(), c_t.clone()],
+    );
+    assert_eq!(types, vec![a_t.clone(), c_t.clone()]);
+    let types = get_types(EdgeDirection::Inbound, None, vec![b_t.clone(), c_t.clone()]);
+    assert_eq!(types, vec![b_t.clone(), c_t.clone()]);
+    let types = get_types(EdgeDirection::Both, None, vec![a_t.clone(), b_t.clone()]);
+    assert_eq!(types.len(), 4);
+    assert!(types.iter().all(|t| t == &a_t || t == &b_t));
+
+    // Both filters have to match
+    let types = get_types(
+        EdgeDirection::Outbound,
+        Some(b_t.clone()),
+        vec![a_t.clone(), b_t.clone()],
+    );
+    assert_eq!(types, vec![b_t.clone()]);
+    let types = get_types(
+        EdgeDirection::Outbound,
+        Some(c_t.clone()),
+        vec![a_t.clone(), b_t.clone()],
+    );
+    assert_eq!(types.len(), 0);
+
+    let types = get_types(EdgeDirection::Outbound, None, Vec::new());
+    assert_eq!(types, vec![a_t, b_t, c_t]);
+}
+
+pub fn should_get_edges_after_an_inbound_id<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let other_edge_t = models::Type::new("test_other_edge_type".to_string()).unwrap();
+    let id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let mut inbound_ids: Vec<Uuid> = (0..5)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+
+    for inbound_id in &inbound_ids {
+        trans
+            .create_edge(&EdgeKey::new(id, edge_t.clone(), *inbound_id))
+            .unwrap();
+        trans
+            .create_edge(&EdgeKey::new(id, other_edge_t.clone(), *inbound_id))
+            .unwrap();
+    }
+
+    inbound_ids.sort();
+
+    let get_page = |converter: EdgeDirection, after_inbound_id: Uuid| {
+        let q = EdgeQuery::Pipe {
+            vertex_query: Box::new(VertexQuery::single(id)),
+            converter,
+            type_filter: Some(edge_t.clone()),
+            type_filters: Vec::new(),
+            high_filter: None,
+            low_filter: None,
+            after_inbound_id: Some(after_inbound_id),
+            offset: 0,
+            limit: 2,
+        };
+
+        trans
+            .get_edges(&q)
+            .map(|edges| edges.into_iter().map(|edge| edge.key.inbound_id).collect::<Vec<Uuid>>())
+    };
+
+    let mut cursor = Uuid::default();
+    let mut pages = Vec::new();
+
+    loop {
+        let page = get_page(EdgeDirection::Outbound, cursor).unwrap();
+
+        match page.last() {
+            Some(last) => cursor = *last,
+            None => break,
+        }
+
+        pages.push(page);
+    }
+
+    assert_eq!(
+        pages,
+        vec![
+            inbound_ids[..2].to_vec(),
+            inbound_ids[2..4].to_vec(),
+            inbound_ids[4..].to_vec()
+        ]
+    );
+
+    // Cursors only make sense for outbound edges
+    assert!(get_page(EdgeDirection::Inbound, inbound_ids[0]).is_err());
+    assert!(get_page(EdgeDirection::Both, inbound_ids[0]).is_err());
+}
+
+pub fn should_get_all_edges_in_a_time_range<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new(format!("all-edges-{}", generate_random_secret(8))).unwrap();
+
+    let create_edge = || {
+        let outbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+        let inbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+        let key = EdgeKey::new(outbound_id, edge_t.clone(), inbound_id);
+        trans.create_edge(&key).unwrap();
+        key
+    };
+
+    create_edge();
+    create_edge();
+    let start_time = Utc::now();
+    let expected_keys: HashSet<EdgeKey> = (0..3).map(|_| create_edge()).collect();
+    let end_time = Utc::now();
+    create_edge();
+
+    let q = EdgeQuery::all(Some(edge_t.clone()), Some(end_time), Some(start_time), 10);
+    let keys: HashSet<EdgeKey> = trans.get_edges(&q).unwrap().into_iter().map(|edge| edge.key).collect();
+    assert_eq!(keys, expected_keys);
+
+    let q = EdgeQuery::all(Some(edge_t.clone()), None, None, 10);
+    assert_eq!(trans.get_edges(&q).unwrap().len(), 6);
+    let q = EdgeQuery::all(Some(edge_t.clone()), None, None, 4);
+    assert_eq!(trans.get_edges(&q).unwrap().len(), 4);
+    let q = EdgeQuery::all(Some(edge_t), None, None, 0);
+    assert_eq!(trans.get_edges(&q).unwrap().len(), 0);
+}
+
+pub fn should_get_no_edges_with_zero_limit<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+
+    let q = VertexQuery::Vertices { ids: vec![outbound_id] }.outbound_edges(None, None, None, 0);
+    assert_eq!(trans.get_edges(&q).unwrap().len(), 0);
+    let q = VertexQuery::Vertices {
+        ids: inbound_ids.to_vec(),
+    }
+    .inbound_edges(None, None, None, 0);
+    assert_eq!(trans.get_edges(&q).unwrap().len(), 0);
+    let q = VertexQuery::Vertices { ids: vec![outbound_id] }
+        .outbound_edges(None, None, None, 10)
+        .inbound_vertices(0);
+    assert_eq!(trans.get_vertices(&q).unwrap().len(), 0);
+    let q = VertexQuery::Vertices { ids: vec![outbound_id] }.traverse(EdgeDirection::Outbound, None, 1, 0);
+    assert_eq!(trans.get_vertices(&q).unwrap().len(), 0);
+}
+
+pub fn should_limit_edges_piped_from_many_vertices<D: Datastore>(datastore: &mut D) {
+    let (_, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+
+    for limit in 1..6 {
+        let q = VertexQuery::Vertices {
+            ids: inbound_ids.to_vec(),
+        }
+        .inbound_edges(None, None, None, limit);
+        assert_eq!(trans.get_edges(&q).unwrap().len(), limit as usize);
+    }
+}
+
+pub fn should_check_edge_existence<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let key = EdgeKey::new(outbound_id, t.clone(), inbound_ids[0]);
+    assert!(trans.edge_exists(&key).unwrap());
+    assert!(!trans
+        .edge_exists(&EdgeKey::new(inbound_ids[0], t.clone(), outbound_id))
+        .unwrap());
+    assert!(!trans
+        .edge_exists(&EdgeKey::new(
+            outbound_id,
+            models::Type::new("foo".to_string()).unwrap(),
+            inbound_ids[0]
+        ))
+        .unwrap());
+
+    trans
+        .delete_edges(&EdgeQuery::Edges {
+            keys: vec![key.clone()],
+        })
+        .unwrap();
+    assert!(!trans.edge_exists(&key).unwrap());
+}
+
+pub fn should_get_induced_edges<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let ids: Vec<Uuid> = (0..4)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+
+    let intra_keys = vec![
+        EdgeKey::new(ids[0], edge_t.clone(), ids[1]),
+        EdgeKey::new(ids[1], edge_t.clone(), ids[2]),
+        EdgeKey::new(ids[2], edge_t.clone(), ids[0]),
+    ];
+    let extra_keys = [
+        EdgeKey::new(ids[0], edge_t.clone(), ids[3]),
+        EdgeKey::new(ids[3], edge_t.clone(), ids[1]),
+    ];
+
+    for key in intra_keys.iter().chain(extra_keys.iter()) {
+        trans.create_edge(key).unwrap();
+    }
+
+    let edges = trans.get_induced_edges(&ids[0..3]).unwrap();
+    let mut keys: Vec<EdgeKey> = edges.into_iter().map(|edge| edge.key).collect();
+    keys.sort();
+    let mut expected_keys = intra_keys;
+    expected_keys.sort();
+    assert_eq!(keys, expected_keys);
+
+    let edges = trans.get_induced_edges(&[ids[3]]).unwrap();
+    assert_eq!(edges.len(), 0);
+}
+
+pub fn should_get_edges_sorted_by_property<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let weights = [3, 1, 2, 5];
+
+    for (inbound_id, weight) in inbound_ids.iter().zip(weights.iter()) {
+        let q = EdgeQuery::Edges {
+            keys: vec![EdgeKey::new(outbound_id, t.clone(), *inbound_id)],
+        };
+        trans
+            .set_edge_properties(&q, "weight", &JsonValue::from(*weight))
+            .unwrap();
+    }
+
+    // Non-numeric values are treated the same as missing ones
+    let q = EdgeQuery::Edges {
+        keys: vec![EdgeKey::new(outbound_id, t.clone(), inbound_ids[4])],
+    };
+    trans
+        .set_edge_properties(&q, "weight", &JsonValue::String("heavy".to_string()))
+        .unwrap();
+
+    let q = VertexQuery::Vertices { ids: vec![outbound_id] }.outbound_edges(None, None, None, 10);
+
+    let edges = trans
+        .get_edges_sorted_by_property(&q, "weight", SortDirection::Ascending, 10)
+        .unwrap();
+    let ids: Vec<Uuid> = edges.into_iter().map(|edge| edge.key.inbound_id).collect();
+    assert_eq!(
+        ids,
+        vec![
+            inbound_ids[1],
+            inbound_ids[2],
+            inbound_ids[0],
+            inbound_ids[3],
+            inbound_ids[4]
+        ]
+    );
+
+    let edges = trans
+        .get_edges_sorted_by_property(&q, "weight", SortDirection::Descending, 2)
+        .unwrap();
+    let ids: Vec<Uuid> = edges.into_iter().map(|edge| edge.key.inbound_id).collect();
+    assert_eq!(ids, vec![inbound_ids[3], inbound_ids[0]]);
+}
+
 fn check_edge_range(range: &[models::Edge], expected_outbound_id: Uuid, expected_length: usize) {
     assert_eq!(range.len(), expected_length);
     let mut covered_ids: HashSet<Uuid> = HashSet::new();
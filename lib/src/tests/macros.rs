@@ -18,17 +18,45 @@ macro_rules! full_test_impl {
         define_test!(should_bulk_insert, $code);
         define_test!(should_bulk_insert_a_redundant_vertex, $code);
         define_test!(should_bulk_insert_an_invalid_edge, $code);
+        define_test!(should_bulk_insert_with_progress, $code);
+        define_test!(should_bulk_insert_partially, $code);
+        define_test!(should_bulk_insert_with_overwrite_mode, $code);
+        define_test!(should_bulk_insert_with_skip_mode, $code);
+        define_test!(should_bulk_insert_with_error_mode, $code);
+        #[cfg(feature = "compression")]
+        define_test!(should_import_compressed, $code);
+
+        // Batches
+        define_test!(should_execute_batch, $code);
+
+        // Datastores
+        define_test!(should_pass_health_check, $code);
 
         // Vertices
         define_test!(should_create_vertex_from_type, $code);
+        define_test!(should_get_or_create_vertex, $code);
         define_test!(should_get_all_vertices, $code);
         define_test!(should_get_all_vertices_with_zero_limit, $code);
         define_test!(should_get_all_vertices_out_of_range, $code);
+        define_test!(should_get_all_vertices_in_id_range, $code);
+        define_test!(should_get_all_vertices_in_a_time_range, $code);
         define_test!(should_get_single_vertices, $code);
         define_test!(should_get_single_vertices_nonexisting, $code);
         define_test!(should_get_vertices, $code);
+        define_test!(should_get_vertices_by_types, $code);
+        define_test!(should_get_vertices_by_property_substring, $code);
+        define_test!(should_get_vertices_paged, $code);
+        define_test!(should_get_vertices_leniently, $code);
+        define_test!(should_estimate_vertex_count, $code);
         define_test!(should_get_vertices_piped, $code);
+        define_test!(should_get_vertex_types, $code);
+        define_test!(should_count_vertices_by_type, $code);
+        define_test!(should_check_vertex_existence, $code);
+        define_test!(should_get_vertices_iter, $code);
+        define_test!(should_get_vertices_traversed, $code);
+        define_test!(should_get_vertices_by_degree, $code);
         define_test!(should_get_a_vertex_count, $code);
+        define_test!(should_get_graph_stats, $code);
         define_test!(should_delete_a_valid_vertex, $code);
         define_test!(should_not_delete_an_invalid_vertex, $code);
 
@@ -36,28 +64,58 @@ macro_rules! full_test_impl {
         define_test!(should_get_a_valid_edge, $code);
         define_test!(should_not_get_an_invalid_edge, $code);
         define_test!(should_create_a_valid_edge, $code);
+        define_test!(should_update_an_edge, $code);
+        define_test!(should_create_an_edge_with_missing_vertices, $code);
         define_test!(should_not_create_an_invalid_edge, $code);
+        define_test!(should_create_a_self_loop, $code);
         define_test!(should_delete_a_valid_edge, $code);
         define_test!(should_not_delete_an_invalid_edge, $code);
         define_test!(should_get_an_edge_count, $code);
         define_test!(should_get_an_edge_count_with_no_type, $code);
         define_test!(should_get_an_edge_count_for_an_invalid_edge, $code);
         define_test!(should_get_an_inbound_edge_count, $code);
+        define_test!(should_get_a_vertex_degree, $code);
+        define_test!(should_get_edge_counts, $code);
         define_test!(should_get_an_edge_range, $code);
         define_test!(should_get_edges_with_no_type, $code);
         define_test!(should_get_no_edges_for_an_invalid_range, $code);
         define_test!(should_get_edges_with_no_high, $code);
+        define_test!(should_get_edges_with_a_far_future_high, $code);
         define_test!(should_get_edges_with_no_low, $code);
         define_test!(should_get_edges_with_no_time, $code);
-        define_test!(should_get_no_edges_for_reversed_time, $code);
+        define_test!(should_not_get_edges_for_reversed_time, $code);
         define_test!(should_get_edges, $code);
+        define_test!(should_get_inbound_edges_grouped_by_vertex, $code);
+        define_test!(should_get_inbound_edges_in_a_stable_order, $code);
+        define_test!(should_get_edges_in_both_directions, $code);
+        define_test!(should_get_edges_of_any_of_several_types, $code);
+        define_test!(should_get_edges_after_an_inbound_id, $code);
+        define_test!(should_get_edges_piped_with_an_offset, $code);
+        define_test!(should_get_all_edges_in_a_time_range, $code);
+        define_test!(should_get_no_edges_with_zero_limit, $code);
+        define_test!(should_limit_edges_piped_from_many_vertices, $code);
+        define_test!(should_check_edge_existence, $code);
+        define_test!(should_get_induced_edges, $code);
+        define_test!(should_get_edges_sorted_by_property, $code);
 
         // Properties
         define_test!(should_handle_vertex_properties, $code);
+        define_test!(should_get_vertex_properties_multi, $code);
+        define_test!(should_get_vertex_properties_bounded, $code);
+        define_test!(should_get_all_vertex_properties_bounded, $code);
+        define_test!(should_patch_vertex_property, $code);
+        define_test!(should_set_vertex_property_where, $code);
         define_test!(should_not_set_invalid_vertex_properties, $code);
         define_test!(should_not_delete_invalid_vertex_properties, $code);
+        define_test!(should_delete_all_vertex_properties, $code);
         define_test!(should_handle_edge_properties, $code);
+        define_test!(should_get_edge_properties_map, $code);
+        define_test!(should_patch_edge_property, $code);
         define_test!(should_not_set_invalid_edge_properties, $code);
         define_test!(should_not_delete_invalid_edge_properties, $code);
+        define_test!(should_delete_all_edge_properties, $code);
+        define_test!(should_get_property_names, $code);
+        define_test!(should_aggregate_edge_property, $code);
+        define_test!(should_get_full_record, $code);
     };
 }
@@ -4,17 +4,23 @@
 //! `indradb` crate can reuse them. Generally you can use the convenience macro
 //! `full_test_impl`.
 
+mod batch;
 mod bulk_insert;
+mod datastore;
 mod edge;
 #[macro_use]
 mod macros;
 mod properties;
+mod traversal;
 mod util;
 mod vertex;
 
+pub use self::batch::*;
 pub use self::bulk_insert::*;
+pub use self::datastore::*;
 pub use self::edge::*;
 pub use self::macros::*;
 pub use self::properties::*;
+pub use self::traversal::*;
 pub use self::util::*;
 pub use self::vertex::*;
@@ -1,4 +1,9 @@
-use super::super::{Datastore, EdgeKey, EdgeQuery, Transaction, Type, Vertex, VertexQuery};
+use super::super::{
+    AggregateOp, ComparisonOperator, Datastore, EdgeKey, EdgeQuery, NamedProperty, PropertyCondition, ReadTransaction,
+    Type, Vertex, VertexProperty, VertexQuery, WriteTransaction,
+};
+use super::util::create_edges;
+use serde_json;
 use serde_json::Value as JsonValue;
 use util::generate_random_secret;
 use uuid::Uuid;
@@ -16,10 +21,12 @@ pub fn should_handle_vertex_properties<D: Datastore>(datastore: &mut D) {
     assert_eq!(result.len(), 0);
 
     // Set and get the value as true
-    trans.set_vertex_properties(&q, &name, &JsonValue::Bool(true)).unwrap();
+    let count = trans.set_vertex_properties(&q, &name, &JsonValue::Bool(true)).unwrap();
+    assert_eq!(count, 1);
     let result = trans.get_vertex_properties(&q, &name).unwrap();
     assert_eq!(result.len(), 1);
     assert_eq!(result[0].id, v.id);
+    assert_eq!(result[0].name, name);
     assert_eq!(result[0].value, JsonValue::Bool(true));
 
     // Set and get the value as false
@@ -35,12 +42,198 @@ pub fn should_handle_vertex_properties<D: Datastore>(datastore: &mut D) {
     assert_eq!(result.len(), 0);
 }
 
+pub fn should_get_vertex_properties_multi<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_vertex_type".to_string()).unwrap();
+    let first = Vertex::new(t.clone());
+    let second = Vertex::new(t);
+    trans.create_vertex(&first).unwrap();
+    trans.create_vertex(&second).unwrap();
+    let names = vec![
+        format!("name-{}", generate_random_secret(8)),
+        format!("age-{}", generate_random_secret(8)),
+        format!("missing-{}", generate_random_secret(8)),
+    ];
+
+    let first_q = VertexQuery::Vertices { ids: vec![first.id] };
+    let second_q = VertexQuery::Vertices { ids: vec![second.id] };
+    trans
+        .set_vertex_properties(&first_q, &names[0], &JsonValue::from("first"))
+        .unwrap();
+    trans
+        .set_vertex_properties(&first_q, &names[1], &JsonValue::from(1))
+        .unwrap();
+    trans
+        .set_vertex_properties(&second_q, &names[1], &JsonValue::from(2))
+        .unwrap();
+
+    let q = VertexQuery::Vertices {
+        ids: vec![second.id, first.id],
+    };
+    let result = trans.get_vertex_properties_multi(&q, &names).unwrap();
+    assert_eq!(
+        result,
+        vec![
+            VertexProperty::new(second.id, names[1].clone(), JsonValue::from(2)),
+            VertexProperty::new(first.id, names[0].clone(), JsonValue::from("first")),
+            VertexProperty::new(first.id, names[1].clone(), JsonValue::from(1)),
+        ]
+    );
+
+    let result = trans.get_vertex_properties_multi(&q, &[]).unwrap();
+    assert_eq!(result.len(), 0);
+}
+
+pub fn should_get_vertex_properties_bounded<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_vertex_type".to_string()).unwrap();
+    let first = Vertex::new(t.clone());
+    let second = Vertex::new(t);
+    trans.create_vertex(&first).unwrap();
+    trans.create_vertex(&second).unwrap();
+    let names = vec![format!("name-{}", generate_random_secret(8))];
+
+    // Each value is 5 bytes when serialized
+    let first_q = VertexQuery::Vertices { ids: vec![first.id] };
+    let second_q = VertexQuery::Vertices { ids: vec![second.id] };
+    trans
+        .set_vertex_properties(&first_q, &names[0], &JsonValue::from("abc"))
+        .unwrap();
+    trans
+        .set_vertex_properties(&second_q, &names[0], &JsonValue::from("def"))
+        .unwrap();
+
+    let q = VertexQuery::Vertices {
+        ids: vec![first.id, second.id],
+    };
+    let (result, truncated) = trans.get_vertex_properties_bounded(&q, &names, 10).unwrap();
+    assert_eq!(result.len(), 2);
+    assert!(!truncated);
+
+    let (result, truncated) = trans.get_vertex_properties_bounded(&q, &names, 9).unwrap();
+    assert_eq!(
+        result,
+        vec![VertexProperty::new(first.id, names[0].clone(), JsonValue::from("abc"))]
+    );
+    assert!(truncated);
+
+    let (result, truncated) = trans.get_vertex_properties_bounded(&q, &names, 0).unwrap();
+    assert_eq!(result.len(), 0);
+    assert!(truncated);
+}
+
+pub fn should_get_all_vertex_properties_bounded<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_vertex_type".to_string()).unwrap();
+    let first = Vertex::new(t.clone());
+    let second = Vertex::new(t);
+    trans.create_vertex(&first).unwrap();
+    trans.create_vertex(&second).unwrap();
+
+    // Each value is 5 bytes when serialized
+    let first_q = VertexQuery::Vertices { ids: vec![first.id] };
+    let second_q = VertexQuery::Vertices { ids: vec![second.id] };
+    trans
+        .set_vertex_properties(&first_q, "a", &JsonValue::from("abc"))
+        .unwrap();
+    trans
+        .set_vertex_properties(&first_q, "b", &JsonValue::from("def"))
+        .unwrap();
+    trans
+        .set_vertex_properties(&second_q, "a", &JsonValue::from("ghi"))
+        .unwrap();
+
+    let q = VertexQuery::Vertices {
+        ids: vec![first.id, second.id],
+    };
+    let (result, truncated) = trans.get_all_vertex_properties_bounded(&q, 15).unwrap();
+    assert_eq!(result.len(), 3);
+    assert!(!truncated);
+
+    // The budget runs out partway through the first vertex's properties
+    let (result, truncated) = trans.get_all_vertex_properties_bounded(&q, 7).unwrap();
+    assert_eq!(
+        result,
+        vec![VertexProperty::new(first.id, "a".to_string(), JsonValue::from("abc"))]
+    );
+    assert!(truncated);
+
+    let (result, truncated) = trans.get_all_vertex_properties_bounded(&q, 0).unwrap();
+    assert_eq!(result.len(), 0);
+    assert!(truncated);
+}
+
+pub fn should_set_vertex_property_where<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_vertex_type".to_string()).unwrap();
+    let spend_name = format!("spend-{}", generate_random_secret(8));
+    let tier_name = format!("tier-{}", generate_random_secret(8));
+    let mut ids = Vec::new();
+
+    for spend in &[500, 1000, 1500, 2000] {
+        let id = trans.create_vertex_from_type(t.clone()).unwrap();
+        let q = VertexQuery::Vertices { ids: vec![id] };
+        trans
+            .set_vertex_properties(&q, &spend_name, &JsonValue::from(*spend))
+            .unwrap();
+        ids.push(id);
+    }
+
+    // A vertex without the property shouldn't match
+    ids.push(trans.create_vertex_from_type(t).unwrap());
+
+    let filter = PropertyCondition::new(spend_name, ComparisonOperator::GreaterThan, JsonValue::from(1000));
+    let gold = JsonValue::from("gold");
+    let count = trans.set_vertex_property_where(&filter, &tier_name, &gold).unwrap();
+    assert_eq!(count, 2);
+
+    let result = trans
+        .get_vertex_properties(&VertexQuery::Vertices { ids: ids.clone() }, &tier_name)
+        .unwrap();
+    let mut updated_ids: Vec<Uuid> = result.iter().map(|property| property.id).collect();
+    updated_ids.sort();
+    let mut expected_ids = vec![ids[2], ids[3]];
+    expected_ids.sort();
+    assert_eq!(updated_ids, expected_ids);
+    assert!(result.iter().all(|property| property.value == gold));
+}
+
+pub fn should_patch_vertex_property<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_vertex_type".to_string()).unwrap();
+    let v = Vertex::new(t);
+    trans.create_vertex(&v).unwrap();
+    let name = format!("vertex-properties-{}", generate_random_secret(8));
+    let q = VertexQuery::Vertices { ids: vec![v.id] };
+
+    // Without an existing value, the patch becomes the value
+    trans
+        .patch_vertex_property(&q, &name, &json_value(r#"{"a": 1, "b": {"c": 2}, "d": null}"#))
+        .unwrap();
+    let result = trans.get_vertex_properties(&q, &name).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].value, json_value(r#"{"a": 1, "b": {"c": 2}}"#));
+
+    trans
+        .patch_vertex_property(&q, &name, &json_value(r#"{"a": null, "b": {"e": 3}}"#))
+        .unwrap();
+    let result = trans.get_vertex_properties(&q, &name).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].value, json_value(r#"{"b": {"c": 2, "e": 3}}"#));
+
+    // Non-object patches replace the value outright
+    trans.patch_vertex_property(&q, &name, &JsonValue::from(4)).unwrap();
+    let result = trans.get_vertex_properties(&q, &name).unwrap();
+    assert_eq!(result[0].value, JsonValue::from(4));
+}
+
 pub fn should_not_set_invalid_vertex_properties<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let q = VertexQuery::Vertices {
         ids: vec![Uuid::default()],
     };
-    trans.set_vertex_properties(&q, "foo", &JsonValue::Null).unwrap();
+    let count = trans.set_vertex_properties(&q, "foo", &JsonValue::Null).unwrap();
+    assert_eq!(count, 0);
     let result = trans.get_vertex_properties(&q, "foo").unwrap();
     assert_eq!(result.len(), 0);
 }
@@ -59,6 +252,36 @@ pub fn should_not_delete_invalid_vertex_properties<D: Datastore>(datastore: &mut
     trans.delete_vertex_properties(&q, "foo").unwrap();
 }
 
+pub fn should_delete_all_vertex_properties<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_vertex_type".to_string()).unwrap();
+    let first_v = Vertex::new(t.clone());
+    let second_v = Vertex::new(t);
+    trans.create_vertex(&first_v).unwrap();
+    trans.create_vertex(&second_v).unwrap();
+    let first_q = VertexQuery::Vertices { ids: vec![first_v.id] };
+    let both_q = VertexQuery::Vertices {
+        ids: vec![first_v.id, second_v.id],
+    };
+
+    for name in &["bar", "foo"] {
+        trans
+            .set_vertex_properties(&both_q, name, &JsonValue::Bool(true))
+            .unwrap();
+    }
+
+    trans.delete_all_vertex_properties(&first_q).unwrap();
+
+    // The vertex should stay, but without any of its properties
+    assert_eq!(trans.get_vertices(&first_q).unwrap().len(), 1);
+
+    for name in &["bar", "foo"] {
+        let result = trans.get_vertex_properties(&both_q, name).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, second_v.id);
+    }
+}
+
 pub fn should_handle_edge_properties<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let vertex_t = Type::new("test_edge_type".to_string()).unwrap();
@@ -84,6 +307,7 @@ pub fn should_handle_edge_properties<D: Datastore>(datastore: &mut D) {
     let result = trans.get_edge_properties(&q, &name).unwrap();
     assert_eq!(result.len(), 1);
     assert_eq!(result[0].key, key);
+    assert_eq!(result[0].name, name);
     assert_eq!(result[0].value, JsonValue::Bool(true));
 
     // Set and get the value as false
@@ -99,6 +323,144 @@ pub fn should_handle_edge_properties<D: Datastore>(datastore: &mut D) {
     assert_eq!(result.len(), 0);
 }
 
+pub fn should_get_edge_properties_map<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_edge_type".to_string()).unwrap();
+    let name = format!("edge-properties-{}", generate_random_secret(8));
+    let first_key = EdgeKey::new(outbound_id, t.clone(), inbound_ids[0]);
+    let second_key = EdgeKey::new(outbound_id, t, inbound_ids[1]);
+    trans
+        .set_edge_properties(&EdgeQuery::single(first_key.clone()), &name, &JsonValue::from(1))
+        .unwrap();
+    trans
+        .set_edge_properties(&EdgeQuery::single(second_key.clone()), &name, &JsonValue::from(2))
+        .unwrap();
+
+    let q = VertexQuery::Vertices { ids: vec![outbound_id] }.outbound_edges(None, None, None, 10);
+    let result = trans.get_edge_properties_map(&q, &name).unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[&first_key], JsonValue::from(1));
+    assert_eq!(result[&second_key], JsonValue::from(2));
+}
+
+pub fn should_patch_edge_property<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_edge_type".to_string()).unwrap();
+    let name = format!("edge-properties-{}", generate_random_secret(8));
+    let first_key = EdgeKey::new(outbound_id, t.clone(), inbound_ids[0]);
+    let second_key = EdgeKey::new(outbound_id, t, inbound_ids[1]);
+    trans
+        .set_edge_properties(&EdgeQuery::single(first_key.clone()), &name, &json_value(r#"{"a": 1}"#))
+        .unwrap();
+
+    let q = EdgeQuery::Edges {
+        keys: vec![first_key.clone(), second_key.clone()],
+    };
+    trans
+        .patch_edge_property(&q, &name, &json_value(r#"{"b": 2}"#))
+        .unwrap();
+    let result = trans.get_edge_properties_map(&q, &name).unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[&first_key], json_value(r#"{"a": 1, "b": 2}"#));
+    assert_eq!(result[&second_key], json_value(r#"{"b": 2}"#));
+}
+
+pub fn should_delete_all_edge_properties<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = Type::new("test_vertex_type".to_string()).unwrap();
+    let outbound_v = Vertex::new(vertex_t.clone());
+    let inbound_v = Vertex::new(vertex_t.clone());
+    trans.create_vertex(&outbound_v).unwrap();
+    trans.create_vertex(&inbound_v).unwrap();
+    let first_key = EdgeKey::new(
+        outbound_v.id,
+        Type::new("first_edge_type".to_string()).unwrap(),
+        inbound_v.id,
+    );
+    let second_key = EdgeKey::new(
+        outbound_v.id,
+        Type::new("second_edge_type".to_string()).unwrap(),
+        inbound_v.id,
+    );
+    trans.create_edge(&first_key).unwrap();
+    trans.create_edge(&second_key).unwrap();
+    let first_q = EdgeQuery::Edges {
+        keys: vec![first_key.clone()],
+    };
+    let both_q = EdgeQuery::Edges {
+        keys: vec![first_key, second_key.clone()],
+    };
+
+    for name in &["bar", "foo"] {
+        trans
+            .set_edge_properties(&both_q, name, &JsonValue::Bool(true))
+            .unwrap();
+    }
+
+    trans.delete_all_edge_properties(&first_q).unwrap();
+
+    // The edge should stay, but without any of its properties
+    assert_eq!(trans.get_edges(&first_q).unwrap().len(), 1);
+
+    for name in &["bar", "foo"] {
+        let result = trans.get_edge_properties(&both_q, name).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].key, second_key);
+    }
+}
+
+pub fn should_get_property_names<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = Type::new("test_edge_type".to_string()).unwrap();
+    let outbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let inbound_id = trans.create_vertex_from_type(vertex_t).unwrap();
+    let key = EdgeKey::new(outbound_id, edge_t.clone(), inbound_id);
+    let reversed_key = EdgeKey::new(inbound_id, edge_t, outbound_id);
+    trans.create_edge(&key).unwrap();
+    trans.create_edge(&reversed_key).unwrap();
+
+    for name in &["foo", "bar"] {
+        trans
+            .set_vertex_properties(&VertexQuery::single(outbound_id), name, &JsonValue::Null)
+            .unwrap();
+        trans
+            .set_edge_properties(&EdgeQuery::single(key.clone()), name, &JsonValue::Null)
+            .unwrap();
+    }
+
+    trans
+        .set_vertex_properties(&VertexQuery::single(inbound_id), "baz", &JsonValue::Null)
+        .unwrap();
+    trans
+        .set_edge_properties(&EdgeQuery::single(reversed_key.clone()), "baz", &JsonValue::Null)
+        .unwrap();
+
+    let expected_names = vec!["bar".to_string(), "foo".to_string()];
+    assert_eq!(trans.get_vertex_property_names(outbound_id).unwrap(), expected_names);
+    assert_eq!(trans.get_edge_property_names(&key).unwrap(), expected_names);
+    assert_eq!(
+        trans.get_vertex_property_names(inbound_id).unwrap(),
+        vec!["baz".to_string()]
+    );
+    assert_eq!(
+        trans.get_edge_property_names(&reversed_key).unwrap(),
+        vec!["baz".to_string()]
+    );
+
+    // Missing vertices and edges don't have any properties
+    let missing_id = Uuid::default();
+    let missing_key = EdgeKey::new(
+        outbound_id,
+        Type::new("missing_edge_type".to_string()).unwrap(),
+        inbound_id,
+    );
+    assert_eq!(trans.get_vertex_property_names(missing_id).unwrap().len(), 0);
+    assert_eq!(trans.get_edge_property_names(&missing_key).unwrap().len(), 0);
+}
+
 pub fn should_not_set_invalid_edge_properties<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let q = EdgeQuery::Edges {
@@ -135,3 +497,130 @@ pub fn should_not_delete_invalid_edge_properties<D: Datastore>(datastore: &mut D
         .delete_edge_properties(&EdgeQuery::Edges { keys: vec![key] }, "bleh")
         .unwrap();
 }
+
+pub fn should_aggregate_edge_property<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let t = Type::new("test_edge_type".to_string()).unwrap();
+    let weights = vec![
+        JsonValue::from(1.5),
+        JsonValue::from(3),
+        JsonValue::from("heavy"),
+        JsonValue::from(-2),
+    ];
+
+    for (inbound_id, weight) in inbound_ids.iter().zip(weights) {
+        let q = EdgeQuery::Edges {
+            keys: vec![EdgeKey::new(outbound_id, t.clone(), *inbound_id)],
+        };
+        trans.set_edge_properties(&q, "weight", &weight).unwrap();
+    }
+
+    let q = VertexQuery::Vertices { ids: vec![outbound_id] }.outbound_edges(None, None, None, 10);
+    let aggregate = |op| trans.aggregate_edge_property(&q, "weight", op).unwrap();
+    assert_eq!(aggregate(AggregateOp::Sum), Some(2.5));
+    assert_eq!(aggregate(AggregateOp::Avg), Some(2.5 / 3.0));
+    assert_eq!(aggregate(AggregateOp::Min), Some(-2.0));
+    assert_eq!(aggregate(AggregateOp::Max), Some(3.0));
+    assert_eq!(aggregate(AggregateOp::Count), Some(3.0));
+
+    let q = VertexQuery::Vertices { ids: vec![outbound_id] }.outbound_edges(None, None, None, 0);
+    assert_eq!(
+        trans.aggregate_edge_property(&q, "weight", AggregateOp::Sum).unwrap(),
+        None
+    );
+    assert_eq!(
+        trans.aggregate_edge_property(&q, "weight", AggregateOp::Count).unwrap(),
+        Some(0.0)
+    );
+}
+
+pub fn should_get_full_record<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = Type::new("test_edge_type".to_string()).unwrap();
+    let id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let vertex_q = VertexQuery::Vertices { ids: vec![id] };
+    trans
+        .set_vertex_properties(&vertex_q, "name", &JsonValue::from("alice"))
+        .unwrap();
+    trans
+        .set_vertex_properties(&vertex_q, "age", &JsonValue::from(30))
+        .unwrap();
+
+    for i in 0..3 {
+        let inbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+        let key = EdgeKey::new(id, edge_t.clone(), inbound_id);
+        trans.create_edge(&key).unwrap();
+        let edge_q = EdgeQuery::Edges { keys: vec![key] };
+        trans
+            .set_edge_properties(&edge_q, "weight", &JsonValue::from(i))
+            .unwrap();
+    }
+
+    for _ in 0..2 {
+        let outbound_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+        trans
+            .create_edge(&EdgeKey::new(outbound_id, edge_t.clone(), id))
+            .unwrap();
+    }
+
+    let record = trans.get_full_record(id, 10).unwrap().unwrap();
+    assert_eq!(record.vertex.id, id);
+    assert_eq!(record.vertex.t, vertex_t);
+    assert_eq!(
+        record.properties,
+        vec![
+            NamedProperty::new("age".to_string(), JsonValue::from(30)),
+            NamedProperty::new("name".to_string(), JsonValue::from("alice")),
+        ]
+    );
+
+    assert_eq!(record.outbound_edges.len(), 3);
+    for edge_record in &record.outbound_edges {
+        assert_eq!(edge_record.edge.key.outbound_id, id);
+        assert_eq!(edge_record.properties.len(), 1);
+        assert_eq!(edge_record.properties[0].name, "weight");
+    }
+
+    assert_eq!(record.inbound_edges.len(), 2);
+    for edge_record in &record.inbound_edges {
+        assert_eq!(edge_record.edge.key.inbound_id, id);
+        assert_eq!(edge_record.properties.len(), 0);
+    }
+
+    let record = trans.get_full_record(id, 1).unwrap().unwrap();
+    assert_eq!(record.outbound_edges.len(), 1);
+    assert_eq!(record.inbound_edges.len(), 1);
+
+    assert!(trans.get_full_record(Uuid::default(), 10).unwrap().is_none());
+}
+
+/// The maximum property size that datastores passed to
+/// `should_reject_oversized_property_values` must be configured with.
+pub const TEST_MAX_PROPERTY_SIZE_BYTES: usize = 16;
+
+pub fn should_reject_oversized_property_values<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let vertex_q = VertexQuery::Vertices { ids: vec![outbound_id] };
+    let edge_q = vertex_q.clone().outbound_edges(None, None, None, 1);
+
+    // `"aaaaaaaaaaaaaa"` is exactly 16 bytes, including the quotes
+    let small_value = JsonValue::from("a".repeat(14));
+    let large_value = JsonValue::from("a".repeat(15));
+
+    trans.set_vertex_properties(&vertex_q, "foo", &small_value).unwrap();
+    assert!(trans.set_vertex_properties(&vertex_q, "foo", &large_value).is_err());
+    let properties = trans.get_vertex_properties(&vertex_q, "foo").unwrap();
+    assert_eq!(properties[0].value, small_value);
+
+    trans.set_edge_properties(&edge_q, "foo", &small_value).unwrap();
+    assert!(trans.set_edge_properties(&edge_q, "foo", &large_value).is_err());
+    let properties = trans.get_edge_properties(&edge_q, "foo").unwrap();
+    assert_eq!(properties[0].value, small_value);
+}
+
+fn json_value(s: &str) -> JsonValue {
+    serde_json::from_str(s).unwrap()
+}
@@ -0,0 +1,73 @@
+use super::super::{Datastore, ReadTransaction, VertexQuery, WriteTransaction};
+use super::util::create_edges;
+use errors::{Error, ErrorKind};
+use models;
+use uuid::Uuid;
+
+/// The limits that datastores passed to the traversal limit tests must be
+/// configured with.
+pub fn test_traversal_limits() -> models::TraversalLimits {
+    models::TraversalLimits {
+        max_vertices: Some(3),
+        max_edges: Some(6),
+        max_hops: Some(2),
+    }
+}
+
+fn assert_limit_exceeded<T>(result: Result<T, Error>, expected_limit: &str) {
+    match result {
+        Err(Error(ErrorKind::TraversalLimitExceeded(ref limit), _)) => assert_eq!(limit, expected_limit),
+        Err(err) => panic!("Unexpected error: {}", err),
+        Ok(_) => panic!("Expected the traversal limit `{}` to be exceeded", expected_limit),
+    }
+}
+
+pub fn should_abort_traversal_over_vertex_limit<D: Datastore>(datastore: &mut D) {
+    // The hub has five neighbors, more than the allowed three
+    let (outbound_id, _) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let start = VertexQuery::Vertices { ids: vec![outbound_id] };
+
+    let q = start.clone().traverse(models::EdgeDirection::Outbound, None, 1, 10);
+    assert_limit_exceeded(trans.get_vertices(&q), "max_vertices");
+
+    // A traversal that stops early because of its own limit is fine
+    let q = start.traverse(models::EdgeDirection::Outbound, None, 1, 2);
+    assert_eq!(trans.get_vertices(&q).unwrap().len(), 2);
+}
+
+pub fn should_abort_traversal_over_edge_limit<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let ids: Vec<Uuid> = (0..4)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+
+    // A complete graph, so that the second hop only follows edges back to
+    // already visited vertices
+    for &outbound_id in &ids {
+        for &inbound_id in &ids {
+            if outbound_id != inbound_id {
+                trans
+                    .create_edge(&models::EdgeKey::new(outbound_id, edge_t.clone(), inbound_id))
+                    .unwrap();
+            }
+        }
+    }
+
+    let start = VertexQuery::Vertices { ids: vec![ids[0]] };
+
+    let q = start.clone().traverse(models::EdgeDirection::Outbound, None, 1, 10);
+    assert_eq!(trans.get_vertices(&q).unwrap().len(), 3);
+
+    let q = start.traverse(models::EdgeDirection::Outbound, None, 2, 10);
+    assert_limit_exceeded(trans.get_vertices(&q), "max_edges");
+}
+
+pub fn should_abort_traversal_over_hop_limit<D: Datastore>(datastore: &mut D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let trans = datastore.transaction().unwrap();
+    let q = VertexQuery::Vertices { ids: vec![outbound_id] }.traverse(models::EdgeDirection::Outbound, None, 3, 1);
+    assert_limit_exceeded(trans.get_vertices(&q), "max_hops");
+}
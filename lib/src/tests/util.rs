@@ -1,4 +1,4 @@
-use super::super::{Datastore, Transaction};
+use super::super::{Datastore, Transaction, WriteTransaction};
 use chrono::offset::Utc;
 use chrono::DateTime;
 use models;
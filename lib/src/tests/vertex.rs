@@ -1,8 +1,11 @@
-use super::super::{Datastore, Transaction, VertexQuery};
+use super::super::{Datastore, ReadTransaction, Transaction, VertexQuery, WriteTransaction};
 use super::util::{create_edge_from, create_edges};
+use chrono::offset::Utc;
 use models;
+use serde_json::Value as JsonValue;
 use std::collections::HashSet;
 use std::u32;
+use util::generate_random_secret;
 use uuid::Uuid;
 
 pub fn should_create_vertex_from_type<D: Datastore>(datastore: &mut D) {
@@ -11,6 +14,29 @@ pub fn should_create_vertex_from_type<D: Datastore>(datastore: &mut D) {
     trans.create_vertex_from_type(t).unwrap();
 }
 
+pub fn should_get_or_create_vertex<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let other_t = models::Type::new("test_other_vertex_type".to_string()).unwrap();
+    let id = models::Vertex::new(t.clone()).id;
+
+    let (vertex, created) = trans.get_or_create_vertex(id, &t).unwrap();
+    assert!(created);
+    assert_eq!(vertex.id, id);
+    assert_eq!(vertex.t, t);
+    assert!(trans.vertex_exists(id).unwrap());
+
+    // The existing vertex is returned as is, even with a different type
+    let (vertex, created) = trans.get_or_create_vertex(id, &other_t).unwrap();
+    assert!(!created);
+    assert_eq!(vertex.id, id);
+    assert_eq!(vertex.t, t);
+
+    let vertices = trans.get_vertices(&VertexQuery::single(id)).unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].t, t);
+}
+
 pub fn should_get_all_vertices<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let mut inserted_ids = create_vertices(&trans);
@@ -18,6 +44,9 @@ pub fn should_get_all_vertices<D: Datastore>(datastore: &mut D) {
     let range = trans
         .get_vertices(&VertexQuery::All {
             start_id: None,
+            end_id: None,
+            high_filter: None,
+            low_filter: None,
             limit: u32::MAX,
         })
         .unwrap();
@@ -44,6 +73,9 @@ pub fn should_get_all_vertices_with_zero_limit<D: Datastore>(datastore: &mut D)
     let range = trans
         .get_vertices(&VertexQuery::All {
             start_id: None,
+            end_id: None,
+            high_filter: None,
+            low_filter: None,
             limit: 0,
         })
         .unwrap();
@@ -58,6 +90,9 @@ pub fn should_get_all_vertices_out_of_range<D: Datastore>(datastore: &mut D) {
     let range = trans
         .get_vertices(&VertexQuery::All {
             start_id: Some(Uuid::parse_str("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF").unwrap()),
+            end_id: None,
+            high_filter: None,
+            low_filter: None,
             limit: u32::MAX,
         })
         .unwrap();
@@ -65,6 +100,96 @@ pub fn should_get_all_vertices_out_of_range<D: Datastore>(datastore: &mut D) {
     assert_eq!(range.len(), 0);
 }
 
+pub fn should_get_all_vertices_in_id_range<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let inserted_ids = create_vertices(&trans);
+
+    let q = VertexQuery::All {
+        start_id: Some(inserted_ids[1]),
+        end_id: Some(inserted_ids[3]),
+        high_filter: None,
+        low_filter: None,
+        limit: u32::MAX,
+    };
+    let ids: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert!(ids.iter().all(|id| *id >= inserted_ids[1] && *id < inserted_ids[3]));
+    assert!(ids.contains(&inserted_ids[2]));
+    let iter_ids: Vec<Uuid> = trans.get_vertices_iter(&q).unwrap().map(|v| v.unwrap().id).collect();
+    assert_eq!(iter_ids, ids);
+
+    let q = VertexQuery::All {
+        start_id: None,
+        end_id: Some(inserted_ids[2]),
+        high_filter: None,
+        low_filter: None,
+        limit: u32::MAX,
+    };
+    let ids: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert!(ids.iter().all(|id| *id < inserted_ids[2]));
+    assert!(ids.contains(&inserted_ids[0]) && ids.contains(&inserted_ids[1]));
+
+    // A range that ends before it starts is empty
+    let q = VertexQuery::All {
+        start_id: Some(inserted_ids[3]),
+        end_id: Some(inserted_ids[1]),
+        high_filter: None,
+        low_filter: None,
+        limit: u32::MAX,
+    };
+    assert_eq!(trans.get_vertices(&q).unwrap().len(), 0);
+    assert_eq!(trans.get_vertices_iter(&q).unwrap().count(), 0);
+}
+
+pub fn should_get_all_vertices_in_a_time_range<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+
+    let before_id = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let start_time = Utc::now();
+    let during_ids: HashSet<Uuid> = (0..3)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+    let end_time = Utc::now();
+    let after_id = trans.create_vertex_from_type(vertex_t).unwrap();
+
+    // Other tests may create vertices in the same time range, so only the
+    // vertices created here are checked
+    let get_ids = |high_filter, low_filter| -> HashSet<Uuid> {
+        let q = VertexQuery::All {
+            start_id: None,
+            end_id: None,
+            high_filter,
+            low_filter,
+            limit: u32::MAX,
+        };
+        let ids: HashSet<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+        let iter_ids: HashSet<Uuid> = trans.get_vertices_iter(&q).unwrap().map(|v| v.unwrap().id).collect();
+        assert_eq!(iter_ids, ids);
+        ids
+    };
+
+    let ids = get_ids(Some(end_time), Some(start_time));
+    assert!(ids.is_superset(&during_ids));
+    assert!(!ids.contains(&before_id) && !ids.contains(&after_id));
+
+    let ids = get_ids(Some(end_time), None);
+    assert!(ids.is_superset(&during_ids) && ids.contains(&before_id));
+    assert!(!ids.contains(&after_id));
+
+    let ids = get_ids(None, Some(start_time));
+    assert!(ids.is_superset(&during_ids) && ids.contains(&after_id));
+    assert!(!ids.contains(&before_id));
+
+    let q = VertexQuery::All {
+        start_id: None,
+        end_id: None,
+        high_filter: Some(start_time),
+        low_filter: Some(end_time),
+        limit: u32::MAX,
+    };
+    assert!(trans.get_vertices(&q).is_err());
+}
+
 pub fn should_get_single_vertices<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
@@ -116,6 +241,205 @@ pub fn should_get_vertices<D: Datastore>(datastore: &mut D) {
     }
 }
 
+pub fn should_get_vertices_by_types<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let suffix = generate_random_secret(8);
+    let types: Vec<models::Type> = ["a", "b", "c"]
+        .iter()
+        .map(|name| models::Type::new(format!("{}-{}", name, suffix)).unwrap())
+        .collect();
+    let mut expected_ids = Vec::new();
+
+    for t in &types {
+        for _ in 0..3 {
+            let id = trans.create_vertex_from_type(t.clone()).unwrap();
+
+            if t != &types[1] {
+                expected_ids.push(id);
+            }
+        }
+    }
+
+    expected_ids.sort();
+
+    let q = VertexQuery::Types {
+        types: vec![types[2].clone(), types[0].clone()],
+        limit: u32::MAX,
+    };
+    let range = trans.get_vertices(&q).unwrap();
+    let ids: Vec<Uuid> = range.iter().map(|vertex| vertex.id).collect();
+    assert_eq!(ids, expected_ids);
+    assert!(range.iter().all(|vertex| vertex.t != types[1]));
+
+    let q = VertexQuery::Types {
+        types: vec![types[0].clone(), types[2].clone()],
+        limit: 2,
+    };
+    let ids: Vec<Uuid> = trans
+        .get_vertices(&q)
+        .unwrap()
+        .into_iter()
+        .map(|vertex| vertex.id)
+        .collect();
+    assert_eq!(ids, expected_ids[..2].to_vec());
+
+    let q = VertexQuery::Types {
+        types: vec![],
+        limit: u32::MAX,
+    };
+    assert_eq!(trans.get_vertices(&q).unwrap().len(), 0);
+}
+
+pub fn should_get_vertices_by_property_substring<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let name = format!("name-{}", generate_random_secret(8));
+    let mut expected_ids = Vec::new();
+
+    let values = vec![
+        JsonValue::from("Alice Smith"),
+        JsonValue::from("SMITHERS"),
+        JsonValue::from("Bob"),
+        JsonValue::from(42),
+        JsonValue::from(vec!["smith"]),
+    ];
+
+    for value in &values {
+        let id = trans.create_vertex_from_type(t.clone()).unwrap();
+        trans
+            .set_vertex_properties(&VertexQuery::single(id), &name, value)
+            .unwrap();
+
+        if value.as_str().is_some_and(|s| s.to_lowercase().contains("smith")) {
+            expected_ids.push(id);
+        }
+    }
+
+    // A vertex without the property at all is skipped too
+    trans.create_vertex_from_type(t.clone()).unwrap();
+    expected_ids.sort();
+
+    let q = VertexQuery::PropertyContains {
+        name: name.clone(),
+        substring: "sMiTh".to_string(),
+        limit: u32::MAX,
+    };
+    let ids: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(ids, expected_ids);
+
+    let q = VertexQuery::PropertyContains {
+        name: name.clone(),
+        substring: "smith".to_string(),
+        limit: 1,
+    };
+    let ids: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(ids, expected_ids[..1].to_vec());
+
+    let q = VertexQuery::PropertyContains {
+        name,
+        substring: "carol".to_string(),
+        limit: u32::MAX,
+    };
+    assert_eq!(trans.get_vertices(&q).unwrap().len(), 0);
+}
+
+pub fn should_get_vertices_paged<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new(format!("paged-{}", generate_random_secret(8))).unwrap();
+
+    for _ in 0..3 {
+        trans.create_vertex_from_type(t.clone()).unwrap();
+    }
+
+    for &(limit, expected_len, expected_has_more) in &[(0, 0, true), (2, 2, true), (3, 3, false), (4, 3, false)] {
+        let q = VertexQuery::Types {
+            types: vec![t.clone()],
+            limit,
+        };
+        let (vertices, has_more) = trans.get_vertices_paged(&q).unwrap();
+        assert_eq!(vertices.len(), expected_len);
+        assert_eq!(has_more, expected_has_more);
+    }
+
+    let q = VertexQuery::Types {
+        types: vec![t.clone()],
+        limit: u32::MAX,
+    };
+    assert_eq!(
+        trans.get_vertices_paged(&q).unwrap(),
+        (trans.get_vertices(&q).unwrap(), false)
+    );
+
+    let ids: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    let q = VertexQuery::Vertices { ids };
+    let (vertices, has_more) = trans.get_vertices_paged(&q).unwrap();
+    assert_eq!(vertices.len(), 3);
+    assert!(!has_more);
+}
+
+pub fn should_estimate_vertex_count<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new(format!("estimate-{}", generate_random_secret(8))).unwrap();
+    let mut ids = Vec::new();
+
+    for _ in 0..3 {
+        ids.push(trans.create_vertex_from_type(t.clone()).unwrap());
+    }
+
+    let q = VertexQuery::all(1);
+    assert_eq!(trans.estimate_vertex_count(&q).unwrap(), 1);
+
+    let q = VertexQuery::Types {
+        types: vec![t.clone()],
+        limit: u32::MAX,
+    };
+    assert!(trans.estimate_vertex_count(&q).unwrap() >= 3);
+
+    let key = models::EdgeKey::new(ids[0], t.clone(), ids[1]);
+    trans.create_edge(&key).unwrap();
+    let q = VertexQuery::Pipe {
+        edge_query: Box::new(models::EdgeQuery::single(key)),
+        converter: models::EdgeDirection::Both,
+        offset: 0,
+        limit: 10,
+    };
+    assert!(trans.estimate_vertex_count(&q).unwrap() >= 2);
+
+    ids.push(Uuid::default());
+    let q = VertexQuery::Vertices { ids };
+    let estimate = trans.estimate_vertex_count(&q).unwrap();
+    assert!((3..=4).contains(&estimate));
+
+    let q = VertexQuery::Vertices { ids: vec![] }
+        .outbound_edges(None, None, None, 10)
+        .inbound_vertices(10);
+    assert_eq!(trans.estimate_vertex_count(&q).unwrap(), 0);
+}
+
+pub fn should_get_vertices_leniently<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new(format!("lenient-{}", generate_random_secret(8))).unwrap();
+
+    for _ in 0..3 {
+        trans.create_vertex_from_type(t.clone()).unwrap();
+    }
+
+    let q = VertexQuery::Types {
+        types: vec![t.clone()],
+        limit: u32::MAX,
+    };
+    let (vertices, errors) = trans.get_vertices_lenient(&q).unwrap();
+    assert_eq!(vertices, trans.get_vertices(&q).unwrap());
+    assert!(errors.is_empty());
+
+    let q = VertexQuery::Vertices {
+        ids: vec![Uuid::default()],
+    };
+    let (vertices, errors) = trans.get_vertices_lenient(&q).unwrap();
+    assert!(vertices.is_empty());
+    assert!(errors.is_empty());
+}
+
 pub fn should_get_vertices_piped<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
     let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
@@ -151,11 +475,213 @@ pub fn should_get_vertices_piped<D: Datastore>(datastore: &mut D) {
     assert_eq!(range[0], v);
 }
 
+pub fn should_get_vertex_types<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let type_names = ["test_vertex_type_b", "test_vertex_type_a", "test_vertex_type_b"];
+
+    for type_name in &type_names {
+        let t = models::Type::new(type_name.to_string()).unwrap();
+        trans.create_vertex_from_type(t).unwrap();
+    }
+
+    let types = trans.get_vertex_types().unwrap();
+    let expected_types = [
+        models::Type::new("test_vertex_type_a".to_string()).unwrap(),
+        models::Type::new("test_vertex_type_b".to_string()).unwrap(),
+    ];
+
+    // Other tests may share the datastore, so only check that the types
+    // are sorted, distinct, and include the ones created here
+    assert!(types.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(expected_types.iter().all(|t| types.contains(t)));
+}
+
+pub fn should_count_vertices_by_type<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t_a = models::Type::new(format!("count-a-{}", generate_random_secret(8))).unwrap();
+    let t_b = models::Type::new(format!("count-b-{}", generate_random_secret(8))).unwrap();
+
+    for t in &[&t_a, &t_b, &t_b] {
+        trans.create_vertex_from_type((*t).clone()).unwrap();
+    }
+
+    // Other tests may share the datastore, so only check the types created
+    // here, which are unique to this test
+    let counts = trans.count_vertices_by_type().unwrap();
+    assert_eq!(counts.get(&t_a), Some(&1));
+    assert_eq!(counts.get(&t_b), Some(&2));
+    assert!(counts.values().all(|&count| count > 0));
+}
+
+pub fn should_check_vertex_existence<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let id = trans.create_vertex_from_type(t).unwrap();
+    assert!(trans.vertex_exists(id).unwrap());
+    assert!(!trans.vertex_exists(Uuid::default()).unwrap());
+
+    trans.delete_vertices(&VertexQuery::Vertices { ids: vec![id] }).unwrap();
+    assert!(!trans.vertex_exists(id).unwrap());
+}
+
+pub fn should_get_vertices_iter<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let inserted_ids = create_vertices(&trans);
+
+    let q = VertexQuery::All {
+        start_id: None,
+        end_id: None,
+        high_filter: None,
+        low_filter: None,
+        limit: u32::MAX,
+    };
+    let expected = trans.get_vertices(&q).unwrap();
+    let range: Vec<models::Vertex> = trans.get_vertices_iter(&q).unwrap().map(|v| v.unwrap()).collect();
+    assert_eq!(range, expected);
+
+    let q = VertexQuery::All {
+        start_id: Some(inserted_ids[1]),
+        end_id: None,
+        high_filter: None,
+        low_filter: None,
+        limit: 2,
+    };
+    let expected = trans.get_vertices(&q).unwrap();
+    let range: Vec<models::Vertex> = trans.get_vertices_iter(&q).unwrap().map(|v| v.unwrap()).collect();
+    assert_eq!(range.len(), 2);
+    assert_eq!(range, expected);
+
+    // Only the first item should need to be produced
+    let q = VertexQuery::Vertices {
+        ids: inserted_ids.clone(),
+    };
+    let first = trans.get_vertices_iter(&q).unwrap().next().unwrap().unwrap();
+    assert_eq!(first.id, inserted_ids[0]);
+}
+
+pub fn should_get_vertices_traversed<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let ids: Vec<Uuid> = (0..4)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+
+    // Create a cycle: 0 -> 1 -> 2 -> 3 -> 0
+    for i in 0..4 {
+        let key = models::EdgeKey::new(ids[i], edge_t.clone(), ids[(i + 1) % 4]);
+        trans.create_edge(&key).unwrap();
+    }
+
+    let start = VertexQuery::Vertices { ids: vec![ids[0]] };
+
+    let q = start
+        .clone()
+        .traverse(models::EdgeDirection::Outbound, Some(edge_t.clone()), 2, 10);
+    let range: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(range, vec![ids[1], ids[2]]);
+
+    // The traversal should terminate despite the cycle, and should not
+    // include the starting vertex
+    let q = start.clone().traverse(models::EdgeDirection::Outbound, None, 10, 10);
+    let range: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(range, vec![ids[1], ids[2], ids[3]]);
+
+    let q = start.clone().traverse(models::EdgeDirection::Inbound, None, 1, 10);
+    let range: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(range, vec![ids[3]]);
+
+    let q = start.clone().traverse(models::EdgeDirection::Outbound, None, 10, 1);
+    let range: Vec<Uuid> = trans.get_vertices(&q).unwrap().into_iter().map(|v| v.id).collect();
+    assert_eq!(range, vec![ids[1]]);
+
+    let q = start.traverse(
+        models::EdgeDirection::Outbound,
+        Some(models::Type::new("foo".to_string()).unwrap()),
+        10,
+        10,
+    );
+    let range = trans.get_vertices(&q).unwrap();
+    assert_eq!(range.len(), 0);
+}
+
+pub fn should_get_vertices_by_degree<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let vertex_t = models::Type::new("test_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new(format!("degree-{}", generate_random_secret(8))).unwrap();
+    let other_edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let ids: Vec<Uuid> = (0..3)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+    let inbound_ids: Vec<Uuid> = (0..3)
+        .map(|_| trans.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+
+    // The first vertex has three edges of `edge_t`, the second has one of
+    // `edge_t` and two of another type, and the third has none
+    for inbound_id in &inbound_ids {
+        let key = models::EdgeKey::new(ids[0], edge_t.clone(), *inbound_id);
+        trans.create_edge(&key).unwrap();
+    }
+
+    trans
+        .create_edge(&models::EdgeKey::new(ids[1], edge_t.clone(), inbound_ids[0]))
+        .unwrap();
+
+    for inbound_id in &inbound_ids[1..] {
+        let key = models::EdgeKey::new(ids[1], other_edge_t.clone(), *inbound_id);
+        trans.create_edge(&key).unwrap();
+    }
+
+    let get_ids = |q: &VertexQuery| -> Vec<Uuid> { trans.get_vertices(q).unwrap().into_iter().map(|v| v.id).collect() };
+    let start = VertexQuery::ids(ids.clone());
+
+    let q = start
+        .clone()
+        .degree_at_least(models::EdgeDirection::Outbound, Some(edge_t.clone()), 2, 10);
+    assert_eq!(get_ids(&q), vec![ids[0]]);
+
+    let q = start
+        .clone()
+        .degree_at_least(models::EdgeDirection::Outbound, None, 3, 10);
+    assert_eq!(get_ids(&q), vec![ids[0], ids[1]]);
+
+    let q = start
+        .clone()
+        .degree_at_least(models::EdgeDirection::Outbound, None, 0, 10);
+    assert_eq!(get_ids(&q), ids);
+
+    let q = start
+        .clone()
+        .degree_at_least(models::EdgeDirection::Outbound, None, 1, 1);
+    assert_eq!(get_ids(&q), vec![ids[0]]);
+
+    let q = start.degree_at_least(models::EdgeDirection::Inbound, None, 1, 10);
+    assert_eq!(get_ids(&q), Vec::<Uuid>::new());
+
+    let q = VertexQuery::ids(inbound_ids.clone()).degree_at_least(models::EdgeDirection::Inbound, None, 2, 10);
+    assert_eq!(get_ids(&q), inbound_ids);
+
+    // Without a starting query, every vertex is a candidate
+    let q = VertexQuery::DegreeAtLeast {
+        start: None,
+        direction: models::EdgeDirection::Both,
+        type_filter: Some(edge_t),
+        min: 2,
+        limit: u32::MAX,
+    };
+    let mut expected_ids = vec![ids[0], inbound_ids[0]];
+    expected_ids.sort();
+    assert_eq!(get_ids(&q), expected_ids);
+}
+
 pub fn should_delete_a_valid_vertex<D: Datastore>(datastore: &mut D) {
     let (outbound_id, _) = create_edges(datastore);
     let trans = datastore.transaction().unwrap();
-    let q = VertexQuery::Vertices { ids: vec![outbound_id] };
-    trans.delete_vertices(&q).unwrap();
+    let q = VertexQuery::Vertices {
+        ids: vec![outbound_id, outbound_id],
+    };
+    assert_eq!(trans.delete_vertices(&q).unwrap(), 1);
     let v = trans.get_vertices(&q).unwrap();
     assert_eq!(v.len(), 0);
     let t = models::Type::new("test_edge_type".to_string()).unwrap();
@@ -167,11 +693,12 @@ pub fn should_delete_a_valid_vertex<D: Datastore>(datastore: &mut D) {
 
 pub fn should_not_delete_an_invalid_vertex<D: Datastore>(datastore: &mut D) {
     let trans = datastore.transaction().unwrap();
-    trans
+    let count = trans
         .delete_vertices(&VertexQuery::Vertices {
             ids: vec![Uuid::default()],
         })
         .unwrap();
+    assert_eq!(count, 0);
 }
 
 pub fn should_get_a_vertex_count<D: Datastore>(datastore: &mut D) {
@@ -183,6 +710,41 @@ pub fn should_get_a_vertex_count<D: Datastore>(datastore: &mut D) {
     assert!(count >= 1);
 }
 
+pub fn should_get_graph_stats<D: Datastore>(datastore: &mut D) {
+    let trans = datastore.transaction().unwrap();
+    let before = trans.get_graph_stats().unwrap();
+    let vertex_t = models::Type::new("test_stats_vertex_type".to_string()).unwrap();
+    let edge_t = models::Type::new("test_edge_type".to_string()).unwrap();
+    let a = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let b = trans.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let key = models::EdgeKey::new(a, edge_t, b);
+    trans.create_edge(&key).unwrap();
+    trans
+        .set_vertex_properties(
+            &VertexQuery::Vertices { ids: vec![a, b] },
+            "name",
+            &JsonValue::from("x"),
+        )
+        .unwrap();
+    trans
+        .set_edge_properties(
+            &models::EdgeQuery::Edges { keys: vec![key] },
+            "weight",
+            &JsonValue::from(1),
+        )
+        .unwrap();
+
+    let after = trans.get_graph_stats().unwrap();
+    assert_eq!(after.vertices, before.vertices + 2);
+    assert_eq!(after.edges, before.edges + 1);
+    assert_eq!(after.vertex_properties, before.vertex_properties + 2);
+    assert_eq!(after.edge_properties, before.edge_properties + 1);
+    let before_count = before.vertex_types.get(&vertex_t).cloned().unwrap_or(0);
+    assert_eq!(after.vertex_types.get(&vertex_t), Some(&(before_count + 2)));
+    assert_eq!(after.vertex_types.values().sum::<u64>(), after.vertices);
+    assert!(after.average_degree > 0.0);
+}
+
 fn create_vertices<T>(trans: &T) -> Vec<Uuid>
 where
     T: Transaction,
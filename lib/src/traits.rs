@@ -1,9 +1,21 @@
-use errors::Result;
+#[cfg(feature = "compression")]
+use errors::Error;
+use errors::{ErrorKind, Result, ValidationError};
 use models;
+#[cfg(feature = "compression")]
+use serde_json;
 use serde_json::value::Value as JsonValue;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+#[cfg(feature = "compression")]
+use std::io::{BufRead, BufReader, Read};
 use std::vec::Vec;
+use util;
 use uuid::Uuid;
 
+// The number of vertices `Transaction::get_graph_stats` fetches at a time.
+const GRAPH_STATS_PAGE_SIZE: u32 = 1000;
+
 /// Specifies a datastore implementation.
 ///
 /// # Errors
@@ -15,6 +27,15 @@ pub trait Datastore {
     /// Creates a new transaction.
     fn transaction(&self) -> Result<Self::Trans>;
 
+    /// Checks whether the datastore is usable, returning an error if it
+    /// isn't. This is meant to be cheap enough to call often, e.g. from a
+    /// readiness probe. By default, this checks that a transaction can be
+    /// created.
+    fn health_check(&self) -> Result<()> {
+        self.transaction()?;
+        Ok(())
+    }
+
     /// Bulk inserts many vertices, edges, and/or properties.
     ///
     /// # Arguments
@@ -26,86 +47,376 @@ pub trait Datastore {
         let trans = self.transaction()?;
 
         for item in items {
-            match item {
-                models::BulkInsertItem::Vertex(ref vertex) => {
-                    trans.create_vertex(vertex)?;
-                }
-                models::BulkInsertItem::Edge(ref edge_key) => {
-                    trans.create_edge(edge_key)?;
-                }
+            insert_bulk_insert_item(&trans, &item)?;
+        }
+
+        Ok(())
+    }
+
+    /// Bulk inserts many vertices, edges, and/or properties like
+    /// `bulk_insert`, calling `progress` with the number of items processed
+    /// so far after every `every` items, and once more at the end if the
+    /// last call didn't cover every item. This is meant for showing the
+    /// progress of long imports.
+    ///
+    /// By default, items are counted as `bulk_insert` takes them from the
+    /// iterator. Datastores that collect the items before inserting them
+    /// should override this to count them as they're inserted instead.
+    ///
+    /// # Arguments
+    /// * `items`: The items to insert.
+    /// * `every`: How many items to process between calls to `progress`.
+    /// * `progress`: Called with the number of items processed so far.
+    ///
+    /// # Errors
+    /// Returns a `ValidationError` if `every` is zero.
+    fn bulk_insert_with_progress<I, F>(&self, items: I, every: u64, mut progress: F) -> Result<()>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+        F: FnMut(u64),
+    {
+        if every == 0 {
+            let err: ValidationError = "The progress interval must be positive".into();
+            return Err(err.into());
+        }
+
+        let mut count = 0;
+
+        self.bulk_insert(items.inspect(|_| {
+            count += 1;
+
+            if count % every == 0 {
+                progress(count);
+            }
+        }))?;
+
+        if count % every != 0 {
+            progress(count);
+        }
+
+        Ok(())
+    }
+
+    /// Bulk inserts many vertices, edges, and/or properties, reporting the
+    /// outcome of each item individually. Unlike `bulk_insert`, every item
+    /// is validated, and an item that cannot be inserted - e.g. an edge
+    /// whose vertices do not exist - does not prevent the remaining items
+    /// from being inserted. The returned results are in the same order as
+    /// the items.
+    ///
+    /// # Arguments
+    /// * `items`: The items to insert.
+    fn bulk_insert_partial<I>(&self, items: I) -> Result<Vec<::std::result::Result<(), models::BulkInsertItemError>>>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        let trans = self.transaction()?;
+        let mut results = Vec::new();
+
+        for item in items {
+            let result = match item {
+                models::BulkInsertItem::Vertex(ref vertex) => trans.create_vertex(vertex).map(|_| Ok(())),
+                models::BulkInsertItem::Edge(ref edge_key) => trans.create_edge(edge_key).map(|created| {
+                    if created {
+                        Ok(())
+                    } else {
+                        Err(models::BulkInsertItemError::MissingVertex)
+                    }
+                }),
                 models::BulkInsertItem::VertexProperty(id, ref name, ref value) => {
                     let query = models::VertexQuery::Vertices { ids: vec![id] };
-                    trans.set_vertex_properties(&query, name, value)?;
+
+                    trans.get_vertices(&query).and_then(|vertices| {
+                        if vertices.is_empty() {
+                            Ok(Err(models::BulkInsertItemError::MissingVertex))
+                        } else {
+                            trans.set_vertex_properties(&query, name, value).map(|_| Ok(()))
+                        }
+                    })
                 }
                 models::BulkInsertItem::EdgeProperty(ref edge_key, ref name, ref value) => {
                     let query = models::EdgeQuery::Edges {
                         keys: vec![edge_key.clone()],
                     };
-                    trans.set_edge_properties(&query, name, value)?;
+
+                    trans.get_edges(&query).and_then(|edges| {
+                        if edges.is_empty() {
+                            Ok(Err(models::BulkInsertItemError::MissingEdge))
+                        } else {
+                            trans.set_edge_properties(&query, name, value).map(|_| Ok(()))
+                        }
+                    })
                 }
+            };
+
+            results.push(match result {
+                Ok(result) => result,
+                Err(err) => Err(models::BulkInsertItemError::Internal(err.to_string())),
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// Bulk inserts many vertices, edges, and/or properties, handling items
+    /// that already exist according to `mode`. Conflicts are only detected
+    /// against what is already in the datastore, not between the items
+    /// themselves. Returns the number of items that were skipped.
+    ///
+    /// # Arguments
+    /// * `items`: The items to insert.
+    /// * `mode`: How to handle items that already exist.
+    ///
+    /// # Errors
+    /// In `BulkInsertMode::Error`, returns a `BulkInsertConflict` error if
+    /// any item already exists.
+    fn bulk_insert_with_mode<I>(&self, items: I, mode: models::BulkInsertMode) -> Result<u64>
+    where
+        I: Iterator<Item = models::BulkInsertItem>,
+    {
+        if mode == models::BulkInsertMode::Overwrite {
+            self.bulk_insert(items)?;
+            return Ok(0);
+        }
+
+        let trans = self.transaction()?;
+        let mut insertable_items = Vec::new();
+        let mut skipped = 0;
+
+        for item in items {
+            if bulk_insert_item_exists(&trans, &item)? {
+                if mode == models::BulkInsertMode::Error {
+                    return Err(ErrorKind::BulkInsertConflict(format!("{:?}", item)).into());
+                }
+
+                skipped += 1;
+            } else {
+                insertable_items.push(item);
             }
         }
 
-        Ok(())
+        self.bulk_insert(insertable_items.into_iter())?;
+        Ok(skipped)
     }
-}
 
-/// Specifies a transaction implementation, which are returned by datastores.
-/// All datastore manipulations are done through transactions. Despite the
-/// name, different datastore implementations carry different guarantees.
-/// Depending on the implementation, it may not be possible to rollback the
-/// changes on error. See the documentation of individual implementations for
-/// details. Transactions are automatically committed on drop. Transactions
-/// should be designed to not fail on commit; i.e. errors should occur when a
-/// method is actually called instead.
-pub trait Transaction {
-    /// Creates a new vertex. Returns whether the vertex was successfully
-    /// created - if this is false, it's because a vertex with the same UUID
-    /// already exists.
+    /// Executes a batch of operations, in order. By default, the operations
+    /// are run one after another in a single transaction, which isn't
+    /// atomic: other operations may be interleaved with them, and if one
+    /// fails, the ones before it stay applied. Datastores that can do better
+    /// override this - see `MemoryDatastore`.
     ///
     /// # Arguments
-    /// * `vertex`: The vertex to create.
-    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool>;
+    /// * `operations`: The operations to execute.
+    fn execute_batch<I>(&self, operations: I) -> Result<()>
+    where
+        I: Iterator<Item = models::BatchOperation>,
+    {
+        let trans = self.transaction()?;
 
-    /// Creates a new vertex with just a type specification. As opposed to
-    /// `create_vertex`, this is used when you do not want to manually specify
-    /// the vertex's UUID. Returns the new vertex's UUID.
+        for operation in operations {
+            match operation {
+                models::BatchOperation::CreateVertex(ref vertex) => {
+                    trans.create_vertex(vertex)?;
+                }
+                models::BatchOperation::CreateEdge(ref key) => {
+                    trans.create_edge(key)?;
+                }
+                models::BatchOperation::DeleteVertices(ref q) => {
+                    trans.delete_vertices(q)?;
+                }
+                models::BatchOperation::DeleteEdges(ref q) => {
+                    trans.delete_edges(q)?;
+                }
+                models::BatchOperation::SetVertexProperties(ref q, ref name, ref value) => {
+                    trans.set_vertex_properties(q, name, value)?;
+                }
+                models::BatchOperation::SetEdgeProperties(ref q, ref name, ref value) => {
+                    trans.set_edge_properties(q, name, value)?;
+                }
+                models::BatchOperation::DeleteVertexProperties(ref q, ref name) => {
+                    trans.delete_vertex_properties(q, name)?;
+                }
+                models::BatchOperation::DeleteEdgeProperties(ref q, ref name) => {
+                    trans.delete_edge_properties(q, name)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Imports a serialized graph via `bulk_insert`. The graph is serialized
+    /// as newline-delimited JSON records, in the format written by
+    /// `MemoryDatastore::export_json` - see `ExportRecord`. If the content is
+    /// gzip or zstd compressed, it is transparently decompressed first.
+    ///
+    /// Records are streamed into `bulk_insert` as they're parsed, rather than
+    /// read up-front, so a malformed record fails the import after the ones
+    /// before it have been inserted. By default, only what `bulk_insert` can
+    /// express is imported - edge datetimes, weights and expirations are
+    /// dropped, and undirected edges become directed ones.
     ///
     /// # Arguments
-    /// * `t`: The type of the vertex to create.
-    fn create_vertex_from_type(&self, t: models::Type) -> Result<Uuid> {
-        let v = models::Vertex::new(t);
+    /// * `reader`: The reader to import the serialized graph from.
+    #[cfg(feature = "compression")]
+    fn import_compressed<R: Read>(&self, reader: R) -> Result<models::ImportReport> {
+        let reader = BufReader::new(util::decompress(reader)?);
+        let mut report = models::ImportReport::default();
+        let mut error = None;
 
-        if !self.create_vertex(&v)? {
-            Err("UUID already taken".into())
-        } else {
-            Ok(v.id)
+        let items = reader
+            .lines()
+            .filter_map(|line| match line {
+                Ok(ref line) if line.trim().is_empty() => None,
+                Ok(line) => Some(serde_json::from_str::<models::ExportRecord>(&line).map_err(Error::from)),
+                Err(err) => Some(Err(Error::from(err))),
+            })
+            .scan(&mut error, |error, record| match record {
+                Ok(record) => Some(record),
+                Err(err) => {
+                    **error = Some(err);
+                    None
+                }
+            })
+            .inspect(|record| report.record(record))
+            .map(models::BulkInsertItem::from);
+
+        self.bulk_insert(items)?;
+
+        match error {
+            Some(err) => Err(err),
+            None => Ok(report),
         }
     }
+}
 
+/// Specifies the read operations of a transaction. Datastores may offer
+/// transactions that only implement this trait, so that code given one is
+/// guaranteed not to change the datastore.
+pub trait ReadTransaction {
     /// Gets a range of vertices specified by a query.
     ///
     /// # Arguments
     /// * `q` - The query to run.
     fn get_vertices(&self, q: &models::VertexQuery) -> Result<Vec<models::Vertex>>;
 
-    /// Deletes existing vertices specified by a query.
+    /// Gets a range of vertices specified by a query, yielding them lazily
+    /// rather than collecting them into a `Vec` up-front.
+    ///
+    /// The default implementation just wraps `get_vertices`, so datastores
+    /// that can stream results should override it.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    fn get_vertices_iter(&self, q: &models::VertexQuery) -> Result<Box<dyn Iterator<Item = Result<models::Vertex>>>> {
+        Ok(Box::new(self.get_vertices(q)?.into_iter().map(Ok)))
+    }
+
+    /// Gets a range of vertices specified by a query, skipping vertices that
+    /// can't be read rather than failing the whole query. Returns the
+    /// vertices that could be read, along with an error for each one that
+    /// couldn't. Errors that affect the query as a whole, e.g. an invalid
+    /// query, still fail it.
+    ///
+    /// The default implementation collects `get_vertices_iter`, so it's only
+    /// lenient for datastores that report errors per vertex there.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    fn get_vertices_lenient(&self, q: &models::VertexQuery) -> Result<(Vec<models::Vertex>, Vec<models::QueryError>)> {
+        let mut vertices = Vec::new();
+        let mut errors = Vec::new();
+
+        for vertex in self.get_vertices_iter(q)? {
+            match vertex {
+                Ok(vertex) => vertices.push(vertex),
+                Err(err) => errors.push(models::QueryError::new(err.to_string())),
+            }
+        }
+
+        Ok((vertices, errors))
+    }
+
+    /// Gets a range of vertices specified by a query, along with whether
+    /// there are more vertices past the query's limit. This fetches one
+    /// extra vertex to find out, rather than running a separate count.
+    /// Queries without a limit never have more results.
     ///
     /// # Arguments
     /// * `q` - The query to run.
-    fn delete_vertices(&self, q: &models::VertexQuery) -> Result<()>;
+    fn get_vertices_paged(&self, q: &models::VertexQuery) -> Result<(Vec<models::Vertex>, bool)> {
+        let mut q = q.clone();
+
+        let limit = match q.limit_mut() {
+            Some(limit) if *limit < u32::MAX => {
+                let original_limit = *limit;
+                *limit += 1;
+                original_limit
+            }
+            _ => return Ok((self.get_vertices(&q)?, false)),
+        };
+
+        let mut vertices = self.get_vertices(&q)?;
+        let has_more = vertices.len() > limit as usize;
+        vertices.truncate(limit as usize);
+        Ok((vertices, has_more))
+    }
 
     /// Gets the number of vertices in the datastore..
     fn get_vertex_count(&self) -> Result<u64>;
 
-    /// Creates a new edge. If the edge already exists, this will update it
-    /// with a new update datetime. Returns whether the edge was successfully
-    /// created - if this is false, it's because one of the specified vertices
-    /// is missing.
+    /// Quickly estimates how many vertices a query will return, without
+    /// running it. The estimate is an upper bound, not an exact count. By
+    /// default, this is `VertexQuery::max_results`; datastores that can
+    /// cheaply count their vertices should also bound it by that count.
     ///
     /// # Arguments
-    /// * `key`: The edge to create.
-    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool>;
+    /// * `q` - The query to estimate.
+    fn estimate_vertex_count(&self, q: &models::VertexQuery) -> Result<u64> {
+        Ok(q.max_results())
+    }
+
+    /// Gets the distinct types of all vertices in the datastore, in sorted
+    /// order.
+    fn get_vertex_types(&self) -> Result<Vec<models::Type>> {
+        let q = models::VertexQuery::All {
+            start_id: None,
+            end_id: None,
+            high_filter: None,
+            low_filter: None,
+            limit: u32::MAX,
+        };
+        let types: BTreeSet<models::Type> = self.get_vertices(&q)?.into_iter().map(|vertex| vertex.t).collect();
+        Ok(types.into_iter().collect())
+    }
+
+    /// Gets the number of vertices of each type, counted in a single pass
+    /// over all vertices. Types without any vertices are left out.
+    fn count_vertices_by_type(&self) -> Result<BTreeMap<models::Type, u64>> {
+        let q = models::VertexQuery::All {
+            start_id: None,
+            end_id: None,
+            high_filter: None,
+            low_filter: None,
+            limit: u32::MAX,
+        };
+        let mut counts = BTreeMap::new();
+
+        for vertex in self.get_vertices_iter(&q)? {
+            *counts.entry(vertex?.t).or_insert(0) += 1;
+        }
+
+        Ok(counts)
+    }
+
+    /// Checks whether a vertex exists.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    fn vertex_exists(&self, id: Uuid) -> Result<bool> {
+        let q = models::VertexQuery::Vertices { ids: vec![id] };
+        Ok(!self.get_vertices(&q)?.is_empty())
+    }
 
     /// Gets a range of edges specified by a query.
     ///
@@ -113,11 +424,80 @@ pub trait Transaction {
     /// * `q` - The query to run.
     fn get_edges(&self, q: &models::EdgeQuery) -> Result<Vec<models::Edge>>;
 
-    /// Deletes a set of edges specified by a query.
+    /// Checks whether an edge exists.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the edge.
+    fn edge_exists(&self, key: &models::EdgeKey) -> Result<bool> {
+        let q = models::EdgeQuery::Edges {
+            keys: vec![key.clone()],
+        };
+        Ok(!self.get_edges(&q)?.is_empty())
+    }
+
+    /// Gets a range of edges specified by a query, sorted by the value of a
+    /// numeric edge property. Edges that are missing the property, or whose
+    /// property value is not a number, are sorted last. Ties retain the order
+    /// in which the query returned the edges.
     ///
     /// # Arguments
     /// * `q` - The query to run.
-    fn delete_edges(&self, q: &models::EdgeQuery) -> Result<()>;
+    /// * `name` - The name of the property to sort by.
+    /// * `direction` - The direction to sort in.
+    /// * `limit` - The maximum number of edges to return.
+    fn get_edges_sorted_by_property(
+        &self,
+        q: &models::EdgeQuery,
+        name: &str,
+        direction: models::SortDirection,
+        limit: u32,
+    ) -> Result<Vec<models::Edge>> {
+        let mut values: HashMap<models::EdgeKey, f64> = self
+            .get_edge_properties(q, name)?
+            .into_iter()
+            .filter_map(|property| property.value.as_f64().map(|value| (property.key, value)))
+            .collect();
+
+        let mut edges: Vec<(models::Edge, Option<f64>)> = self
+            .get_edges(q)?
+            .into_iter()
+            .map(|edge| {
+                let value = values.remove(&edge.key);
+                (edge, value)
+            })
+            .collect();
+
+        edges.sort_by(|&(_, first), &(_, second)| match (first, second) {
+            (Some(first), Some(second)) => {
+                let ordering = first.partial_cmp(&second).unwrap_or(Ordering::Equal);
+
+                match direction {
+                    models::SortDirection::Ascending => ordering,
+                    models::SortDirection::Descending => ordering.reverse(),
+                }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+
+        Ok(edges.into_iter().take(limit as usize).map(|(edge, _)| edge).collect())
+    }
+
+    /// Gets the edges of the subgraph induced by a set of vertices, i.e. all
+    /// edges where both the outbound and inbound vertex are in the set.
+    ///
+    /// # Arguments
+    /// * `ids` - The ids of the vertices in the set.
+    fn get_induced_edges(&self, ids: &[Uuid]) -> Result<Vec<models::Edge>> {
+        let id_set: HashSet<&Uuid> = ids.iter().collect();
+        let q = models::VertexQuery::Vertices { ids: ids.to_vec() }.outbound_edges(None, None, None, u32::MAX);
+        let edges = self.get_edges(&q)?;
+        Ok(edges
+            .into_iter()
+            .filter(|edge| id_set.contains(&edge.key.inbound_id))
+            .collect())
+    }
 
     /// Gets the number of edges associated with a vertex.
     ///
@@ -132,6 +512,39 @@ pub trait Transaction {
         direction: models::EdgeDirection,
     ) -> Result<u64>;
 
+    /// Gets the number of edges associated with each of several vertices,
+    /// as `(id, count)` pairs in the same order as `ids`. This is the same
+    /// as calling `get_edge_count` for each vertex, but datastores may count
+    /// the edges of all of them at once.
+    ///
+    /// # Arguments
+    /// * `ids` - The ids of the vertices.
+    /// * `type_filter` - Only get the counts for a specified edge type.
+    /// * `direction`: The direction of edges to get.
+    fn get_edge_counts(
+        &self,
+        ids: &[Uuid],
+        type_filter: Option<&models::Type>,
+        direction: models::EdgeDirection,
+    ) -> Result<Vec<(Uuid, u64)>> {
+        ids.iter()
+            .map(|&id| Ok((id, self.get_edge_count(id, type_filter, direction)?)))
+            .collect()
+    }
+
+    /// Gets the number of outbound and inbound edges of a vertex, as
+    /// `(outbound, inbound)`. This is the same as two calls to
+    /// `get_edge_count`, but datastores may get both counts at once.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    /// * `type_filter` - Only count edges of a specified type.
+    fn get_vertex_degree(&self, id: Uuid, type_filter: Option<&models::Type>) -> Result<(u64, u64)> {
+        let outbound = self.get_edge_count(id, type_filter, models::EdgeDirection::Outbound)?;
+        let inbound = self.get_edge_count(id, type_filter, models::EdgeDirection::Inbound)?;
+        Ok((outbound, inbound))
+    }
+
     /// Gets vertex properties.
     ///
     /// # Arguments
@@ -139,13 +552,456 @@ pub trait Transaction {
     /// * `name` - The property name.
     fn get_vertex_properties(&self, q: &models::VertexQuery, name: &str) -> Result<Vec<models::VertexProperty>>;
 
-    /// Sets a vertex properties.
+    /// Gets several vertex properties at once, running the query only once.
+    /// Results are grouped by vertex, in the order the query returns them,
+    /// and then ordered by `names`. Properties that aren't set are skipped.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `names` - The property names.
+    fn get_vertex_properties_multi(
+        &self,
+        q: &models::VertexQuery,
+        names: &[String],
+    ) -> Result<Vec<models::VertexProperty>> {
+        let ids: Vec<Uuid> = self.get_vertices(q)?.into_iter().map(|vertex| vertex.id).collect();
+        let ids_q = models::VertexQuery::Vertices { ids: ids.clone() };
+        let mut values_by_name = Vec::with_capacity(names.len());
+
+        for name in names {
+            let values: HashMap<Uuid, JsonValue> = self
+                .get_vertex_properties(&ids_q, name)?
+                .into_iter()
+                .map(|property| (property.id, property.value))
+                .collect();
+            values_by_name.push(values);
+        }
+
+        let mut result = Vec::new();
+
+        for id in ids {
+            for (name, values) in names.iter().zip(values_by_name.iter_mut()) {
+                if let Some(value) = values.remove(&id) {
+                    result.push(models::VertexProperty::new(id, name.clone(), value));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Gets several vertex properties at once, like
+    /// `get_vertex_properties_multi`, but stops once the properties' values
+    /// would take up more than `max_bytes` when serialized as JSON. Returns
+    /// the properties that fit in the budget, and whether any were left out
+    /// because of it.
+    ///
+    /// The default implementation gets every property first, so it only
+    /// bounds the size of the result, not the work done to get it.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `names` - The property names.
+    /// * `max_bytes` - The maximum total size of the property values.
+    fn get_vertex_properties_bounded(
+        &self,
+        q: &models::VertexQuery,
+        names: &[String],
+        max_bytes: u64,
+    ) -> Result<(Vec<models::VertexProperty>, bool)> {
+        let mut properties = self.get_vertex_properties_multi(q, names)?;
+        let mut total_bytes = 0;
+
+        for (i, property) in properties.iter().enumerate() {
+            total_bytes += util::json_size(&property.value) as u64;
+
+            if total_bytes > max_bytes {
+                properties.truncate(i);
+                return Ok((properties, true));
+            }
+        }
+
+        Ok((properties, false))
+    }
+
+    /// Gets every property of the vertices returned by a query, stopping once
+    /// the properties' values would take up more than `max_bytes` when
+    /// serialized as JSON. Returns the properties that fit in the budget,
+    /// ordered by vertex and then by name, and whether any were left out
+    /// because of it. The budget can run out partway through a vertex's
+    /// properties, in which case only some of them are returned.
+    ///
+    /// By default, this gets each vertex's full record in turn, without any
+    /// of its edges.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `max_bytes` - The maximum total size of the property values.
+    fn get_all_vertex_properties_bounded(
+        &self,
+        q: &models::VertexQuery,
+        max_bytes: u64,
+    ) -> Result<(Vec<models::VertexProperty>, bool)> {
+        let mut result = Vec::new();
+        let mut total_bytes = 0;
+
+        for vertex in self.get_vertices(q)? {
+            let properties = match self.get_full_record(vertex.id, 0)? {
+                Some(record) => record.properties,
+                None => continue,
+            };
+
+            for property in properties {
+                total_bytes += util::json_size(&property.value) as u64;
+
+                if total_bytes > max_bytes {
+                    return Ok((result, true));
+                }
+
+                result.push(models::VertexProperty::new(vertex.id, property.name, property.value));
+            }
+        }
+
+        Ok((result, false))
+    }
+
+    /// Gets the names of the properties set on a vertex, in sorted order,
+    /// without their values. Returns an empty list if the vertex doesn't
+    /// exist. By default, this gets the vertex's full record.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    fn get_vertex_property_names(&self, id: Uuid) -> Result<Vec<String>> {
+        let mut names: Vec<String> = match self.get_full_record(id, 0)? {
+            Some(record) => record.properties.into_iter().map(|property| property.name).collect(),
+            None => Vec::new(),
+        };
+
+        names.sort();
+        Ok(names)
+    }
+
+    /// Gets edge properties.
     ///
     /// # Arguments
     /// * `q` - The query to run.
     /// * `name` - The property name.
+    fn get_edge_properties(&self, q: &models::EdgeQuery, name: &str) -> Result<Vec<models::EdgeProperty>>;
+
+    /// Gets edge properties, keyed by the edges they belong to. Edges that
+    /// don't have the property are left out.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    fn get_edge_properties_map(
+        &self,
+        q: &models::EdgeQuery,
+        name: &str,
+    ) -> Result<HashMap<models::EdgeKey, JsonValue>> {
+        Ok(self
+            .get_edge_properties(q, name)?
+            .into_iter()
+            .map(|property| (property.key, property.value))
+            .collect())
+    }
+
+    /// Gets the names of the properties set on an edge, in sorted order,
+    /// without their values. Returns an empty list if the edge doesn't
+    /// exist. By default, this gets the full record of the edge's outbound
+    /// vertex, so datastores should override it.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the edge.
+    fn get_edge_property_names(&self, key: &models::EdgeKey) -> Result<Vec<String>> {
+        let record = match self.get_full_record(key.outbound_id, u32::MAX)? {
+            Some(record) => record,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut names: Vec<String> = record
+            .outbound_edges
+            .into_iter()
+            .find(|edge_record| &edge_record.edge.key == key)
+            .map(|edge_record| {
+                edge_record
+                    .properties
+                    .into_iter()
+                    .map(|property| property.name)
+                    .collect()
+            })
+            .unwrap_or_else(Vec::new);
+
+        names.sort();
+        Ok(names)
+    }
+
+    /// Aggregates a numeric edge property over the edges matched by a
+    /// query. Edges that are missing the property, or whose property value
+    /// is not a number, are skipped. Returns `None` if no values were
+    /// aggregated, except for `AggregateOp::Count`, which returns zero.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    /// * `op` - The aggregation to perform.
+    fn aggregate_edge_property(
+        &self,
+        q: &models::EdgeQuery,
+        name: &str,
+        op: models::AggregateOp,
+    ) -> Result<Option<f64>> {
+        let properties = self.get_edge_properties(q, name)?;
+        Ok(op.aggregate(properties.iter().map(|property| &property.value)))
+    }
+
+    /// Gets a vertex along with all of its properties, and up to
+    /// `edge_limit` of its outbound and inbound edges along with their
+    /// properties. Returns `None` if the vertex doesn't exist.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the vertex.
+    /// * `edge_limit` - The maximum number of edges to get in each direction.
+    fn get_full_record(&self, id: Uuid, edge_limit: u32) -> Result<Option<models::FullRecord>>;
+
+    /// Gets a summary of the size and shape of the graph. Edges are counted
+    /// once, as outbound edges of their vertices. By default, this pages
+    /// through every vertex, so it's slow on large graphs, but it never
+    /// holds more than a page of vertices in memory.
+    fn get_graph_stats(&self) -> Result<models::GraphStats> {
+        let mut stats = models::GraphStats::default();
+        let mut total_degree = 0;
+        let mut start_id = None;
+
+        loop {
+            let q = models::VertexQuery::All {
+                start_id,
+                end_id: None,
+                high_filter: None,
+                low_filter: None,
+                limit: GRAPH_STATS_PAGE_SIZE,
+            };
+            let vertices = self.get_vertices(&q)?;
+
+            for vertex in &vertices {
+                let record = match self.get_full_record(vertex.id, u32::MAX)? {
+                    Some(record) => record,
+                    None => continue,
+                };
+
+                stats.vertices += 1;
+                *stats.vertex_types.entry(vertex.t.clone()).or_insert(0) += 1;
+                stats.vertex_properties += record.properties.len() as u64;
+                stats.edges += record.outbound_edges.len() as u64;
+                stats.edge_properties += record
+                    .outbound_edges
+                    .iter()
+                    .map(|edge_record| edge_record.properties.len() as u64)
+                    .sum::<u64>();
+                total_degree += (record.outbound_edges.len() + record.inbound_edges.len()) as u64;
+            }
+
+            if vertices.len() < GRAPH_STATS_PAGE_SIZE as usize {
+                break;
+            }
+
+            // The last possible id was reached if there's no next one
+            match util::next_uuid(vertices[vertices.len() - 1].id) {
+                Ok(next_id) => start_id = Some(next_id),
+                Err(_) => break,
+            }
+        }
+
+        stats.set_average_degree(total_degree);
+        Ok(stats)
+    }
+}
+
+/// Specifies the write operations of a transaction.
+pub trait WriteTransaction: ReadTransaction {
+    /// Creates a new vertex. Returns whether the vertex was successfully
+    /// created - if this is false, it's because a vertex with the same UUID
+    /// already exists.
+    ///
+    /// # Arguments
+    /// * `vertex`: The vertex to create.
+    fn create_vertex(&self, vertex: &models::Vertex) -> Result<bool>;
+
+    /// Creates a new vertex with just a type specification. As opposed to
+    /// `create_vertex`, this is used when you do not want to manually specify
+    /// the vertex's UUID. Returns the new vertex's UUID.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex to create.
+    fn create_vertex_from_type(&self, t: models::Type) -> Result<Uuid> {
+        let v = models::Vertex::new(t);
+
+        if !self.create_vertex(&v)? {
+            Err("UUID already taken".into())
+        } else {
+            Ok(v.id)
+        }
+    }
+
+    /// Gets the vertex with the given id, creating it with the given type if
+    /// it doesn't exist. Returns the vertex, and whether it was created. An
+    /// existing vertex is returned as is, even if its type is different.
+    ///
+    /// The default implementation tries to create the vertex and then gets
+    /// it if it already existed, so datastores should override it to do both
+    /// at once.
+    ///
+    /// # Arguments
+    /// * `id`: The id of the vertex.
+    /// * `t`: The type to create the vertex with.
+    fn get_or_create_vertex(&self, id: Uuid, t: &models::Type) -> Result<(models::Vertex, bool)> {
+        let vertex = models::Vertex::with_id(id, t.clone());
+
+        if self.create_vertex(&vertex)? {
+            return Ok((vertex, true));
+        }
+
+        match self.get_vertices(&models::VertexQuery::single(id))?.pop() {
+            Some(vertex) => Ok((vertex, false)),
+            None => Err("Vertex was deleted while getting it".into()),
+        }
+    }
+
+    /// Deletes existing vertices specified by a query. Returns the number of
+    /// vertices that were deleted, which doesn't include the edges that were
+    /// deleted along with them.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    fn delete_vertices(&self, q: &models::VertexQuery) -> Result<u64>;
+
+    /// Creates a new edge. If the edge already exists, this will update it
+    /// with a new update datetime. Returns whether the edge was successfully
+    /// created - if this is false, it's because one of the specified vertices
+    /// is missing.
+    ///
+    /// # Arguments
+    /// * `key`: The edge to create.
+    fn create_edge(&self, key: &models::EdgeKey) -> Result<bool>;
+
+    /// Creates a new edge, first creating either of its vertices with type
+    /// `default_type` if they don't exist. Returns whether the edge was
+    /// newly created - if this is false, it's because the edge already
+    /// existed, and has been updated with a new update datetime, or because
+    /// the datastore rejected it, e.g. as a disallowed self-loop.
+    ///
+    /// The default implementation creates the vertices and the edge one
+    /// after another, so datastores should override it to do it atomically.
+    ///
+    /// # Arguments
+    /// * `key`: The edge to create.
+    /// * `default_type`: The type to create missing vertices with.
+    fn create_edge_auto(&self, key: &models::EdgeKey, default_type: &models::Type) -> Result<bool> {
+        self.create_vertex(&models::Vertex::with_id(key.outbound_id, default_type.clone()))?;
+        self.create_vertex(&models::Vertex::with_id(key.inbound_id, default_type.clone()))?;
+        let created = !self.edge_exists(key)?;
+        Ok(self.create_edge(key)? && created)
+    }
+
+    /// Bumps the update datetime of an existing edge to now, e.g. to mark it
+    /// as recently active. Unlike `create_edge`, this never creates the edge.
+    /// Returns whether the edge was updated - if this is false, it's because
+    /// the edge doesn't exist.
+    ///
+    /// The default implementation checks that the edge exists and then
+    /// creates it again, so datastores should override it to do both at
+    /// once.
+    ///
+    /// # Arguments
+    /// * `key`: The edge to update.
+    fn update_edge(&self, key: &models::EdgeKey) -> Result<bool> {
+        if self.edge_exists(key)? {
+            self.create_edge(key)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Deletes a set of edges specified by a query. Returns the number of
+    /// edges that were deleted.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    fn delete_edges(&self, q: &models::EdgeQuery) -> Result<u64>;
+
+    /// Sets a vertex properties. Returns the number of properties that were
+    /// set, which is zero if the query matched no vertices.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    /// * `value` - The property value.
+    fn set_vertex_properties(&self, q: &models::VertexQuery, name: &str, value: &JsonValue) -> Result<u64>;
+
+    /// Sets a vertex property on every vertex that matches a condition on
+    /// another (or the same) property. Returns the number of vertices that
+    /// were updated.
+    ///
+    /// # Arguments
+    /// * `filter` - The condition that vertices must match.
+    /// * `name` - The property name.
     /// * `value` - The property value.
-    fn set_vertex_properties(&self, q: &models::VertexQuery, name: &str, value: &JsonValue) -> Result<()>;
+    fn set_vertex_property_where(
+        &self,
+        filter: &models::PropertyCondition,
+        name: &str,
+        value: &JsonValue,
+    ) -> Result<u64> {
+        let q = models::VertexQuery::All {
+            start_id: None,
+            end_id: None,
+            high_filter: None,
+            low_filter: None,
+            limit: u32::MAX,
+        };
+        let ids: Vec<Uuid> = self
+            .get_vertex_properties(&q, &filter.name)?
+            .into_iter()
+            .filter(|property| filter.matches(&property.value))
+            .map(|property| property.id)
+            .collect();
+
+        if ids.is_empty() {
+            Ok(0)
+        } else {
+            self.set_vertex_properties(&models::VertexQuery::Vertices { ids }, name, value)
+        }
+    }
+
+    /// Partially updates a vertex property by applying a JSON merge patch
+    /// (RFC 7386) to its current value on each vertex matched by a query. If
+    /// a vertex doesn't have the property yet, the patch is applied to
+    /// `null`, so it becomes the value with any `null` members removed.
+    ///
+    /// The default implementation reads and then writes the properties, so
+    /// it isn't atomic; datastores should override it to patch the values
+    /// while holding their write lock.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    /// * `patch` - The merge patch to apply.
+    fn patch_vertex_property(&self, q: &models::VertexQuery, name: &str, patch: &JsonValue) -> Result<()> {
+        let ids: Vec<Uuid> = self.get_vertices(q)?.into_iter().map(|vertex| vertex.id).collect();
+        let mut values: HashMap<Uuid, JsonValue> = self
+            .get_vertex_properties(&models::VertexQuery::Vertices { ids: ids.clone() }, name)?
+            .into_iter()
+            .map(|property| (property.id, property.value))
+            .collect();
+
+        for id in ids {
+            let mut value = values.remove(&id).unwrap_or(JsonValue::Null);
+            util::json_merge_patch(&mut value, patch);
+            self.set_vertex_properties(&models::VertexQuery::Vertices { ids: vec![id] }, name, &value)?;
+        }
+
+        Ok(())
+    }
 
     /// Deletes vertex properties.
     ///
@@ -154,12 +1010,12 @@ pub trait Transaction {
     /// * `name` - The property name.
     fn delete_vertex_properties(&self, q: &models::VertexQuery, name: &str) -> Result<()>;
 
-    /// Gets edge properties.
+    /// Deletes every property of the vertices matched by a query, leaving
+    /// the vertices themselves in place.
     ///
     /// # Arguments
     /// * `q` - The query to run.
-    /// * `name` - The property name.
-    fn get_edge_properties(&self, q: &models::EdgeQuery, name: &str) -> Result<Vec<models::EdgeProperty>>;
+    fn delete_all_vertex_properties(&self, q: &models::VertexQuery) -> Result<()>;
 
     /// Sets edge properties.
     ///
@@ -169,10 +1025,108 @@ pub trait Transaction {
     /// * `value` - The property value.
     fn set_edge_properties(&self, q: &models::EdgeQuery, name: &str, value: &JsonValue) -> Result<()>;
 
+    /// Partially updates an edge property by applying a JSON merge patch
+    /// (RFC 7386) to its current value on each edge matched by a query. If
+    /// an edge doesn't have the property yet, the patch is applied to
+    /// `null`, so it becomes the value with any `null` members removed.
+    ///
+    /// The default implementation reads and then writes the properties, so
+    /// it isn't atomic; datastores should override it to patch the values
+    /// while holding their write lock.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    /// * `name` - The property name.
+    /// * `patch` - The merge patch to apply.
+    fn patch_edge_property(&self, q: &models::EdgeQuery, name: &str, patch: &JsonValue) -> Result<()> {
+        let keys: Vec<models::EdgeKey> = self.get_edges(q)?.into_iter().map(|edge| edge.key).collect();
+        let mut values = self.get_edge_properties_map(&models::EdgeQuery::Edges { keys: keys.clone() }, name)?;
+
+        for key in keys {
+            let mut value = values.remove(&key).unwrap_or(JsonValue::Null);
+            util::json_merge_patch(&mut value, patch);
+            self.set_edge_properties(&models::EdgeQuery::Edges { keys: vec![key] }, name, &value)?;
+        }
+
+        Ok(())
+    }
+
     /// Deletes edge properties.
     ///
     /// # Arguments
     /// * `q` - The query to run.
     /// * `name` - The property name.
     fn delete_edge_properties(&self, q: &models::EdgeQuery, name: &str) -> Result<()>;
+
+    /// Deletes every property of the edges matched by a query, leaving the
+    /// edges themselves in place.
+    ///
+    /// # Arguments
+    /// * `q` - The query to run.
+    fn delete_all_edge_properties(&self, q: &models::EdgeQuery) -> Result<()>;
+}
+
+/// Specifies a transaction implementation, which are returned by datastores.
+/// All datastore manipulations are done through transactions. Despite the
+/// name, different datastore implementations carry different guarantees.
+/// Depending on the implementation, it may not be possible to rollback the
+/// changes on error. See the documentation of individual implementations for
+/// details. Transactions are automatically committed on drop. Transactions
+/// should be designed to not fail on commit; i.e. errors should occur when a
+/// method is actually called instead.
+///
+/// This is implemented for every type that implements both
+/// `ReadTransaction` and `WriteTransaction`.
+pub trait Transaction: ReadTransaction + WriteTransaction {}
+
+impl<T: ReadTransaction + WriteTransaction> Transaction for T {}
+
+/// Inserts a single bulk insert item through a transaction, as the default
+/// implementation of `Datastore::bulk_insert` does for each item. This is
+/// meant for datastores that override `bulk_insert` but still insert items
+/// one at a time.
+///
+/// # Arguments
+/// * `trans`: The transaction to insert the item through.
+/// * `item`: The item to insert.
+pub fn insert_bulk_insert_item<T: Transaction>(trans: &T, item: &models::BulkInsertItem) -> Result<()> {
+    match *item {
+        models::BulkInsertItem::Vertex(ref vertex) => {
+            trans.create_vertex(vertex)?;
+        }
+        models::BulkInsertItem::Edge(ref edge_key) => {
+            trans.create_edge(edge_key)?;
+        }
+        models::BulkInsertItem::VertexProperty(id, ref name, ref value) => {
+            let query = models::VertexQuery::Vertices { ids: vec![id] };
+            trans.set_vertex_properties(&query, name, value)?;
+        }
+        models::BulkInsertItem::EdgeProperty(ref edge_key, ref name, ref value) => {
+            let query = models::EdgeQuery::Edges {
+                keys: vec![edge_key.clone()],
+            };
+            trans.set_edge_properties(&query, name, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Checks whether the vertex, edge or property a bulk insert item would
+// create already exists.
+fn bulk_insert_item_exists<T: Transaction>(trans: &T, item: &models::BulkInsertItem) -> Result<bool> {
+    match *item {
+        models::BulkInsertItem::Vertex(ref vertex) => trans.vertex_exists(vertex.id),
+        models::BulkInsertItem::Edge(ref edge_key) => trans.edge_exists(edge_key),
+        models::BulkInsertItem::VertexProperty(id, ref name, _) => {
+            let query = models::VertexQuery::Vertices { ids: vec![id] };
+            Ok(!trans.get_vertex_properties(&query, name)?.is_empty())
+        }
+        models::BulkInsertItem::EdgeProperty(ref edge_key, ref name, _) => {
+            let query = models::EdgeQuery::Edges {
+                keys: vec![edge_key.clone()],
+            };
+            Ok(!trans.get_edge_properties(&query, name)?.is_empty())
+        }
+    }
 }
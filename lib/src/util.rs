@@ -2,17 +2,31 @@
 
 use chrono::offset::Utc;
 use chrono::DateTime;
-use errors::ValidationResult;
+use errors::{Result, ValidationResult};
+#[cfg(feature = "compression")]
+use flate2::read::GzDecoder;
 use rand::{OsRng, Rng};
+use serde_json;
+use serde_json::{Map, Value as JsonValue};
 use std::env;
+use std::io;
+#[cfg(feature = "compression")]
+use std::io::{Cursor, Read, Result as IoResult};
+use std::sync::{Mutex, PoisonError};
 use uuid::v1::Context;
 use uuid::Uuid;
 
 const TEMP_PATH_RANDOM_PART_LENGTH: usize = 8;
 const NODE_ID: [u8; 6] = [0, 0, 0, 0, 0, 0];
+const UUID_V7_RANDOM_BITS: u32 = 74;
+#[cfg(feature = "compression")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+#[cfg(feature = "compression")]
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
 
 lazy_static! {
     static ref CONTEXT: Context = Context::new(0);
+    static ref LAST_UUID_V7: Mutex<(u64, u128)> = Mutex::new((0, 0));
 }
 
 /// Gets the path to a file or directory within the temporary directory, in a
@@ -40,6 +54,53 @@ pub fn generate_uuid_v1() -> Uuid {
     .expect("Expected to be able to generate a UUID")
 }
 
+/// Generates a UUID v7, which starts with a millisecond unix timestamp
+/// followed by random bits. UUIDs generated by this process are strictly
+/// increasing: if the clock hasn't advanced (or went backwards) since the
+/// last UUID, the random part of the previous one is incremented instead.
+///
+/// # Errors
+/// Returns an IO error if the OS random number generator is unavailable.
+pub fn generate_uuid_v7() -> Result<Uuid> {
+    let now = Utc::now();
+    let mut millis = now.timestamp() as u64 * 1000 + u64::from(now.timestamp_subsec_millis());
+    let mut rng = OsRng::new().map_err(io::Error::from)?;
+    let mut random =
+        ((u128::from(rng.gen::<u64>()) << 64) | u128::from(rng.gen::<u64>())) & ((1 << UUID_V7_RANDOM_BITS) - 1);
+
+    {
+        // The last UUID is always replaced in one assignment, so it's never
+        // left half-updated by a panicking thread, and a poisoned lock can
+        // be used as-is
+        let mut last = LAST_UUID_V7.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if millis <= last.0 {
+            millis = last.0;
+            random = last.1 + 1;
+
+            if random >> UUID_V7_RANDOM_BITS != 0 {
+                millis += 1;
+                random = 0;
+            }
+        }
+
+        *last = (millis, random);
+    }
+
+    let value = (u128::from(millis & 0xffff_ffff_ffff) << 80)
+        | (0x7 << 76)
+        | ((random >> 62) << 64)
+        | (0b10 << 62)
+        | (random & ((1 << 62) - 1));
+    let mut bytes = [0; 16];
+
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = (value >> (120 - 8 * i)) as u8;
+    }
+
+    Ok(Uuid::from_bytes(bytes))
+}
+
 /// Generates a securely random string consisting of letters (uppercase and
 /// lowercase) and digits.
 pub fn generate_random_secret(count: usize) -> String {
@@ -89,12 +150,134 @@ pub fn nanos_since_epoch(datetime: &DateTime<Utc>) -> u64 {
     timestamp * 1_000_000_000 + nanoseconds
 }
 
+/// Checks whether a JSON value is a string that contains the given
+/// substring, ignoring case. Values that aren't strings never match.
+///
+/// # Arguments
+/// * `value` - The JSON value to check.
+/// * `substring` - The substring to look for.
+pub fn json_string_contains(value: &JsonValue, substring: &str) -> bool {
+    match *value {
+        JsonValue::String(ref s) => s.to_lowercase().contains(&substring.to_lowercase()),
+        _ => false,
+    }
+}
+
+/// Applies a JSON merge patch (RFC 7386) to a value in place. Object
+/// members of the patch are merged recursively, with `null` members removing
+/// the corresponding member from the target. Any other patch value replaces
+/// the target outright.
+///
+/// # Arguments
+/// * `target` - The value to patch.
+/// * `patch` - The merge patch to apply.
+pub fn json_merge_patch(target: &mut JsonValue, patch: &JsonValue) {
+    if let JsonValue::Object(ref patch_members) = *patch {
+        if !target.is_object() {
+            *target = JsonValue::Object(Map::new());
+        }
+
+        if let JsonValue::Object(ref mut target_members) = *target {
+            for (key, value) in patch_members {
+                if value.is_null() {
+                    target_members.remove(key);
+                } else {
+                    json_merge_patch(target_members.entry(key.clone()).or_insert(JsonValue::Null), value);
+                }
+            }
+        }
+    } else {
+        *target = patch.clone();
+    }
+}
+
+/// Gets the length of a JSON value when serialized, without allocating a
+/// buffer to serialize it into.
+///
+/// # Arguments
+/// * `value` - The JSON value to measure.
+pub fn json_size(value: &JsonValue) -> usize {
+    let mut counter = ByteCounter(0);
+    serde_json::to_writer(&mut counter, value).expect("Expected JSON values to always be serializable");
+    counter.0
+}
+
+// A writer that discards what's written to it, only counting the bytes.
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Checks that a property value isn't larger than a maximum size, measured
+/// as the length of the value serialized as JSON.
+///
+/// # Arguments
+/// * `name` - The property name, for the error message.
+/// * `value` - The property value.
+/// * `max_size` - The maximum size in bytes, or `None` for no maximum.
+///
+/// # Errors
+/// Returns a `ValidationError` if the value is too large.
+pub fn validate_property_size(name: &str, value: &JsonValue, max_size: Option<usize>) -> ValidationResult<()> {
+    if let Some(max_size) = max_size {
+        let size = json_size(value);
+
+        if size > max_size {
+            return Err(format!(
+                "Property `{}` is {} bytes, which is larger than the maximum of {} bytes",
+                name, size, max_size
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Wraps a reader so that gzip or zstd compressed content is transparently
+/// decompressed. The compression format is detected by its magic bytes;
+/// content that doesn't start with either is passed through as-is.
+///
+/// # Arguments
+/// * `reader` - The reader to wrap.
+#[cfg(feature = "compression")]
+pub fn decompress<'a, R: Read + 'a>(mut reader: R) -> IoResult<Box<dyn Read + 'a>> {
+    let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+    reader.by_ref().take(ZSTD_MAGIC.len() as u64).read_to_end(&mut magic)?;
+    let is_gzip = magic.starts_with(&GZIP_MAGIC);
+    let is_zstd = magic == ZSTD_MAGIC;
+
+    // Put the magic bytes back in front of the rest of the content
+    let reader = Cursor::new(magic).chain(reader);
+
+    if is_gzip {
+        Ok(Box::new(GzDecoder::new(reader)))
+    } else if is_zstd {
+        Ok(Box::new(zstd::stream::read::Decoder::new(reader)?))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{generate_random_secret, generate_temporary_path, generate_uuid_v1, nanos_since_epoch, next_uuid};
+    use super::{
+        generate_random_secret, generate_temporary_path, generate_uuid_v1, generate_uuid_v7, json_merge_patch,
+        json_size, nanos_since_epoch, next_uuid, validate_property_size,
+    };
     use chrono::{DateTime, NaiveDateTime, Utc};
     use core::str::FromStr;
     use regex::Regex;
+    use serde_json;
+    use serde_json::Value as JsonValue;
     use uuid::Uuid;
 
     #[test]
@@ -113,6 +296,14 @@ mod tests {
         assert_ne!(first, second);
     }
 
+    #[test]
+    fn should_generate_increasing_uuid_v7() {
+        let uuids: Vec<Uuid> = (0..100).map(|_| generate_uuid_v7().unwrap()).collect();
+        assert!(uuids.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(uuids[0].as_bytes()[6] >> 4, 7);
+        assert_eq!(uuids[0].as_bytes()[8] >> 6, 0b10);
+    }
+
     #[test]
     fn should_generate_random_secret() {
         let secret = generate_random_secret(8);
@@ -137,4 +328,35 @@ mod tests {
         let datetime = DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(61, 62), Utc);
         assert_eq!(nanos_since_epoch(&datetime), 61000000062);
     }
+
+    #[test]
+    fn should_apply_json_merge_patch() {
+        let parse = |s: &str| -> JsonValue { serde_json::from_str(s).unwrap() };
+
+        let mut value = parse(r#"{"a": "b", "c": {"d": "e", "f": "g"}}"#);
+        json_merge_patch(&mut value, &parse(r#"{"a": "z", "c": {"f": null}}"#));
+        assert_eq!(value, parse(r#"{"a": "z", "c": {"d": "e"}}"#));
+
+        let mut value = parse(r#"["a", "b"]"#);
+        json_merge_patch(&mut value, &parse(r#"{"a": {"b": null, "c": 1}}"#));
+        assert_eq!(value, parse(r#"{"a": {"c": 1}}"#));
+
+        json_merge_patch(&mut value, &parse("[1]"));
+        assert_eq!(value, parse("[1]"));
+    }
+
+    #[test]
+    fn should_get_json_size() {
+        assert_eq!(json_size(&JsonValue::from("abc")), 5);
+        assert_eq!(json_size(&JsonValue::from(vec![1, 2])), 5);
+        assert_eq!(json_size(&JsonValue::Null), 4);
+    }
+
+    #[test]
+    fn should_validate_property_size() {
+        let value = JsonValue::from("abc");
+        assert!(validate_property_size("name", &value, None).is_ok());
+        assert!(validate_property_size("name", &value, Some(5)).is_ok());
+        assert!(validate_property_size("name", &value, Some(4)).is_err());
+    }
 }